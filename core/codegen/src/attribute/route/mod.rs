@@ -118,7 +118,8 @@ fn request_guard_decl(guard: &Guard) -> TokenStream {
     );
 
     quote_spanned! { ty.span() =>
-        let #ident: #ty = match <#ty as #FromRequest>::from_request(#__req).await {
+        let __guard = <#ty as #FromRequest>::from_request(#__req);
+        let #ident: #ty = match #_log::trace_guard("request", stringify!(#ty), __guard).await {
             #Outcome::Success(__v) => __v,
             #Outcome::Forward(_) => {
                 #_log::warn_!("Request guard `{}` is forwarding.", stringify!(#ty));
@@ -181,7 +182,8 @@ fn data_guard_decl(guard: &Guard) -> TokenStream {
     define_spanned_export!(ty.span() => _log, __req, __data, FromData, Outcome);
 
     quote_spanned! { ty.span() =>
-        let #ident: #ty = match <#ty as #FromData>::from_data(#__req, #__data).await {
+        let __guard = <#ty as #FromData>::from_data(#__req, #__data);
+        let #ident: #ty = match #_log::trace_guard("data", stringify!(#ty), __guard).await {
             #Outcome::Success(__d) => __d,
             #Outcome::Forward(__d) => {
                 #_log::warn_!("Data guard `{}` is forwarding.", stringify!(#ty));
@@ -330,6 +332,7 @@ fn codegen_route(route: Route) -> Result<TokenStream> {
     let method = route.attr.method;
     let uri = route.attr.uri.to_string();
     let rank = Optional(route.attr.rank);
+    let timeout = Optional(route.attr.timeout);
     let format = Optional(route.attr.format.as_ref());
 
     Ok(quote! {
@@ -365,6 +368,7 @@ fn codegen_route(route: Route) -> Result<TokenStream> {
                     handler: monomorphized_function,
                     format: #format,
                     rank: #rank,
+                    timeout: #timeout,
                     sentinels: #sentinels,
                 }
             }
@@ -417,6 +421,7 @@ fn incomplete_route(
         data: method_attribute.data,
         format: method_attribute.format,
         rank: method_attribute.rank,
+        timeout: method_attribute.timeout,
     };
 
     codegen_route(Route::from(attribute, function)?)
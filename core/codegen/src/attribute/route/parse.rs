@@ -47,6 +47,7 @@ pub struct Attribute {
     pub data: Option<SpanWrapped<Dynamic>>,
     pub format: Option<MediaType>,
     pub rank: Option<isize>,
+    pub timeout: Option<Timeout>,
 }
 
 /// The parsed `#[method(..)]` (e.g, `get`, `put`, etc.) attribute.
@@ -57,8 +58,13 @@ pub struct MethodAttribute {
     pub data: Option<SpanWrapped<Dynamic>>,
     pub format: Option<MediaType>,
     pub rank: Option<isize>,
+    pub timeout: Option<Timeout>,
 }
 
+/// A handler timeout in seconds: `timeout = 300`.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout(pub u32);
+
 #[derive(Debug)]
 pub struct RouteUri {
     origin: Origin<'static>,
@@ -66,6 +72,22 @@ pub struct RouteUri {
     query_span: Option<Span>,
 }
 
+impl FromMeta for Timeout {
+    fn from_meta(meta: &devise::MetaItem) -> Result<Self> {
+        let secs = usize::from_meta(meta)?;
+        u32::try_from(secs)
+            .map(Timeout)
+            .map_err(|_| meta.value_span().error("timeout is out of range for `u32`"))
+    }
+}
+
+impl quote::ToTokens for Timeout {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let secs = self.0;
+        tokens.extend(quote!(#secs));
+    }
+}
+
 impl FromMeta for RouteUri {
     fn from_meta(meta: &devise::MetaItem) -> Result<Self> {
         let string = crate::proc_macro_ext::StringLit::from_meta(meta)?;
@@ -157,6 +157,7 @@ macro_rules! route_attribute {
         /// parameter := 'rank' '=' INTEGER
         ///            | 'format' '=' '"' MEDIA_TYPE '"'
         ///            | 'data' '=' '"' SINGLE_PARAM '"'
+        ///            | 'timeout' '=' INTEGER
         ///
         /// SINGLE_PARAM := '<' IDENT '>'
         /// TRAILING_PARAM := '<' IDENT '..>'
@@ -258,9 +259,11 @@ macro_rules! route_attribute {
         ///   2. A static structure used by [`routes!`] to generate a [`Route`].
         ///
         ///      The static structure (and resulting [`Route`]) is populated
-        ///      with the name (the function's name), path, query, rank, and
-        ///      format from the route attribute. The handler is set to the
-        ///      generated handler.
+        ///      with the name (the function's name), path, query, rank,
+        ///      format, and timeout from the route attribute. The handler is
+        ///      set to the generated handler. The timeout, in seconds,
+        ///      overrides the configured handler timeout for the route; `0`
+        ///      disables it.
         ///
         ///   3. A macro used by [`uri!`] to type-check and generate an
         ///      [`Origin`].
@@ -6,6 +6,7 @@ use serde_::de::{self, Deserializer};
 use serde_::{Deserialize, Serialize};
 use std::fmt::{self, Display, Formatter};
 use std::net::{AddrParseError, IpAddr, SocketAddr};
+use std::num::ParseIntError;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -20,7 +21,23 @@ pub enum BindableAddr {
     /// Listen on an address and port with the TCP protocol
     Tcp(SocketAddr),
     /// Listen on a Unix socket
+    ///
+    /// On Linux, a path beginning with `@`, as in `unix://@name`, names a
+    /// socket in the abstract namespace, which has no socket file.
     Unix(PathBuf),
+    /// Listen on a socket inherited from the parent process via socket
+    /// activation, identified by its file descriptor number
+    ///
+    /// The descriptor must be one of those passed according to `LISTEN_PID`
+    /// and `LISTEN_FDS`, i.e, at least 3 and less than `3 + LISTEN_FDS`.
+    Fd(i32),
+    /// Listen on a socket inherited from the parent process via socket
+    /// activation, identified by its name in `LISTEN_FDNAMES`
+    ///
+    /// With systemd, the name is set by `FileDescriptorName=` in the socket
+    /// unit and defaults to the name of the unit. If several sockets share a
+    /// name, each address with that name adopts the next one.
+    Systemd(String),
 }
 
 impl BindableAddr {
@@ -29,6 +46,8 @@ impl BindableAddr {
         match self {
             Self::Tcp(_) => "tcp",
             Self::Unix(_) => "unix",
+            Self::Fd(_) => "fd",
+            Self::Systemd(_) => "systemd",
         }
     }
     /// The IP address, if the inner address type has one
@@ -52,6 +71,8 @@ impl Display for BindableAddr {
         match self {
             Self::Tcp(addr) => write!(formatter, "{}://{}", self.protocol_name(), addr),
             Self::Unix(path) => write!(formatter, "{}://{}", self.protocol_name(), path.display()),
+            Self::Fd(fd) => write!(formatter, "{}://{}", self.protocol_name(), fd),
+            Self::Systemd(name) => write!(formatter, "{}://{}", self.protocol_name(), name),
         }
     }
 }
@@ -65,6 +86,10 @@ pub enum FromStrError {
     RequiresPort(IpAddr),
     /// For protocols that take an IP address, that IP address could not be parsed
     Ip(AddrParseError),
+    /// The file descriptor of an `fd` address could not be parsed
+    Fd(ParseIntError),
+    /// The name of a `systemd` address was empty or contained a `:`
+    Name(String),
 }
 
 impl Display for FromStrError {
@@ -85,6 +110,8 @@ impl Display for FromStrError {
                 write!(formatter, "raw IP address {:?} requires a port", addr)
             }
             Self::Ip(err) => write!(formatter, "invalid IP: {}", err),
+            Self::Fd(err) => write!(formatter, "invalid file descriptor: {}", err),
+            Self::Name(name) => write!(formatter, "invalid socket name {:?}", name),
         }
     }
 }
@@ -93,6 +120,11 @@ impl From<AddrParseError> for FromStrError {
         Self::Ip(err)
     }
 }
+impl From<ParseIntError> for FromStrError {
+    fn from(err: ParseIntError) -> Self {
+        Self::Fd(err)
+    }
+}
 impl std::error::Error for FromStrError {}
 
 impl FromStr for BindableAddr {
@@ -103,6 +135,11 @@ impl FromStr for BindableAddr {
             Ok(match protocol_name {
                 "tcp" => Self::Tcp(SocketAddr::from_str(data)?),
                 "unix" => Self::Unix(PathBuf::from(data)),
+                "fd" => Self::Fd(i32::from_str(data)?),
+                "systemd" if data.is_empty() || data.contains(':') => {
+                    return Err(Self::Err::Name(data.to_string()))
+                }
+                "systemd" => Self::Systemd(data.to_string()),
                 unknown => return Err(Self::Err::UnknownProtocol(unknown.to_string())),
             })
         } else {
@@ -167,3 +204,22 @@ impl<'de> Deserialize<'de> for BindableAddr {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_inherited() {
+        assert_eq!(BindableAddr::from_str("fd://3").unwrap(), BindableAddr::Fd(3));
+        let systemd = BindableAddr::Systemd("web".into());
+        assert_eq!(BindableAddr::from_str("systemd://web").unwrap(), systemd);
+        assert_eq!(BindableAddr::Fd(4).to_string(), "fd://4");
+        assert_eq!(BindableAddr::Systemd("web".into()).to_string(), "systemd://web");
+
+        assert!(matches!(BindableAddr::from_str("fd://three"), Err(FromStrError::Fd(_))));
+        assert!(matches!(BindableAddr::from_str("fd://"), Err(FromStrError::Fd(_))));
+        assert!(matches!(BindableAddr::from_str("systemd://"), Err(FromStrError::Name(_))));
+        assert!(matches!(BindableAddr::from_str("systemd://a:b"), Err(FromStrError::Name(_))));
+    }
+}
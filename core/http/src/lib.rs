@@ -16,6 +16,7 @@ extern crate pear;
 pub mod bindable;
 pub mod ext;
 pub mod hyper;
+pub mod proxy_protocol;
 pub mod uri;
 
 #[macro_use]
@@ -44,7 +45,9 @@ pub mod uncased {
 #[path = "."]
 pub mod private {
     pub use crate::listener::{
-        bind_tcp, bind_unix, Certificates, Connection, Incoming, Listener, TcpListener,
+        bind_inherited, bind_tcp, bind_unix, Certificates, Connection, Connections, Counted,
+        Handshake, HandshakeData, Incoming, Inherited, Listener, PeerCredentials, TcpListener,
+        UnixOptions,
     };
     pub use crate::parse::Indexed;
     pub use cookie;
@@ -3,14 +3,15 @@ use std::future::Future;
 use std::io;
 use std::net::SocketAddr;
 use std::pin::Pin;
-use std::sync::Arc;
-use std::task::{Context, Poll};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll, Waker};
 use std::time::Duration;
 
 use hyper::server::accept::Accept;
 use log::warn;
 use state::Storage;
-use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio::net::TcpStream;
 use tokio::time::Sleep;
 
@@ -45,7 +46,53 @@ impl Certificates {
     }
 }
 
+/// The parameters negotiated in a TLS handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandshakeData {
+    /// The protocol version, as encoded on the wire, e.g. `0x0304` for TLS 1.3.
+    pub version: u16,
+    /// The IANA-assigned value of the cipher suite.
+    pub cipher_suite: u16,
+    /// The protocol agreed on via ALPN (Application-Layer Protocol
+    /// Negotiation), if any.
+    pub alpn_protocol: Option<Vec<u8>>,
+    /// The server name requested by the client via SNI (Server Name
+    /// Indication), if any.
+    pub server_name: Option<String>,
+}
+
+/// The credentials of the process on the other end of a Unix socket
+/// connection, as reported by the operating system when it was accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PeerCredentials {
+    /// The effective user ID of the peer.
+    pub uid: u32,
+    /// The effective group ID of the peer.
+    pub gid: u32,
+    /// The process ID of the peer, if the platform reports it.
+    pub pid: Option<i32>,
+}
+
+/// The parameters of a TLS handshake, available once it completes.
+#[derive(Clone, Default)]
+pub struct Handshake(Arc<Storage<HandshakeData>>);
+
+impl Handshake {
+    /// Set the handshake parameters. Only the first call actually sets the
+    /// data; the remaining do nothing.
+    #[cfg(feature = "tls")]
+    pub(crate) fn set(&self, data: HandshakeData) {
+        self.0.set(data);
+    }
+
+    /// Returns the handshake parameters, if the handshake has completed.
+    pub fn data(&self) -> Option<&HandshakeData> {
+        self.0.try_get()
+    }
+}
+
 use crate::bindable::BindableAddr;
+use crate::proxy_protocol::ProxyHeader;
 
 // TODO.async: 'Listener' and 'Connection' provide common enough functionality
 // that they could be introduced in upstream libraries.
@@ -86,6 +133,189 @@ pub trait Connection: AsyncRead + AsyncWrite {
     fn peer_certificates(&self) -> Option<Certificates> {
         None
     }
+
+    /// The parameters negotiated in the TLS handshake, if the connection is
+    /// secured by TLS. Like
+    /// [`peer_certificates()`](Connection::peer_certificates()), these may be
+    /// filled in only once the handshake completes.
+    ///
+    /// Defaults to `None`.
+    fn tls_handshake(&self) -> Option<Handshake> {
+        None
+    }
+
+    /// The PROXY protocol header received before any data on the connection,
+    /// if any. See [`ProxyListener`](crate::proxy_protocol::ProxyListener).
+    ///
+    /// Defaults to `None`.
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        None
+    }
+
+    /// The credentials of the peer process, if the connection is over a Unix
+    /// socket and the platform reports them.
+    ///
+    /// Defaults to `None`.
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        None
+    }
+}
+
+/// The number of open connections accepted by the [`Incoming`]s sharing it
+/// and, optionally, a limit on that number.
+///
+/// While the limit is reached, each `Incoming` holds at most one newly
+/// accepted connection, unserved, until an open connection closes; any others
+/// wait in the listener's backlog. An idle `Incoming` never holds a slot.
+#[derive(Debug, Default)]
+pub struct Connections {
+    open: AtomicUsize,
+    max: Option<usize>,
+    waiting: Mutex<Vec<Waker>>,
+}
+
+impl Connections {
+    /// A counter for connections, allowing at most `max` open at once if
+    /// `max` is `Some`.
+    pub fn new(max: Option<usize>) -> Self {
+        Connections { max, ..Default::default() }
+    }
+
+    /// The number of connections that are currently open.
+    pub fn open(&self) -> usize {
+        self.open.load(Ordering::Acquire)
+    }
+
+    /// The maximum number of open connections, if limited.
+    pub fn max(&self) -> Option<usize> {
+        self.max
+    }
+
+    /// Takes a slot for an accepted connection, waiting while all are taken.
+    pub(crate) fn poll_reserve(self: &Arc<Self>, cx: &mut Context<'_>) -> Poll<Slot> {
+        let mut registered = false;
+        loop {
+            let open = self.open.load(Ordering::Acquire);
+            if self.max.map_or(false, |max| open >= max) {
+                // Register before checking again to not miss a wakeup.
+                if registered {
+                    return Poll::Pending;
+                }
+
+                self.waiting.lock().unwrap().push(cx.waker().clone());
+                registered = true;
+                continue;
+            }
+
+            let (success, failure) = (Ordering::AcqRel, Ordering::Acquire);
+            if self.open.compare_exchange(open, open + 1, success, failure).is_ok() {
+                return Poll::Ready(Slot { connections: self.clone() });
+            }
+        }
+    }
+}
+
+/// An open connection's slot in [`Connections`], freed on drop.
+pub(crate) struct Slot {
+    connections: Arc<Connections>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.connections.open.fetch_sub(1, Ordering::AcqRel);
+        let waiting = std::mem::take(&mut *self.connections.waiting.lock().unwrap());
+        waiting.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl fmt::Debug for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Slot").finish()
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A connection accepted by [`Incoming`], counted in its [`Connections`],
+    /// if any, until it is dropped.
+    #[derive(Debug)]
+    pub struct Counted<C> {
+        #[pin]
+        conn: C,
+        slot: Option<Slot>,
+    }
+}
+
+impl<C> Counted<C> {
+    /// The underlying connection.
+    pub fn get_ref(&self) -> &C {
+        &self.conn
+    }
+}
+
+impl<C: AsyncRead> AsyncRead for Counted<C> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.project().conn.poll_read(cx, buf)
+    }
+}
+
+impl<C: AsyncWrite> AsyncWrite for Counted<C> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().conn.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().conn.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().conn.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.project().conn.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.conn.is_write_vectored()
+    }
+}
+
+impl<C: Connection> Connection for Counted<C> {
+    fn peer_address(&self) -> Option<BindableAddr> {
+        self.conn.peer_address()
+    }
+
+    fn enable_nodelay(&self) -> io::Result<()> {
+        self.conn.enable_nodelay()
+    }
+
+    fn peer_certificates(&self) -> Option<Certificates> {
+        self.conn.peer_certificates()
+    }
+
+    fn tls_handshake(&self) -> Option<Handshake> {
+        self.conn.tls_handshake()
+    }
+
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        self.conn.proxy_header()
+    }
+
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        self.conn.peer_credentials()
+    }
 }
 
 pin_project_lite::pin_project! {
@@ -94,9 +324,12 @@ pin_project_lite::pin_project! {
     /// sockets. It does so by bridging the `Listener` trait to what hyper wants (an
     /// Accept). This type is internal to Rocket.
     #[must_use = "streams do nothing unless polled"]
-    pub struct Incoming<L> {
+    pub struct Incoming<L: Listener> {
         sleep_on_errors: Option<Duration>,
         nodelay: bool,
+        connections: Option<Arc<Connections>>,
+        // A connection accepted while all slots were taken.
+        held: Option<L::Connection>,
         #[pin]
         pending_error_delay: Option<Sleep>,
         #[pin]
@@ -112,9 +345,19 @@ impl<L: Listener> Incoming<L> {
             sleep_on_errors: Some(Duration::from_millis(250)),
             pending_error_delay: None,
             nodelay: false,
+            connections: None,
+            held: None,
         }
     }
 
+    /// Count the open connections in `connections`, sharable with other
+    /// `Incoming`s, and limit them to its maximum. By default, connections
+    /// aren't counted or limited.
+    pub fn connections(mut self, connections: Arc<Connections>) -> Self {
+        self.connections = Some(connections);
+        self
+    }
+
     /// Set whether and how long to sleep on accept errors.
     ///
     /// A possible scenario is that the process has hit the max open files
@@ -145,7 +388,7 @@ impl<L: Listener> Incoming<L> {
     fn poll_accept_next(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
-    ) -> Poll<io::Result<L::Connection>> {
+    ) -> Poll<io::Result<Counted<L::Connection>>> {
         /// This function defines per-connection errors: errors that affect only
         /// a single connection. Since the error affects only one connection, we
         /// can attempt to `accept()` another connection immediately. All other
@@ -169,15 +412,33 @@ impl<L: Listener> Incoming<L> {
 
             this.pending_error_delay.set(None);
 
-            match futures::ready!(this.listener.as_mut().poll_accept(cx)) {
+            // Serve a held connection before accepting another.
+            let accepted = match this.held.take() {
+                Some(stream) => Ok(stream),
+                None => futures::ready!(this.listener.as_mut().poll_accept(cx)),
+            };
+
+            match accepted {
                 Ok(stream) => {
+                    // Hold the connection until a slot is free.
+                    let slot = match &this.connections {
+                        Some(connections) => match connections.poll_reserve(cx) {
+                            Poll::Ready(slot) => Some(slot),
+                            Poll::Pending => {
+                                *this.held = Some(stream);
+                                return Poll::Pending;
+                            }
+                        },
+                        None => None,
+                    };
+
                     if *this.nodelay {
                         if let Err(e) = stream.enable_nodelay() {
                             warn!("failed to enable NODELAY: {}", e);
                         }
                     }
 
-                    return Poll::Ready(Ok(stream));
+                    return Poll::Ready(Ok(Counted { conn: stream, slot }));
                 }
                 Err(e) => {
                     if is_connection_error(&e) {
@@ -201,7 +462,7 @@ impl<L: Listener> Incoming<L> {
 }
 
 impl<L: Listener> Accept for Incoming<L> {
-    type Conn = L::Connection;
+    type Conn = Counted<L::Connection>;
     type Error = io::Error;
 
     #[inline]
@@ -213,7 +474,7 @@ impl<L: Listener> Accept for Incoming<L> {
     }
 }
 
-impl<L: fmt::Debug> fmt::Debug for Incoming<L> {
+impl<L: Listener + fmt::Debug> fmt::Debug for Incoming<L> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Incoming")
             .field("listener", &self.listener)
@@ -255,51 +516,442 @@ impl Connection for TcpStream {
     }
 }
 
+/// Options for binding a Unix domain socket with [`bind_unix()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnixOptions {
+    /// The permissions to set on the socket file, if any.
+    pub mode: Option<u32>,
+    /// The user, by name or numeric ID, to make the owner of the socket file.
+    pub owner: Option<String>,
+    /// The group, by name or numeric ID, to make the group of the socket file.
+    pub group: Option<String>,
+    /// Whether to remove an existing socket file that nothing is listening on
+    /// before binding.
+    pub remove_stale: bool,
+}
+
+impl UnixOptions {
+    /// Whether any of the options apply to the socket file.
+    fn changes_file(&self) -> bool {
+        self.mode.is_some() || self.owner.is_some() || self.group.is_some()
+    }
+}
+
 #[cfg(unix)]
 mod platform {
 
-    use super::{Connection, Listener};
+    use super::{Connection, Inherited, Listener, PeerCredentials, TcpListener, UnixOptions};
     use crate::bindable::BindableAddr;
+    use state::Storage;
+    use std::ffi::CString;
     use std::io;
-    use std::path::Path;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+    use std::os::unix::io::{FromRawFd, RawFd};
+    use std::path::{Path, PathBuf};
     use std::pin::Pin;
+    use std::sync::Mutex;
     use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
     use tokio::net::{UnixListener, UnixStream};
 
-    #[repr(transparent)]
-    pub struct UnixListenerWrapper(UnixListener);
+    /// A Unix socket listener that removes its socket file when dropped.
+    pub struct UnixListenerWrapper {
+        listener: UnixListener,
+        /// The socket file to remove on drop, if any, with its device and
+        /// inode numbers. Sockets inherited from another process are left in
+        /// place for it to hand out again, and a file that has since been
+        /// replaced, say by another instance binding to the same path, is left
+        /// in place for that instance.
+        unlink: Option<(PathBuf, u64, u64)>,
+        /// The name of the socket, if it is in the abstract namespace.
+        abstract_name: Option<PathBuf>,
+    }
 
     impl std::ops::Deref for UnixListenerWrapper {
         type Target = UnixListener;
         fn deref(&self) -> &Self::Target {
-            &self.0
+            &self.listener
         }
     }
     impl std::ops::DerefMut for UnixListenerWrapper {
         fn deref_mut(&mut self) -> &mut UnixListener {
-            &mut self.0
+            &mut self.listener
         }
     }
     impl Drop for UnixListenerWrapper {
         fn drop(&mut self) {
-            if let Ok(addr) = self.0.local_addr() {
-                if let Some(path) = addr.as_pathname() {
-                    let _ = std::fs::remove_file(path);
+            if let Some((path, dev, ino)) = &self.unlink {
+                match std::fs::symlink_metadata(path) {
+                    Ok(meta) if meta.dev() == *dev && meta.ino() == *ino => {
+                        let _ = std::fs::remove_file(path);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn invalid_input<E>(error: E) -> io::Error
+        where E: Into<Box<dyn std::error::Error + Send + Sync>>
+    {
+        io::Error::new(io::ErrorKind::InvalidInput, error)
+    }
+
+    /// Binds a Unix socket listener to `path` with `options` and returns it.
+    ///
+    /// A `path` beginning with `@` names a socket in the Linux abstract
+    /// namespace, which has no socket file: `options` must not set its mode,
+    /// owner, or group.
+    pub fn bind_unix(path: &Path, options: &UnixOptions) -> io::Result<UnixListenerWrapper> {
+        if let Some(name) = path.as_os_str().as_bytes().strip_prefix(b"@") {
+            if options.changes_file() {
+                let msg = "abstract sockets have no file to set a mode, owner, or group on";
+                return Err(invalid_input(msg));
+            }
+
+            let listener = bind_abstract(name)?;
+            let abstract_name = Some(path.to_owned());
+            return Ok(UnixListenerWrapper { listener, unlink: None, abstract_name });
+        }
+
+        if options.remove_stale {
+            remove_stale(path)?;
+        }
+
+        let result = if options.changes_file() {
+            bind_private(path, options)
+        } else {
+            UnixListener::bind(path)
+        };
+
+        let listener = match result {
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+                let msg = format!("{}: {}; is another process listening on it?", path.display(), e);
+                return Err(io::Error::new(e.kind(), msg));
+            }
+            result => result?,
+        };
+
+        let meta = std::fs::symlink_metadata(path)?;
+        Ok(UnixListenerWrapper {
+            listener,
+            unlink: Some((path.to_owned(), meta.dev(), meta.ino())),
+            abstract_name: None,
+        })
+    }
+
+    /// Binds a listener to `path` whose socket file has the mode, owner, and
+    /// group in `options` from the moment it appears at `path`.
+    ///
+    /// The socket is bound in a private directory, only accessible to this
+    /// process's user, and changed there before it is linked to `path`. Unlike
+    /// a rename, the link fails if `path` exists, so a socket in use is never
+    /// replaced.
+    fn bind_private(path: &Path, options: &UnixOptions) -> io::Result<UnixListener> {
+        use std::os::unix::fs::DirBuilderExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        let id = NEXT.fetch_add(1, Ordering::Relaxed);
+        let dir = parent.join(format!(".rocket-{}-{}", std::process::id(), id));
+        std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+
+        let private = dir.join("s");
+        let result = UnixListener::bind(&private).and_then(|listener| {
+            if let Some(mode) = options.mode {
+                std::fs::set_permissions(&private, std::fs::Permissions::from_mode(mode))?;
+            }
+
+            if options.owner.is_some() || options.group.is_some() {
+                let uid = options.owner.as_deref().map(user_id).transpose()?;
+                let gid = options.group.as_deref().map(group_id).transpose()?;
+                chown(&private, uid, gid)?;
+            }
+
+            match std::fs::hard_link(&private, path) {
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    Err(io::ErrorKind::AddrInUse.into())
                 }
+                result => result.map(|_| listener),
+            }
+        });
+
+        let _ = std::fs::remove_file(&private);
+        let _ = std::fs::remove_dir(&dir);
+        result
+    }
+
+    /// Removes the socket file at `path` if nothing is listening on it. A file
+    /// that isn't a socket, or that is being listened on, is left in place.
+    fn remove_stale(path: &Path) -> io::Result<()> {
+        match std::fs::symlink_metadata(path) {
+            Ok(meta) if meta.file_type().is_socket() => {},
+            Ok(_) => return Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        }
+
+        match std::os::unix::net::UnixStream::connect(path) {
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+                log::warn!("removing stale Unix socket {}", path.display());
+                std::fs::remove_file(path)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Binds a listener to `name` in the abstract namespace.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn bind_abstract(name: &[u8]) -> io::Result<UnixListener> {
+        let check = |result: i32| match result {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        };
+
+        // SAFETY: `addr` is zeroed, and the name copied into `sun_path` after
+        // its leading NUL fits as checked; `len` covers exactly the family,
+        // the NUL, and the name. `fd` is a new socket owned by the listener.
+        unsafe {
+            let mut addr: libc::sockaddr_un = std::mem::zeroed();
+            if name.len() >= addr.sun_path.len() {
+                return Err(invalid_input("abstract socket name is too long"));
+            }
+
+            addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+            for (dst, src) in addr.sun_path[1..].iter_mut().zip(name) {
+                *dst = *src as std::os::raw::c_char;
+            }
+
+            let fd = libc::socket(libc::AF_UNIX, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0);
+            check(fd)?;
+            let listener = std::os::unix::net::UnixListener::from_raw_fd(fd);
+
+            let offset = addr.sun_path.as_ptr() as usize - &addr as *const _ as usize;
+            let len = (offset + 1 + name.len()) as libc::socklen_t;
+            let ptr = &addr as *const libc::sockaddr_un as *const libc::sockaddr;
+            check(libc::bind(fd, ptr, len))?;
+            check(libc::listen(fd, 1024))?;
+
+            listener.set_nonblocking(true)?;
+            UnixListener::from_std(listener)
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn bind_abstract(_: &[u8]) -> io::Result<UnixListener> {
+        Err(invalid_input("abstract sockets are only supported on Linux"))
+    }
+
+    /// Returns the ID of `user`, a user name or numeric ID.
+    fn user_id(user: &str) -> io::Result<libc::uid_t> {
+        if let Ok(id) = user.parse() {
+            return Ok(id);
+        }
+
+        // SAFETY: `name` and `buf` outlive the call, and `len` is the length
+        // of `buf`. `passwd` is only read if the lookup found the user.
+        let name = CString::new(user).map_err(invalid_input)?;
+        lookup(|buf, len| unsafe {
+            let mut passwd: libc::passwd = std::mem::zeroed();
+            let mut found = std::ptr::null_mut();
+            match libc::getpwnam_r(name.as_ptr(), &mut passwd, buf, len, &mut found) {
+                0 if found.is_null() => Ok(None),
+                0 => Ok(Some(passwd.pw_uid)),
+                e => Err(e),
             }
+        })?.ok_or_else(|| invalid_input(format!("unknown user {:?}", user)))
+    }
+
+    /// Returns the ID of `group`, a group name or numeric ID.
+    fn group_id(group: &str) -> io::Result<libc::gid_t> {
+        if let Ok(id) = group.parse() {
+            return Ok(id);
         }
+
+        // SAFETY: As in `user_id()`.
+        let name = CString::new(group).map_err(invalid_input)?;
+        lookup(|buf, len| unsafe {
+            let mut entry: libc::group = std::mem::zeroed();
+            let mut found = std::ptr::null_mut();
+            match libc::getgrnam_r(name.as_ptr(), &mut entry, buf, len, &mut found) {
+                0 if found.is_null() => Ok(None),
+                0 => Ok(Some(entry.gr_gid)),
+                e => Err(e),
+            }
+        })?.ok_or_else(|| invalid_input(format!("unknown group {:?}", group)))
     }
 
-    /// Binds a Unix socket listener to `path` and returns it.
-    pub fn bind_unix(path: &Path) -> io::Result<UnixListenerWrapper> {
-        UnixListener::bind(path).map(UnixListenerWrapper)
+    /// Calls a reentrant `get*nam_r` lookup `f` with a buffer and its length,
+    /// growing the buffer while `f` reports that it is too small.
+    fn lookup<T, F>(mut f: F) -> io::Result<Option<T>>
+        where F: FnMut(*mut std::os::raw::c_char, libc::size_t) -> Result<Option<T>, i32>
+    {
+        let mut buf = vec![0 as std::os::raw::c_char; 1024];
+        loop {
+            match f(buf.as_mut_ptr(), buf.len()) {
+                Err(libc::ERANGE) if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+                Err(e) => return Err(io::Error::from_raw_os_error(e)),
+                Ok(value) => return Ok(value),
+            }
+        }
+    }
+
+    /// Changes the owner and/or group of `path`, leaving `None`s unchanged.
+    fn chown(path: &Path, uid: Option<libc::uid_t>, gid: Option<libc::gid_t>) -> io::Result<()> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(invalid_input)?;
+        let uid = uid.unwrap_or(libc::uid_t::MAX);
+        let gid = gid.unwrap_or(libc::gid_t::MAX);
+
+        // SAFETY: `path` is a NUL-terminated string that outlives the call.
+        match unsafe { libc::chown(path.as_ptr(), uid, gid) } {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// `SD_LISTEN_FDS_START`: the first file descriptor passed by socket
+    /// activation. The remaining ones follow consecutively.
+    const LISTEN_FDS_START: RawFd = 3;
+
+    /// The inherited file descriptors that have been adopted so far.
+    static ADOPTED: Storage<Mutex<Vec<RawFd>>> = Storage::new();
+
+    fn activation_error<E>(error: E) -> io::Error
+        where E: Into<Box<dyn std::error::Error + Send + Sync>>
+    {
+        io::Error::new(io::ErrorKind::Other, error)
+    }
+
+    /// Returns the file descriptors passed to this process along with their
+    /// names, if any, as described by `LISTEN_PID`, `LISTEN_FDS`, and
+    /// `LISTEN_FDNAMES`.
+    fn passed_fds() -> io::Result<Vec<(RawFd, Option<String>)>> {
+        let var = |name| std::env::var(name).ok();
+        let pid = var("LISTEN_PID").and_then(|pid| pid.parse::<u32>().ok());
+        if pid != Some(std::process::id()) {
+            return Err(activation_error("no sockets were passed to this process: \
+                `LISTEN_PID` is missing or names another process"));
+        }
+
+        let count = var("LISTEN_FDS")
+            .and_then(|count| count.parse::<RawFd>().ok())
+            .ok_or_else(|| activation_error("`LISTEN_FDS` is missing or invalid"))?;
+
+        let names = var("LISTEN_FDNAMES").unwrap_or_default();
+        let mut names = names.split(':');
+        Ok((0..count.max(0))
+            .map(|i| (LISTEN_FDS_START + i, names.next().map(String::from)))
+            .collect())
+    }
+
+    /// Checks that `fd` is a listening socket, returning its address family.
+    fn listening_family(fd: RawFd) -> io::Result<i32> {
+        let check = |result: i32| match result {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        };
+
+        // SAFETY: The pointers refer to live locals whose sizes are passed.
+        unsafe {
+            let mut listening: i32 = 0;
+            let mut len = std::mem::size_of::<i32>() as libc::socklen_t;
+            let ptr = &mut listening as *mut i32 as *mut std::ffi::c_void;
+            check(libc::getsockopt(fd, libc::SOL_SOCKET, libc::SO_ACCEPTCONN, ptr, &mut len))?;
+            if listening == 0 {
+                let msg = format!("file descriptor {} is not a listening socket", fd);
+                return Err(activation_error(msg));
+            }
+
+            let mut addr: libc::sockaddr_storage = std::mem::zeroed();
+            let mut len = std::mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+            let ptr = &mut addr as *mut libc::sockaddr_storage as *mut libc::sockaddr;
+            check(libc::getsockname(fd, ptr, &mut len))?;
+            check(libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+            Ok(addr.ss_family as i32)
+        }
+    }
+
+    /// Adopts the listening socket inherited via socket activation that is
+    /// identified by `address`, a `BindableAddr::Fd` or `BindableAddr::Systemd`.
+    ///
+    /// Each inherited socket can be adopted at most once.
+    pub fn bind_inherited(address: &BindableAddr) -> io::Result<Inherited> {
+        let passed = passed_fds()?;
+        let mut adopted = ADOPTED.get_or_set(|| Mutex::new(vec![]))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let fd = match address {
+            BindableAddr::Fd(fd) => {
+                if !passed.iter().any(|(passed, _)| passed == fd) {
+                    let msg = format!("file descriptor {} was not passed to this process", fd);
+                    return Err(activation_error(msg));
+                }
+
+                if adopted.contains(fd) {
+                    let msg = format!("file descriptor {} is already in use", fd);
+                    return Err(activation_error(msg));
+                }
+
+                *fd
+            }
+            BindableAddr::Systemd(name) => passed.iter()
+                .filter(|(_, passed)| passed.as_deref() == Some(name))
+                .map(|(fd, _)| *fd)
+                .find(|fd| !adopted.contains(fd))
+                .ok_or_else(|| {
+                    activation_error(format!("no unused socket named {:?} was passed to this \
+                        process", name))
+                })?,
+            _ => return Err(activation_error(format!("{} is not an inherited socket", address))),
+        };
+
+        let family = listening_family(fd)?;
+        adopted.push(fd);
+
+        // SAFETY: `fd` is an open socket that we were passed and that is now
+        // marked as adopted, so ownership of it is taken at most once.
+        match family {
+            libc::AF_INET | libc::AF_INET6 => {
+                let listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+                listener.set_nonblocking(true)?;
+                Ok(Inherited::Tcp(TcpListener::from_std(listener)?))
+            }
+            libc::AF_UNIX => {
+                let listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(fd) };
+                listener.set_nonblocking(true)?;
+                let listener = UnixListener::from_std(listener)?;
+                let (unlink, abstract_name) = (None, None);
+                Ok(Inherited::Unix(UnixListenerWrapper { listener, unlink, abstract_name }))
+            }
+            family => {
+                Err(activation_error(format!("file descriptor {} has unsupported address \
+                    family {}", fd, family)))
+            }
+        }
     }
 
     impl Listener for UnixListenerWrapper {
-        type Connection = UnixStream;
+        type Connection = UnixStreamWrapper;
 
         fn local_addr(&self) -> Option<BindableAddr> {
-            self.0.local_addr().ok().and_then(|addr| {
+            if let Some(name) = &self.abstract_name {
+                return Some(BindableAddr::Unix(name.clone()));
+            }
+
+            // The socket may have been bound at a private path and linked.
+            if let Some((path, ..)) = &self.unlink {
+                return Some(BindableAddr::Unix(path.clone()));
+            }
+
+            self.listener.local_addr().ok().and_then(|addr| {
                 addr.as_pathname()
                     .map(|path| BindableAddr::Unix(path.to_owned()))
             })
@@ -309,13 +961,68 @@ mod platform {
             self: Pin<&mut Self>,
             cx: &mut Context<'_>,
         ) -> Poll<io::Result<Self::Connection>> {
-            (*self).poll_accept(cx).map_ok(|(stream, _addr)| stream)
+            (*self).poll_accept(cx).map_ok(|(stream, _addr)| {
+                let credentials = stream.peer_cred().ok().map(|cred| PeerCredentials {
+                    uid: cred.uid(),
+                    gid: cred.gid(),
+                    pid: cred.pid(),
+                });
+
+                UnixStreamWrapper { stream, credentials }
+            })
+        }
+    }
+
+    /// A Unix socket connection along with the credentials of its peer, read
+    /// when it was accepted.
+    pub struct UnixStreamWrapper {
+        stream: UnixStream,
+        credentials: Option<PeerCredentials>,
+    }
+
+    impl AsyncRead for UnixStreamWrapper {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.stream).poll_read(cx, buf)
         }
     }
 
-    impl Connection for UnixStream {
+    impl AsyncWrite for UnixStreamWrapper {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.stream).poll_write(cx, buf)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.stream).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.stream).poll_shutdown(cx)
+        }
+
+        fn poll_write_vectored(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[io::IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.stream).poll_write_vectored(cx, bufs)
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            self.stream.is_write_vectored()
+        }
+    }
+
+    impl Connection for UnixStreamWrapper {
         fn peer_address(&self) -> Option<BindableAddr> {
-            self.peer_addr().ok().and_then(|addr| {
+            self.stream.peer_addr().ok().and_then(|addr| {
                 addr.as_pathname()
                     .map(|path| BindableAddr::Unix(path.to_owned()))
             })
@@ -324,13 +1031,17 @@ mod platform {
         fn enable_nodelay(&self) -> std::io::Result<()> {
             Ok(())
         }
+
+        fn peer_credentials(&self) -> Option<PeerCredentials> {
+            self.credentials
+        }
     }
 }
 
 #[cfg(windows)]
 mod platform {
 
-    use super::{Connection, Listener};
+    use super::{Connection, Inherited, Listener, UnixOptions};
     use crate::bindable::BindableAddr;
     use std::io;
     use std::path::Path;
@@ -339,11 +1050,37 @@ mod platform {
     use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
     use uds_windows::{UnixListener as SyncListener, UnixStream as SyncStream};
 
-    /// Binds a Unix socket listener to `path` and returns it.
-    pub fn bind_unix(path: &Path) -> io::Result<UnixListenerWrapper> {
+    /// Binds a Unix socket listener to `path` with `options` and returns it.
+    ///
+    /// Socket files have no Unix mode, owner, or group on Windows, and
+    /// abstract sockets are not supported.
+    pub fn bind_unix(path: &Path, options: &UnixOptions) -> io::Result<UnixListenerWrapper> {
+        let unsupported = |msg| Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        if options.changes_file() {
+            return unsupported("setting a socket's mode, owner, or group requires Unix");
+        } else if path.to_str().map_or(false, |path| path.starts_with('@')) {
+            return unsupported("abstract sockets are only supported on Linux");
+        }
+
+        if options.remove_stale && path.exists() {
+            if let Err(e) = SyncStream::connect(path) {
+                if e.kind() == io::ErrorKind::ConnectionRefused {
+                    log::warn!("removing stale Unix socket {}", path.display());
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+
         SyncListener::bind(path).and_then(UnixListenerWrapper::new)
     }
 
+    /// Socket activation is not supported on Windows: always returns an error.
+    pub fn bind_inherited(address: &BindableAddr) -> io::Result<Inherited> {
+        let msg = format!("{}: socket activation is only supported on Unix platforms", address);
+        Err(io::Error::new(io::ErrorKind::Other, msg))
+    }
+
+    /// A Unix socket listener that removes its socket file when dropped.
     #[repr(transparent)]
     pub struct UnixListenerWrapper(SyncListener);
 
@@ -461,4 +1198,13 @@ mod platform {
     }
 }
 
-pub use platform::bind_unix;
+pub use platform::{bind_inherited, bind_unix, UnixListenerWrapper};
+
+/// A listening socket inherited from the parent process via socket activation,
+/// as returned by [`bind_inherited()`].
+pub enum Inherited {
+    /// An inherited TCP socket.
+    Tcp(TcpListener),
+    /// An inherited Unix domain socket.
+    Unix(UnixListenerWrapper),
+}
@@ -0,0 +1,899 @@
+//! Support for the PROXY protocol, versions 1 and 2.
+//!
+//! Proxies and load balancers that forward TCP connections, like HAProxy or
+//! AWS Network Load Balancers, can prefix each connection with a PROXY
+//! protocol header that describes the original client connection. A
+//! [`ProxyListener`] reads and removes the header from connections made by
+//! trusted peers, making the client's address the connection's
+//! [`peer_address()`](crate::private::Connection::peer_address()) and the
+//! parsed [`ProxyHeader`] available via
+//! [`proxy_header()`](crate::private::Connection::proxy_header()).
+//!
+//! See the [specification] for details on the protocol.
+//!
+//! [specification]: https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt
+
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::stream::{FuturesUnordered, Stream};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+use crate::bindable::BindableAddr;
+use crate::listener::{Certificates, Connection, Connections, Handshake, Listener};
+use crate::listener::{PeerCredentials, Slot};
+
+#[doc(inline)]
+pub use ipnet::IpNet;
+
+/// The signature that starts a version 2 header.
+const V2_SIGNATURE: &[u8; 12] = b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The most connections whose headers are read at once when connections
+/// aren't otherwise limited.
+const MAX_PENDING: usize = 1024;
+
+/// The prefix that starts a version 1 header.
+const V1_PREFIX: &[u8; 6] = b"PROXY ";
+
+/// The maximum length of a version 1 header, including the `\r\n`.
+const V1_MAX_LEN: usize = 107;
+
+/// The command in a PROXY protocol header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// The connection was established by the proxy itself, for example for a
+    /// health check, and the address information, if any, is meaningless.
+    Local,
+    /// The connection was established on behalf of another node.
+    Proxy,
+}
+
+/// A type-length-value field in a version 2 PROXY protocol header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tlv {
+    /// The type of the field.
+    pub kind: u8,
+    /// The value of the field.
+    pub value: Vec<u8>,
+}
+
+impl Tlv {
+    /// `PP2_TYPE_ALPN`: the application protocol negotiated by the client.
+    pub const ALPN: u8 = 0x01;
+    /// `PP2_TYPE_AUTHORITY`: the host name sent by the client, i.e, the SNI.
+    pub const AUTHORITY: u8 = 0x02;
+    /// `PP2_TYPE_CRC32C`: a checksum of the header, which is verified.
+    pub const CRC32C: u8 = 0x03;
+    /// `PP2_TYPE_NOOP`: padding, to be ignored.
+    pub const NOOP: u8 = 0x04;
+    /// `PP2_TYPE_UNIQUE_ID`: an opaque identifier for the connection.
+    pub const UNIQUE_ID: u8 = 0x05;
+    /// `PP2_TYPE_SSL`: information about a TLS connection to the proxy.
+    pub const SSL: u8 = 0x20;
+    /// `PP2_SUBTYPE_SSL_VERSION`: the TLS version, within [`Tlv::SSL`].
+    pub const SSL_VERSION: u8 = 0x21;
+    /// `PP2_SUBTYPE_SSL_CN`: the client certificate's common name, within
+    /// [`Tlv::SSL`].
+    pub const SSL_CN: u8 = 0x22;
+    /// `PP2_SUBTYPE_SSL_CIPHER`: the cipher in use, within [`Tlv::SSL`].
+    pub const SSL_CIPHER: u8 = 0x23;
+    /// `PP2_SUBTYPE_SSL_SIG_ALG`: the algorithm used to sign the client
+    /// certificate, within [`Tlv::SSL`].
+    pub const SSL_SIG_ALG: u8 = 0x24;
+    /// `PP2_SUBTYPE_SSL_KEY_ALG`: the algorithm of the client certificate's
+    /// key, within [`Tlv::SSL`].
+    pub const SSL_KEY_ALG: u8 = 0x25;
+    /// `PP2_TYPE_NETNS`: the name of the network namespace the connection was
+    /// accepted in.
+    pub const NETNS: u8 = 0x30;
+
+    /// Parses a sequence of TLVs that makes up all of `bytes`.
+    fn parse_all(mut bytes: &[u8]) -> Result<Vec<Tlv>, InvalidHeader> {
+        let mut tlvs = vec![];
+        while !bytes.is_empty() {
+            if bytes.len() < 3 {
+                return Err(InvalidHeader("truncated TLV"));
+            }
+
+            let len = u16::from_be_bytes([bytes[1], bytes[2]]) as usize;
+            let value = bytes.get(3..(3 + len)).ok_or(InvalidHeader("truncated TLV"))?;
+            tlvs.push(Tlv { kind: bytes[0], value: value.to_vec() });
+            bytes = &bytes[(3 + len)..];
+        }
+
+        Ok(tlvs)
+    }
+}
+
+/// Returns the value of the first TLV of type `kind` in `tlvs`.
+fn find(tlvs: &[Tlv], kind: u8) -> Option<&[u8]> {
+    tlvs.iter().find(|tlv| tlv.kind == kind).map(|tlv| &*tlv.value)
+}
+
+/// Returns the value of the first TLV of type `kind` in `tlvs` as a string.
+fn find_str(tlvs: &[Tlv], kind: u8) -> Option<&str> {
+    find(tlvs, kind).and_then(|value| std::str::from_utf8(value).ok())
+}
+
+/// The information in a [`Tlv::SSL`] field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ssl {
+    /// A bit field: `0x01` if the client connected over TLS, `0x02` if it
+    /// presented a certificate over the connection, and `0x04` if it
+    /// presented one at least once over the TLS session.
+    pub client: u8,
+    /// Zero if the client presented a certificate that was verified.
+    pub verify: u32,
+    /// The `SSL_*` sub-fields.
+    pub tlvs: Vec<Tlv>,
+}
+
+impl Ssl {
+    /// Returns `true` if the client connected to the proxy over TLS.
+    pub fn is_tls(&self) -> bool {
+        self.client & 0x01 != 0
+    }
+
+    /// Returns `true` if the client presented a certificate that the proxy
+    /// verified.
+    pub fn verified_certificate(&self) -> bool {
+        self.client & 0x06 != 0 && self.verify == 0
+    }
+
+    /// The TLS version, like `TLSv1.3`, from [`Tlv::SSL_VERSION`].
+    pub fn version(&self) -> Option<&str> {
+        find_str(&self.tlvs, Tlv::SSL_VERSION)
+    }
+
+    /// The common name of the client certificate, from [`Tlv::SSL_CN`].
+    pub fn common_name(&self) -> Option<&str> {
+        find_str(&self.tlvs, Tlv::SSL_CN)
+    }
+
+    /// The name of the cipher in use, from [`Tlv::SSL_CIPHER`].
+    pub fn cipher(&self) -> Option<&str> {
+        find_str(&self.tlvs, Tlv::SSL_CIPHER)
+    }
+}
+
+/// A parsed PROXY protocol header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyHeader {
+    version: u8,
+    command: Command,
+    source: Option<BindableAddr>,
+    destination: Option<BindableAddr>,
+    tlvs: Vec<Tlv>,
+}
+
+/// The error returned when a PROXY protocol header is malformed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidHeader(&'static str);
+
+impl fmt::Display for InvalidHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid PROXY protocol header: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidHeader {}
+
+impl From<InvalidHeader> for io::Error {
+    fn from(error: InvalidHeader) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+impl ProxyHeader {
+    /// Parses the PROXY protocol header at the start of `bytes`.
+    ///
+    /// Returns `Ok(Some((header, len)))` if `bytes` starts with a valid header
+    /// that is `len` bytes long, `Ok(None)` if `bytes` is a prefix of what may
+    /// be a valid header, and `Err` otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// use rocket::http::proxy_protocol::ProxyHeader;
+    ///
+    /// let bytes = b"PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\nGET /";
+    /// let (header, len) = ProxyHeader::parse(bytes).unwrap().unwrap();
+    /// assert_eq!(header.source().unwrap().to_string(), "tcp://192.0.2.1:56324");
+    /// assert_eq!(&bytes[len..], b"GET /");
+    ///
+    /// assert!(ProxyHeader::parse(b"PROXY TCP4 192.0").unwrap().is_none());
+    /// assert!(ProxyHeader::parse(b"GET / HTTP/1.1\r\n").is_err());
+    /// ```
+    pub fn parse(bytes: &[u8]) -> Result<Option<(ProxyHeader, usize)>, InvalidHeader> {
+        let is_prefix = |sig: &[u8]| {
+            let n = bytes.len().min(sig.len());
+            bytes[..n] == sig[..n]
+        };
+
+        if bytes.len() < V2_SIGNATURE.len() && is_prefix(V2_SIGNATURE) {
+            Ok(None)
+        } else if bytes.starts_with(V2_SIGNATURE) {
+            ProxyHeader::parse_v2(bytes)
+        } else if is_prefix(V1_PREFIX) {
+            ProxyHeader::parse_v1(bytes)
+        } else {
+            Err(InvalidHeader("unrecognized signature"))
+        }
+    }
+
+    fn parse_v1(bytes: &[u8]) -> Result<Option<(ProxyHeader, usize)>, InvalidHeader> {
+        let end = match bytes.windows(2).position(|w| w == b"\r\n") {
+            Some(end) if end + 2 <= V1_MAX_LEN => end,
+            Some(_) => return Err(InvalidHeader("version 1 header is too long")),
+            None if bytes.len() < V1_MAX_LEN => return Ok(None),
+            None => return Err(InvalidHeader("version 1 header is too long")),
+        };
+
+        let line = std::str::from_utf8(&bytes[V1_PREFIX.len()..end])
+            .map_err(|_| InvalidHeader("version 1 header is not ASCII"))?;
+
+        let mut parts = line.split(' ');
+        let (source, destination) = match parts.next() {
+            Some("UNKNOWN") => (None, None),
+            Some(family @ ("TCP4" | "TCP6")) => {
+                let mut next = || parts.next().ok_or(InvalidHeader("missing address"));
+                let (src, dst, sport, dport) = (next()?, next()?, next()?, next()?);
+                if parts.next().is_some() {
+                    return Err(InvalidHeader("trailing data in version 1 header"));
+                }
+
+                let ip = |ip: &str| match (family, IpAddr::from_str(ip)) {
+                    ("TCP4", Ok(ip @ IpAddr::V4(_))) | ("TCP6", Ok(ip @ IpAddr::V6(_))) => Ok(ip),
+                    _ => Err(InvalidHeader("invalid address")),
+                };
+
+                // Ports are decimal numbers without leading zeros.
+                let port = |port: &str| match port.parse::<u16>() {
+                    Ok(n) if n.to_string() == port => Ok(n),
+                    _ => Err(InvalidHeader("invalid port")),
+                };
+
+                let source = SocketAddr::new(ip(src)?, port(sport)?);
+                let destination = SocketAddr::new(ip(dst)?, port(dport)?);
+                (Some(BindableAddr::Tcp(source)), Some(BindableAddr::Tcp(destination)))
+            }
+            _ => return Err(InvalidHeader("unknown protocol in version 1 header")),
+        };
+
+        let header = ProxyHeader {
+            version: 1,
+            command: Command::Proxy,
+            source,
+            destination,
+            tlvs: vec![],
+        };
+
+        Ok(Some((header, end + 2)))
+    }
+
+    fn parse_v2(bytes: &[u8]) -> Result<Option<(ProxyHeader, usize)>, InvalidHeader> {
+        if bytes.len() < 16 {
+            return Ok(None);
+        }
+
+        let command = match bytes[12] {
+            0x20 => Command::Local,
+            0x21 => Command::Proxy,
+            v if v >> 4 != 2 => return Err(InvalidHeader("unsupported version")),
+            _ => return Err(InvalidHeader("unknown command")),
+        };
+
+        let len = 16 + u16::from_be_bytes([bytes[14], bytes[15]]) as usize;
+        let header = match bytes.get(..len) {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        let (addresses, rest) = match bytes[13] >> 4 {
+            0x0 => (None, &header[16..]),
+            0x1 if len >= 16 + 12 => {
+                let b = &header[16..];
+                let ip = |i: usize| IpAddr::V4(Ipv4Addr::new(b[i], b[i + 1], b[i + 2], b[i + 3]));
+                let port = |i: usize| u16::from_be_bytes([b[i], b[i + 1]]);
+                let src = SocketAddr::new(ip(0), port(8));
+                let dst = SocketAddr::new(ip(4), port(10));
+                (Some((BindableAddr::Tcp(src), BindableAddr::Tcp(dst))), &b[12..])
+            }
+            0x2 if len >= 16 + 36 => {
+                let b = &header[16..];
+                let ip = |i: usize| {
+                    let mut octets = [0; 16];
+                    octets.copy_from_slice(&b[i..(i + 16)]);
+                    IpAddr::V6(Ipv6Addr::from(octets))
+                };
+
+                let port = |i: usize| u16::from_be_bytes([b[i], b[i + 1]]);
+                let src = SocketAddr::new(ip(0), port(32));
+                let dst = SocketAddr::new(ip(16), port(34));
+                (Some((BindableAddr::Tcp(src), BindableAddr::Tcp(dst))), &b[36..])
+            }
+            0x3 if len >= 16 + 216 => {
+                let b = &header[16..];
+                let path = |path: &[u8]| {
+                    let end = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+                    let path = std::str::from_utf8(&path[..end])
+                        .map_err(|_| InvalidHeader("invalid Unix socket path"))?;
+
+                    Ok(BindableAddr::Unix(PathBuf::from(path)))
+                };
+
+                (Some((path(&b[..108])?, path(&b[108..216])?)), &b[216..])
+            }
+            0x1..=0x3 => return Err(InvalidHeader("truncated address")),
+            _ => return Err(InvalidHeader("unknown address family")),
+        };
+
+        let tlvs = Tlv::parse_all(rest)?;
+        let mut offset = len - rest.len();
+        for tlv in &tlvs {
+            if tlv.kind == Tlv::CRC32C {
+                if tlv.value.len() != 4 {
+                    return Err(InvalidHeader("invalid CRC32C TLV"));
+                }
+
+                verify_crc32c(header, offset + 3)?;
+                break;
+            }
+
+            offset += 3 + tlv.value.len();
+        }
+
+        // The addresses of a `LOCAL` connection must be ignored.
+        let (source, destination) = match (command, addresses) {
+            (Command::Proxy, Some((src, dst))) => (Some(src), Some(dst)),
+            _ => (None, None),
+        };
+
+        let header = ProxyHeader { version: 2, command, source, destination, tlvs };
+        Ok(Some((header, len)))
+    }
+
+    /// The version of the header: `1` or `2`.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The command in the header. Always [`Command::Proxy`] for version 1.
+    pub fn command(&self) -> Command {
+        self.command
+    }
+
+    /// The address of the client, if the header contains one.
+    pub fn source(&self) -> Option<&BindableAddr> {
+        self.source.as_ref()
+    }
+
+    /// The address the client connected to, if the header contains one.
+    pub fn destination(&self) -> Option<&BindableAddr> {
+        self.destination.as_ref()
+    }
+
+    /// All TLVs in the header, in order. Always empty for version 1.
+    pub fn tlvs(&self) -> &[Tlv] {
+        &self.tlvs
+    }
+
+    /// The value of the first TLV of type `kind`, if there is one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// use rocket::http::proxy_protocol::ProxyHeader;
+    ///
+    /// fn vpc_endpoint_id(header: &ProxyHeader) -> Option<&[u8]> {
+    ///     // AWS sets a custom TLV with type `0xEA`.
+    ///     header.tlv(0xEA)
+    /// }
+    /// ```
+    pub fn tlv(&self, kind: u8) -> Option<&[u8]> {
+        find(&self.tlvs, kind)
+    }
+
+    /// The application protocol negotiated by the client, from [`Tlv::ALPN`].
+    pub fn alpn(&self) -> Option<&[u8]> {
+        self.tlv(Tlv::ALPN)
+    }
+
+    /// The host name sent by the client, from [`Tlv::AUTHORITY`].
+    pub fn authority(&self) -> Option<&str> {
+        find_str(&self.tlvs, Tlv::AUTHORITY)
+    }
+
+    /// The identifier assigned to the connection, from [`Tlv::UNIQUE_ID`].
+    pub fn unique_id(&self) -> Option<&[u8]> {
+        self.tlv(Tlv::UNIQUE_ID)
+    }
+
+    /// The network namespace the connection was accepted in, from
+    /// [`Tlv::NETNS`].
+    pub fn netns(&self) -> Option<&str> {
+        find_str(&self.tlvs, Tlv::NETNS)
+    }
+
+    /// The TLS information in the [`Tlv::SSL`] field, if there is one and it
+    /// is well-formed.
+    pub fn ssl(&self) -> Option<Ssl> {
+        let value = self.tlv(Tlv::SSL)?;
+        let verify = value.get(1..5)?;
+        let verify = u32::from_be_bytes([verify[0], verify[1], verify[2], verify[3]]);
+        let tlvs = Tlv::parse_all(&value[5..]).ok()?;
+        Some(Ssl { client: value[0], verify, tlvs })
+    }
+}
+
+/// Computes the CRC32C (Castagnoli) checksum of `bytes`.
+fn crc32c<I: IntoIterator<Item = u8>>(bytes: I) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0x82F6_3B78 & mask);
+        }
+    }
+
+    !crc
+}
+
+/// Verifies the CRC32C TLV whose 4-byte value is at `start` in `header`: it
+/// must be the checksum of `header` with the value zeroed.
+fn verify_crc32c(header: &[u8], start: usize) -> Result<(), InvalidHeader> {
+    let checksum = header.get(start..(start + 4)).ok_or(InvalidHeader("invalid CRC32C TLV"))?;
+    let expected = u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+    let zeroed = header.iter().enumerate()
+        .map(|(i, &byte)| if (start..(start + 4)).contains(&i) { 0 } else { byte });
+
+    match crc32c(zeroed) == expected {
+        true => Ok(()),
+        false => Err(InvalidHeader("CRC32C mismatch")),
+    }
+}
+
+/// A listener that reads PROXY protocol headers from connections from trusted
+/// peers.
+///
+/// A connection whose peer has an IP address in one of the trusted networks
+/// must begin with a PROXY protocol header of either version, which is read
+/// and removed before the connection is yielded. For the connection, the
+/// client's address in the header replaces the peer's address, and the header
+/// itself is made available via [`Connection::proxy_header()`]. If the header
+/// is malformed or isn't received within the timeout, the connection is
+/// closed.
+///
+/// Connections whose peer has no IP address, for instance over Unix sockets,
+/// are always trusted. Connections from all other peers are yielded
+/// unchanged.
+///
+/// Headers are read concurrently with accepting new connections, so slow
+/// peers don't prevent others from connecting. A connection whose header is
+/// being read counts as open in the listener's [`Connections`], if any, and
+/// waits for a free slot before its header is read. At most 1024 headers are
+/// read at once; further connections wait in the listener's backlog.
+pub struct ProxyListener<L: Listener> {
+    listener: L,
+    trusted: Arc<[IpNet]>,
+    timeout: Duration,
+    connections: Option<Arc<Connections>>,
+    // A trusted connection accepted while all slots were taken.
+    held: Option<L::Connection>,
+    pending: FuturesUnordered<BoxFuture<'static, io::Result<ProxyStream<L::Connection>>>>,
+}
+
+impl<L: Listener> ProxyListener<L> {
+    /// Wraps `listener` so that connections from peers in the `trusted`
+    /// networks must begin with a PROXY protocol header, received within
+    /// `timeout`.
+    pub fn new(listener: L, trusted: Vec<IpNet>, timeout: Duration) -> Self {
+        ProxyListener {
+            listener,
+            trusted: trusted.into(),
+            timeout,
+            connections: None,
+            held: None,
+            pending: FuturesUnordered::new(),
+        }
+    }
+
+    /// Count connections whose headers are being read as open in
+    /// `connections`, and wait for a free slot before reading one. By default,
+    /// they aren't counted.
+    pub fn connections(mut self, connections: Arc<Connections>) -> Self {
+        self.connections = Some(connections);
+        self
+    }
+}
+
+/// Returns `ip` with an IPv4-mapped IPv6 address, as seen by dual-stack
+/// sockets, converted to IPv4 so that it matches IPv4 networks.
+pub fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.segments() {
+            [0, 0, 0, 0, 0, 0xffff, hi, lo] => {
+                IpAddr::V4(Ipv4Addr::from(((hi as u32) << 16) | lo as u32))
+            }
+            _ => IpAddr::V6(v6),
+        },
+        ip => ip,
+    }
+}
+
+/// Returns `true` if `peer` is trusted to send a header given `trusted`.
+fn is_trusted(trusted: &[IpNet], peer: Option<&BindableAddr>) -> bool {
+    let ip = match peer.and_then(|peer| peer.ip()) {
+        Some(ip) => canonical_ip(ip),
+        None => return true,
+    };
+
+    trusted.iter().any(|network| network.contains(&ip))
+}
+
+/// Reads the PROXY protocol header from `conn`.
+async fn read_header<C>(mut conn: C) -> io::Result<ProxyStream<C>>
+    where C: Connection + Unpin
+{
+    let mut buf = Vec::with_capacity(V1_MAX_LEN);
+    loop {
+        match ProxyHeader::parse(&buf)? {
+            Some((header, len)) => {
+                let remote = match (header.command, &header.source) {
+                    (Command::Proxy, Some(source)) => Some(source.clone()),
+                    _ => conn.peer_address(),
+                };
+
+                buf.drain(..len);
+                return Ok(ProxyStream {
+                    inner: conn,
+                    remote,
+                    header: Some(Arc::new(header)),
+                    buffered: buf,
+                    consumed: 0,
+                });
+            }
+            None => {
+                let mut chunk = [0; 256];
+                let n = conn.read(&mut chunk).await?;
+                if n == 0 {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+
+                buf.extend_from_slice(&chunk[..n]);
+            }
+        }
+    }
+}
+
+impl<L> Listener for ProxyListener<L>
+where
+    L: Listener + Unpin,
+    L::Connection: Send + Unpin + 'static,
+{
+    type Connection = ProxyStream<L::Connection>;
+
+    fn local_addr(&self) -> Option<BindableAddr> {
+        self.listener.local_addr()
+    }
+
+    fn poll_accept(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<Self::Connection>> {
+        loop {
+            // Accept connections while there's room, then yield the first ready.
+            while self.pending.len() < MAX_PENDING {
+                let conn = match self.held.take() {
+                    Some(conn) => conn,
+                    None => match Pin::new(&mut self.listener).poll_accept(cx) {
+                        Poll::Ready(result) => result?,
+                        Poll::Pending => break,
+                    },
+                };
+
+                let peer = conn.peer_address();
+                if !is_trusted(&self.trusted, peer.as_ref()) {
+                    return Poll::Ready(Ok(ProxyStream::new(conn)));
+                }
+
+                // Hold the connection until a slot is free to read its header.
+                let slot = match &self.connections {
+                    Some(connections) => match connections.poll_reserve(cx) {
+                        Poll::Ready(slot) => Some(slot),
+                        Poll::Pending => {
+                            self.held = Some(conn);
+                            break;
+                        }
+                    },
+                    None => None,
+                };
+
+                let timeout = self.timeout;
+                self.pending.push(Box::pin(async move {
+                    // The slot is freed once the header is read and the
+                    // connection is yielded, to be counted by its `Incoming`.
+                    let _slot: Option<Slot> = slot;
+                    match tokio::time::timeout(timeout, read_header(conn)).await {
+                        Ok(Ok(stream)) => Ok(stream),
+                        Ok(Err(e)) => {
+                            log::warn!("failed to read PROXY header from {:?}: {}", peer, e);
+                            Err(e)
+                        }
+                        Err(_) => {
+                            log::warn!("timed out reading PROXY header from {:?}", peer);
+                            Err(io::ErrorKind::TimedOut.into())
+                        }
+                    }
+                }));
+            }
+
+            match Pin::new(&mut self.pending).poll_next(cx) {
+                Poll::Ready(Some(Ok(stream))) => return Poll::Ready(Ok(stream)),
+                // The connection is dropped, and thus closed. Try the next one.
+                Poll::Ready(Some(Err(_))) => continue,
+                Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// A connection from a [`ProxyListener`], with any PROXY protocol header
+/// removed.
+pub struct ProxyStream<C> {
+    inner: C,
+    remote: Option<BindableAddr>,
+    header: Option<Arc<ProxyHeader>>,
+    /// Data read past the end of the header.
+    buffered: Vec<u8>,
+    /// How much of `buffered` has been read.
+    consumed: usize,
+}
+
+impl<C: Connection> ProxyStream<C> {
+    /// Wraps `inner`, which has no PROXY protocol header.
+    fn new(inner: C) -> Self {
+        ProxyStream {
+            remote: inner.peer_address(),
+            inner,
+            header: None,
+            buffered: vec![],
+            consumed: 0,
+        }
+    }
+}
+
+impl<C: Connection + Unpin> Connection for ProxyStream<C> {
+    fn peer_address(&self) -> Option<BindableAddr> {
+        self.remote.clone()
+    }
+
+    fn enable_nodelay(&self) -> io::Result<()> {
+        self.inner.enable_nodelay()
+    }
+
+    fn peer_certificates(&self) -> Option<Certificates> {
+        self.inner.peer_certificates()
+    }
+
+    fn tls_handshake(&self) -> Option<Handshake> {
+        self.inner.tls_handshake()
+    }
+
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        self.header.clone()
+    }
+
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        // With a header, the peer is the proxy, not the client it describes.
+        match self.header {
+            Some(_) => None,
+            None => self.inner.peer_credentials(),
+        }
+    }
+}
+
+impl<C: AsyncRead + Unpin> AsyncRead for ProxyStream<C> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if self.consumed < self.buffered.len() {
+            let n = buf.remaining().min(self.buffered.len() - self.consumed);
+            buf.put_slice(&self.buffered[self.consumed..(self.consumed + n)]);
+            self.consumed += n;
+            if self.consumed == self.buffered.len() {
+                self.buffered = vec![];
+                self.consumed = 0;
+            }
+
+            return Poll::Ready(Ok(()));
+        }
+
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<C: AsyncWrite + Unpin> AsyncWrite for ProxyStream<C> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tcp(addr: &str) -> Option<BindableAddr> {
+        Some(BindableAddr::Tcp(addr.parse().unwrap()))
+    }
+
+    fn v2(command: u8, family: u8, body: &[u8]) -> Vec<u8> {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[command, family]);
+        header.extend_from_slice(&(body.len() as u16).to_be_bytes());
+        header.extend_from_slice(body);
+        header
+    }
+
+    #[test]
+    fn test_v1() {
+        let (header, len) = ProxyHeader::parse(b"PROXY TCP6 2001:db8::1 ::1 80 8000\r\nabc")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(len, 36);
+        assert_eq!(header.version(), 1);
+        assert_eq!(header.command(), Command::Proxy);
+        assert_eq!(header.source().cloned(), tcp("[2001:db8::1]:80"));
+        assert_eq!(header.destination().cloned(), tcp("[::1]:8000"));
+
+        let (header, len) = ProxyHeader::parse(b"PROXY UNKNOWN ignored\r\n").unwrap().unwrap();
+        assert_eq!(len, 23);
+        assert!(header.source().is_none());
+
+        for prefix in [&b""[..], b"PRO", b"PROXY TCP4 1.2.3.4 ", b"\r\n\r\n\0"] {
+            assert_eq!(ProxyHeader::parse(prefix), Ok(None));
+        }
+
+        let invalid: &[&[u8]] = &[
+            b"PROXY TCP4 1.2.3.4 5.6.7.8 1 2 3\r\n",
+            b"PROXY TCP4 ::1 ::1 1 2\r\n",
+            b"PROXY TCP6 1.2.3.4 5.6.7.8 1 2\r\n",
+            b"PROXY TCP4 1.2.3.4 5.6.7.8 01 2\r\n",
+            b"PROXY TCP4 1.2.3.4 5.6.7.8 1 65536\r\n",
+            b"PROXY UDP4 1.2.3.4 5.6.7.8 1 2\r\n",
+            b"PROXY TCP4 1.2.3.4\r\n",
+            b"proxy TCP4",
+            b"GET / HTTP/1.1\r\n",
+        ];
+
+        for bytes in invalid {
+            assert!(ProxyHeader::parse(bytes).is_err(), "{:?}", bytes);
+        }
+
+        assert!(ProxyHeader::parse(&[b'P'; 200][..]).is_err());
+        let mut long = b"PROXY UNKNOWN ".to_vec();
+        long.resize(V1_MAX_LEN, b'a');
+        assert!(ProxyHeader::parse(&long).is_err());
+    }
+
+    #[test]
+    fn test_v2() {
+        let mut body = vec![192, 0, 2, 1, 192, 0, 2, 2, 0xdc, 0x04, 0x01, 0xbb];
+        body.extend_from_slice(&[Tlv::AUTHORITY, 0, 11]);
+        body.extend_from_slice(b"example.com");
+        body.extend_from_slice(&[Tlv::SSL, 0, 13, 0x07, 0, 0, 0, 0]);
+        body.extend_from_slice(&[Tlv::SSL_VERSION, 0, 5]);
+        body.extend_from_slice(b"TLSv3");
+        body.extend_from_slice(&[Tlv::NOOP, 0, 0]);
+
+        let mut bytes = v2(0x21, 0x11, &body);
+        let len = bytes.len();
+        bytes.extend_from_slice(b"GET /");
+
+        let (header, parsed) = ProxyHeader::parse(&bytes).unwrap().unwrap();
+        assert_eq!(parsed, len);
+        assert_eq!(header.version(), 2);
+        assert_eq!(header.source().cloned(), tcp("192.0.2.1:56324"));
+        assert_eq!(header.destination().cloned(), tcp("192.0.2.2:443"));
+        assert_eq!(header.authority(), Some("example.com"));
+        assert_eq!(header.tlvs().len(), 3);
+
+        let ssl = header.ssl().unwrap();
+        assert!(ssl.is_tls() && ssl.verified_certificate());
+        assert_eq!(ssl.version(), Some("TLSv3"));
+        assert_eq!(ssl.common_name(), None);
+
+        // Every strict prefix is incomplete.
+        for i in 0..len {
+            assert_eq!(ProxyHeader::parse(&bytes[..i]), Ok(None), "{}", i);
+        }
+
+        // Addresses are ignored for `LOCAL`, and TLVs follow the address block.
+        let (header, _) = ProxyHeader::parse(&v2(0x20, 0x11, &body)).unwrap().unwrap();
+        assert_eq!(header.command(), Command::Local);
+        assert!(header.source().is_none());
+        assert_eq!(header.authority(), Some("example.com"));
+
+        let mut body = vec![0; 36];
+        body[15] = 1;
+        body[31] = 2;
+        body[32..].copy_from_slice(&[0, 80, 0, 81]);
+        let (header, _) = ProxyHeader::parse(&v2(0x21, 0x21, &body)).unwrap().unwrap();
+        assert_eq!(header.source().cloned(), tcp("[::1]:80"));
+        assert_eq!(header.destination().cloned(), tcp("[::2]:81"));
+
+        let mut body = vec![0; 216];
+        body[..4].copy_from_slice(b"/src");
+        body[108..112].copy_from_slice(b"/dst");
+        let (header, _) = ProxyHeader::parse(&v2(0x21, 0x31, &body)).unwrap().unwrap();
+        assert_eq!(header.source(), Some(&BindableAddr::Unix("/src".into())));
+        assert_eq!(header.destination(), Some(&BindableAddr::Unix("/dst".into())));
+
+        let (header, _) = ProxyHeader::parse(&v2(0x21, 0x00, &[])).unwrap().unwrap();
+        assert!(header.source().is_none());
+
+        assert!(ProxyHeader::parse(&v2(0x11, 0x11, &[0; 12])).is_err());
+        assert!(ProxyHeader::parse(&v2(0x22, 0x11, &[0; 12])).is_err());
+        assert!(ProxyHeader::parse(&v2(0x21, 0x11, &[0; 11])).is_err());
+        assert!(ProxyHeader::parse(&v2(0x21, 0x41, &[])).is_err());
+        assert!(ProxyHeader::parse(&v2(0x21, 0x00, &[Tlv::NOOP, 0, 2, 0])).is_err());
+    }
+
+    #[test]
+    fn test_crc32c() {
+        let addresses = [127, 0, 0, 1, 127, 0, 0, 1, 0, 1, 0, 2];
+        let crc = [Tlv::CRC32C, 0, 4, 0, 0, 0, 0];
+        let mut bytes = v2(0x21, 0x11, &[&addresses[..], &crc[..]].concat());
+
+        assert_eq!(crc32c(b"123456789".iter().copied()), 0xE306_9283);
+
+        // The CRC32C of the header with a zeroed value, computed independently.
+        let crc = 0xE916_6D07u32.to_be_bytes();
+        let n = bytes.len();
+        bytes[(n - 4)..].copy_from_slice(&crc);
+        assert!(ProxyHeader::parse(&bytes).unwrap().is_some());
+
+        bytes[n - 1] ^= 1;
+        assert!(ProxyHeader::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_trust() {
+        let trusted = ["10.0.0.0/8".parse().unwrap(), "fd00::/8".parse().unwrap()];
+        assert!(is_trusted(&trusted, tcp("10.1.2.3:80").as_ref()));
+        assert!(is_trusted(&trusted, tcp("[::ffff:10.1.2.3]:80").as_ref()));
+        assert!(is_trusted(&trusted, tcp("[fd00::1]:80").as_ref()));
+        assert!(!is_trusted(&trusted, tcp("11.0.0.1:80").as_ref()));
+        assert!(!is_trusted(&trusted, tcp("[::1]:80").as_ref()));
+        assert!(is_trusted(&trusted, None));
+        assert!(is_trusted(&[], None));
+        assert!(!is_trusted(&[], tcp("10.1.2.3:80").as_ref()));
+    }
+}
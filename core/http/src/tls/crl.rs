@@ -0,0 +1,390 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use rustls::server::{ClientCertVerified, ClientCertVerifier};
+use rustls::{Certificate, DigitallySignedStruct, DistinguishedNames, Error, SignatureScheme};
+use rustls::client::HandshakeSignatureValid;
+use ring::signature::{self, VerificationAlgorithm, UnparsedPublicKey};
+use x509_parser::der_parser::oid::Oid;
+use x509_parser::oid_registry::*;
+use x509_parser::revocation_list::CertificateRevocationList;
+use x509_parser::traits::FromDer;
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// A certificate revocation list (CRL): the serial numbers of the certificates
+/// an issuer has revoked.
+#[derive(Debug, Clone)]
+pub struct Crl {
+    /// The DER-encoded distinguished name of the issuer.
+    issuer: Vec<u8>,
+    /// The issuer's name, for display.
+    issuer_name: String,
+    /// The UNIX time after which a newer CRL is expected, if any.
+    next_update: Option<i64>,
+    /// The DER-encoded serial numbers of the revoked certificates.
+    revoked: HashSet<Vec<u8>>,
+    /// The DER-encoded part of the CRL covered by its signature.
+    signed: Vec<u8>,
+    /// The algorithm the CRL is signed with.
+    algorithm: Oid<'static>,
+    /// The issuer's signature over `signed`.
+    signature: Vec<u8>,
+}
+
+impl Crl {
+    fn parse(der: &[u8]) -> io::Result<Crl> {
+        let (_, crl) = CertificateRevocationList::from_der(der)
+            .map_err(|e| invalid_data(format!("bad CRL: {}", e)))?;
+
+        Ok(Crl {
+            issuer: crl.issuer().as_raw().to_vec(),
+            issuer_name: crl.issuer().to_string(),
+            next_update: crl.next_update().map(|time| time.timestamp()),
+            revoked: crl.iter_revoked_certificates().map(|c| c.raw_serial().to_vec()).collect(),
+            signed: crl.tbs_cert_list.as_ref().to_vec(),
+            algorithm: crl.signature_algorithm.algorithm.to_owned(),
+            signature: crl.signature_value.data.to_vec(),
+        })
+    }
+
+    fn is_stale(&self, now: SystemTime) -> bool {
+        let now = now.duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+        self.next_update.map_or(false, |next_update| now > next_update)
+    }
+
+    /// Verifies the CRL's signature with the key of one of the `issuers` with
+    /// the CRL's issuer as its subject.
+    fn verify(&self, issuers: &[Issuer]) -> io::Result<()> {
+        let mut candidates = issuers.iter()
+            .filter(|issuer| issuer.subject == self.issuer)
+            .peekable();
+
+        if candidates.peek().is_none() {
+            let msg = format!("bad CRL: issuer `{}` is not a CA certificate", self.issuer_name);
+            return Err(invalid_data(msg));
+        }
+
+        let verified = candidates.any(|issuer| {
+            issuer.algorithm(&self.algorithm).map_or(false, |algorithm| {
+                UnparsedPublicKey::new(algorithm, &issuer.key)
+                    .verify(&self.signed, &self.signature)
+                    .is_ok()
+            })
+        });
+
+        if !verified {
+            let msg = format!("bad CRL: invalid signature from `{}`", self.issuer_name);
+            return Err(invalid_data(msg));
+        }
+
+        Ok(())
+    }
+}
+
+/// The subject and public key of a CA certificate, which verifies the
+/// signatures of the CRLs it issues.
+#[derive(Debug, Clone)]
+struct Issuer {
+    /// The DER-encoded distinguished name of the CA.
+    subject: Vec<u8>,
+    /// The algorithm of the CA's key.
+    key_algorithm: Oid<'static>,
+    /// The named curve of the CA's key, if it is an EC key.
+    curve: Option<Oid<'static>>,
+    /// The CA's public key.
+    key: Vec<u8>,
+}
+
+impl Issuer {
+    fn parse(cert: &Certificate) -> io::Result<Issuer> {
+        let (_, cert) = x509_parser::parse_x509_certificate(&cert.0)
+            .map_err(|e| invalid_data(format!("bad CA cert: {}", e)))?;
+
+        let spki = cert.public_key();
+        Ok(Issuer {
+            subject: cert.subject().as_raw().to_vec(),
+            key_algorithm: spki.algorithm.algorithm.to_owned(),
+            curve: spki.algorithm.parameters.as_ref()
+                .and_then(|params| params.as_oid().ok())
+                .map(|curve| curve.to_owned()),
+            key: spki.subject_public_key.data.to_vec(),
+        })
+    }
+
+    /// The algorithm to verify a signature made with this issuer's key using
+    /// the signature algorithm `algorithm`, if it is supported.
+    fn algorithm(&self, algorithm: &Oid<'_>) -> Option<&'static dyn VerificationAlgorithm> {
+        let is_rsa = self.key_algorithm == OID_PKCS1_RSAENCRYPTION;
+        let is_ec = self.key_algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY;
+        let is_ed25519 = self.key_algorithm == OID_SIG_ED25519;
+        let curve = self.curve.as_ref();
+        let algorithm: &'static dyn VerificationAlgorithm = match algorithm {
+            a if is_rsa && *a == OID_PKCS1_SHA256WITHRSA => &signature::RSA_PKCS1_2048_8192_SHA256,
+            a if is_rsa && *a == OID_PKCS1_SHA384WITHRSA => &signature::RSA_PKCS1_2048_8192_SHA384,
+            a if is_rsa && *a == OID_PKCS1_SHA512WITHRSA => &signature::RSA_PKCS1_2048_8192_SHA512,
+            a if is_ec && *a == OID_SIG_ECDSA_WITH_SHA256 => match curve? {
+                c if *c == OID_EC_P256 => &signature::ECDSA_P256_SHA256_ASN1,
+                c if *c == OID_NIST_EC_P384 => &signature::ECDSA_P384_SHA256_ASN1,
+                _ => return None,
+            },
+            a if is_ec && *a == OID_SIG_ECDSA_WITH_SHA384 => match curve? {
+                c if *c == OID_EC_P256 => &signature::ECDSA_P256_SHA384_ASN1,
+                c if *c == OID_NIST_EC_P384 => &signature::ECDSA_P384_SHA384_ASN1,
+                _ => return None,
+            },
+            a if is_ed25519 && *a == OID_SIG_ED25519 => &signature::ED25519,
+            _ => return None,
+        };
+
+        Some(algorithm)
+    }
+}
+
+/// Verifies client certificates with an inner verifier and then rejects those
+/// revoked by a certificate revocation list (CRL) most recently
+/// [`set()`](CrlVerifier::set()).
+///
+/// Each CRL must be issued by one of the CA certificates the verifier is
+/// created with and is only accepted if its signature verifies with that CA's
+/// key. The client's certificate and any intermediate certificates it presents
+/// are each checked against every CRL from their issuer. As a result, revoking
+/// a certificate issued by an intermediate CA requires that the intermediate
+/// be among the CA certificates. A CRL is _stale_ when its next update is past
+/// due. Unless stale CRLs are allowed, in which case a warning is logged,
+/// certificates from the issuer of a stale CRL are rejected.
+///
+/// Replacing the CRLs only affects handshakes that begin afterwards;
+/// established connections continue unaffected.
+pub struct CrlVerifier {
+    inner: Arc<dyn ClientCertVerifier>,
+    issuers: Vec<Issuer>,
+    crls: RwLock<Arc<Vec<Crl>>>,
+    allow_stale: bool,
+}
+
+impl CrlVerifier {
+    /// Creates a verifier which verifies with `inner` and then against `crls`,
+    /// accepting certificates from the issuers of stale CRLs iff `allow_stale`.
+    /// Fails unless each CRL is signed by one of the CAs in `ca_certs`.
+    pub fn new(
+        inner: Arc<dyn ClientCertVerifier>,
+        ca_certs: &[Certificate],
+        crls: Vec<Crl>,
+        allow_stale: bool,
+    ) -> io::Result<Self> {
+        let issuers = ca_certs.iter().map(Issuer::parse).collect::<io::Result<Vec<_>>>()?;
+        let verifier = CrlVerifier { inner, issuers, crls: RwLock::default(), allow_stale };
+        verifier.set(crls)?;
+        Ok(verifier)
+    }
+
+    /// Loads the CRLs in `reader`, which is either PEM-encoded, with any number
+    /// of CRLs, or a single DER-encoded CRL.
+    pub fn load(reader: &mut dyn io::BufRead) -> io::Result<Vec<Crl>> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        let start = bytes.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(0);
+        if !bytes[start..].starts_with(b"-----BEGIN") {
+            return Ok(vec![Crl::parse(&bytes)?]);
+        }
+
+        let crls = rustls_pemfile::crls(&mut &bytes[..])
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad CRL PEM"))?;
+
+        let crls = crls.iter().map(|der| Crl::parse(der)).collect::<io::Result<Vec<_>>>()?;
+        if crls.is_empty() {
+            let msg = "bad CRL: no CRLs found";
+            return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        }
+
+        Ok(crls)
+    }
+
+    /// Checks new handshakes against `crls`, replacing all previously set CRLs,
+    /// unless one of `crls` isn't signed by one of the verifier's CAs.
+    pub fn set(&self, crls: Vec<Crl>) -> io::Result<()> {
+        for crl in &crls {
+            crl.verify(&self.issuers)?;
+        }
+
+        *self.crls.write().unwrap() = Arc::new(crls);
+        Ok(())
+    }
+
+    /// Checks `cert` against the CRLs from its issuer.
+    fn check(&self, crls: &[Crl], cert: &Certificate, now: SystemTime) -> Result<(), Error> {
+        let (_, cert) = x509_parser::parse_x509_certificate(&cert.0)
+            .map_err(|_| Error::InvalidCertificateEncoding)?;
+
+        let issuer = cert.issuer().as_raw();
+        for crl in crls.iter().filter(|crl| crl.issuer == issuer) {
+            if crl.is_stale(now) {
+                if !self.allow_stale {
+                    let msg = format!("CRL from `{}` is stale", crl.issuer_name);
+                    return Err(Error::InvalidCertificateData(msg));
+                }
+
+                log::warn!("accepting client certificate despite stale CRL from `{}`",
+                    crl.issuer_name);
+            }
+
+            if crl.revoked.contains(cert.raw_serial()) {
+                let msg = format!("certificate {} is revoked", cert.raw_serial_as_string());
+                return Err(Error::InvalidCertificateData(msg));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for CrlVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CrlVerifier")
+            .field("allow_stale", &self.allow_stale)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientCertVerifier for CrlVerifier {
+    fn offer_client_auth(&self) -> bool {
+        self.inner.offer_client_auth()
+    }
+
+    fn client_auth_mandatory(&self) -> Option<bool> {
+        self.inner.client_auth_mandatory()
+    }
+
+    fn client_auth_root_subjects(&self) -> Option<DistinguishedNames> {
+        self.inner.client_auth_root_subjects()
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        now: SystemTime,
+    ) -> Result<ClientCertVerified, Error> {
+        let verified = self.inner.verify_client_cert(end_entity, intermediates, now)?;
+        let crls = self.crls.read().unwrap().clone();
+        for cert in std::iter::once(end_entity).chain(intermediates) {
+            self.check(&crls, cert, now)?;
+        }
+
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_verifier(mut ca_certs: &[u8], mut crls: &[u8]) -> io::Result<CrlVerifier> {
+        let ca_certs = crate::tls::util::load_certs(&mut ca_certs).unwrap();
+        let roots = crate::tls::util::ca_roots(&ca_certs).unwrap();
+        let inner = rustls::server::AllowAnyAuthenticatedClient::new(roots);
+        CrlVerifier::new(inner, &ca_certs, CrlVerifier::load(&mut crls)?, false)
+    }
+
+    fn crl_verifier(mut crls: &[u8], allow_stale: bool) -> CrlVerifier {
+        let mut ca_certs = tls_example_key!("ca_cert.pem");
+        let ca_certs = crate::tls::util::load_certs(&mut ca_certs).unwrap();
+        let roots = crate::tls::util::ca_roots(&ca_certs).unwrap();
+        let inner = rustls::server::AllowAnyAuthenticatedClient::new(roots);
+        let crls = CrlVerifier::load(&mut crls).unwrap();
+        CrlVerifier::new(inner, &ca_certs, crls, allow_stale).unwrap()
+    }
+
+    fn verify(verifier: &CrlVerifier, mut cert: &[u8]) -> Result<ClientCertVerified, Error> {
+        let cert = crate::tls::util::load_certs(&mut cert).unwrap().remove(0);
+        verifier.verify_client_cert(&cert, &[], SystemTime::now())
+    }
+
+    #[test]
+    fn rejects_revoked_certificates() {
+        let (rsa, ecdsa) = (tls_example_key!("rsa_sha256_cert.pem"),
+            tls_example_key!("ecdsa_nistp256_sha256_cert.pem"));
+
+        let verifier = crl_verifier(tls_example_key!("crl.pem"), false);
+        assert!(verify(&verifier, rsa).is_ok());
+        assert!(verify(&verifier, ecdsa).is_err());
+
+        verifier.set(vec![]).unwrap();
+        assert!(verify(&verifier, ecdsa).is_ok());
+
+        // The same CRL, DER-encoded.
+        let der = rustls_pemfile::crls(&mut tls_example_key!("crl.pem")).unwrap().remove(0);
+        let verifier = crl_verifier(&der, false);
+        assert!(verify(&verifier, rsa).is_ok());
+        assert!(verify(&verifier, ecdsa).is_err());
+    }
+
+    #[test]
+    fn stale_crls_fail_closed_unless_allowed() {
+        let (rsa, ecdsa) = (tls_example_key!("rsa_sha256_cert.pem"),
+            tls_example_key!("ecdsa_nistp256_sha256_cert.pem"));
+
+        let verifier = crl_verifier(tls_example_key!("stale_crl.pem"), false);
+        assert!(verify(&verifier, rsa).is_err());
+
+        let verifier = crl_verifier(tls_example_key!("stale_crl.pem"), true);
+        assert!(verify(&verifier, rsa).is_ok());
+        assert!(verify(&verifier, ecdsa).is_err());
+    }
+
+    #[test]
+    fn rejects_crls_not_signed_by_a_ca() {
+        let ca_cert = tls_example_key!("ca_cert.pem");
+        let crl = tls_example_key!("crl.pem");
+        assert!(new_verifier(ca_cert, crl).is_ok());
+
+        // A CRL whose issuer isn't among the CAs.
+        assert!(new_verifier(tls_example_key!("rsa_sha256_cert.pem"), crl).is_err());
+
+        // A CRL with a tampered signature.
+        let mut der = rustls_pemfile::crls(&mut &crl[..]).unwrap().remove(0);
+        *der.last_mut().unwrap() ^= 1;
+        assert!(new_verifier(ca_cert, &der).is_err());
+
+        // Replacing the CRLs verifies them too.
+        let verifier = crl_verifier(crl, false);
+        let tampered = CrlVerifier::load(&mut &der[..]).unwrap();
+        assert!(verifier.set(tampered).is_err());
+        assert!(verify(&verifier, tls_example_key!("ecdsa_nistp256_sha256_cert.pem")).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_crls() {
+        assert!(CrlVerifier::load(&mut &b"-----BEGIN X509 CRL-----\n"[..]).is_err());
+        assert!(CrlVerifier::load(&mut tls_example_key!("ca_cert.pem")).is_err());
+        assert!(CrlVerifier::load(&mut &b"\x30\x00"[..]).is_err());
+    }
+}
@@ -3,10 +3,17 @@ use std::io;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crate::bindable::BindableAddr;
-use crate::listener::{Certificates, Connection, Listener};
-use crate::tls::util::{load_ca_certs, load_certs, load_private_key};
+use crate::listener::{Certificates, Connection, Handshake, HandshakeData, Listener};
+use crate::listener::PeerCredentials;
+use crate::proxy_protocol::ProxyHeader;
+use crate::tls::CertResolver;
+#[cfg(feature = "mtls")]
+use crate::tls::CrlVerifier;
+use crate::tls::ticketer::RotatingTicketer;
+use crate::tls::util::{load_certs, ca_roots};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_rustls::{server::TlsStream as BareTlsStream, Accept, TlsAcceptor};
 
@@ -14,6 +21,9 @@ use tokio_rustls::{server::TlsStream as BareTlsStream, Accept, TlsAcceptor};
 pub struct TlsListener<L: Listener> {
     listener: L,
     acceptor: TlsAcceptor,
+    resolver: Arc<CertResolver>,
+    #[cfg(feature = "mtls")]
+    crl_verifier: Option<Arc<CrlVerifier>>,
 }
 
 /// This implementation exists so that ROCKET_WORKERS=1 can make progress while
@@ -45,10 +55,16 @@ pub struct TlsListener<L: Listener> {
 /// certificate data when we have a `Request` object, which implies we're
 /// receiving payload data, which implies the TLS handshake has finished, so the
 /// certificate data as seen by a Rocket application will always be "fresh".
+///
+/// The parameters negotiated in the handshake, like the protocol version and
+/// the server name requested via SNI, are made available in the same manner.
 pub struct TlsStream<C: Connection + Unpin> {
     remote: Option<BindableAddr>,
+    proxy: Option<Arc<ProxyHeader>>,
+    credentials: Option<PeerCredentials>,
     state: TlsState<C>,
     certs: Certificates,
+    handshake: Handshake,
 }
 
 /// State of `TlsStream`.
@@ -63,10 +79,22 @@ pub enum TlsState<C: Connection> {
 pub struct Config<R> {
     pub cert_chain: R,
     pub private_key: R,
+    /// Server names with their respective cert chain and private key.
+    pub sni: Vec<(String, R, R)>,
     pub ciphersuites: Vec<rustls::SupportedCipherSuite>,
     pub prefer_server_order: bool,
+    /// Enabled protocol versions; those without a cipher suite are disabled.
+    pub versions: Vec<&'static rustls::SupportedProtocolVersion>,
+    /// Seconds between session ticket key rotations; `None` disables tickets.
+    pub ticket_rotation: Option<u32>,
+    /// Number of sessions to cache for resumption; `0` disables the cache.
+    pub session_cache_size: usize,
     pub ca_certs: Option<R>,
     pub mandatory_mtls: bool,
+    /// CRLs to check client certificates against. Requires `mtls`.
+    pub crls: Vec<R>,
+    /// Whether to accept client certificates from the issuer of a stale CRL.
+    pub allow_stale_crls: bool,
 }
 
 impl<L: Listener> TlsListener<L> {
@@ -75,31 +103,58 @@ impl<L: Listener> TlsListener<L> {
         R: io::BufRead,
     {
         use rustls::server::{AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient};
-        use rustls::server::{NoClientAuth, ServerConfig, ServerSessionMemoryCache};
+        use rustls::server::{NoClientAuth, NoServerSessionStorage, ServerConfig};
+        use rustls::server::ServerSessionMemoryCache;
+
+        let key = CertResolver::load(&mut c.cert_chain, &mut c.private_key)?;
+        let mut names = Vec::with_capacity(c.sni.len());
+        for (name, mut cert_chain, mut private_key) in c.sni.drain(..) {
+            let key = CertResolver::load_named(&name, &mut cert_chain, &mut private_key)?;
+            names.push((name, key));
+        }
 
-        let cert_chain = load_certs(&mut c.cert_chain)
-            .map_err(|e| io::Error::new(e.kind(), format!("bad TLS cert chain: {}", e)))?;
+        let resolver = Arc::new(CertResolver::new(key, names));
 
-        let key = load_private_key(&mut c.private_key)
-            .map_err(|e| io::Error::new(e.kind(), format!("bad TLS private key: {}", e)))?;
+        let bad_ca_certs = |e: io::Error| {
+            io::Error::new(e.kind(), format!("bad CA cert(s): {}", e))
+        };
+
+        let ca_certs = match c.ca_certs {
+            Some(ref mut ca_certs) => load_certs(ca_certs).map_err(bad_ca_certs)?,
+            None => vec![],
+        };
 
         let client_auth = match c.ca_certs {
-            Some(ref mut ca_certs) => match load_ca_certs(ca_certs) {
+            Some(_) => match ca_roots(&ca_certs) {
                 Ok(ca_roots) if c.mandatory_mtls => AllowAnyAuthenticatedClient::new(ca_roots),
                 Ok(ca_roots) => AllowAnyAnonymousOrAuthenticatedClient::new(ca_roots),
-                Err(e) => return Err(io::Error::new(e.kind(), format!("bad CA cert(s): {}", e))),
+                Err(e) => return Err(bad_ca_certs(e)),
             },
             None => NoClientAuth::new(),
         };
 
+        #[cfg(feature = "mtls")]
+        let (client_auth, crl_verifier) = match c.crls.is_empty() {
+            true => (client_auth, None),
+            false => {
+                let mut crls = vec![];
+                for reader in &mut c.crls {
+                    crls.extend(CrlVerifier::load(reader)?);
+                }
+
+                let verifier = CrlVerifier::new(client_auth, &ca_certs, crls, c.allow_stale_crls)?;
+                let verifier = Arc::new(verifier);
+                (verifier.clone() as Arc<dyn rustls::server::ClientCertVerifier>, Some(verifier))
+            }
+        };
+
         let mut tls_config = ServerConfig::builder()
             .with_cipher_suites(&c.ciphersuites)
             .with_safe_default_kx_groups()
-            .with_safe_default_protocol_versions()
+            .with_protocol_versions(&c.versions)
             .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("bad TLS config: {}", e)))?
             .with_client_cert_verifier(client_auth)
-            .with_single_cert(cert_chain, key)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("bad TLS config: {}", e)))?;
+            .with_cert_resolver(resolver.clone());
 
         tls_config.ignore_client_order = c.prefer_server_order;
 
@@ -108,13 +163,41 @@ impl<L: Listener> TlsListener<L> {
             tls_config.alpn_protocols.insert(0, b"h2".to_vec());
         }
 
-        tls_config.session_storage = ServerSessionMemoryCache::new(1024);
-        tls_config.ticketer = rustls::Ticketer::new().map_err(|e| {
-            io::Error::new(io::ErrorKind::Other, format!("bad TLS ticketer: {}", e))
-        })?;
+        tls_config.session_storage = match c.session_cache_size {
+            0 => Arc::new(NoServerSessionStorage {}),
+            size => ServerSessionMemoryCache::new(size),
+        };
+
+        if let Some(secs) = c.ticket_rotation {
+            let period = Duration::from_secs(secs.into());
+            let ticketer = RotatingTicketer::new(period).map_err(|e| {
+                io::Error::new(io::ErrorKind::Other, format!("bad TLS ticketer: {}", e))
+            })?;
+
+            tls_config.ticketer = Arc::new(ticketer);
+        }
 
         let acceptor = TlsAcceptor::from(Arc::new(tls_config));
-        Ok(TlsListener { listener, acceptor })
+        Ok(TlsListener {
+            listener,
+            acceptor,
+            resolver,
+            #[cfg(feature = "mtls")]
+            crl_verifier,
+        })
+    }
+
+    /// The resolver of the certificate presented in new handshakes. Use it to
+    /// replace the certificate without rebinding.
+    pub fn resolver(&self) -> &Arc<CertResolver> {
+        &self.resolver
+    }
+
+    /// The verifier that checks client certificates against CRLs, if any were
+    /// configured. Use it to replace the CRLs without rebinding.
+    #[cfg(feature = "mtls")]
+    pub fn crl_verifier(&self) -> Option<&Arc<CrlVerifier>> {
+        self.crl_verifier.as_ref()
     }
 }
 
@@ -136,9 +219,12 @@ where
         match futures::ready!(Pin::new(&mut self.listener).poll_accept(cx)) {
             Ok(conn) => Poll::Ready(Ok(TlsStream {
                 remote: conn.peer_address(),
+                proxy: conn.proxy_header(),
+                credentials: conn.peer_credentials(),
                 state: TlsState::Handshaking(self.acceptor.accept(conn)),
                 // These are empty and filled in after handshake is complete.
                 certs: Certificates::default(),
+                handshake: Handshake::default(),
             })),
             Err(e) => Poll::Ready(Err(e)),
         }
@@ -167,6 +253,18 @@ impl<C: Connection + Unpin> Connection for TlsStream<C> {
     fn peer_certificates(&self) -> Option<Certificates> {
         Some(self.certs.clone())
     }
+
+    fn tls_handshake(&self) -> Option<Handshake> {
+        Some(self.handshake.clone())
+    }
+
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        self.proxy.clone()
+    }
+
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        self.credentials
+    }
 }
 
 impl<C: Connection + Unpin> TlsStream<C> {
@@ -187,6 +285,18 @@ impl<C: Connection + Unpin> TlsStream<C> {
                                 self.certs.set(cert_chain.to_vec());
                             }
 
+                            let conn = stream.get_ref().1;
+                            if let (Some(version), Some(suite)) =
+                                (conn.protocol_version(), conn.negotiated_cipher_suite())
+                            {
+                                self.handshake.set(HandshakeData {
+                                    version: version.get_u16(),
+                                    cipher_suite: suite.suite().get_u16(),
+                                    alpn_protocol: conn.alpn_protocol().map(|p| p.to_vec()),
+                                    server_name: conn.sni_hostname().map(|n| n.to_string()),
+                                });
+                            }
+
                             self.state = TlsState::Streaming(stream);
                         }
                         Err(e) => {
@@ -1,8 +1,24 @@
+#[cfg(test)]
+macro_rules! tls_example_key {
+    ($k:expr) => {
+        &include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/tls/private/", $k))[..]
+    }
+}
+
 mod listener;
+mod resolver;
+mod ticketer;
 mod util;
 
+#[cfg(feature = "mtls")]
+mod crl;
+
 #[cfg(feature = "mtls")]
 pub mod mtls;
 
 pub use rustls;
 pub use listener::{TlsListener, Config};
+pub use resolver::CertResolver;
+
+#[cfg(feature = "mtls")]
+pub use crl::{Crl, CrlVerifier};
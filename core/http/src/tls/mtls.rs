@@ -35,11 +35,13 @@ pub mod x509 {
 
 use std::fmt;
 use std::ops::Deref;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::num::NonZeroUsize;
 
 use ref_cast::RefCast;
 use x509_parser::nom;
 use x509::{ParsedExtension, X509Name, X509Certificate, TbsCertificate, X509Error, FromDer};
+use x509::{GeneralName, KeyUsage, ExtendedKeyUsage};
 use oid::OID_X509_EXT_SUBJECT_ALT_NAME as SUBJECT_ALT_NAME;
 
 use crate::listener::CertificateData;
@@ -63,6 +65,7 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 ///   * The client's certificate chain was signed by the CA identified by the
 ///     configured `ca_certs` and with respect to SNI, if any. See [module level
 ///     docs](self) for configuration details.
+///   * The client's certificate isn't revoked by any configured `crls`.
 ///
 /// If the client does not present certificates, the guard _forwards_.
 ///
@@ -140,9 +143,12 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 ///     // _does_ run if a valid (Ok) or invalid (Err) one was presented.
 /// }
 /// ```
-#[repr(transparent)]
 #[derive(Debug, PartialEq)]
-pub struct Certificate<'a>(X509Certificate<'a>);
+pub struct Certificate<'a> {
+    x509: X509Certificate<'a>,
+    /// The raw data of this certificate followed by the rest of the chain.
+    chain: &'a [CertificateData],
+}
 
 /// An X.509 Distinguished Name (DN) found in a [`Certificate`].
 ///
@@ -189,6 +195,8 @@ pub enum Error {
     Incomplete(Option<NonZeroUsize>),
     /// The certificate contained `.0` bytes of trailing data.
     Trailing(usize),
+    /// The certificate has none of the attributes that identify a client.
+    NoIdentity,
 }
 
 impl<'a> Certificate<'a> {
@@ -216,18 +224,28 @@ impl<'a> Certificate<'a> {
 
     #[inline(always)]
     fn inner(&self) -> &TbsCertificate<'a> {
-        &self.0.tbs_certificate
+        &self.x509.tbs_certificate
     }
 
     /// PRIVATE: For internal Rocket use only!
     #[doc(hidden)]
     pub fn parse(chain: &[CertificateData]) -> Result<Certificate<'_>> {
         match chain.first() {
-            Some(cert) => Certificate::parse_one(&cert.0).map(Certificate),
+            Some(cert) => Ok(Certificate { x509: Certificate::parse_one(&cert.0)?, chain }),
             None => Err(Error::Empty)
         }
     }
 
+    /// Returns an iterator over the general names in the subjectAlt extension.
+    fn alt_names(&self) -> impl Iterator<Item = &GeneralName<'a>> + '_ {
+        self.extensions().iter()
+            .filter_map(|ext| match ext.parsed_extension() {
+                ParsedExtension::SubjectAlternativeName(alt) => Some(&alt.general_names),
+                _ => None,
+            })
+            .flatten()
+    }
+
     /// Returns the serial number of the X.509 certificate.
     ///
     /// # Example
@@ -337,6 +355,273 @@ impl<'a> Certificate<'a> {
         &self.inner().extensions()
     }
 
+    /// Returns the time the certificate becomes valid: its "not before" time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     println!("Valid since {}.", cert.not_before());
+    /// }
+    /// ```
+    pub fn not_before(&self) -> time::OffsetDateTime {
+        self.inner().validity.not_before.to_datetime()
+    }
+
+    /// Returns the time the certificate expires: its "not after" time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    /// use rocket::time::{Duration, OffsetDateTime};
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     if cert.not_after() - OffsetDateTime::now_utc() < Duration::days(7) {
+    ///         println!("Your certificate expires within a week.");
+    ///     }
+    /// }
+    /// ```
+    pub fn not_after(&self) -> time::OffsetDateTime {
+        self.inner().validity.not_after.to_datetime()
+    }
+
+    /// Returns the SHA-256 fingerprint of the certificate: the SHA-256 digest
+    /// of its DER encoding.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     let hex: String = cert.fingerprint().iter().map(|b| format!("{:02x}", b)).collect();
+    ///     println!("Fingerprint: {}", hex);
+    /// }
+    /// ```
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let digest = ring::digest::digest(&ring::digest::SHA256, self.as_bytes());
+        let mut fingerprint = [0; 32];
+        fingerprint.copy_from_slice(digest.as_ref());
+        fingerprint
+    }
+
+    /// Returns the raw, DER-encoded certificate.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     let der: &[u8] = cert.as_bytes();
+    /// }
+    /// ```
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.chain[0].0
+    }
+
+    /// Returns an iterator over the DNS names in the subjectAlt extension.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     for name in cert.dns_names() {
+    ///         println!("Certified for {}.", name);
+    ///     }
+    /// }
+    /// ```
+    pub fn dns_names(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.alt_names().filter_map(|name| match name {
+            GeneralName::DNSName(name) => Some(*name),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the IP addresses in the subjectAlt extension.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     let loopback = cert.ip_addrs().any(|ip| ip.is_loopback());
+    /// }
+    /// ```
+    pub fn ip_addrs(&self) -> impl Iterator<Item = IpAddr> + '_ {
+        self.alt_names().filter_map(|name| match name {
+            GeneralName::IPAddress(bytes) => match bytes.len() {
+                4 => Some(IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(*bytes).ok()?))),
+                16 => Some(IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(*bytes).ok()?))),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the URIs in the subjectAlt extension.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     for uri in cert.uris() {
+    ///         println!("Identified by {}.", uri);
+    ///     }
+    /// }
+    /// ```
+    pub fn uris(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.alt_names().filter_map(|name| match name {
+            GeneralName::URI(uri) => Some(*uri),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the email addresses in the subjectAlt
+    /// extension. Email addresses in the subject are available via
+    /// [`Name::emails()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     for email in cert.alt_emails().chain(cert.subject().emails()) {
+    ///         println!("Reach me at: {}", email);
+    ///     }
+    /// }
+    /// ```
+    pub fn alt_emails(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.alt_names().filter_map(|name| match name {
+            GeneralName::RFC822Name(email) => Some(*email),
+            _ => None,
+        })
+    }
+
+    /// Returns the [SPIFFE ID] of the workload the certificate identifies, if
+    /// any: the URI in the subjectAlt extension with a `spiffe` scheme.
+    ///
+    /// [SPIFFE ID]: https://github.com/spiffe/spiffe/blob/main/standards/SPIFFE-ID.md
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     if cert.spiffe_id() == Some("spiffe://example.org/billing") {
+    ///         println!("Hello, billing service!");
+    ///     }
+    /// }
+    /// ```
+    pub fn spiffe_id(&self) -> Option<&'a str> {
+        self.uris().find(|uri| {
+            uri.get(..9).map_or(false, |scheme| scheme.eq_ignore_ascii_case("spiffe://"))
+        })
+    }
+
+    /// Returns the key usage extension, if any, which restricts the purposes
+    /// of the certificate's key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     let signs = cert.key_usage().map_or(true, |usage| usage.digital_signature());
+    /// }
+    /// ```
+    pub fn key_usage(&self) -> Option<&KeyUsage> {
+        self.extensions().iter().find_map(|ext| match ext.parsed_extension() {
+            ParsedExtension::KeyUsage(usage) => Some(usage),
+            _ => None,
+        })
+    }
+
+    /// Returns the extended key usage extension, if any, which restricts the
+    /// purposes of the certificate.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     let client = cert.extended_key_usage().map_or(true, |usage| usage.client_auth);
+    /// }
+    /// ```
+    pub fn extended_key_usage(&self) -> Option<&ExtendedKeyUsage<'a>> {
+        self.extensions().iter().find_map(|ext| match ext.parsed_extension() {
+            ParsedExtension::ExtendedKeyUsage(usage) => Some(usage),
+            _ => None,
+        })
+    }
+
+    /// Parses the certificate chain the client presented. The first
+    /// certificate is `self`, followed by any intermediate certificates, each
+    /// certifying the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     for cert in cert.chain().into_iter().flatten() {
+    ///         println!("{} issued by {}", cert.subject(), cert.issuer());
+    ///     }
+    /// }
+    /// ```
+    pub fn chain(&self) -> Result<Vec<Certificate<'a>>> {
+        (0..self.chain.len()).map(|i| Certificate::parse(&self.chain[i..])).collect()
+    }
+
     /// Checks if the certificate has the serial number `number`.
     ///
     /// If `number` is not a valid unsigned integer in base 10, returns `None`.
@@ -511,6 +796,7 @@ impl fmt::Display for Error {
             Error::Empty => write!(f, "empty certificate chain"),
             Error::NoSubject => write!(f, "empty subject without subjectAlt"),
             Error::NonCriticalSubjectAlt => write!(f, "empty subject without critical subjectAlt"),
+            Error::NoIdentity => write!(f, "no identifying attributes"),
         }
     }
 }
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, RwLock};
+
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::{any_supported_type, CertifiedKey};
+
+use crate::tls::util::{load_certs, load_private_key};
+
+/// Resolves the server's certificate for each TLS handshake to the certificate
+/// chain and key most recently [`set()`](CertResolver::set()).
+///
+/// A certificate chain and key may be set for specific server names. When the
+/// client requests a server name via SNI, the key for that name is used, if
+/// there is one. A name of the form `*.example.com` matches any name with
+/// exactly one more label, like `www.example.com`, but not `example.com`. If no
+/// name matches or the client doesn't request one, the default key is used.
+///
+/// Replacing the certificates only affects handshakes that begin afterwards;
+/// established connections continue unaffected.
+pub struct CertResolver {
+    current: RwLock<Arc<Keys>>,
+}
+
+struct Keys {
+    default: Arc<CertifiedKey>,
+    names: HashMap<String, Arc<CertifiedKey>>,
+}
+
+impl Keys {
+    fn new(default: CertifiedKey, names: Vec<(String, CertifiedKey)>) -> Self {
+        let names = names.into_iter()
+            .map(|(name, key)| (CertResolver::normalize(&name), Arc::new(key)))
+            .collect();
+
+        Keys { default: Arc::new(default), names }
+    }
+
+    fn get(&self, name: Option<&str>) -> &Arc<CertifiedKey> {
+        let name = match name {
+            Some(name) => CertResolver::normalize(name),
+            None => return &self.default,
+        };
+
+        let wildcard = name.split_once('.').map(|(_, parent)| format!("*.{}", parent));
+        self.names.get(&name)
+            .or_else(|| wildcard.and_then(|wildcard| self.names.get(&wildcard)))
+            .unwrap_or(&self.default)
+    }
+}
+
+impl CertResolver {
+    /// Creates a resolver which initially resolves to `default` or, for the
+    /// server names in `names`, to the respective key.
+    pub fn new(default: CertifiedKey, names: Vec<(String, CertifiedKey)>) -> Self {
+        CertResolver { current: RwLock::new(Arc::new(Keys::new(default, names))) }
+    }
+
+    /// Loads a certificate chain and private key from `cert_chain` and
+    /// `private_key`, both PEM-encoded.
+    pub fn load(
+        cert_chain: &mut dyn io::BufRead,
+        private_key: &mut dyn io::BufRead,
+    ) -> io::Result<CertifiedKey> {
+        let cert_chain = load_certs(cert_chain)
+            .map_err(|e| io::Error::new(e.kind(), format!("bad TLS cert chain: {}", e)))?;
+
+        let key = load_private_key(private_key)
+            .map_err(|e| io::Error::new(e.kind(), format!("bad TLS private key: {}", e)))?;
+
+        if cert_chain.is_empty() {
+            let msg = "bad TLS cert chain: no certificates found";
+            return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        }
+
+        // `load_private_key()` ensures that the key is usable.
+        let key = any_supported_type(&key).expect("usable key");
+        Ok(CertifiedKey::new(cert_chain, key))
+    }
+
+    /// Like [`CertResolver::load()`], but for the certificate chain and key of
+    /// the server name `name`, which errors mention.
+    pub fn load_named(
+        name: &str,
+        cert_chain: &mut dyn io::BufRead,
+        private_key: &mut dyn io::BufRead,
+    ) -> io::Result<CertifiedKey> {
+        CertResolver::load(cert_chain, private_key)
+            .map_err(|e| io::Error::new(e.kind(), format!("server name `{}`: {}", name, e)))
+    }
+
+    /// Resolves new handshakes to `default` or, for the server names in
+    /// `names`, to the respective key, replacing all previously set keys.
+    pub fn set(&self, default: CertifiedKey, names: Vec<(String, CertifiedKey)>) {
+        *self.current.write().unwrap() = Arc::new(Keys::new(default, names));
+    }
+
+    /// Server names are case-insensitive and may be written fully-qualified,
+    /// with a trailing `.`; returns `name` in a canonical form.
+    fn normalize(name: &str) -> String {
+        name.trim_end_matches('.').to_ascii_lowercase()
+    }
+}
+
+impl fmt::Debug for CertResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertResolver").finish_non_exhaustive()
+    }
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().get(hello.server_name()).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(mut certs: &[u8], mut key: &[u8]) -> CertifiedKey {
+        CertResolver::load(&mut certs, &mut key).unwrap()
+    }
+
+    #[test]
+    fn resolves_keys_by_server_name() {
+        let rsa = key(
+            tls_example_key!("rsa_sha256_cert.pem"),
+            tls_example_key!("rsa_sha256_key.pem"),
+        );
+        let ecdsa = key(
+            tls_example_key!("ecdsa_nistp256_sha256_cert.pem"),
+            tls_example_key!("ecdsa_nistp256_sha256_key_pkcs8.pem"),
+        );
+        let ed25519 = key(
+            tls_example_key!("ed25519_cert.pem"),
+            tls_example_key!("ed25519_key.pem"),
+        );
+
+        let (rsa_der, ecdsa_der, ed25519_der) =
+            (rsa.cert.clone(), ecdsa.cert.clone(), ed25519.cert.clone());
+
+        let keys = Keys::new(rsa, vec![
+            ("Example.COM.".into(), ecdsa),
+            ("*.example.com".into(), ed25519),
+        ]);
+
+        let resolve = |name| &keys.get(name).cert;
+        assert_eq!(resolve(None), &rsa_der);
+        assert_eq!(resolve(Some("rocket.rs")), &rsa_der);
+        assert_eq!(resolve(Some("example.com")), &ecdsa_der);
+        assert_eq!(resolve(Some("EXAMPLE.com.")), &ecdsa_der);
+        assert_eq!(resolve(Some("www.example.com")), &ed25519_der);
+        assert_eq!(resolve(Some("a.b.example.com")), &rsa_der);
+    }
+}
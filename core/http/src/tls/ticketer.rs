@@ -0,0 +1,93 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rustls::server::ProducesTickets;
+
+/// Encrypts stateless session tickets, rotating to a fresh random key every
+/// `period`. Tickets encrypted with the previous key remain decryptable until
+/// the following rotation, so a ticket is usable for up to twice `period`.
+///
+/// Keys are generated by [`rustls::Ticketer`], which rotates its own keys every
+/// six hours; `period` should thus be at most six hours.
+pub(crate) struct RotatingTicketer {
+    period: Duration,
+    state: Mutex<State>,
+}
+
+struct State {
+    current: Arc<dyn ProducesTickets>,
+    previous: Option<Arc<dyn ProducesTickets>>,
+    next_rotation: Instant,
+}
+
+impl RotatingTicketer {
+    pub(crate) fn new(period: Duration) -> Result<Self, rustls::Error> {
+        let state = State {
+            current: rustls::Ticketer::new()?,
+            previous: None,
+            next_rotation: Instant::now() + period,
+        };
+
+        Ok(RotatingTicketer { period, state: Mutex::new(state) })
+    }
+
+    /// Returns the current and previous ticketers, first rotating them if
+    /// `period` has elapsed. If a new key can't be generated, the current one
+    /// remains in use until the next attempt.
+    fn ticketers(&self) -> (Arc<dyn ProducesTickets>, Option<Arc<dyn ProducesTickets>>) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if now >= state.next_rotation {
+            match rustls::Ticketer::new() {
+                Ok(next) => {
+                    let current = std::mem::replace(&mut state.current, next);
+                    state.previous = Some(current);
+                    state.next_rotation = now + self.period;
+                }
+                Err(e) => log::warn!("failed to rotate TLS session ticket key: {}", e),
+            }
+        }
+
+        (state.current.clone(), state.previous.clone())
+    }
+}
+
+impl ProducesTickets for RotatingTicketer {
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn lifetime(&self) -> u32 {
+        u32::try_from(self.period.as_secs().saturating_mul(2)).unwrap_or(u32::MAX)
+    }
+
+    fn encrypt(&self, plain: &[u8]) -> Option<Vec<u8>> {
+        self.ticketers().0.encrypt(plain)
+    }
+
+    fn decrypt(&self, cipher: &[u8]) -> Option<Vec<u8>> {
+        let (current, previous) = self.ticketers();
+        current.decrypt(cipher).or_else(|| previous?.decrypt(cipher))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decrypts_tickets_from_previous_key() {
+        let ticketer = RotatingTicketer::new(Duration::from_millis(100)).unwrap();
+        let ticket = ticketer.encrypt(b"session").unwrap();
+        assert_eq!(ticketer.decrypt(&ticket).unwrap(), b"session");
+
+        std::thread::sleep(Duration::from_millis(150));
+        let fresh = ticketer.encrypt(b"session").unwrap();
+        assert_eq!(ticketer.decrypt(&ticket).unwrap(), b"session");
+
+        std::thread::sleep(Duration::from_millis(150));
+        ticketer.encrypt(b"session").unwrap();
+        assert!(ticketer.decrypt(&ticket).is_none());
+        assert_eq!(ticketer.decrypt(&fresh).unwrap(), b"session");
+    }
+}
@@ -39,11 +39,11 @@ pub fn load_private_key(reader: &mut dyn io::BufRead) -> io::Result<PrivateKey>
         .map(|_| key)
 }
 
-/// Load and decode CA certificates from `reader`.
-pub fn load_ca_certs(reader: &mut dyn io::BufRead) -> io::Result<RootCertStore> {
+/// Build a store of trust roots from the CA certificates `certs`.
+pub fn ca_roots(certs: &[Certificate]) -> io::Result<RootCertStore> {
     let mut roots = rustls::RootCertStore::empty();
-    for cert in load_certs(reader)? {
-        roots.add(&cert).map_err(|e| err(format!("CA cert error: {}", e)))?;
+    for cert in certs {
+        roots.add(cert).map_err(|e| err(format!("CA cert error: {}", e)))?;
     }
 
     Ok(roots)
@@ -53,12 +53,6 @@ pub fn load_ca_certs(reader: &mut dyn io::BufRead) -> io::Result<RootCertStore>
 mod test {
     use super::*;
 
-    macro_rules! tls_example_key {
-        ($k:expr) => {
-            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/tls/private/", $k))
-        }
-    }
-
     #[test]
     fn verify_load_private_keys_of_different_types() -> io::Result<()> {
         let rsa_sha256_key = tls_example_key!("rsa_sha256_key.pem");
@@ -0,0 +1,529 @@
+//! Structured access logging.
+//!
+//! The [`AccessLog`] fairing writes one record per request, after the response
+//! is ready, in one of three [`Format`]s: the [Common Log Format], the
+//! [Combined Log Format], or JSON lines. Records are written, as configured, to
+//! standard output, to a file, or to a custom sink. Unlike Rocket's own
+//! logging, access logging is unaffected by the [`log_level`].
+//!
+//! [Common Log Format]: https://httpd.apache.org/docs/current/logs.html#common
+//! [Combined Log Format]: https://httpd.apache.org/docs/current/logs.html#combined
+//! [`log_level`]: crate::Config::log_level
+//!
+//! # Records
+//!
+//! A JSON record contains the following fields, each of which is `null` when
+//! unknown:
+//!
+//! | Field         | Description                                                     |
+//! |---------------|-----------------------------------------------------------------|
+//! | `time`        | The time the response was ready, in UTC, as an RFC 3339 string. |
+//! | `remote`      | The address of the connection's peer, e.g. `tcp://[::1]:9000`.  |
+//! | `client_ip`   | The [client's IP](crate::Request::client_ip()).                 |
+//! | `method`      | The request method.                                             |
+//! | `uri`         | The request URI: its path and query.                            |
+//! | `version`     | The HTTP version, e.g. `HTTP/1.1`.                              |
+//! | `route`       | The name of the route that handled the request.                 |
+//! | `status`      | The response status code.                                       |
+//! | `size`        | The size of the response body in bytes.                         |
+//! | `latency_ms`  | Milliseconds between the request and the response being ready.  |
+//! | `user_agent`  | The value of the `User-Agent` header.                           |
+//! | `referer`     | The value of the `Referer` header.                              |
+//! | `request_id`  | The [request's ID](crate::Request::request_id()).               |
+//! | `tls_version` | The negotiated TLS version, e.g. `TLSv1.3`.                     |
+//! | `tls_cipher`  | The negotiated TLS cipher suite.                                |
+//!
+//! The Common and Combined formats contain the client's IP, the time, the
+//! request line, the status, and the body size; the Combined format adds the
+//! referer and user agent. Their fields are fixed, so neither contains the
+//! request ID or the TLS version and cipher suite: use the JSON format to log
+//! them. The TLS fields are always `null` without the `tls` feature. Every
+//! request gets a record, including those shed by
+//! [`max_in_flight_requests`](crate::Config::max_in_flight_requests). The
+//! body size of a streamed response isn't known when the record is written and
+//! is logged as unknown. Latency is measured from when the `AccessLog`
+//! fairing's request callback runs; attach it before other request fairings to
+//! include their running time.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! use rocket::access_log::{AccessLog, Format};
+//!
+//! #[launch]
+//! fn rocket() -> _ {
+//!     rocket::build()
+//!         .attach(AccessLog::new(Format::Combined).to_file("/var/log/app/access.log"))
+//! }
+//! ```
+//!
+//! # Writing
+//!
+//! Records are handed to a dedicated writer thread, started at ignition, so a
+//! slow sink never blocks a runtime worker. Up to 1024 records are queued;
+//! beyond that, responses wait for the writer to catch up. When shutdown is
+//! requested, the fairing's shutdown callback waits for the records queued by
+//! then to be written. Records of requests that complete during the grace
+//! period are queued as usual but may be lost if the process exits before the
+//! writer thread gets to them.
+//!
+//! # Log Rotation
+//!
+//! On Unix, a file sink is reopened when the process receives `SIGHUP`, so the
+//! file can be rotated by renaming it and then signaling the process. Records
+//! queued before the signal are written to the old file. Be sure `SIGHUP` isn't
+//! also configured as a [shutdown trigger](crate::config::Shutdown#triggers).
+
+use std::fmt::{self, Write as _};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use state::Storage;
+use time::OffsetDateTime;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{Rocket, Request, Response, Data, Build, Orbit};
+use crate::fairing::{self, Fairing, Info, Kind};
+
+/// The format of [`AccessLog`] records.
+///
+/// See the [module level docs](self) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// The Common Log Format.
+    Common,
+    /// The Combined Log Format.
+    Combined,
+    /// A JSON object per line.
+    Json,
+}
+
+/// A fairing that writes an access log record for every request.
+///
+/// By default, records are written to standard output. Use
+/// [`AccessLog::to_file()`] or [`AccessLog::to_sink()`] to write them
+/// elsewhere. See the [module level docs](self) for details.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::access_log::{AccessLog, Format};
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build().attach(AccessLog::new(Format::Json))
+/// }
+/// ```
+pub struct AccessLog {
+    format: Format,
+    sink: Sink,
+    writer: Storage<mpsc::Sender<Message>>,
+}
+
+#[derive(Clone)]
+enum Sink {
+    Stdout,
+    File(PathBuf),
+    Custom(Arc<dyn Fn(&str) + Send + Sync>),
+}
+
+/// The most records queued for the writer thread at once.
+const QUEUED_RECORDS: usize = 1024;
+
+/// A message to the writer thread.
+enum Message {
+    /// A record to write, without a trailing newline.
+    Record(String),
+    /// Reopen the log file, on `SIGHUP`.
+    Reopen,
+    /// Signals the sender once all previous messages have been handled.
+    Flush(oneshot::Sender<()>),
+}
+
+/// The writer thread's end of a sink: it owns the log file, if any.
+struct Writer {
+    sink: Sink,
+    file: Option<File>,
+}
+
+impl Writer {
+    /// Opens the log file of `sink`, if it has one.
+    fn open(sink: Sink) -> io::Result<Self> {
+        let mut writer = Writer { sink, file: None };
+        writer.reopen()?;
+        Ok(writer)
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        if let Sink::File(path) = &self.sink {
+            self.file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, record: &str) -> io::Result<()> {
+        match (&self.sink, &mut self.file) {
+            (Sink::Stdout, _) => {
+                let stdout = io::stdout();
+                let mut stdout = stdout.lock();
+                stdout.write_all(record.as_bytes()).and_then(|_| stdout.write_all(b"\n"))
+            }
+            (Sink::File(_), Some(file)) => file.write_all(format!("{}\n", record).as_bytes()),
+            (Sink::File(_), None) => Ok(()),
+            (Sink::Custom(sink), _) => {
+                sink(record);
+                Ok(())
+            }
+        }
+    }
+
+    /// Handles messages until every sender is dropped.
+    fn run(mut self, mut messages: mpsc::Receiver<Message>) {
+        while let Some(message) = messages.blocking_recv() {
+            match message {
+                Message::Record(record) => if let Err(e) = self.write(&record) {
+                    warn!("Failed to write access log record: {}", e);
+                },
+                Message::Reopen => if let Err(e) = self.reopen() {
+                    if let Sink::File(path) = &self.sink {
+                        warn!("Failed to reopen access log {}: {}", path.display(), e);
+                    }
+                },
+                Message::Flush(done) => { let _ = done.send(()); }
+            }
+        }
+    }
+}
+
+/// The time at which the `AccessLog` request callback ran for a request.
+struct Started(Instant);
+
+impl AccessLog {
+    /// Returns an `AccessLog` fairing which writes records in `format` to
+    /// standard output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::access_log::{AccessLog, Format};
+    ///
+    /// let access_log = AccessLog::new(Format::Common);
+    /// ```
+    pub fn new(format: Format) -> Self {
+        AccessLog { format, sink: Sink::Stdout, writer: Storage::new() }
+    }
+
+    /// Writes records to standard output. This is the default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::access_log::{AccessLog, Format};
+    ///
+    /// let access_log = AccessLog::new(Format::Json).to_stdout();
+    /// ```
+    pub fn to_stdout(mut self) -> Self {
+        self.sink = Sink::Stdout;
+        self
+    }
+
+    /// Appends records to the file at `path`, creating it if it doesn't exist.
+    /// The file is opened at ignition, which fails if the file can't be
+    /// opened, and is reopened on `SIGHUP` on Unix.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::access_log::{AccessLog, Format};
+    ///
+    /// let access_log = AccessLog::new(Format::Combined).to_file("access.log");
+    /// ```
+    pub fn to_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.sink = Sink::File(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Calls `sink` with each record, formatted without a trailing newline, on
+    /// the writer thread.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::access_log::{AccessLog, Format};
+    ///
+    /// let access_log = AccessLog::new(Format::Json).to_sink(|record| {
+    ///     // Send `record` to a log pipeline.
+    ///     eprintln!("{}", record);
+    /// });
+    /// ```
+    pub fn to_sink<F>(mut self, sink: F) -> Self
+        where F: Fn(&str) + Send + Sync + 'static
+    {
+        self.sink = Sink::Custom(Arc::new(sink));
+        self
+    }
+
+    /// Sends `message` to the writer thread, waiting while the queue is full.
+    async fn send(&self, message: Message) {
+        if let Some(writer) = self.writer.try_get() {
+            if writer.send(message).await.is_err() {
+                warn!("The access log writer thread has stopped.");
+            }
+        }
+    }
+}
+
+#[crate::async_trait]
+impl Fairing for AccessLog {
+    fn info(&self) -> Info {
+        Info {
+            name: "Access Log",
+            kind: Kind::Ignite | Kind::Liftoff | Kind::Request | Kind::Response | Kind::Shutdown,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let writer = match Writer::open(self.sink.clone()) {
+            Ok(writer) => writer,
+            Err(e) => {
+                if let Sink::File(path) = &self.sink {
+                    error!("Failed to open access log {}: {}", path.display(), e);
+                }
+
+                return Err(rocket);
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(QUEUED_RECORDS);
+        let thread = std::thread::Builder::new()
+            .name("rocket-access-log".into())
+            .spawn(move || writer.run(rx));
+
+        if let Err(e) = thread {
+            error!("Failed to start the access log writer thread: {}", e);
+            return Err(rocket);
+        }
+
+        self.writer.set(tx);
+        Ok(rocket)
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        #[cfg(unix)]
+        if let (Sink::File(_), Some(writer)) = (&self.sink, self.writer.try_get()) {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let mut hangups = match signal(SignalKind::hangup()) {
+                Ok(signal) => signal,
+                Err(e) => return warn!("Failed to enable `SIGHUP` access log reopening: {}", e),
+            };
+
+            let (writer, shutdown) = (writer.clone(), rocket.shutdown());
+            tokio::spawn(async move {
+                tokio::pin!(shutdown);
+                loop {
+                    tokio::select! {
+                        Some(_) = hangups.recv() => if writer.send(Message::Reopen).await.is_err() {
+                            break;
+                        },
+                        _ = &mut shutdown => break,
+                    }
+                }
+            });
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        req.local_cache(|| Started(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let latency = req.local_cache(|| Started(Instant::now())).0.elapsed();
+        let record = Record {
+            time: OffsetDateTime::now_utc(),
+            req,
+            route: req.route().and_then(|route| route.name.as_deref()),
+            status: res.status().code,
+            size: res.body().preset_size(),
+            latency_ms: latency.as_secs_f64() * 1000.0,
+        };
+
+        self.send(Message::Record(record.format(self.format))).await;
+    }
+
+    async fn on_shutdown(&self, _: &Rocket<Orbit>) {
+        let (done, flushed) = oneshot::channel();
+        self.send(Message::Flush(done)).await;
+        let _ = flushed.await;
+    }
+}
+
+/// The information about a request and its response logged in a record.
+struct Record<'a, 'r> {
+    time: OffsetDateTime,
+    req: &'a Request<'r>,
+    route: Option<&'a str>,
+    status: u16,
+    size: Option<usize>,
+    latency_ms: f64,
+}
+
+impl Record<'_, '_> {
+    fn format(&self, format: Format) -> String {
+        let mut record = String::with_capacity(256);
+        let _ = match format {
+            Format::Common => self.write_clf(&mut record, false),
+            Format::Combined => self.write_clf(&mut record, true),
+            Format::Json => self.write_json(&mut record),
+        };
+
+        record
+    }
+
+    fn version(&self) -> Option<String> {
+        self.req.state.version.map(|version| format!("{:?}", version))
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.req.headers().get_one(name)
+    }
+
+    #[cfg(feature = "tls")]
+    fn tls(&self) -> Option<(String, String)> {
+        let tls = self.req.tls_info()?;
+        Some((tls.version().to_string(), tls.cipher_suite().to_string()))
+    }
+
+    #[cfg(not(feature = "tls"))]
+    fn tls(&self) -> Option<(String, String)> {
+        None
+    }
+
+    fn write_clf(&self, out: &mut String, combined: bool) -> fmt::Result {
+        const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun",
+            "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+        let t = &self.time;
+        match self.req.client_ip() {
+            Some(ip) => write!(out, "{} - - ", ip)?,
+            None => out.push_str("- - - "),
+        }
+
+        write!(out, "[{:02}/{}/{:04}:{:02}:{:02}:{:02} +0000] ", t.day(),
+            MONTHS[u8::from(t.month()) as usize - 1], t.year(), t.hour(), t.minute(), t.second())?;
+
+        out.push('"');
+        write_clf_escaped(out, self.req.method().as_str());
+        out.push(' ');
+        write_clf_escaped(out, &self.req.uri().to_string());
+        if let Some(version) = self.version() {
+            out.push(' ');
+            out.push_str(&version);
+        }
+
+        write!(out, "\" {} ", self.status)?;
+        match self.size {
+            Some(size) => write!(out, "{}", size)?,
+            None => out.push('-'),
+        }
+
+        if combined {
+            for header in ["Referer", "User-Agent"] {
+                out.push_str(" \"");
+                write_clf_escaped(out, self.header(header).unwrap_or("-"));
+                out.push('"');
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_json(&self, out: &mut String) -> fmt::Result {
+        fn string<T: fmt::Display>(out: &mut String, value: Option<T>) {
+            match value {
+                Some(value) => write_json_string(out, &value.to_string()),
+                None => out.push_str("null"),
+            }
+        }
+
+        let t = &self.time;
+        write!(out, "{{\"time\":\"{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z\"",
+            t.year(), u8::from(t.month()), t.day(),
+            t.hour(), t.minute(), t.second(), t.millisecond())?;
+
+        out.push_str(",\"remote\":");
+        string(out, self.req.remote());
+        out.push_str(",\"client_ip\":");
+        string(out, self.req.client_ip());
+        out.push_str(",\"method\":");
+        string(out, Some(self.req.method()));
+        out.push_str(",\"uri\":");
+        string(out, Some(self.req.uri()));
+        out.push_str(",\"version\":");
+        string(out, self.version());
+        out.push_str(",\"route\":");
+        string(out, self.route);
+        write!(out, ",\"status\":{}", self.status)?;
+        match self.size {
+            Some(size) => write!(out, ",\"size\":{}", size)?,
+            None => out.push_str(",\"size\":null"),
+        }
+
+        write!(out, ",\"latency_ms\":{:.3}", self.latency_ms)?;
+        out.push_str(",\"user_agent\":");
+        string(out, self.header("User-Agent"));
+        out.push_str(",\"referer\":");
+        string(out, self.header("Referer"));
+        out.push_str(",\"request_id\":");
+        string(out, Some(self.req.request_id()));
+        let tls = self.tls();
+        out.push_str(",\"tls_version\":");
+        string(out, tls.as_ref().map(|(version, _)| version));
+        out.push_str(",\"tls_cipher\":");
+        string(out, tls.as_ref().map(|(_, cipher)| cipher));
+        out.push('}');
+        Ok(())
+    }
+}
+
+/// Writes `value`, escaping quotes, backslashes, and non-printable characters
+/// as Apache's `mod_log_config` does.
+fn write_clf_escaped(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_ascii_graphic() || c == ' ' => out.push(c),
+            c => {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    let _ = write!(out, "\\x{:02x}", byte);
+                }
+            }
+        }
+    }
+}
+
+/// Writes `value` as a quoted JSON string.
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => { let _ = write!(out, "\\u{:04x}", c as u32); }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
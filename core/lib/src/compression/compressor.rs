@@ -0,0 +1,164 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::ready;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::compression::{Encoding, Level};
+
+/// An `async-compression` encoder writing into an in-memory buffer.
+enum Encoder {
+    #[cfg(feature = "gzip")]
+    Gzip(async_compression::tokio::write::GzipEncoder<Vec<u8>>),
+    #[cfg(feature = "deflate")]
+    Deflate(async_compression::tokio::write::ZlibEncoder<Vec<u8>>),
+    #[cfg(feature = "brotli")]
+    Brotli(Box<async_compression::tokio::write::BrotliEncoder<Vec<u8>>>),
+    #[cfg(feature = "zstd")]
+    Zstd(async_compression::tokio::write::ZstdEncoder<Vec<u8>>),
+}
+
+macro_rules! with_encoder {
+    ($encoder:expr, $e:ident => $body:expr) => {
+        match $encoder {
+            #[cfg(feature = "gzip")] Encoder::Gzip($e) => $body,
+            #[cfg(feature = "deflate")] Encoder::Deflate($e) => $body,
+            #[cfg(feature = "brotli")] Encoder::Brotli($e) => $body,
+            #[cfg(feature = "zstd")] Encoder::Zstd($e) => $body,
+        }
+    };
+}
+
+impl Encoder {
+    fn new(encoding: Encoding, level: Level) -> Self {
+        use async_compression::tokio::write::*;
+
+        let (buf, level) = (Vec::new(), level.for_encoding(encoding));
+        match encoding {
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => Encoder::Gzip(GzipEncoder::with_quality(buf, level)),
+            #[cfg(feature = "deflate")]
+            Encoding::Deflate => Encoder::Deflate(ZlibEncoder::with_quality(buf, level)),
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => Encoder::Brotli(Box::new(BrotliEncoder::with_quality(buf, level))),
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => Encoder::Zstd(ZstdEncoder::with_quality(buf, level)),
+        }
+    }
+
+    fn output(&mut self) -> &mut Vec<u8> {
+        with_encoder!(self, e => e.get_mut())
+    }
+
+    fn as_writer(&mut self) -> Pin<&mut (dyn AsyncWrite + Send + Unpin)> {
+        with_encoder!(self, e => Pin::new(e))
+    }
+
+    /// Writes all of `data` to the encoder. The encoder writes to a `Vec`, so
+    /// this never blocks.
+    fn compress(&mut self, cx: &mut Context<'_>, mut data: &[u8]) -> Poll<io::Result<()>> {
+        while !data.is_empty() {
+            match ready!(self.as_writer().poll_write(cx, data))? {
+                0 => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                n => data = &data[n..],
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// Reading from the inner reader and compressing.
+    Reading,
+    /// The inner reader is exhausted; finishing the compressed stream.
+    Finishing,
+    /// The compressed stream is complete.
+    Done,
+}
+
+/// Compresses the bytes read from `R` as they become available.
+///
+/// Whenever the inner reader would block after producing some data, all of the
+/// data compressed so far is flushed so that it can be sent to the client right
+/// away. This means that streaming responses, like server-sent events, remain
+/// streaming when compressed.
+pub(crate) struct Compressor<R> {
+    reader: R,
+    encoder: Encoder,
+    state: State,
+    /// Scratch space for data read from `reader`.
+    input: Box<[u8]>,
+    /// How much of `encoder.output()` has been read out.
+    position: usize,
+    /// Whether data has been written to `encoder` since the last flush.
+    dirty: bool,
+}
+
+impl<R: AsyncRead + Unpin> Compressor<R> {
+    pub(crate) fn new(reader: R, encoding: Encoding, level: Level, chunk_size: usize) -> Self {
+        Compressor {
+            reader,
+            encoder: Encoder::new(encoding, level),
+            state: State::Reading,
+            input: vec![0; chunk_size].into_boxed_slice(),
+            position: 0,
+            dirty: false,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for Compressor<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            // Hand out any compressed data we already have.
+            let position = this.position;
+            let output = this.encoder.output();
+            if position < output.len() {
+                let n = std::cmp::min(buf.remaining(), output.len() - position);
+                buf.put_slice(&output[position..(position + n)]);
+                this.position += n;
+                if this.position == output.len() {
+                    output.clear();
+                    this.position = 0;
+                }
+
+                return Poll::Ready(Ok(()));
+            }
+
+            match this.state {
+                State::Done => return Poll::Ready(Ok(())),
+                State::Finishing => {
+                    ready!(this.encoder.as_writer().poll_shutdown(cx))?;
+                    this.state = State::Done;
+                }
+                State::Reading => {
+                    let mut input = ReadBuf::new(&mut this.input);
+                    match Pin::new(&mut this.reader).poll_read(cx, &mut input) {
+                        Poll::Ready(Ok(())) if input.filled().is_empty() => {
+                            this.state = State::Finishing;
+                        }
+                        Poll::Ready(Ok(())) => {
+                            let n = input.filled().len();
+                            ready!(this.encoder.compress(cx, &this.input[..n]))?;
+                            this.dirty = true;
+                        }
+                        Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                        Poll::Pending if this.dirty => {
+                            ready!(this.encoder.as_writer().poll_flush(cx))?;
+                            this.dirty = false;
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, de};
+
+use crate::data::{ByteUnit, ToByteUnit};
+use crate::http::{ContentType, MediaType};
+use crate::compression::Encoding;
+
+/// Configuration for the [`Compression`](super::Compression) fairing.
+///
+/// Configuration is read from the `compression` key of the configured
+/// provider, usually `Rocket.toml`, when the fairing is created via
+/// [`Compression::fairing()`](super::Compression::fairing()). Every field is
+/// optional; missing fields take their default value:
+///
+/// ```toml
+/// [default.compression]
+/// level = "default"
+/// min_size = "1KiB"
+/// encodings = ["br", "zstd", "gzip", "deflate"]
+/// exclude = ["image/png", "image/jpeg", "video/*", "application/zip"]
+/// ```
+///
+/// | field       | type                           | default                      |
+/// |-------------|--------------------------------|------------------------------|
+/// | `level`     | [`Level`]                      | `"default"`                  |
+/// | `min_size`  | [`ByteUnit`]                   | `1KiB`                       |
+/// | `encodings` | array of [`Encoding`] names    | [`Encoding::ALL`]            |
+/// | `exclude`   | array of media type strings    | [`CompressionConfig::DEFAULT_EXCLUDE`] |
+///
+/// `encodings` lists the encodings the fairing may use in order of preference:
+/// when a client accepts several with the same quality, the first is used.
+/// Encodings must be enabled via their crate feature. `exclude` lists media
+/// types that are never compressed, usually because they are already
+/// compressed. A `*` subtype, as in `video/*`, excludes an entire top-level
+/// type. Responses smaller than `min_size` are never compressed.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::compression::{CompressionConfig, Level};
+/// use rocket::data::ToByteUnit;
+/// use rocket::http::ContentType;
+///
+/// let config = CompressionConfig {
+///     level: Level::Fastest,
+///     min_size: 4.kibibytes(),
+///     exclude: vec![ContentType::PNG, ContentType::JPEG],
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// The compression level. **(default: [`Level::Default`])**
+    pub level: Level,
+    /// The minimum size of a body to compress. Bodies of unknown size, such as
+    /// streams, are always eligible. **(default: `1KiB`)**
+    pub min_size: ByteUnit,
+    /// The encodings to use, in order of preference. **(default:
+    /// [`Encoding::ALL`])**
+    pub encodings: Vec<Encoding>,
+    /// The content types which are never compressed. **(default:
+    /// [`CompressionConfig::DEFAULT_EXCLUDE`])**
+    #[serde(deserialize_with = "deserialize_content_types")]
+    pub exclude: Vec<ContentType>,
+}
+
+impl CompressionConfig {
+    /// The media types excluded from compression by default. These are media
+    /// types whose contents are typically already compressed.
+    pub const DEFAULT_EXCLUDE: &'static [&'static str] = &[
+        "image/png", "image/jpeg", "image/gif", "image/webp", "image/avif",
+        "image/heic", "video/*", "audio/*", "font/woff", "font/woff2",
+        "application/zip", "application/gzip", "application/x-gzip",
+        "application/x-bzip", "application/x-bzip2", "application/x-xz",
+        "application/x-7z-compressed", "application/vnd.rar", "application/zstd",
+        "application/x-brotli", "application/pdf", "application/octet-stream",
+    ];
+
+    /// Returns `true` if `content_type` is excluded from compression.
+    pub(crate) fn is_excluded(&self, content_type: &ContentType) -> bool {
+        let matches = |excluded: &MediaType| {
+            let given = content_type.media_type();
+            excluded.top() == given.top()
+                && (excluded.sub() == "*" || excluded.sub() == given.sub())
+        };
+
+        self.exclude.iter().any(|e| matches(e.media_type()))
+    }
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        CompressionConfig {
+            level: Level::Default,
+            min_size: 1.kibibytes(),
+            encodings: Encoding::ALL.to_vec(),
+            exclude: CompressionConfig::DEFAULT_EXCLUDE.iter()
+                .map(|s| ContentType::parse_flexible(s).expect("valid media type"))
+                .collect(),
+        }
+    }
+}
+
+fn deserialize_content_types<'de, D>(de: D) -> Result<Vec<ContentType>, D::Error>
+    where D: Deserializer<'de>
+{
+    Vec::<String>::deserialize(de)?
+        .into_iter()
+        .map(|s| ContentType::parse_flexible(&s).ok_or_else(|| {
+            de::Error::invalid_value(de::Unexpected::Str(&s), &"a media type")
+        }))
+        .collect()
+}
+
+/// A compression level.
+///
+/// In configuration, a level is either one of the strings `"fastest"`,
+/// `"default"`, or `"best"`, or an integer, which is interpreted according to
+/// the selected encoding and clamped to its valid range.
+///
+/// # Example
+///
+/// ```toml
+/// [default.compression]
+/// level = "fastest"
+///
+/// [release.compression]
+/// level = 6
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Level {
+    /// The fastest compression the encoding supports.
+    Fastest,
+    /// A level that balances speed and size, appropriate for compressing
+    /// responses on the fly. For `br`, this is quality `4`; for all others,
+    /// it is the encoding's default.
+    Default,
+    /// The smallest output the encoding supports. This can be slow.
+    Best,
+    /// An encoding-specific level.
+    Precise(i32),
+}
+
+impl Level {
+    /// The `async-compression` level to use for `encoding`.
+    #[cfg_attr(not(feature = "brotli"), allow(unused_variables))]
+    pub(crate) fn for_encoding(self, encoding: Encoding) -> async_compression::Level {
+        use async_compression::Level as L;
+
+        // Brotli's default, 11, is far too slow for on-the-fly compression.
+        #[cfg(feature = "brotli")]
+        if self == Level::Default && encoding == Encoding::Brotli {
+            return L::Precise(4);
+        }
+
+        match self {
+            Level::Default => L::Default,
+            Level::Fastest => L::Fastest,
+            Level::Best => L::Best,
+            Level::Precise(n) => L::Precise(n),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Fastest => "fastest".fmt(f),
+            Level::Default => "default".fmt(f),
+            Level::Best => "best".fmt(f),
+            Level::Precise(n) => n.fmt(f),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Level {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Level;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("`fastest`, `default`, `best`, or an integer level")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                i32::try_from(v)
+                    .map(Level::Precise)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                i32::try_from(v)
+                    .map(Level::Precise)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                match v {
+                    v if v.eq_ignore_ascii_case("fastest") => Ok(Level::Fastest),
+                    v if v.eq_ignore_ascii_case("default") => Ok(Level::Default),
+                    v if v.eq_ignore_ascii_case("best") => Ok(Level::Best),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+                }
+            }
+        }
+
+        de.deserialize_any(Visitor)
+    }
+}
@@ -0,0 +1,55 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
+
+use crate::compression::Encoding;
+
+/// Decompresses the bytes read from `R` as they are read.
+pub(crate) enum Decompressor<R> {
+    #[cfg(feature = "gzip")]
+    Gzip(async_compression::tokio::bufread::GzipDecoder<R>),
+    #[cfg(feature = "deflate")]
+    Deflate(async_compression::tokio::bufread::ZlibDecoder<R>),
+    #[cfg(feature = "brotli")]
+    Brotli(Box<async_compression::tokio::bufread::BrotliDecoder<R>>),
+    #[cfg(feature = "zstd")]
+    Zstd(async_compression::tokio::bufread::ZstdDecoder<R>),
+}
+
+impl<R: AsyncBufRead> Decompressor<R> {
+    pub(crate) fn new(reader: R, encoding: Encoding) -> Self {
+        use async_compression::tokio::bufread::*;
+
+        match encoding {
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => Decompressor::Gzip(GzipDecoder::new(reader)),
+            #[cfg(feature = "deflate")]
+            Encoding::Deflate => Decompressor::Deflate(ZlibDecoder::new(reader)),
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => Decompressor::Brotli(Box::new(BrotliDecoder::new(reader))),
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => Decompressor::Zstd(ZstdDecoder::new(reader)),
+        }
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncRead for Decompressor<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            #[cfg(feature = "gzip")]
+            Decompressor::Gzip(d) => Pin::new(d).poll_read(cx, buf),
+            #[cfg(feature = "deflate")]
+            Decompressor::Deflate(d) => Pin::new(d).poll_read(cx, buf),
+            #[cfg(feature = "brotli")]
+            Decompressor::Brotli(d) => Pin::new(d).poll_read(cx, buf),
+            #[cfg(feature = "zstd")]
+            Decompressor::Zstd(d) => Pin::new(d).poll_read(cx, buf),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer};
+
+use crate::http::uncased::UncasedStr;
+
+/// A content coding supported by the [`Compression`](super::Compression)
+/// fairing.
+///
+/// Each variant is only available when the eponymous crate feature is enabled.
+/// In configuration, an `Encoding` is written as its [name](Encoding::name()):
+/// `gzip`, `deflate`, `br`, or `zstd`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// The `gzip` content coding.
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(nightly, doc(cfg(feature = "gzip")))]
+    Gzip,
+    /// The `deflate` content coding: zlib-wrapped DEFLATE.
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(nightly, doc(cfg(feature = "deflate")))]
+    Deflate,
+    /// The `br` (Brotli) content coding.
+    #[cfg(feature = "brotli")]
+    #[cfg_attr(nightly, doc(cfg(feature = "brotli")))]
+    Brotli,
+    /// The `zstd` (Zstandard) content coding.
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(nightly, doc(cfg(feature = "zstd")))]
+    Zstd,
+}
+
+impl Encoding {
+    /// All of the encodings enabled in this build, in Rocket's default order
+    /// of preference.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::compression::Encoding;
+    ///
+    /// assert!(!Encoding::ALL.is_empty());
+    /// ```
+    pub const ALL: &'static [Encoding] = &[
+        #[cfg(feature = "brotli")] Encoding::Brotli,
+        #[cfg(feature = "zstd")] Encoding::Zstd,
+        #[cfg(feature = "gzip")] Encoding::Gzip,
+        #[cfg(feature = "deflate")] Encoding::Deflate,
+    ];
+
+    /// Returns the name of the encoding as it appears in the `Accept-Encoding`
+    /// and `Content-Encoding` headers.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "gzip")] {
+    /// use rocket::compression::Encoding;
+    ///
+    /// assert_eq!(Encoding::Gzip.name(), "gzip");
+    /// # }
+    /// ```
+    pub const fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "gzip")] Encoding::Gzip => "gzip",
+            #[cfg(feature = "deflate")] Encoding::Deflate => "deflate",
+            #[cfg(feature = "brotli")] Encoding::Brotli => "br",
+            #[cfg(feature = "zstd")] Encoding::Zstd => "zstd",
+        }
+    }
+
+    /// Returns the encoding named `name`, if it is enabled, compared
+    /// case-insensitively. `x-gzip` is accepted as an alias for `gzip`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "gzip")] {
+    /// use rocket::compression::Encoding;
+    ///
+    /// assert_eq!(Encoding::from_name("GZIP"), Some(Encoding::Gzip));
+    /// assert_eq!(Encoding::from_name("x-gzip"), Some(Encoding::Gzip));
+    /// assert_eq!(Encoding::from_name("identity"), None);
+    /// # }
+    /// ```
+    pub fn from_name(name: &str) -> Option<Encoding> {
+        let name = UncasedStr::new(name);
+        Encoding::ALL.iter()
+            .copied()
+            .find(|e| name == e.name())
+            .or_else(|| {
+                #[cfg(feature = "gzip")]
+                if name == "x-gzip" {
+                    return Some(Encoding::Gzip);
+                }
+
+                None
+            })
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+impl<'de> Deserialize<'de> for Encoding {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(de)?;
+        Encoding::from_name(&name).ok_or_else(|| {
+            let features = "`gzip`, `deflate`, `brotli`, or `zstd` feature";
+            let expected = format!("a content coding enabled via the {}", features);
+            de::Error::invalid_value(de::Unexpected::Str(&name), &&*expected)
+        })
+    }
+}
+
+/// Returns the most preferred encoding in `available` acceptable according to
+/// the `Accept-Encoding` header values `accept`, if any.
+///
+/// The encoding with the largest quality value wins. Ties are broken by the
+/// order of `available`. Encodings not named in `accept` are acceptable only
+/// if `*` is. When there is no `Accept-Encoding` header, returns `None`: while
+/// technically any coding is acceptable, clients that don't ask for one rarely
+/// expect one.
+pub(crate) fn negotiate<'a, I>(accept: I, available: &[Encoding]) -> Option<Encoding>
+    where I: Iterator<Item = &'a str>
+{
+    let mut wildcard = None;
+    let mut qualities: Vec<(Encoding, f32)> = vec![];
+    let mut any = false;
+    for (name, q) in accept.flat_map(|v| v.split(',')).filter_map(parse_coding) {
+        any = true;
+        if name == "*" {
+            wildcard = Some(q);
+        } else if let Some(encoding) = Encoding::from_name(name) {
+            qualities.push((encoding, q));
+        }
+    }
+
+    if !any {
+        return None;
+    }
+
+    let quality = |encoding: Encoding| qualities.iter()
+        .find(|(e, _)| *e == encoding)
+        .map(|(_, q)| *q)
+        .or(wildcard)
+        .unwrap_or(0.0);
+
+    let mut best: Option<(Encoding, f32)> = None;
+    for &encoding in available {
+        let q = quality(encoding);
+        if q > 0.0 && best.map_or(true, |(_, best_q)| q > best_q) {
+            best = Some((encoding, q));
+        }
+    }
+
+    best.map(|(encoding, _)| encoding)
+}
+
+/// Parses one `coding;q=value` element of an `Accept-Encoding` header.
+fn parse_coding(element: &str) -> Option<(&str, f32)> {
+    let mut parts = element.split(';').map(str::trim);
+    let name = parts.next().filter(|n| !n.is_empty())?;
+    let mut q = 1.0;
+    for param in parts {
+        let (key, value) = param.split_once('=')?;
+        if key.trim().eq_ignore_ascii_case("q") {
+            q = value.trim().parse::<f32>().ok().filter(|q| (0.0..=1.0).contains(q))?;
+        }
+    }
+
+    Some((name, q))
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "gzip", feature = "brotli"))]
+mod tests {
+    use super::{negotiate, Encoding::{self, *}};
+
+    const AVAILABLE: &[Encoding] = &[Brotli, Gzip];
+
+    fn best(accept: &[&str]) -> Option<Encoding> {
+        negotiate(accept.iter().copied(), AVAILABLE)
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(best(&[]), None);
+        assert_eq!(best(&[""]), None);
+        assert_eq!(best(&["identity"]), None);
+        assert_eq!(best(&["gzip"]), Some(Gzip));
+        assert_eq!(best(&["GZip"]), Some(Gzip));
+        assert_eq!(best(&["x-gzip"]), Some(Gzip));
+        assert_eq!(best(&["gzip, br"]), Some(Brotli));
+        assert_eq!(best(&["gzip", "br"]), Some(Brotli));
+        assert_eq!(best(&["gzip, deflate, br"]), Some(Brotli));
+        assert_eq!(best(&["*"]), Some(Brotli));
+        assert_eq!(best(&["br;q=0, *"]), Some(Gzip));
+        assert_eq!(best(&["gzip;q=1.0, br;q=0.5"]), Some(Gzip));
+        assert_eq!(best(&["gzip;q=0.5, br;q=0.5"]), Some(Brotli));
+        assert_eq!(best(&["gzip;q=0.2, *;q=0.1"]), Some(Gzip));
+        assert_eq!(best(&["gzip;q=0, br;q=0"]), None);
+        assert_eq!(best(&["*;q=0"]), None);
+        assert_eq!(best(&["gzip;q=2, br;q=nope"]), None);
+        assert_eq!(best(&[" gzip ; q=0.8 , identity"]), Some(Gzip));
+    }
+}
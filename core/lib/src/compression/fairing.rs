@@ -0,0 +1,185 @@
+use state::Storage;
+use yansi::Paint;
+
+use crate::{Rocket, Request, Response, Build, Orbit};
+use crate::fairing::{self, Fairing, Info, Kind};
+use crate::http::{Header, Status, uncased::eq};
+use crate::log::PaintExt;
+use crate::compression::{CompressionConfig, Compressor, negotiate};
+
+/// A fairing that compresses response bodies.
+///
+/// See the [module level docs](crate::compression) for details.
+pub struct Compression {
+    custom: Option<CompressionConfig>,
+    config: Storage<CompressionConfig>,
+}
+
+impl Compression {
+    /// Returns a `Compression` fairing configured from the `compression` key
+    /// of the configuration provider, falling back to
+    /// [`CompressionConfig::default()`] for missing values. An invalid
+    /// configuration aborts launch.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::compression::Compression;
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     rocket::build().attach(Compression::fairing())
+    /// }
+    /// ```
+    pub fn fairing() -> Self {
+        Compression { custom: None, config: Storage::new() }
+    }
+
+    /// Returns a `Compression` fairing using `config`. The configuration
+    /// provider is not consulted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::compression::{Compression, CompressionConfig, Level};
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     let config = CompressionConfig { level: Level::Best, ..Default::default() };
+    ///     rocket::build().attach(Compression::custom(config))
+    /// }
+    /// ```
+    pub fn custom(config: CompressionConfig) -> Self {
+        Compression { custom: Some(config), config: Storage::new() }
+    }
+
+    /// Returns `true` if `response` is eligible for compression, ignoring the
+    /// request's `Accept-Encoding`.
+    async fn is_compressible(config: &CompressionConfig, response: &mut Response<'_>) -> bool {
+        let status = response.status();
+        if status.class().is_informational()
+            || status == Status::NoContent
+            || status == Status::NotModified
+            || status == Status::PartialContent
+        {
+            return false;
+        }
+
+        let headers = response.headers();
+        if headers.contains("Content-Encoding") || headers.contains("Content-Range") {
+            return false;
+        }
+
+        let no_transform = headers.get("Cache-Control")
+            .flat_map(|v| v.split(','))
+            .any(|directive| eq(directive.trim(), "no-transform"));
+
+        if no_transform {
+            return false;
+        }
+
+        if let Some(content_type) = response.content_type() {
+            if config.is_excluded(&content_type) {
+                return false;
+            }
+        }
+
+        let body = response.body_mut();
+        if body.is_none() {
+            return false;
+        }
+
+        match body.size().await {
+            Some(size) => size as u64 >= config.min_size.as_u64(),
+            None => true,
+        }
+    }
+}
+
+#[crate::async_trait]
+impl Fairing for Compression {
+    fn info(&self) -> Info {
+        Info {
+            name: "Compression",
+            kind: Kind::Ignite | Kind::Liftoff | Kind::Response | Kind::Singleton,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let config = match self.custom.clone() {
+            Some(config) => config,
+            None => match rocket.figment().extract_inner::<CompressionConfig>("compression") {
+                Ok(config) => config,
+                Err(e) if e.missing() => CompressionConfig::default(),
+                Err(e) => {
+                    crate::config::pretty_print_error(e);
+                    return Err(rocket);
+                }
+            }
+        };
+
+        self.config.set(config);
+        Ok(rocket)
+    }
+
+    async fn on_liftoff(&self, _: &Rocket<Orbit>) {
+        let config = match self.config.try_get() {
+            Some(config) => config,
+            None => return,
+        };
+
+        info!("{}{}:", Paint::emoji("🗜️ "), Paint::magenta("Compression"));
+        let encodings: Vec<_> = config.encodings.iter().map(|e| e.name()).collect();
+        info_!("encodings: {}", Paint::default(encodings.join(", ")).bold());
+        info_!("level: {}", Paint::default(config.level).bold());
+        info_!("min size: {}", Paint::default(config.min_size).bold());
+        if encodings.is_empty() {
+            warn_!("No encodings are configured. Responses will not be compressed.");
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let config = match self.config.try_get() {
+            Some(config) => config,
+            None => return,
+        };
+
+        if config.encodings.is_empty() || !Self::is_compressible(config, res).await {
+            return;
+        }
+
+        // The response's representation now depends on `Accept-Encoding`.
+        let has_vary = res.headers().get("Vary")
+            .flat_map(|v| v.split(','))
+            .any(|v| v.trim() == "*" || eq(v.trim(), "Accept-Encoding"));
+
+        if !has_vary {
+            res.adjoin_raw_header("Vary", "Accept-Encoding");
+        }
+
+        let accept = req.headers().get("Accept-Encoding");
+        let encoding = match negotiate(accept, &config.encodings) {
+            Some(encoding) => encoding,
+            None => return,
+        };
+
+        // A strong validator must change with the representation.
+        if let Some(etag) = res.headers().get_one("ETag") {
+            if etag.starts_with('"') {
+                let weak = format!("W/{}", etag);
+                res.set_raw_header("ETag", weak);
+            }
+        }
+
+        let body = res.body_mut().take();
+        let chunk_size = body.max_chunk_size();
+        let compressor = Compressor::new(body, encoding, config.level, chunk_size);
+        res.set_streamed_body(compressor);
+        res.set_max_chunk_size(chunk_size);
+        res.set_header(Header::new("Content-Encoding", encoding.name()));
+        res.remove_header("Content-Length");
+        res.remove_header("Accept-Ranges");
+    }
+}
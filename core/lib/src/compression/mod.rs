@@ -0,0 +1,85 @@
+//! Response compression and request decompression.
+//!
+//! The [`Compression`] fairing compresses response bodies with the content
+//! coding most preferred by the client, as indicated by the request's
+//! `Accept-Encoding` header and its quality values, among those that are
+//! enabled. Each supported coding is enabled via a crate feature:
+//!
+//! | Feature   | Content Coding | [`Encoding`]          |
+//! |-----------|----------------|-----------------------|
+//! | `gzip`    | `gzip`         | [`Encoding::Gzip`]    |
+//! | `deflate` | `deflate`      | [`Encoding::Deflate`] |
+//! | `brotli`  | `br`           | [`Encoding::Brotli`]  |
+//! | `zstd`    | `zstd`         | [`Encoding::Zstd`]    |
+//!
+//! This module is only available when at least one of these features is
+//! enabled. The fairing is not attached by default.
+//!
+//! # Usage
+//!
+//! Attach [`Compression::fairing()`] to read configuration from the
+//! `compression` key of the configuration provider, or
+//! [`Compression::custom()`] to configure the fairing programmatically. See
+//! [`CompressionConfig`] for the available options.
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! use rocket::compression::Compression;
+//!
+//! #[launch]
+//! fn rocket() -> _ {
+//!     rocket::build().attach(Compression::fairing())
+//! }
+//! ```
+//!
+//! # Behavior
+//!
+//! A response is compressed only if all of the following hold:
+//!
+//!   * The request's `Accept-Encoding` header accepts one of the configured
+//!     [encodings](CompressionConfig::encodings) with a non-zero quality.
+//!   * The response has a body that is at least
+//!     [`min_size`](CompressionConfig::min_size) bytes long or whose size is
+//!     unknown, as is the case for streams.
+//!   * The response's `Content-Type` is not [excluded].
+//!   * The response doesn't already have a `Content-Encoding` or a
+//!     `Content-Range`, its status isn't `1xx`, `204`, `206`, or `304`, and
+//!     its `Cache-Control` doesn't include `no-transform`.
+//!
+//! When all but the first condition hold, `Vary: Accept-Encoding` is added to
+//! the response, whether it is ultimately compressed or not. A compressed
+//! response has its `Content-Encoding` set, is sent without a
+//! `Content-Length` or `Accept-Ranges`, and has any strong `ETag` weakened.
+//!
+//! Bodies are compressed as they are read, chunk by chunk. Whenever a streamed
+//! body has no more data immediately available, the compressed data produced
+//! so far is flushed to the client. As a result, streaming responses, like
+//! [`EventStream`](crate::response::stream::EventStream)s, remain streaming.
+//!
+//! # Request Bodies
+//!
+//! Independently of the fairing, request bodies with a `Content-Encoding`
+//! naming only enabled content codings are decoded transparently: the
+//! [`DataStream`](crate::data::DataStream) returned by [`Data::open()`] yields
+//! decoded data, and the limit passed to `open()` applies to the _decoded_
+//! size. As a result, data guards like `Json`, [`Form`](crate::form::Form),
+//! and [`TempFile`](crate::fs::TempFile) accept compressed bodies unchanged,
+//! and a small compressed body can't expand beyond the configured
+//! [`Limits`](crate::data::Limits). See [`Data::open()`] for details.
+//!
+//! [excluded]: CompressionConfig::exclude
+//! [`Data::open()`]: crate::data::Data::open()
+
+mod config;
+mod encoding;
+mod compressor;
+mod decompressor;
+mod fairing;
+
+pub use self::config::{CompressionConfig, Level};
+pub use self::encoding::Encoding;
+pub use self::fairing::Compression;
+
+pub(crate) use self::encoding::negotiate;
+pub(crate) use self::compressor::Compressor;
+pub(crate) use self::decompressor::Decompressor;
@@ -4,9 +4,10 @@ use figment::{Figment, Profile, Provider, Metadata, error::Result};
 use figment::providers::{Serialized, Env, Toml, Format};
 use figment::value::{Map, Dict, magic::RelativePathBuf};
 use serde::{Deserialize, Serialize};
+use serde::de::{self, Deserializer};
 use yansi::Paint;
 
-use crate::config::{LogLevel, Shutdown, Ident};
+use crate::config::{LogLevel, Shutdown, Timeouts, Ident, ListenerConfig, ProxyProtocol, UnixSocket};
 use crate::request::{self, Request, FromRequest};
 use crate::data::Limits;
 
@@ -16,7 +17,12 @@ use crate::config::TlsConfig;
 #[cfg(feature = "secrets")]
 use crate::config::SecretKey;
 
+#[cfg(feature = "tracing")]
+use crate::config::Tracing;
+
 use crate::http::bindable::BindableAddr;
+use crate::http::uncased::Uncased;
+use crate::http::proxy_protocol::IpNet;
 
 /// Rocket server configuration.
 ///
@@ -69,6 +75,9 @@ pub struct Config {
     /// The address to listen on for HTTP requests. **(default: `"tcp://localhost:8000"`)**
     #[serde(flatten)]
     pub address: BindableAddr,
+    /// Additional addresses to listen on, each with its own, optional TLS
+    /// configuration. See [`ListenerConfig`] for details. **(default: `[]`)**
+    pub listeners: Vec<ListenerConfig>,
     /// Number of threads to use for executing futures. **(default: `num_cores`)**
     ///
     /// _**Note:** Rocket only reads this value from sources in the [default
@@ -85,6 +94,56 @@ pub struct Config {
     pub temp_dir: RelativePathBuf,
     /// Keep-alive timeout in seconds; disabled when `0`. **(default: `5`)**
     pub keep_alive: u32,
+    /// Header, body, idle, and handler timeouts. **(default:
+    /// [`Timeouts::default()`])**
+    pub timeouts: Timeouts,
+    /// The maximum number of connections to keep open at once across all
+    /// listeners, if limited. When reached, new connections aren't accepted
+    /// until an open one closes. **(default: `None`)**
+    pub max_connections: Option<usize>,
+    /// The maximum number of requests to serve at once, if limited. Requests
+    /// beyond the limit are shed: they're responded to with a `503 Service
+    /// Unavailable` with a `Retry-After` header of `retry_after` seconds,
+    /// without routing. Response fairings still run. **(default: `None`)**
+    pub max_in_flight_requests: Option<usize>,
+    /// Seconds a client is asked to wait before retrying a shed request.
+    /// **(default: `1`)**
+    pub retry_after: u32,
+    /// The PROXY protocol configuration for [`Config::address`], if any. See
+    /// [`ProxyProtocol`] for details. **(default: `None`)**
+    pub proxy_protocol: Option<ProxyProtocol>,
+    /// The socket file configuration for [`Config::address`] when it is a
+    /// Unix socket, if any. See [`UnixSocket`] for details.
+    /// **(default: `None`)**
+    pub unix_socket: Option<UnixSocket>,
+    /// The name of the header, if any, to read the client's IP address from.
+    /// **(default: `"X-Real-IP"`)**
+    ///
+    /// The `Forwarded` and `X-Forwarded-For` headers are read as chains of
+    /// proxies; any other header must contain a single IP address. A value of
+    /// `false` disables reading the client's IP from a header. See
+    /// [`Request::real_ip()`] for details.
+    #[serde(deserialize_with = "deserialize_header_name")]
+    pub ip_header: Option<Uncased<'static>>,
+    /// The name of the header, if any, to read a request's ID from and echo it
+    /// in on the response. **(default: `"X-Request-Id"`)**
+    ///
+    /// A request without a valid ID in the header, or from a peer not in
+    /// `trusted_proxies`, when set, is assigned a random one. The ID is echoed
+    /// on every response. A value of `false` disables both reading and echoing
+    /// the header; requests are then always assigned a random ID. See
+    /// [`Request::request_id()`] for details.
+    #[serde(deserialize_with = "deserialize_header_name")]
+    pub request_id_header: Option<Uncased<'static>>,
+    /// The networks of the proxies trusted to set forwarding headers, if
+    /// limited. **(default: `None`)**
+    ///
+    /// When `None`, the header named by `ip_header` is trusted from every
+    /// peer and `Forwarded` protocols and hosts are ignored. When `Some`, only
+    /// peers in the listed networks, or without an IP address, are trusted,
+    /// and chains are walked right-to-left through trusted proxies. See
+    /// [`Request::real_ip()`] and [`Request::forwarded_proto()`].
+    pub trusted_proxies: Option<Vec<IpNet>>,
     /// The TLS configuration, if any. **(default: `None`)**
     #[cfg(feature = "tls")]
     #[cfg_attr(nightly, doc(cfg(feature = "tls")))]
@@ -104,6 +163,10 @@ pub struct Config {
     /// Whether to use colors and emoji when logging. **(default: `true`)**
     #[serde(deserialize_with = "figment::util::bool_from_str_or_int")]
     pub cli_colors: bool,
+    /// The tracing subscriber configuration, if any. **(default: `None`)**
+    #[cfg(feature = "tracing")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tracing")))]
+    pub tracing: Option<Tracing>,
     /// PRIVATE: This structure may grow (but never change otherwise) in a
     /// non-breaking release. As such, constructing this structure should
     /// _always_ be done using a public constructor or update syntax:
@@ -172,11 +235,21 @@ impl Config {
             address: BindableAddr::Tcp(
                 SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 8000))
             ),
+            listeners: vec![],
             workers: num_cpus::get(),
             ident: Ident::default(),
             limits: Limits::default(),
             temp_dir: std::env::temp_dir().into(),
             keep_alive: 5,
+            timeouts: Timeouts::default(),
+            max_connections: None,
+            max_in_flight_requests: None,
+            retry_after: 1,
+            proxy_protocol: None,
+            unix_socket: None,
+            ip_header: Some(Uncased::from_borrowed("X-Real-IP")),
+            request_id_header: Some(Uncased::from_borrowed("X-Request-Id")),
+            trusted_proxies: None,
             #[cfg(feature = "tls")]
             tls: None,
             #[cfg(feature = "secrets")]
@@ -184,6 +257,8 @@ impl Config {
             shutdown: Shutdown::default(),
             log_level: LogLevel::Normal,
             cli_colors: true,
+            #[cfg(feature = "tracing")]
+            tracing: None,
             __non_exhaustive: (),
         }
     }
@@ -247,6 +322,9 @@ impl Config {
     }
 
     /// Attempts to extract a `Config` from `provider`, returning the result.
+    /// Also fails if a TLS configuration is inconsistent, such as when its
+    /// `min_version` is newer than its `max_version`, or if a tracing `filter`
+    /// is malformed.
     ///
     /// # Example
     ///
@@ -266,9 +344,46 @@ impl Config {
         let figment = Figment::from(provider);
         let mut config = figment.extract::<Self>()?;
         config.profile = figment.profile().clone();
+
+        #[cfg(feature = "tls")]
+        if let Err((key, msg)) = config.validate_tls() {
+            let mut error = figment::Error::from(msg).with_path(&key);
+            error.metadata = figment.find_metadata(&key).cloned();
+            error.profile = Some(figment.profile().clone());
+            return Err(error);
+        }
+
+        #[cfg(feature = "tracing")]
+        if let Some(Err(e)) = config.tracing.as_ref().and_then(|t| t.filter.as_deref())
+            .map(str::parse::<tracing_subscriber::filter::Targets>)
+        {
+            let key = format!("{}.filter", Self::TRACING);
+            let mut error = figment::Error::from(format!("invalid filter: {}", e)).with_path(&key);
+            error.metadata = figment.find_metadata(&key).cloned();
+            error.profile = Some(figment.profile().clone());
+            return Err(error);
+        }
+
         Ok(config)
     }
 
+    /// Validates the primary and every listener's TLS configuration, if any.
+    /// On error, returns the full key of the offending parameter and a
+    /// description of the problem.
+    #[cfg(feature = "tls")]
+    fn validate_tls(&self) -> std::result::Result<(), (String, String)> {
+        let listeners = self.listeners.iter().enumerate().filter_map(|(i, l)| {
+            Some((format!("{}.{}.{}", Self::LISTENERS, i, Self::TLS), l.tls.as_ref()?))
+        });
+
+        let configs = self.tls.iter().map(|tls| (Self::TLS.to_string(), tls)).chain(listeners);
+        for (key, tls) in configs {
+            tls.validate().map_err(|(param, msg)| (format!("{}.{}", key, param), msg))?;
+        }
+
+        Ok(())
+    }
+
     /// Extract a `Config` from `provider`, panicking if extraction fails.
     ///
     /// # Panics
@@ -350,6 +465,14 @@ impl Config {
         #[cfg(not(feature = "mtls"))] { false }
     }
 
+    /// Returns every address Rocket listens on, [`Config::address`] first and
+    /// then those in [`Config::listeners`], each paired with whether TLS is
+    /// enabled for it.
+    pub(crate) fn endpoints(&self) -> impl Iterator<Item = (&BindableAddr, bool)> {
+        std::iter::once((&self.address, self.tls_enabled()))
+            .chain(self.listeners.iter().map(|l| (&l.address, l.tls_enabled())))
+    }
+
     pub(crate) fn pretty_print(&self, figment: &Figment) {
         use crate::log::PaintExt;
 
@@ -359,6 +482,47 @@ impl Config {
 
         launch_info!("{}Configured for {}.", Paint::emoji("🔧 "), self.profile);
         launch_info_!("address: {}", bold(&self.address));
+        if !self.listeners.is_empty() {
+            let listeners = self.listeners.iter()
+                .map(|l| {
+                    let tls = if l.tls_enabled() { " (tls)" } else { "" };
+                    let proxy = if l.proxy_protocol.is_some() { " (proxy protocol)" } else { "" };
+                    format!("{}{}{}", l.address, tls, proxy)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            launch_info_!("listeners: {}", bold(listeners));
+        }
+
+        if let Some(proxy_protocol) = &self.proxy_protocol {
+            let trusted = proxy_protocol.trusted.iter()
+                .map(|network| network.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            launch_info_!("proxy protocol: trusting {}", bold(format!("[{}]", trusted)));
+        }
+
+        match &self.ip_header {
+            Some(name) => launch_info_!("ip header: {}", bold(name)),
+            None => launch_info_!("ip header: {}", bold("disabled")),
+        }
+
+        match &self.request_id_header {
+            Some(name) => launch_info_!("request id header: {}", bold(name)),
+            None => launch_info_!("request id header: {}", bold("disabled")),
+        }
+
+        if let Some(trusted) = &self.trusted_proxies {
+            let trusted = trusted.iter()
+                .map(|network| network.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            launch_info_!("trusted proxies: {}", bold(format!("[{}]", trusted)));
+        }
+
         launch_info_!("workers: {}", bold(self.workers));
         launch_info_!("ident: {}", bold(&self.ident));
         launch_info_!("limits: {}", bold(&self.limits));
@@ -370,6 +534,17 @@ impl Config {
             ka => launch_info_!("keep-alive: {}{}", bold(ka), bold("s")),
         }
 
+        launch_info_!("timeouts: {}", bold(&self.timeouts));
+
+        if let Some(max) = self.max_connections {
+            launch_info_!("max connections: {}", bold(max));
+        }
+
+        if let Some(max) = self.max_in_flight_requests {
+            launch_info_!("max in-flight requests: {} (retry after {}s)",
+                bold(max), bold(self.retry_after));
+        }
+
         match (self.tls_enabled(), self.mtls_enabled()) {
             (true, true) => launch_info_!("tls: {}", bold("enabled w/mtls")),
             (true, false) => launch_info_!("tls: {} w/o mtls", bold("enabled")),
@@ -389,6 +564,12 @@ impl Config {
         launch_info_!("log level: {}", bold(self.log_level));
         launch_info_!("cli colors: {}", bold(&self.cli_colors));
 
+        #[cfg(feature = "tracing")]
+        match &self.tracing {
+            Some(tracing) => launch_info_!("tracing: {}", bold(tracing)),
+            None => launch_info_!("tracing: {}", bold("disabled")),
+        }
+
         // Check for now depreacted config values.
         for (key, replacement) in Self::DEPRECATED_KEYS {
             if let Some(md) = figment.find_metadata(key) {
@@ -447,12 +628,49 @@ impl Config {
     /// The stringy parameter name for setting/extracting [`Config::address`].
     pub const ADDRESS: &'static str = "address";
 
+    /// The stringy parameter name for setting/extracting [`Config::listeners`].
+    pub const LISTENERS: &'static str = "listeners";
+
     /// The stringy parameter name for setting/extracting [`Config::workers`].
     pub const WORKERS: &'static str = "workers";
 
     /// The stringy parameter name for setting/extracting [`Config::keep_alive`].
     pub const KEEP_ALIVE: &'static str = "keep_alive";
 
+    /// The stringy parameter name for setting/extracting [`Config::timeouts`].
+    pub const TIMEOUTS: &'static str = "timeouts";
+
+    /// The stringy parameter name for setting/extracting
+    /// [`Config::max_connections`].
+    pub const MAX_CONNECTIONS: &'static str = "max_connections";
+
+    /// The stringy parameter name for setting/extracting
+    /// [`Config::max_in_flight_requests`].
+    pub const MAX_IN_FLIGHT_REQUESTS: &'static str = "max_in_flight_requests";
+
+    /// The stringy parameter name for setting/extracting
+    /// [`Config::retry_after`].
+    pub const RETRY_AFTER: &'static str = "retry_after";
+
+    /// The stringy parameter name for setting/extracting
+    /// [`Config::proxy_protocol`].
+    pub const PROXY_PROTOCOL: &'static str = "proxy_protocol";
+
+    /// The stringy parameter name for setting/extracting
+    /// [`Config::unix_socket`].
+    pub const UNIX_SOCKET: &'static str = "unix_socket";
+
+    /// The stringy parameter name for setting/extracting [`Config::ip_header`].
+    pub const IP_HEADER: &'static str = "ip_header";
+
+    /// The stringy parameter name for setting/extracting
+    /// [`Config::request_id_header`].
+    pub const REQUEST_ID_HEADER: &'static str = "request_id_header";
+
+    /// The stringy parameter name for setting/extracting
+    /// [`Config::trusted_proxies`].
+    pub const TRUSTED_PROXIES: &'static str = "trusted_proxies";
+
     /// The stringy parameter name for setting/extracting [`Config::limits`].
     pub const LIMITS: &'static str = "limits";
 
@@ -473,6 +691,11 @@ impl Config {
 
     /// The stringy parameter name for setting/extracting [`Config::cli_colors`].
     pub const CLI_COLORS: &'static str = "cli_colors";
+
+    /// The stringy parameter name for setting/extracting [`Config::tracing`].
+    #[cfg(feature = "tracing")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tracing")))]
+    pub const TRACING: &'static str = "tracing";
 }
 
 impl Provider for Config {
@@ -510,6 +733,60 @@ impl<'r> FromRequest<'r> for &'r Config {
     }
 }
 
+/// Deserializes a header name config value, like [`Config::ip_header`], from a
+/// header name or `false`.
+fn deserialize_header_name<'de, D>(de: D) -> std::result::Result<Option<Uncased<'static>>, D::Error>
+    where D: Deserializer<'de>
+{
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = Option<Uncased<'static>>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            formatter.write_str("a header name or `false`")
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> std::result::Result<Self::Value, E> {
+            if !v {
+                return Ok(None);
+            }
+
+            Err(E::invalid_value(de::Unexpected::Bool(v), &self))
+        }
+
+        fn visit_some<D>(self, de: D) -> std::result::Result<Self::Value, D::Error>
+            where D: Deserializer<'de>
+        {
+            de.deserialize_any(self)
+        }
+
+        fn visit_none<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Self::Value, E> {
+            self.visit_string(v.into())
+        }
+
+        fn visit_string<E: de::Error>(self, v: String) -> std::result::Result<Self::Value, E> {
+            // A header name is a non-empty RFC 7230 token.
+            let is_tchar = |b: u8| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b);
+            if v.is_empty() || !v.bytes().all(is_tchar) {
+                return Err(E::invalid_value(de::Unexpected::Str(&v), &self));
+            }
+
+            Ok(Some(Uncased::from_owned(v)))
+        }
+    }
+
+    de.deserialize_any(Visitor)
+}
+
 #[doc(hidden)]
 pub fn pretty_print_error(error: figment::Error) {
     use figment::error::{Kind, OneOf};
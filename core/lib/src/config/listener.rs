@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ProxyProtocol, UnixSocket};
+use crate::http::bindable::BindableAddr;
+
+#[cfg(feature = "tls")]
+use crate::config::TlsConfig;
+
+/// An additional address to listen on, with optional TLS settings.
+///
+/// Rocket always listens on [`Config::address`](crate::Config::address), with
+/// TLS configured by [`Config::tls`](crate::Config::tls). Each entry in
+/// [`Config::listeners`](crate::Config::listeners) makes Rocket listen on
+/// another address at the same time, with TLS configured independently: an
+/// additional listener only uses TLS if its own `tls` is set. All listeners
+/// serve the same application and are shut down together.
+///
+/// The `address` of a listener is parsed exactly like the top-level `address`:
+/// a `tcp://`, `unix://`, `fd://`, or `systemd://` address.
+///
+/// # Example
+///
+/// To serve HTTPS publicly while also serving plain HTTP on a Unix socket for
+/// internal use, in `Rocket.toml`:
+///
+/// ```toml
+/// [default]
+/// address = "tcp://0.0.0.0:443"
+/// tls = { certs = "cert.pem", key = "key.pem" }
+///
+/// [[default.listeners]]
+/// address = "unix:///run/app/internal.sock"
+///
+/// [[default.listeners]]
+/// address = "tcp://127.0.0.1:8443"
+/// tls = { certs = "internal-cert.pem", key = "internal-key.pem" }
+/// ```
+///
+/// Or, equivalently, programmatically:
+///
+/// ```rust
+/// use rocket::config::{Config, ListenerConfig};
+///
+/// let internal = ListenerConfig::new("unix:///run/app/internal.sock".parse().unwrap());
+/// let figment = Config::figment()
+///     .merge((Config::ADDRESS, "tcp://0.0.0.0:443"))
+///     .merge((Config::LISTENERS, [internal]));
+///
+/// let config = Config::from(figment);
+/// assert_eq!(config.listeners.len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ListenerConfig {
+    /// The address to listen on.
+    #[serde(flatten)]
+    pub address: BindableAddr,
+    /// The TLS configuration for this listener, if any. **(default: `None`)**
+    #[cfg(feature = "tls")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tls")))]
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// The PROXY protocol configuration for this listener, if any. See
+    /// [`ProxyProtocol`] for details. **(default: `None`)**
+    #[serde(default)]
+    pub proxy_protocol: Option<ProxyProtocol>,
+    /// The socket file configuration for this listener when its address is a
+    /// Unix socket, if any. See [`UnixSocket`] for details.
+    /// **(default: `None`)**
+    #[serde(default)]
+    pub unix_socket: Option<UnixSocket>,
+}
+
+impl ListenerConfig {
+    /// Returns a listener configuration for `address` without TLS.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::ListenerConfig;
+    ///
+    /// let listener = ListenerConfig::new("tcp://127.0.0.1:9000".parse().unwrap());
+    /// ```
+    pub fn new(address: BindableAddr) -> Self {
+        ListenerConfig {
+            address,
+            #[cfg(feature = "tls")]
+            tls: None,
+            proxy_protocol: None,
+            unix_socket: None,
+        }
+    }
+
+    /// Sets the TLS configuration of `self` to `tls`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{ListenerConfig, TlsConfig};
+    ///
+    /// let tls = TlsConfig::from_paths("/ssl/certs.pem", "/ssl/key.pem");
+    /// let listener = ListenerConfig::new("tcp://127.0.0.1:9443".parse().unwrap())
+    ///     .with_tls(tls);
+    /// ```
+    #[cfg(feature = "tls")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tls")))]
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Sets the PROXY protocol configuration of `self` to `proxy_protocol`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{ListenerConfig, ProxyProtocol};
+    ///
+    /// let trusted = vec!["10.0.0.0/8".parse().unwrap()];
+    /// let listener = ListenerConfig::new("tcp://0.0.0.0:8080".parse().unwrap())
+    ///     .with_proxy_protocol(ProxyProtocol::new(trusted));
+    /// ```
+    pub fn with_proxy_protocol(mut self, proxy_protocol: ProxyProtocol) -> Self {
+        self.proxy_protocol = Some(proxy_protocol);
+        self
+    }
+
+    /// Sets the Unix socket configuration of `self` to `unix_socket`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{ListenerConfig, UnixSocket};
+    ///
+    /// let unix_socket = UnixSocket { mode: Some(0o600), ..UnixSocket::default() };
+    /// let listener = ListenerConfig::new("unix:///run/app/admin.sock".parse().unwrap())
+    ///     .with_unix_socket(unix_socket);
+    /// ```
+    pub fn with_unix_socket(mut self, unix_socket: UnixSocket) -> Self {
+        self.unix_socket = Some(unix_socket);
+        self
+    }
+
+    /// Returns `true` if TLS is enabled for this listener.
+    ///
+    /// As with [`Config::tls_enabled()`](crate::Config::tls_enabled()), TLS is
+    /// enabled when the `tls` feature is enabled and TLS has been configured
+    /// with at least one ciphersuite.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::ListenerConfig;
+    ///
+    /// let listener = ListenerConfig::new("tcp://127.0.0.1:9000".parse().unwrap());
+    /// assert!(!listener.tls_enabled());
+    /// ```
+    pub fn tls_enabled(&self) -> bool {
+        #[cfg(feature = "tls")] {
+            self.tls.as_ref().map_or(false, |tls| !tls.ciphers.is_empty())
+        }
+
+        #[cfg(not(feature = "tls"))] { false }
+    }
+}
+
+impl From<BindableAddr> for ListenerConfig {
+    fn from(address: BindableAddr) -> Self {
+        ListenerConfig::new(address)
+    }
+}
@@ -113,7 +113,11 @@
 #[macro_use]
 mod ident;
 mod config;
+mod listener;
+mod proxy_protocol;
 mod shutdown;
+mod timeouts;
+mod unix_socket;
 
 #[cfg(feature = "tls")]
 mod tls;
@@ -121,23 +125,34 @@ mod tls;
 #[cfg(feature = "secrets")]
 mod secret_key;
 
+#[cfg(feature = "tracing")]
+mod tracing;
+
 pub use crate::http::bindable::BindableAddr;
+pub use crate::http::proxy_protocol::IpNet;
 pub use crate::log::LogLevel;
 #[doc(hidden)]
 pub use config::pretty_print_error;
 pub use config::Config;
 pub use ident::Ident;
+pub use listener::ListenerConfig;
+pub use proxy_protocol::ProxyProtocol;
 pub use shutdown::Shutdown;
+pub use timeouts::Timeouts;
+pub use unix_socket::UnixSocket;
 
 #[cfg(feature = "tls")]
-pub use tls::{CipherSuite, TlsConfig};
+pub use tls::{CipherSuite, SniCertificate, TlsConfig, TlsVersion};
 
 #[cfg(feature = "mtls")]
-pub use tls::MutualTls;
+pub use tls::{IdentityAttribute, MutualTls};
 
 #[cfg(feature = "secrets")]
 pub use secret_key::SecretKey;
 
+#[cfg(feature = "tracing")]
+pub use self::tracing::{Tracing, TraceFormat};
+
 #[cfg(unix)]
 pub use shutdown::Sig;
 
@@ -499,6 +514,7 @@ mod tests {
     #[cfg(feature = "mtls")]
     fn test_mtls_config() {
         use std::path::Path;
+        use crate::config::IdentityAttribute;
 
         figment::Jail::expect_with(|jail| {
             jail.create_file(
@@ -551,6 +567,36 @@ mod tests {
             let mtls = config.tls.as_ref().unwrap().mutual.as_ref().unwrap();
             assert_eq!(mtls.ca_certs().unwrap_left(), Path::new("/ssl/ca.pem"));
             assert!(mtls.mandatory);
+            assert_eq!(mtls.crls().count(), 0);
+            assert!(!mtls.allow_stale_crls);
+            assert_eq!(mtls.identity(), &[IdentityAttribute::CommonName]);
+
+            jail.create_file(
+                "Rocket.toml",
+                r#"
+                [default.tls]
+                certs = "/ssl/cert.pem"
+                key = "/ssl/key.pem"
+
+                [default.tls.mutual]
+                ca_certs = "/ssl/ca.pem"
+                crls = ["/ssl/crl.pem", [48, 0]]
+                allow_stale_crls = true
+                identity = ["spiffe", "email", "fingerprint"]
+            "#,
+            )?;
+
+            let config = Config::from(Config::figment());
+            let mtls = config.tls.as_ref().unwrap().mutual.as_ref().unwrap();
+            let crls: Vec<_> = mtls.crls().collect();
+            assert_eq!(crls[0].as_ref().unwrap_left(), Path::new("/ssl/crl.pem"));
+            assert_eq!(crls[1].as_ref().unwrap_right(), &[48, 0]);
+            assert!(mtls.allow_stale_crls);
+            assert_eq!(mtls.identity(), &[
+                IdentityAttribute::Spiffe,
+                IdentityAttribute::Email,
+                IdentityAttribute::Fingerprint,
+            ]);
 
             jail.create_file(
                 "Rocket.toml",
@@ -905,4 +951,457 @@ mod tests {
             Ok(())
         });
     }
+
+    #[test]
+    fn test_listeners() {
+        use std::path::PathBuf;
+        use crate::config::ListenerConfig;
+
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "Rocket.toml",
+                r#"
+                [default]
+                address = "tcp://127.0.0.1:8000"
+
+                [[default.listeners]]
+                address = "unix:///path/to/socket.sock"
+
+                [[default.listeners]]
+                address = "tcp://127.0.0.1:9000"
+            "#,
+            )?;
+
+            let config = Config::from(Config::figment());
+            assert_eq!(config.listeners, vec![
+                ListenerConfig::new(BindableAddr::Unix(PathBuf::from("/path/to/socket.sock"))),
+                ListenerConfig::new(BindableAddr::Tcp((Ipv4Addr::LOCALHOST, 9000).into())),
+            ]);
+
+            assert!(config.listeners.iter().all(|l| !l.tls_enabled()));
+            assert_eq!(config.endpoints().count(), 3);
+
+            jail.set_env("ROCKET_LISTENERS", r#"[{address="unix:///other.sock"}]"#);
+            let config = Config::from(Config::figment());
+            assert_eq!(config.listeners, vec![
+                ListenerConfig::new(BindableAddr::Unix(PathBuf::from("/other.sock"))),
+            ]);
+
+            jail.set_env("ROCKET_LISTENERS", r#"[{address="foo://bar"}]"#);
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_proxy_protocol() {
+        use crate::config::{ListenerConfig, ProxyProtocol};
+
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "Rocket.toml",
+                r#"
+                [default]
+                proxy_protocol = { trusted = ["10.0.0.0/8", "fd00::/8"], timeout = 2 }
+
+                [[default.listeners]]
+                address = "tcp://127.0.0.1:9000"
+                proxy_protocol = { trusted = ["192.168.1.1/32"] }
+
+                [[default.listeners]]
+                address = "tcp://127.0.0.1:9001"
+            "#,
+            )?;
+
+            let config = Config::from(Config::figment());
+            assert_eq!(config.proxy_protocol, Some(ProxyProtocol {
+                timeout: 2,
+                ..ProxyProtocol::new(vec![
+                    "10.0.0.0/8".parse().unwrap(),
+                    "fd00::/8".parse().unwrap(),
+                ])
+            }));
+
+            let address = "tcp://127.0.0.1:9000".parse().unwrap();
+            let proxy_protocol = ProxyProtocol::new(vec!["192.168.1.1/32".parse().unwrap()]);
+            let listener = ListenerConfig::new(address).with_proxy_protocol(proxy_protocol);
+            assert_eq!(config.listeners[0], listener);
+            assert_eq!(config.listeners[1].proxy_protocol, None);
+
+            jail.set_env("ROCKET_PROXY_PROTOCOL", r#"{trusted=["10.0.0.0/33"]}"#);
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            jail.set_env("ROCKET_PROXY_PROTOCOL", r#"{timeout=1}"#);
+            let proxy_protocol = Config::from(Config::figment()).proxy_protocol.unwrap();
+            assert_eq!(proxy_protocol.timeout, 1);
+            assert_eq!(proxy_protocol.trusted.len(), 2);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_unix_socket() {
+        use crate::config::UnixSocket;
+
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "Rocket.toml",
+                r#"
+                [default]
+                address = "unix:///run/app.sock"
+                unix_socket = { mode = "0660", owner = "www", group = 33, remove_stale = true }
+
+                [[default.listeners]]
+                address = "unix://@app"
+                unix_socket = { mode = 0o600 }
+
+                [[default.listeners]]
+                address = "unix:///run/admin.sock"
+            "#,
+            )?;
+
+            let config = Config::from(Config::figment());
+            assert_eq!(config.unix_socket, Some(UnixSocket {
+                mode: Some(0o660),
+                owner: Some("www".into()),
+                group: Some("33".into()),
+                remove_stale: true,
+            }));
+
+            assert_eq!(config.listeners[0].address, BindableAddr::Unix("@app".into()));
+            assert_eq!(config.listeners[0].unix_socket, Some(UnixSocket {
+                mode: Some(0o600),
+                ..UnixSocket::default()
+            }));
+            assert_eq!(config.listeners[1].unix_socket, None);
+
+            jail.set_env("ROCKET_UNIX_SOCKET", r#"{mode="0999"}"#);
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            jail.set_env("ROCKET_UNIX_SOCKET", r#"{mode=65536}"#);
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_ip_header_and_trusted_proxies() {
+        use crate::http::uncased::Uncased;
+
+        figment::Jail::expect_with(|jail| {
+            let config = Config::from(Config::figment());
+            assert_eq!(config.ip_header, Some(Uncased::from("X-Real-IP")));
+            assert_eq!(config.trusted_proxies, None);
+
+            jail.create_file(
+                "Rocket.toml",
+                r#"
+                [default]
+                ip_header = "x-forwarded-for"
+                trusted_proxies = ["10.0.0.0/8", "::1/128"]
+            "#,
+            )?;
+
+            let config = Config::from(Config::figment());
+            assert_eq!(config.ip_header, Some(Uncased::from("X-Forwarded-For")));
+            assert_eq!(config.trusted_proxies, Some(vec![
+                "10.0.0.0/8".parse().unwrap(),
+                "::1/128".parse().unwrap(),
+            ]));
+
+            jail.set_env("ROCKET_IP_HEADER", false);
+            assert_eq!(Config::from(Config::figment()).ip_header, None);
+
+            let config = Config { ip_header: None, ..Config::debug_default() };
+            assert_eq!(Config::from(&config).ip_header, None);
+
+            jail.set_env("ROCKET_IP_HEADER", "CF-Connecting-IP");
+            let config = Config::from(Config::figment());
+            assert_eq!(config.ip_header, Some(Uncased::from("cf-connecting-ip")));
+
+            jail.set_env("ROCKET_IP_HEADER", "X Real IP");
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            jail.set_env("ROCKET_IP_HEADER", true);
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            jail.set_env("ROCKET_IP_HEADER", "X-Real-IP");
+            jail.set_env("ROCKET_TRUSTED_PROXIES", "[\"10.0.0.0/33\"]");
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_timeouts() {
+        use crate::config::Timeouts;
+        use crate::http::Status;
+
+        figment::Jail::expect_with(|jail| {
+            let config = Config::from(Config::figment());
+            assert_eq!(config.timeouts, Timeouts::default());
+            assert_eq!(config.timeouts.to_string(), "header = 15s, body = 30s, handler = off");
+
+            jail.create_file(
+                "Rocket.toml",
+                r#"
+                [default.timeouts]
+                header = 0
+                idle = 120
+                handler = 10
+                handler_status = 504
+            "#,
+            )?;
+
+            let config = Config::from(Config::figment());
+            assert_eq!(config.timeouts, Timeouts {
+                header: 0,
+                idle: Some(120),
+                handler: 10,
+                handler_status: Status::GatewayTimeout,
+                ..Default::default()
+            });
+
+            assert_eq!(
+                config.timeouts.to_string(),
+                "header = off, body = 30s, idle = 120s, handler = 10s (504)"
+            );
+
+            jail.set_env("ROCKET_TIMEOUTS", "{body=5}");
+            let config = Config::from(Config::figment());
+            assert_eq!(config.timeouts.body, 5);
+            assert_eq!(config.timeouts.handler, 10);
+
+            jail.set_env("ROCKET_TIMEOUTS", "{handler_status=500}");
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            jail.set_env("ROCKET_TIMEOUTS", "{header=-1}");
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "tls")]
+    fn test_listener_tls() {
+        use crate::config::{ListenerConfig, TlsConfig};
+
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "Rocket.toml",
+                r#"
+                [default]
+                address = "tcp://127.0.0.1:8000"
+
+                [[default.listeners]]
+                address = "tcp://127.0.0.1:8443"
+                tls = { certs = "/ssl/cert.pem", key = "/ssl/key.pem" }
+            "#,
+            )?;
+
+            let config = Config::from(Config::figment());
+            let addr = BindableAddr::Tcp(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 8443));
+            let tls = TlsConfig::from_paths("/ssl/cert.pem", "/ssl/key.pem");
+            assert_eq!(config.listeners, vec![ListenerConfig::new(addr).with_tls(tls)]);
+            assert!(!config.tls_enabled());
+            assert!(config.listeners[0].tls_enabled());
+
+            let endpoints = config.endpoints().map(|(_, tls)| tls).collect::<Vec<_>>();
+            assert_eq!(endpoints, vec![false, true]);
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "tls")]
+    fn test_tls_reload() {
+        use crate::config::TlsConfig;
+
+        figment::Jail::expect_with(|jail| {
+            jail.create_file("Rocket.toml", r#"
+                [default.tls]
+                certs = "/ssl/cert.pem"
+                key = "/ssl/key.pem"
+            "#)?;
+
+            let tls = Config::from(Config::figment()).tls.unwrap();
+            assert_eq!(tls.reload_interval(), 0);
+            assert!(!tls.reload_on_sighup());
+
+            jail.set_env("ROCKET_TLS", "{reload_interval=3600,reload_on_sighup=true}");
+            let tls = Config::from(Config::figment()).tls.unwrap();
+            assert_eq!(tls, TlsConfig::from_paths("/ssl/cert.pem", "/ssl/key.pem")
+                .with_reload_interval(3600)
+                .with_reload_on_sighup(true));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "tls")]
+    fn test_tls_sni() {
+        use crate::config::{SniCertificate, TlsConfig};
+
+        figment::Jail::expect_with(|jail| {
+            jail.create_file("Rocket.toml", r#"
+                [default.tls]
+                certs = "/ssl/cert.pem"
+                key = "/ssl/key.pem"
+
+                [default.tls.sni."example.com"]
+                certs = "/ssl/example/cert.pem"
+                key = "/ssl/example/key.pem"
+
+                [default.tls.sni."*.example.com"]
+                certs = "/ssl/wildcard/cert.pem"
+                key = "/ssl/wildcard/key.pem"
+            "#)?;
+
+            let tls = Config::from(Config::figment()).tls.unwrap();
+            let example = SniCertificate::from_paths(
+                "/ssl/example/cert.pem",
+                "/ssl/example/key.pem",
+            );
+
+            let wildcard = SniCertificate::from_paths(
+                "/ssl/wildcard/cert.pem",
+                "/ssl/wildcard/key.pem",
+            );
+
+            assert_eq!(tls, TlsConfig::from_paths("/ssl/cert.pem", "/ssl/key.pem")
+                .with_sni_certificate("example.com", example.clone())
+                .with_sni_certificate("*.example.com", wildcard.clone()));
+
+            let names = tls.sni_certificates().collect::<Vec<_>>();
+            assert_eq!(names.len(), 2);
+            assert!(names.contains(&("example.com", &example)));
+            assert!(names.contains(&("*.example.com", &wildcard)));
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "tls")]
+    fn test_tls_versions_and_sessions() {
+        use crate::config::{CipherSuite, TlsConfig, TlsVersion};
+
+        figment::Jail::expect_with(|jail| {
+            jail.create_file("Rocket.toml", r#"
+                [default.tls]
+                certs = "/ssl/cert.pem"
+                key = "/ssl/key.pem"
+                min_version = "TLSv1.3"
+                session_tickets = false
+                session_cache_size = 0
+            "#)?;
+
+            let tls = Config::from(Config::figment()).tls.unwrap();
+            assert_eq!(tls, TlsConfig::from_paths("/ssl/cert.pem", "/ssl/key.pem")
+                .with_min_version(TlsVersion::TLSv1_3)
+                .with_session_tickets(false)
+                .with_session_cache_size(0));
+
+            jail.set_env("ROCKET_TLS", r#"{certs="a.pem",key="b.pem",ticket_rotation=600}"#);
+            let tls = Config::from(Config::figment()).tls.unwrap();
+            assert_eq!(tls.min_version(), TlsVersion::TLSv1_3);
+            assert_eq!(tls.max_version(), TlsVersion::TLSv1_3);
+            assert_eq!(tls.ticket_rotation(), 600);
+            assert!(!tls.session_tickets());
+            assert_eq!(tls.session_cache_size(), 0);
+
+            // Tickets are disabled, so their rotation period isn't checked.
+            jail.set_env("ROCKET_TLS", r#"{ticket_rotation=0}"#);
+            assert!(Config::try_from(Config::figment()).is_ok());
+
+            jail.set_env("ROCKET_TLS", r#"{max_version="TLSv1.2"}"#);
+            let err = Config::try_from(Config::figment()).unwrap_err();
+            assert_eq!(err.path, vec!["tls", "min_version"]);
+            assert!(err.to_string().contains("is newer than `max_version`"), "{}", err);
+
+            let versions = r#"min_version="TLSv1.2",max_version="TLSv1.2""#;
+            let tls = format!(r#"{{ciphers=["TLS_AES_128_GCM_SHA256"],{}}}"#, versions);
+            jail.set_env("ROCKET_TLS", tls);
+            let err = Config::try_from(Config::figment()).unwrap_err();
+            assert_eq!(err.path, vec!["tls", "ciphers"]);
+            assert!(err.to_string().contains("supports TLSv1.2"), "{}", err);
+
+            jail.set_env("ROCKET_TLS", r#"{ciphers=["TLS_AES_128_GCM_SHA256"]}"#);
+            let tls = Config::from(Config::figment()).tls.unwrap();
+            assert!(tls.ciphers().eq([CipherSuite::TLS_AES_128_GCM_SHA256]));
+
+            jail.set_env("ROCKET_TLS", r#"{session_tickets=true,ticket_rotation=0}"#);
+            let err = Config::try_from(Config::figment()).unwrap_err();
+            assert_eq!(err.path, vec!["tls", "ticket_rotation"]);
+
+            jail.set_env("ROCKET_TLS", r#"{session_tickets=true,ticket_rotation=21601}"#);
+            let err = Config::try_from(Config::figment()).unwrap_err();
+            assert_eq!(err.path, vec!["tls", "ticket_rotation"]);
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "tls")]
+    fn test_tls_validation_of_listeners() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file("Rocket.toml", r#"
+                [[default.listeners]]
+                address = "tcp://127.0.0.1:9000"
+
+                [[default.listeners]]
+                address = "tcp://127.0.0.1:9001"
+
+                [default.listeners.tls]
+                certs = "/ssl/cert.pem"
+                key = "/ssl/key.pem"
+                ciphers = ["TLS_AES_128_GCM_SHA256"]
+                max_version = "TLSv1.2"
+            "#)?;
+
+            let err = Config::try_from(Config::figment()).unwrap_err();
+            assert_eq!(err.path, vec!["listeners", "1", "tls", "ciphers"]);
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn test_tracing() {
+        use crate::config::{Tracing, TraceFormat};
+
+        figment::Jail::expect_with(|jail| {
+            assert_eq!(Config::from(Config::figment()).tracing, None);
+
+            jail.set_env("ROCKET_TRACING", "{}");
+            let config = Config::from(Config::figment());
+            assert_eq!(config.tracing, Some(Tracing::default()));
+            assert_eq!(config.tracing.unwrap().to_string(), "full");
+
+            jail.create_file("Rocket.toml", r#"
+                [default.tracing]
+                format = "json"
+                filter = "info,rocket=debug,hyper=off"
+            "#)?;
+
+            jail.set_env("ROCKET_TRACING", "{format=compact}");
+            let tracing = Config::from(Config::figment()).tracing.unwrap();
+            assert_eq!(tracing.format, TraceFormat::Compact);
+            assert_eq!(tracing.filter.as_deref(), Some("info,rocket=debug,hyper=off"));
+            assert_eq!(tracing.to_string(), "compact (info,rocket=debug,hyper=off)");
+
+            jail.set_env("ROCKET_TRACING", "{format=pretty}");
+            assert!(Config::try_from(Config::figment()).is_err());
+
+            jail.set_env("ROCKET_TRACING", "{filter=\"rocket=loud\"}");
+            let err = Config::try_from(Config::figment()).unwrap_err();
+            assert_eq!(err.path, vec!["tracing", "filter"]);
+            Ok(())
+        });
+    }
 }
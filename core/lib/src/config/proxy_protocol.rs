@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::http::proxy_protocol::IpNet;
+
+/// PROXY protocol configuration for a listener.
+///
+/// When a listener is configured with `ProxyProtocol`, every connection from a
+/// peer in one of the `trusted` networks must begin with a [PROXY protocol]
+/// header, version 1 or 2, as sent by proxies like HAProxy or by AWS Network
+/// Load Balancers. The header is removed, and the client address in it
+/// becomes the request's [`remote()`](crate::Request::remote()) address. The
+/// parsed header, including any TLVs, is available via
+/// [`Request::proxy_header()`](crate::Request::proxy_header()) and as a
+/// request guard.
+///
+/// Connections from trusted peers whose header is malformed or not received
+/// within `timeout` seconds are closed. Connections from peers outside of the
+/// `trusted` networks are served as if PROXY protocol were not configured.
+/// Connections over Unix sockets have no peer IP address and are always
+/// trusted. When TLS is also configured, the header is expected before the
+/// TLS handshake, as proxies send it.
+///
+/// [PROXY protocol]: https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt
+///
+/// # Example
+///
+/// To accept PROXY headers from load balancers in `10.0.0.0/8` on the primary
+/// address, in `Rocket.toml`:
+///
+/// ```toml
+/// [default.proxy_protocol]
+/// trusted = ["10.0.0.0/8"]
+/// timeout = 3
+/// ```
+///
+/// Or, equivalently, programmatically:
+///
+/// ```rust
+/// use rocket::config::{Config, ProxyProtocol};
+///
+/// let figment = Config::figment()
+///     .merge((Config::PROXY_PROTOCOL, ProxyProtocol::new(vec!["10.0.0.0/8".parse().unwrap()])));
+///
+/// let config = Config::from(figment);
+/// let proxy_protocol = config.proxy_protocol.unwrap();
+/// assert_eq!(proxy_protocol.trusted.len(), 1);
+/// assert_eq!(proxy_protocol.timeout, 5);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ProxyProtocol {
+    /// The networks of peers that send a PROXY protocol header.
+    pub trusted: Vec<IpNet>,
+    /// The number of seconds to wait for the header of a trusted connection.
+    ///
+    /// **default: `5`**
+    #[serde(default = "ProxyProtocol::default_timeout")]
+    pub timeout: u32,
+}
+
+impl ProxyProtocol {
+    fn default_timeout() -> u32 {
+        5
+    }
+
+    /// Returns a PROXY protocol configuration that trusts the peers in
+    /// `trusted` with the default `timeout`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::ProxyProtocol;
+    ///
+    /// let proxy_protocol = ProxyProtocol::new(vec!["192.168.0.0/16".parse().unwrap()]);
+    /// assert_eq!(proxy_protocol.timeout, 5);
+    /// ```
+    pub fn new(trusted: Vec<IpNet>) -> Self {
+        ProxyProtocol { trusted, timeout: ProxyProtocol::default_timeout() }
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde::de::{self, Deserializer};
+
+use crate::http::Status;
+
+/// Server timeout configuration.
+///
+/// # Summary
+///
+/// These timeouts protect a server from slow or stalled clients, such as
+/// "slowloris" clients that open connections and send requests very slowly,
+/// and from runaway handlers. Every timeout is a number of seconds; a value of
+/// `0` disables the timeout.
+///
+///   * **`header`**: A request's head, that is, its request line and headers,
+///     must be received within `header` seconds of its first byte. A new
+///     connection must also send the first byte of its first request within
+///     `header` seconds. When a head is late, Rocket responds with `408
+///     Request Timeout` and closes the connection.
+///
+///   * **`body`**: Each chunk of a request's body must be received within
+///     `body` seconds of the previous one, or of the body first being read.
+///     When a chunk is late, the read fails with an I/O error of kind
+///     [`TimedOut`](std::io::ErrorKind::TimedOut) and, irrespective of what
+///     the handler returns, Rocket responds with `408 Request Timeout` and
+///     closes the connection.
+///
+///   * **`idle`**: A keep-alive connection is closed when the first byte of
+///     its next request isn't received within `idle` seconds of the previous
+///     response. When unset, [`Config::keep_alive`](crate::Config::keep_alive)
+///     is used.
+///
+///   * **`handler`**: A route's handler, including its guards, must complete
+///     within `handler` seconds. When it doesn't, it is cancelled and Rocket
+///     responds with `handler_status`, either `503 Service Unavailable` or
+///     `504 Gateway Timeout`, via the corresponding catcher. Individual routes
+///     can override `handler` via the `timeout` route attribute parameter:
+///
+///     ```rust
+///     # #[macro_use] extern crate rocket;
+///     // This handler may take up to 5 minutes. A `timeout` of `0` disables
+///     // the handler deadline for the route.
+///     #[get("/report", timeout = 300)]
+///     async fn report() -> &'static str {
+///         /* .. */
+///         # "report"
+///     }
+///     ```
+///
+/// The `header` and `idle` timeouts apply to HTTP/1 connections only. HTTP/2
+/// connections are instead kept alive via pings; see
+/// [`Config::keep_alive`](crate::Config::keep_alive).
+///
+/// # Example
+///
+/// As with all Rocket configuration options, when using the default
+/// [`Config::figment()`](crate::Config::figment()), `Timeouts` can be
+/// configured via a `Rocket.toml` file. Defaults are provided, documented
+/// below, so only the timeouts to change need to be configured:
+///
+/// ```rust
+/// # use rocket::figment::{Figment, providers::{Format, Toml}};
+/// use rocket::Config;
+/// use rocket::http::Status;
+///
+/// // If these are the contents of `Rocket.toml`...
+/// # let toml = Toml::string(r#"
+/// [default.timeouts]
+/// header = 5
+/// idle = 60
+/// handler = 30
+/// handler_status = 504
+/// # "#).nested();
+///
+/// // The config parses as follows:
+/// # let config = Config::from(Figment::from(Config::debug_default()).merge(toml));
+/// assert_eq!(config.timeouts.header, 5);
+/// assert_eq!(config.timeouts.body, 30);
+/// assert_eq!(config.timeouts.idle, Some(60));
+/// assert_eq!(config.timeouts.handler, 30);
+/// assert_eq!(config.timeouts.handler_status, Status::GatewayTimeout);
+/// ```
+///
+/// Or, programmatically:
+///
+/// ```rust
+/// use rocket::config::{Config, Timeouts};
+///
+/// let config = Config {
+///     timeouts: Timeouts {
+///         body: 10,
+///         handler: 60,
+///         ..Default::default()
+///     },
+///     ..Config::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Timeouts {
+    /// Seconds to receive a request's head in, from its first byte.
+    ///
+    /// **default: `15`**
+    pub header: u32,
+    /// Seconds to receive each chunk of a request's body in.
+    ///
+    /// **default: `30`**
+    pub body: u32,
+    /// Seconds to keep an idle keep-alive connection open for, if not
+    /// [`Config::keep_alive`](crate::Config::keep_alive).
+    ///
+    /// **default: `None`**
+    pub idle: Option<u32>,
+    /// Seconds a handler may run for.
+    ///
+    /// **default: `0`**
+    pub handler: u32,
+    /// The status to respond with when a handler exceeds its deadline: `503`
+    /// or `504`.
+    ///
+    /// **default: `503`**
+    #[serde(serialize_with = "serialize_status", deserialize_with = "deserialize_status")]
+    pub handler_status: Status,
+    /// PRIVATE: This structure may grow (but never change otherwise) in a
+    /// non-breaking release. As such, constructing this structure should
+    /// _always_ be done using a public constructor or update syntax:
+    ///
+    /// ```rust
+    /// use rocket::config::Timeouts;
+    ///
+    /// let config = Timeouts {
+    ///     header: 5,
+    ///     ..Default::default()
+    /// };
+    /// ```
+    #[doc(hidden)]
+    #[serde(skip)]
+    pub __non_exhaustive: (),
+}
+
+impl Timeouts {
+    /// Returns `secs` as a `Duration` or `None` if `secs` is `0`.
+    pub(crate) fn duration(secs: u32) -> Option<Duration> {
+        match secs {
+            0 => None,
+            n => Some(Duration::from_secs(n as u64)),
+        }
+    }
+}
+
+impl fmt::Display for Timeouts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn secs(f: &mut fmt::Formatter<'_>, name: &str, secs: u32) -> fmt::Result {
+            match secs {
+                0 => write!(f, "{} = off", name),
+                n => write!(f, "{} = {}s", name, n),
+            }
+        }
+
+        secs(f, "header", self.header)?;
+        secs(f, ", body", self.body)?;
+        if let Some(idle) = self.idle {
+            secs(f, ", idle", idle)?;
+        }
+
+        secs(f, ", handler", self.handler)?;
+        if self.handler != 0 {
+            write!(f, " ({})", self.handler_status.code)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            header: 15,
+            body: 30,
+            idle: None,
+            handler: 0,
+            handler_status: Status::ServiceUnavailable,
+            __non_exhaustive: (),
+        }
+    }
+}
+
+fn serialize_status<S: serde::Serializer>(status: &Status, ser: S) -> Result<S::Ok, S::Error> {
+    ser.serialize_u16(status.code)
+}
+
+fn deserialize_status<'de, D: Deserializer<'de>>(de: D) -> Result<Status, D::Error> {
+    let code = u16::deserialize(de)?;
+    match Status::from_code(code) {
+        Some(status) if code == 503 || code == 504 => Ok(status),
+        _ => Err(de::Error::invalid_value(de::Unexpected::Unsigned(code as u64), &"503 or 504")),
+    }
+}
@@ -1,6 +1,6 @@
 use figment::value::magic::{Either, RelativePathBuf};
 use serde::{Deserialize, Serialize};
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 
 /// TLS configuration: certificate chain, key, and ciphersuites.
 ///
@@ -26,6 +26,65 @@ use indexmap::IndexSet;
 ///     ciphersuite preferences over the client's. The default and recommended
 ///     value is `false`.
 ///
+/// Five parameters control protocol versions and session resumption:
+///
+///   * `min_version`, `max_version`
+///
+///     The oldest and newest [`TlsVersion`] to negotiate. The defaults are
+///     `"TLSv1.2"` and `"TLSv1.3"`, enabling every supported version. A version
+///     in this range is nonetheless disabled if none of `ciphers` support it.
+///
+///   * `session_tickets`
+///
+///     A boolean that indicates whether to issue stateless session tickets,
+///     which let clients resume sessions without server-side state. The
+///     default is `true`.
+///
+///   * `ticket_rotation`
+///
+///     The number of seconds between rotations of the key that encrypts
+///     session tickets. Tickets remain valid for up to twice this period. It
+///     must be between `1` and `21600`, six hours, the default.
+///
+///   * `session_cache_size`
+///
+///     The number of sessions to cache in memory for stateful resumption.
+///     Disabled when `0`. The default is `1024`.
+///
+/// These parameters, like `ciphers`, are checked when Rocket ignites. It is an
+/// error for `min_version` to be newer than `max_version`, for `ciphers` to
+/// support none of the versions between the two, or for `ticket_rotation` to be
+/// out of range while `session_tickets` are enabled.
+///
+/// Two more parameters control reloading `certs` and `key` from their paths
+/// while the server is running, such as when certificates are rotated:
+///
+///   * `reload_interval`
+///
+///     The number of seconds between reloads. Disabled when `0`, the default.
+///
+///   * `reload_on_sighup`
+///
+///     A boolean that indicates whether to reload on `SIGHUP`, on Unix. The
+///     default is `false`. `SIGHUP` should not also be a
+///     [shutdown signal](crate::config::Shutdown::signals).
+///
+/// Certificates can also be reloaded on demand via
+/// [`Rocket::reload_tls()`](crate::Rocket::reload_tls()). A reload only
+/// affects new TLS handshakes; established connections are unaffected. If the
+/// certificate chain or key can't be read, the previous ones remain in use.
+///
+/// To serve several domains, each with its own certificate, the `sni`
+/// parameter maps server names to an [`SniCertificate`]: a `certs` and `key`
+/// pair, configured like the top-level ones. During the TLS handshake, the
+/// certificate for the server name the client requests via SNI (Server Name
+/// Indication) is used. A name like `*.example.com` matches `www.example.com`
+/// but neither `example.com` nor `a.b.example.com`. The top-level `certs` and
+/// `key` are used when no name matches or the client doesn't request one. The
+/// requested name is available via
+/// [`Request::server_name()`](crate::Request::server_name()). Reloading
+/// reloads every certificate.
+///
 /// Additionally, the `mutual` parameter controls if and how the server
 /// authenticates clients via mutual TLS. It works in concert with the
 /// [`mtls`](crate::mtls) module. See [`MutualTls`] for configuration details.
@@ -36,6 +95,13 @@ use indexmap::IndexSet;
 /// [default.tls]
 /// certs = "private/rsa_sha256_cert.pem"
 /// key = "private/rsa_sha256_key.pem"
+///
+/// min_version = "TLSv1.3"
+/// session_cache_size = 0
+///
+/// [default.tls.sni."api.example.com"]
+/// certs = "private/api_cert.pem"
+/// key = "private/api_key.pem"
 /// ```
 ///
 /// With a custom programmatic configuration, this might look like:
@@ -90,6 +156,30 @@ pub struct TlsConfig {
     /// Whether to prefer the server's cipher suite order over the client's.
     #[serde(default)]
     pub(crate) prefer_server_cipher_order: bool,
+    /// The oldest TLS version to negotiate.
+    #[serde(default = "TlsVersion::oldest")]
+    pub(crate) min_version: TlsVersion,
+    /// The newest TLS version to negotiate.
+    #[serde(default = "TlsVersion::newest")]
+    pub(crate) max_version: TlsVersion,
+    /// Whether to issue stateless session tickets.
+    #[serde(default = "TlsConfig::default_session_tickets")]
+    pub(crate) session_tickets: bool,
+    /// Seconds between rotations of the session ticket key.
+    #[serde(default = "TlsConfig::default_ticket_rotation")]
+    pub(crate) ticket_rotation: u32,
+    /// Number of sessions to cache for stateful resumption; disabled when `0`.
+    #[serde(default = "TlsConfig::default_session_cache_size")]
+    pub(crate) session_cache_size: usize,
+    /// Seconds between reloads of `certs` and `key`; disabled when `0`.
+    #[serde(default)]
+    pub(crate) reload_interval: u32,
+    /// Whether to reload `certs` and `key` on `SIGHUP`.
+    #[serde(default)]
+    pub(crate) reload_on_sighup: bool,
+    /// Certificate chains and keys for specific server names.
+    #[serde(default)]
+    pub(crate) sni: IndexMap<String, SniCertificate>,
     /// Configuration for mutual TLS, if any.
     #[serde(default)]
     #[cfg(feature = "mtls")]
@@ -97,6 +187,52 @@ pub struct TlsConfig {
     pub(crate) mutual: Option<MutualTls>,
 }
 
+/// A certificate chain and private key for a specific server name.
+///
+/// See the `sni` parameter of [`TlsConfig`] for details. In a `Rocket.toml`,
+/// configuration might look like:
+///
+/// ```toml
+/// [default.tls.sni."example.com"]
+/// certs = "/ssl/example_cert.pem"
+/// key = "/ssl/example_key.pem"
+///
+/// [default.tls.sni."*.example.com"]
+/// certs = "/ssl/wildcard_example_cert.pem"
+/// key = "/ssl/wildcard_example_key.pem"
+/// ```
+///
+/// Programmatically, configuration might look like:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::config::{Config, TlsConfig, SniCertificate};
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     let example = SniCertificate::from_paths("/ssl/example_cert.pem", "/ssl/example_key.pem");
+///     let tls_config = TlsConfig::from_paths("/ssl/certs.pem", "/ssl/key.pem")
+///         .with_sni_certificate("example.com", example);
+///
+///     let config = Config {
+///         tls: Some(tls_config),
+///         ..Default::default()
+///     };
+///
+///     rocket::custom(config)
+/// }
+/// ```
+#[derive(PartialEq, Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(nightly, doc(cfg(feature = "tls")))]
+pub struct SniCertificate {
+    /// Path to a PEM file with, or raw bytes for, a DER-encoded X.509 TLS
+    /// certificate chain.
+    pub(crate) certs: Either<RelativePathBuf, Vec<u8>>,
+    /// Path to a PEM file with, or raw bytes for, DER-encoded private key in
+    /// either PKCS#8 or PKCS#1 format.
+    pub(crate) key: Either<RelativePathBuf, Vec<u8>>,
+}
+
 /// Mutual TLS configuration.
 ///
 /// Configuration works in concert with the [`mtls`](crate::mtls) module, which
@@ -126,12 +262,41 @@ pub struct TlsConfig {
 ///     either case, if a certificate _is_ presented, it must be valid or the
 ///     connection is terminated.
 ///
+/// Two more optional parameters configure certificate revocation checking:
+///
+///   * `crls`
+///
+///     An array of paths to or raw bytes of certificate revocation lists
+///     (CRLs), each a PEM file with any number of CRLs or a single DER-encoded
+///     CRL. Each CRL must be signed by one of the `ca_certs`; otherwise,
+///     launch fails, or a reload keeps the previous CRLs. During the TLS
+///     handshake, a client certificate, or an intermediate certificate the
+///     client presents, is rejected if a CRL from its issuer lists it as
+///     revoked. CRLs are reloaded along with the certificates; see
+///     [`TlsConfig`].
+///
+///   * `allow_stale_crls`
+///
+///     A boolean that controls whether to fail open when a CRL is _stale_,
+///     that is, when its next update is past due. When `false`, the default,
+///     every certificate from the issuer of a stale CRL is rejected. When
+///     `true`, such certificates are accepted unless the stale CRL lists them
+///     as revoked, and a warning is logged.
+///
+/// Finally, the `identity` parameter is an array of [`IdentityAttribute`]s
+/// that maps client certificates to the identities retrieved by the
+/// [`mtls::Identity`](crate::mtls::Identity) request guard. The first
+/// attribute the certificate has is the client's identity. It defaults to
+/// `["common_name"]`.
+///
 /// In a `Rocket.toml`, configuration might look like:
 ///
 /// ```toml
 /// [default.tls.mutual]
 /// ca_certs = "/ssl/ca_cert.pem"
 /// mandatory = true                # when absent, defaults to false
+/// crls = ["/ssl/ca_crl.pem"]      # when absent, defaults to none
+/// identity = ["spiffe", "dns"]    # when absent, defaults to ["common_name"]
 /// ```
 ///
 /// Programmatically, configuration might look like:
@@ -174,6 +339,116 @@ pub struct MutualTls {
     #[serde(default)]
     #[serde(deserialize_with = "figment::util::bool_from_str_or_int")]
     pub mandatory: bool,
+    /// Paths to PEM or DER files with, or raw bytes for, certificate revocation
+    /// lists to check client-presented certificates against.
+    #[serde(default)]
+    pub(crate) crls: Vec<Either<RelativePathBuf, Vec<u8>>>,
+    /// Whether to accept certificates from the issuer of a stale CRL.
+    ///
+    /// When `true`, a certificate from the issuer of a CRL whose next update
+    /// is past due is accepted unless the CRL lists it as revoked. When
+    /// `false`, such a certificate is rejected.
+    #[serde(default)]
+    #[serde(deserialize_with = "figment::util::bool_from_str_or_int")]
+    pub allow_stale_crls: bool,
+    /// Certificate attributes that identify a client, in order of preference.
+    #[serde(default = "IdentityAttribute::default_set")]
+    pub(crate) identity: Vec<IdentityAttribute>,
+}
+
+/// A certificate attribute that identifies a mutual TLS client.
+///
+/// The [`mtls::Identity`](crate::mtls::Identity) request guard identifies a
+/// client by the first of the configured [`MutualTls`] `identity` attributes
+/// its certificate has. In configuration, attributes are written in
+/// `snake_case`, such as `"common_name"`.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg(feature = "mtls")]
+#[cfg_attr(nightly, doc(cfg(feature = "mtls")))]
+#[non_exhaustive]
+pub enum IdentityAttribute {
+    /// The SPIFFE ID: the first subjectAlt URI with a `spiffe` scheme.
+    Spiffe,
+    /// The first subjectAlt URI.
+    Uri,
+    /// The first subjectAlt DNS name.
+    Dns,
+    /// The first subjectAlt IP address.
+    Ip,
+    /// The first subjectAlt email address or, if there is none, the first
+    /// email address in the subject.
+    Email,
+    /// The first common name in the subject.
+    CommonName,
+    /// The serial number, in base 10.
+    Serial,
+    /// The SHA-256 fingerprint, in lowercase hexadecimal.
+    Fingerprint,
+}
+
+#[cfg(feature = "mtls")]
+impl IdentityAttribute {
+    /// Used as the `serde` default for `identity`.
+    fn default_set() -> Vec<Self> {
+        vec![IdentityAttribute::CommonName]
+    }
+}
+
+#[cfg(feature = "mtls")]
+impl std::fmt::Display for IdentityAttribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentityAttribute::Spiffe => "spiffe".fmt(f),
+            IdentityAttribute::Uri => "uri".fmt(f),
+            IdentityAttribute::Dns => "dns".fmt(f),
+            IdentityAttribute::Ip => "ip".fmt(f),
+            IdentityAttribute::Email => "email".fmt(f),
+            IdentityAttribute::CommonName => "common_name".fmt(f),
+            IdentityAttribute::Serial => "serial".fmt(f),
+            IdentityAttribute::Fingerprint => "fingerprint".fmt(f),
+        }
+    }
+}
+
+/// A supported version of the TLS protocol.
+///
+/// Versions are ordered from oldest to newest, so that, for instance,
+/// `version >= TlsVersion::TLSv1_3` checks for TLS 1.3 or later. In
+/// configuration and when displayed, versions are written as `"TLSv1.2"` and
+/// `"TLSv1.3"`.
+#[allow(non_camel_case_types)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Hash, Deserialize, Serialize)]
+#[cfg_attr(nightly, doc(cfg(feature = "tls")))]
+#[non_exhaustive]
+pub enum TlsVersion {
+    /// TLS 1.2.
+    #[serde(rename = "TLSv1.2")]
+    TLSv1_2,
+    /// TLS 1.3.
+    #[serde(rename = "TLSv1.3")]
+    TLSv1_3,
+}
+
+impl TlsVersion {
+    /// Used as the `serde` default for `min_version`.
+    fn oldest() -> Self {
+        TlsVersion::TLSv1_2
+    }
+
+    /// Used as the `serde` default for `max_version`.
+    fn newest() -> Self {
+        TlsVersion::TLSv1_3
+    }
+}
+
+impl std::fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsVersion::TLSv1_2 => "TLSv1.2".fmt(f),
+            TlsVersion::TLSv1_3 => "TLSv1.3".fmt(f),
+        }
+    }
 }
 
 /// A supported TLS cipher suite.
@@ -244,6 +519,33 @@ impl CipherSuite {
     fn default_set() -> IndexSet<Self> {
         Self::DEFAULT_SET.iter().copied().collect()
     }
+
+    /// Returns the version of TLS this cipher suite is used with.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{CipherSuite, TlsVersion};
+    ///
+    /// let suite = CipherSuite::TLS_AES_128_GCM_SHA256;
+    /// assert_eq!(suite.version(), TlsVersion::TLSv1_3);
+    ///
+    /// let suite = CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256;
+    /// assert_eq!(suite.version(), TlsVersion::TLSv1_2);
+    /// ```
+    pub fn version(&self) -> TlsVersion {
+        if Self::TLS_V13_SET.contains(self) {
+            TlsVersion::TLSv1_3
+        } else {
+            TlsVersion::TLSv1_2
+        }
+    }
+}
+
+impl std::fmt::Display for CipherSuite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
 }
 
 impl TlsConfig {
@@ -253,11 +555,34 @@ impl TlsConfig {
             key: Either::Right(vec![]),
             ciphers: CipherSuite::default_set(),
             prefer_server_cipher_order: false,
+            min_version: TlsVersion::oldest(),
+            max_version: TlsVersion::newest(),
+            session_tickets: TlsConfig::default_session_tickets(),
+            ticket_rotation: TlsConfig::default_ticket_rotation(),
+            session_cache_size: TlsConfig::default_session_cache_size(),
+            reload_interval: 0,
+            reload_on_sighup: false,
+            sni: IndexMap::new(),
             #[cfg(feature = "mtls")]
             mutual: None,
         }
     }
 
+    /// Used as the `serde` default for `session_tickets`.
+    fn default_session_tickets() -> bool {
+        true
+    }
+
+    /// Used as the `serde` default for `ticket_rotation`: six hours.
+    fn default_ticket_rotation() -> u32 {
+        6 * 60 * 60
+    }
+
+    /// Used as the `serde` default for `session_cache_size`.
+    fn default_session_cache_size() -> usize {
+        1024
+    }
+
     /// Constructs a `TlsConfig` from paths to a `certs` certificate chain
     /// a `key` private-key. This method does no validation; it simply creates a
     /// structure suitable for passing into a [`Config`](crate::Config).
@@ -397,6 +722,166 @@ impl TlsConfig {
         self
     }
 
+    /// Sets the oldest version of TLS to negotiate. The default is
+    /// [`TlsVersion::TLSv1_2`].
+    ///
+    /// # Example
+    ///
+    /// Disable TLS v1.2:
+    ///
+    /// ```rust
+    /// use rocket::config::{TlsConfig, TlsVersion};
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf)
+    ///     .with_min_version(TlsVersion::TLSv1_3);
+    ///
+    /// assert_eq!(tls_config.min_version(), TlsVersion::TLSv1_3);
+    /// ```
+    pub fn with_min_version(mut self, version: TlsVersion) -> Self {
+        self.min_version = version;
+        self
+    }
+
+    /// Sets the newest version of TLS to negotiate. The default is
+    /// [`TlsVersion::TLSv1_3`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{TlsConfig, TlsVersion};
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf)
+    ///     .with_max_version(TlsVersion::TLSv1_2);
+    ///
+    /// assert_eq!(tls_config.max_version(), TlsVersion::TLSv1_2);
+    /// ```
+    pub fn with_max_version(mut self, version: TlsVersion) -> Self {
+        self.max_version = version;
+        self
+    }
+
+    /// Sets whether to issue stateless session tickets. Enabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf)
+    ///     .with_session_tickets(false);
+    ///
+    /// assert!(!tls_config.session_tickets());
+    /// ```
+    pub fn with_session_tickets(mut self, enabled: bool) -> Self {
+        self.session_tickets = enabled;
+        self
+    }
+
+    /// Rotates the key that encrypts session tickets every `secs` seconds.
+    /// Must be between `1` and `21600`, six hours, the default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf)
+    ///     .with_ticket_rotation(60 * 60);
+    ///
+    /// assert_eq!(tls_config.ticket_rotation(), 3600);
+    /// ```
+    pub fn with_ticket_rotation(mut self, secs: u32) -> Self {
+        self.ticket_rotation = secs;
+        self
+    }
+
+    /// Caches up to `size` sessions in memory for stateful resumption. A
+    /// `size` of `0` disables the cache. The default is `1024`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf)
+    ///     .with_session_cache_size(0);
+    ///
+    /// assert_eq!(tls_config.session_cache_size(), 0);
+    /// ```
+    pub fn with_session_cache_size(mut self, size: usize) -> Self {
+        self.session_cache_size = size;
+        self
+    }
+
+    /// Reloads `certs` and `key` from their paths every `secs` seconds while
+    /// the server is running. A value of `0` disables periodic reloading.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// let tls_config = TlsConfig::from_paths("/ssl/certs.pem", "/ssl/key.pem")
+    ///     .with_reload_interval(60 * 60);
+    ///
+    /// assert_eq!(tls_config.reload_interval(), 3600);
+    /// ```
+    pub fn with_reload_interval(mut self, secs: u32) -> Self {
+        self.reload_interval = secs;
+        self
+    }
+
+    /// Sets whether to reload `certs` and `key` from their paths when the
+    /// process receives `SIGHUP`. Only supported on Unix.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// let tls_config = TlsConfig::from_paths("/ssl/certs.pem", "/ssl/key.pem")
+    ///     .with_reload_on_sighup(true);
+    ///
+    /// assert!(tls_config.reload_on_sighup());
+    /// ```
+    pub fn with_reload_on_sighup(mut self, reload: bool) -> Self {
+        self.reload_on_sighup = reload;
+        self
+    }
+
+    /// Serves the certificate chain and key in `certificate` to clients that
+    /// request the server name `name` via SNI, replacing any previously set
+    /// for `name`. `name` may be a wildcard like `*.example.com`. See
+    /// [`TlsConfig`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{TlsConfig, SniCertificate};
+    ///
+    /// let api = SniCertificate::from_paths("/ssl/api_cert.pem", "/ssl/api_key.pem");
+    /// let tls_config = TlsConfig::from_paths("/ssl/certs.pem", "/ssl/key.pem")
+    ///     .with_sni_certificate("api.example.com", api);
+    ///
+    /// assert_eq!(tls_config.sni_certificates().count(), 1);
+    /// ```
+    pub fn with_sni_certificate<N>(mut self, name: N, certificate: SniCertificate) -> Self
+        where N: Into<String>
+    {
+        self.sni.insert(name.into(), certificate);
+        self
+    }
+
     /// Configures mutual TLS. See [`MutualTls`] for details.
     ///
     /// # Example
@@ -513,6 +998,139 @@ impl TlsConfig {
         self.prefer_server_cipher_order
     }
 
+    /// Returns the value of the `min_version` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{TlsConfig, TlsVersion};
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf);
+    /// assert_eq!(tls_config.min_version(), TlsVersion::TLSv1_2);
+    /// ```
+    pub fn min_version(&self) -> TlsVersion {
+        self.min_version
+    }
+
+    /// Returns the value of the `max_version` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{TlsConfig, TlsVersion};
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf);
+    /// assert_eq!(tls_config.max_version(), TlsVersion::TLSv1_3);
+    /// ```
+    pub fn max_version(&self) -> TlsVersion {
+        self.max_version
+    }
+
+    /// Returns the value of the `session_tickets` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf);
+    /// assert!(tls_config.session_tickets());
+    /// ```
+    pub fn session_tickets(&self) -> bool {
+        self.session_tickets
+    }
+
+    /// Returns the value of the `ticket_rotation` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf);
+    /// assert_eq!(tls_config.ticket_rotation(), 21600);
+    /// ```
+    pub fn ticket_rotation(&self) -> u32 {
+        self.ticket_rotation
+    }
+
+    /// Returns the value of the `session_cache_size` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf);
+    /// assert_eq!(tls_config.session_cache_size(), 1024);
+    /// ```
+    pub fn session_cache_size(&self) -> usize {
+        self.session_cache_size
+    }
+
+    /// Returns the value of the `reload_interval` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf);
+    /// assert_eq!(tls_config.reload_interval(), 0);
+    /// ```
+    pub fn reload_interval(&self) -> u32 {
+        self.reload_interval
+    }
+
+    /// Returns the value of the `reload_on_sighup` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsConfig;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let tls_config = TlsConfig::from_bytes(certs_buf, key_buf);
+    /// assert!(!tls_config.reload_on_sighup());
+    /// ```
+    pub fn reload_on_sighup(&self) -> bool {
+        self.reload_on_sighup
+    }
+
+    /// Returns an iterator over the server names in the `sni` parameter and
+    /// their respective certificate chain and key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use rocket::config::{TlsConfig, SniCertificate};
+    ///
+    /// let api = SniCertificate::from_paths("/ssl/api_cert.pem", "/ssl/api_key.pem");
+    /// let tls_config = TlsConfig::from_paths("/ssl/certs.pem", "/ssl/key.pem")
+    ///     .with_sni_certificate("api.example.com", api);
+    ///
+    /// let (name, certificate) = tls_config.sni_certificates().next().unwrap();
+    /// assert_eq!(name, "api.example.com");
+    /// assert_eq!(certificate.certs().unwrap_left(), Path::new("/ssl/api_cert.pem"));
+    /// ```
+    pub fn sni_certificates(&self) -> impl Iterator<Item = (&str, &SniCertificate)> {
+        self.sni.iter().map(|(name, certificate)| (name.as_str(), certificate))
+    }
+
     /// Returns the value of the `mutual` parameter.
     ///
     /// # Example
@@ -535,6 +1153,116 @@ impl TlsConfig {
     pub fn mutual(&self) -> Option<&MutualTls> {
         self.mutual.as_ref()
     }
+
+    /// Checks that the protocol version and session ticket parameters are
+    /// consistent with each other and with `ciphers`. On error, returns the
+    /// name of the offending parameter and a description of the problem.
+    pub(crate) fn validate(&self) -> Result<(), (&'static str, String)> {
+        let (min, max) = (self.min_version, self.max_version);
+        if min > max {
+            let msg = format!("`min_version` {} is newer than `max_version` {}", min, max);
+            return Err(("min_version", msg));
+        }
+
+        let supported = |suite: &CipherSuite| (min..=max).contains(&suite.version());
+        if !self.ciphers.is_empty() && !self.ciphers.iter().any(supported) {
+            let msg = if min == max {
+                format!("no cipher suite in `ciphers` supports {}", min)
+            } else {
+                format!("no cipher suite in `ciphers` supports {} or {}", min, max)
+            };
+
+            return Err(("ciphers", msg));
+        }
+
+        if self.session_tickets && !(1..=21600).contains(&self.ticket_rotation) {
+            let msg = format!("`ticket_rotation` of {}s is not between 1s and 21600s",
+                self.ticket_rotation);
+
+            return Err(("ticket_rotation", msg));
+        }
+
+        Ok(())
+    }
+}
+
+impl SniCertificate {
+    /// Constructs an `SniCertificate` from paths to a `certs` certificate chain
+    /// and a `key` private-key. This method does no validation; it simply
+    /// creates a structure suitable for passing into a [`TlsConfig`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::SniCertificate;
+    ///
+    /// let certificate = SniCertificate::from_paths("/ssl/certs.pem", "/ssl/key.pem");
+    /// ```
+    pub fn from_paths<C, K>(certs: C, key: K) -> Self
+        where C: AsRef<std::path::Path>, K: AsRef<std::path::Path>
+    {
+        SniCertificate {
+            certs: Either::Left(certs.as_ref().to_path_buf().into()),
+            key: Either::Left(key.as_ref().to_path_buf().into()),
+        }
+    }
+
+    /// Constructs an `SniCertificate` from byte buffers to a `certs`
+    /// certificate chain and a `key` private-key. This method does no
+    /// validation; it simply creates a structure suitable for passing into a
+    /// [`TlsConfig`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::SniCertificate;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let certificate = SniCertificate::from_bytes(certs_buf, key_buf);
+    /// ```
+    pub fn from_bytes(certs: &[u8], key: &[u8]) -> Self {
+        SniCertificate {
+            certs: Either::Right(certs.to_vec()),
+            key: Either::Right(key.to_vec()),
+        }
+    }
+
+    /// Returns the value of the `certs` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use rocket::config::SniCertificate;
+    ///
+    /// let certificate = SniCertificate::from_paths("/ssl/certs.pem", "/ssl/key.pem");
+    /// assert_eq!(certificate.certs().unwrap_left(), Path::new("/ssl/certs.pem"));
+    /// ```
+    pub fn certs(&self) -> either::Either<std::path::PathBuf, &[u8]> {
+        match &self.certs {
+            Either::Left(path) => either::Either::Left(path.relative()),
+            Either::Right(bytes) => either::Either::Right(bytes),
+        }
+    }
+
+    /// Returns the value of the `key` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use rocket::config::SniCertificate;
+    ///
+    /// let certificate = SniCertificate::from_paths("/ssl/certs.pem", "/ssl/key.pem");
+    /// assert_eq!(certificate.key().unwrap_left(), Path::new("/ssl/key.pem"));
+    /// ```
+    pub fn key(&self) -> either::Either<std::path::PathBuf, &[u8]> {
+        match &self.key {
+            Either::Left(path) => either::Either::Left(path.relative()),
+            Either::Right(bytes) => either::Either::Right(bytes),
+        }
+    }
 }
 
 #[cfg(feature = "mtls")]
@@ -557,7 +1285,10 @@ impl MutualTls {
     pub fn from_path<C: AsRef<std::path::Path>>(ca_certs: C) -> Self {
         MutualTls {
             ca_certs: Either::Left(ca_certs.as_ref().to_path_buf().into()),
-            mandatory: Default::default()
+            mandatory: Default::default(),
+            crls: vec![],
+            allow_stale_crls: Default::default(),
+            identity: IdentityAttribute::default_set(),
         }
     }
 
@@ -580,7 +1311,10 @@ impl MutualTls {
     pub fn from_bytes(ca_certs: &[u8]) -> Self {
         MutualTls {
             ca_certs: Either::Right(ca_certs.to_vec()),
-            mandatory: Default::default()
+            mandatory: Default::default(),
+            crls: vec![],
+            allow_stale_crls: Default::default(),
+            identity: IdentityAttribute::default_set(),
         }
     }
 
@@ -605,6 +1339,81 @@ impl MutualTls {
         self
     }
 
+    /// Adds a path to a PEM file with certificate revocation lists, or to a
+    /// DER-encoded CRL, to check client-presented certificates against.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::MutualTls;
+    ///
+    /// let mtls_config = MutualTls::from_path("/ssl/ca_cert.pem")
+    ///     .with_crl_path("/ssl/ca_crl.pem");
+    /// ```
+    pub fn with_crl_path<P: AsRef<std::path::Path>>(mut self, crl: P) -> Self {
+        self.crls.push(Either::Left(crl.as_ref().to_path_buf().into()));
+        self
+    }
+
+    /// Adds a byte buffer with PEM-encoded certificate revocation lists, or a
+    /// DER-encoded CRL, to check client-presented certificates against.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::MutualTls;
+    ///
+    /// # let ca_certs_buf = &[];
+    /// # let crl_buf = &[];
+    /// let mtls_config = MutualTls::from_bytes(ca_certs_buf).with_crl_bytes(crl_buf);
+    /// ```
+    pub fn with_crl_bytes(mut self, crl: &[u8]) -> Self {
+        self.crls.push(Either::Right(crl.to_vec()));
+        self
+    }
+
+    /// Sets whether to accept certificates from the issuer of a stale CRL,
+    /// one whose next update is past due. Disabled by default.
+    ///
+    /// When `true`, such a certificate is accepted unless the CRL lists it as
+    /// revoked, and a warning is logged. When `false`, it is rejected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::MutualTls;
+    ///
+    /// let mtls_config = MutualTls::from_path("/ssl/ca_cert.pem")
+    ///     .with_crl_path("/ssl/ca_crl.pem")
+    ///     .allow_stale_crls(true);
+    ///
+    /// assert!(mtls_config.allow_stale_crls);
+    /// ```
+    pub fn allow_stale_crls(mut self, allow: bool) -> Self {
+        self.allow_stale_crls = allow;
+        self
+    }
+
+    /// Sets the certificate attributes that identify a client, in order of
+    /// preference. The default is `[IdentityAttribute::CommonName]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{MutualTls, IdentityAttribute};
+    ///
+    /// let mtls_config = MutualTls::from_path("/ssl/ca_cert.pem")
+    ///     .with_identity([IdentityAttribute::Spiffe, IdentityAttribute::Dns]);
+    ///
+    /// assert_eq!(mtls_config.identity(), &[IdentityAttribute::Spiffe, IdentityAttribute::Dns]);
+    /// ```
+    pub fn with_identity<I>(mut self, attributes: I) -> Self
+        where I: IntoIterator<Item = IdentityAttribute>
+    {
+        self.identity = attributes.into_iter().collect();
+        self
+    }
+
     /// Returns the value of the `ca_certs` parameter.
     /// # Example
     ///
@@ -621,6 +1430,41 @@ impl MutualTls {
             Either::Right(bytes) => either::Either::Right(&bytes),
         }
     }
+
+    /// Returns an iterator over the values of the `crls` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use rocket::config::MutualTls;
+    ///
+    /// let mtls_config = MutualTls::from_path("/ssl/ca_cert.pem")
+    ///     .with_crl_path("/ssl/ca_crl.pem");
+    ///
+    /// let crls: Vec<_> = mtls_config.crls().collect();
+    /// assert_eq!(crls[0].as_ref().unwrap_left(), Path::new("/ssl/ca_crl.pem"));
+    /// ```
+    pub fn crls(&self) -> impl Iterator<Item = either::Either<std::path::PathBuf, &[u8]>> + '_ {
+        self.crls.iter().map(|crl| match crl {
+            Either::Left(path) => either::Either::Left(path.relative()),
+            Either::Right(bytes) => either::Either::Right(&bytes[..]),
+        })
+    }
+
+    /// Returns the value of the `identity` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{MutualTls, IdentityAttribute};
+    ///
+    /// let mtls_config = MutualTls::from_path("/ssl/ca_cert.pem");
+    /// assert_eq!(mtls_config.identity(), &[IdentityAttribute::CommonName]);
+    /// ```
+    pub fn identity(&self) -> &[IdentityAttribute] {
+        &self.identity
+    }
 }
 
 #[cfg(feature = "tls")]
@@ -628,16 +1472,23 @@ mod with_tls_feature {
     use std::fs;
     use std::io::{self, Error};
 
-    use crate::http::tls::Config;
+    use crate::http::tls::{CertResolver, Config};
+    #[cfg(feature = "mtls")]
+    use crate::http::tls::{Crl, CrlVerifier};
+    use crate::http::tls::rustls::sign::CertifiedKey;
     use crate::http::tls::rustls::SupportedCipherSuite as RustlsCipher;
-    use crate::http::tls::rustls::cipher_suite;
+    use crate::http::tls::rustls::{cipher_suite, version, ProtocolVersion};
+    use crate::http::tls::rustls::SupportedProtocolVersion;
 
     use yansi::Paint;
 
-    use super::{Either, RelativePathBuf, TlsConfig, CipherSuite};
+    use super::{Either, RelativePathBuf, TlsConfig, CipherSuite, TlsVersion};
 
     type Reader = Box<dyn std::io::BufRead + Sync + Send>;
 
+    /// A default certificate chain and key and those for each server name.
+    type CertifiedKeys = (CertifiedKey, Vec<(String, CertifiedKey)>);
+
     fn to_reader(value: &Either<RelativePathBuf, Vec<u8>>) -> io::Result<Reader> {
         match value {
             Either::Left(path) => {
@@ -659,12 +1510,25 @@ mod with_tls_feature {
             Ok(Config {
                 cert_chain: to_reader(&self.certs)?,
                 private_key: to_reader(&self.key)?,
+                sni: self.sni.iter()
+                    .map(|(name, c)| Ok((name.clone(), to_reader(&c.certs)?, to_reader(&c.key)?)))
+                    .collect::<io::Result<_>>()?,
                 ciphersuites: self.rustls_ciphers().collect(),
                 prefer_server_order: self.prefer_server_cipher_order,
+                versions: [TlsVersion::TLSv1_2, TlsVersion::TLSv1_3].iter()
+                    .filter(|v| (self.min_version..=self.max_version).contains(v))
+                    .map(|v| v.to_rustls_version())
+                    .collect(),
+                ticket_rotation: self.session_tickets.then(|| self.ticket_rotation),
+                session_cache_size: self.session_cache_size,
                 #[cfg(not(feature = "mtls"))]
                 mandatory_mtls: false,
                 #[cfg(not(feature = "mtls"))]
                 ca_certs: None,
+                #[cfg(not(feature = "mtls"))]
+                crls: vec![],
+                #[cfg(not(feature = "mtls"))]
+                allow_stale_crls: false,
                 #[cfg(feature = "mtls")]
                 mandatory_mtls: self.mutual.as_ref().map_or(false, |m| m.mandatory),
                 #[cfg(feature = "mtls")]
@@ -672,11 +1536,57 @@ mod with_tls_feature {
                     Some(ref mtls) => Some(to_reader(&mtls.ca_certs)?),
                     None => None
                 },
+                #[cfg(feature = "mtls")]
+                crls: match self.mutual {
+                    Some(ref mtls) => mtls.crls.iter().map(to_reader).collect::<io::Result<_>>()?,
+                    None => vec![],
+                },
+                #[cfg(feature = "mtls")]
+                allow_stale_crls: self.mutual.as_ref().map_or(false, |m| m.allow_stale_crls),
             })
         }
 
+        /// Reads and parses the default certificate chain and key and those
+        /// for each server name in `sni`.
+        pub(crate) fn load_certified_keys(&self) -> io::Result<CertifiedKeys> {
+            let (mut certs, mut key) = (to_reader(&self.certs)?, to_reader(&self.key)?);
+            let default = CertResolver::load(&mut certs, &mut key)?;
+
+            let mut names = Vec::with_capacity(self.sni.len());
+            for (name, c) in &self.sni {
+                let (mut certs, mut key) = (to_reader(&c.certs)?, to_reader(&c.key)?);
+                names.push((name.clone(), CertResolver::load_named(name, &mut certs, &mut key)?));
+            }
+
+            Ok((default, names))
+        }
+
+        /// Reads and parses the certificate revocation lists in `mutual.crls`.
+        #[cfg(feature = "mtls")]
+        pub(crate) fn load_crls(&self) -> io::Result<Vec<Crl>> {
+            let mut crls = vec![];
+            for crl in self.mutual.iter().flat_map(|m| &m.crls) {
+                crls.extend(CrlVerifier::load(&mut to_reader(crl)?)?);
+            }
+
+            Ok(crls)
+        }
+
         fn rustls_ciphers(&self) -> impl Iterator<Item = RustlsCipher> + '_ {
-            self.ciphers().map(|ciphersuite| match ciphersuite {
+            self.ciphers().map(CipherSuite::to_rustls)
+        }
+    }
+
+    impl CipherSuite {
+        /// Returns the suite with the IANA-assigned value `value`, if any.
+        pub(crate) fn from_u16(value: u16) -> Option<CipherSuite> {
+            CipherSuite::DEFAULT_SET.iter()
+                .copied()
+                .find(|suite| suite.to_rustls().suite().get_u16() == value)
+        }
+
+        fn to_rustls(self) -> RustlsCipher {
+            match self {
                 CipherSuite::TLS_CHACHA20_POLY1305_SHA256 =>
                     cipher_suite::TLS13_CHACHA20_POLY1305_SHA256,
                 CipherSuite::TLS_AES_256_GCM_SHA384 =>
@@ -695,7 +1605,30 @@ mod with_tls_feature {
                     cipher_suite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
                 CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256 =>
                     cipher_suite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
-            })
+            }
+        }
+    }
+
+    impl TlsVersion {
+        /// Returns the version encoded on the wire as `value`, if supported.
+        pub(crate) fn from_u16(value: u16) -> Option<TlsVersion> {
+            [TlsVersion::TLSv1_2, TlsVersion::TLSv1_3].iter()
+                .copied()
+                .find(|version| version.to_rustls().get_u16() == value)
+        }
+
+        fn to_rustls(self) -> ProtocolVersion {
+            match self {
+                TlsVersion::TLSv1_2 => ProtocolVersion::TLSv1_2,
+                TlsVersion::TLSv1_3 => ProtocolVersion::TLSv1_3,
+            }
+        }
+
+        fn to_rustls_version(self) -> &'static SupportedProtocolVersion {
+            match self {
+                TlsVersion::TLSv1_2 => &version::TLS12,
+                TlsVersion::TLSv1_3 => &version::TLS13,
+            }
         }
     }
 }
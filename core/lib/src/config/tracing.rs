@@ -0,0 +1,106 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Tracing subscriber configuration.
+///
+/// With the `tracing` feature enabled, Rocket emits a span for every
+/// connection and, within it, a span for every request, recording its method,
+/// URI, ID, matched route, response status, and latency. Request and response
+/// fairings, request and data guards, and route and catcher handlers each run
+/// in a child span of the request span. Spans are emitted to whichever
+/// subscriber is the default, if any.
+///
+/// When `Config::tracing` is `Some`, Rocket installs a global default
+/// subscriber that writes spans and events to standard output in the
+/// configured [`format`](Tracing::format) and forwards Rocket's own log
+/// messages to it in place of the default pretty logger. When it is `None`,
+/// the default, Rocket logs as it otherwise would and installs no subscriber.
+///
+///   * **`format`**: The output format: `full`, `compact`, or `json`.
+///
+///   * **`filter`**: A comma-separated list of `target=level` directives, as
+///     in `info,rocket=debug,hyper=off`, selecting the spans and events that
+///     are written. When unset, the filter is derived from
+///     [`Config::log_level`](crate::Config::log_level).
+///
+/// # Example
+///
+/// As with all Rocket configuration options, when using the default
+/// [`Config::figment()`](crate::Config::figment()), `Tracing` can be
+/// configured via a `Rocket.toml` file:
+///
+/// ```rust
+/// # use rocket::figment::{Figment, providers::{Format, Toml}};
+/// use rocket::Config;
+/// use rocket::config::TraceFormat;
+///
+/// // If these are the contents of `Rocket.toml`...
+/// # let toml = Toml::string(r#"
+/// [default.tracing]
+/// format = "json"
+/// filter = "info,hyper=off"
+/// # "#).nested();
+///
+/// // The config parses as follows:
+/// # let config = Config::from(Figment::from(Config::debug_default()).merge(toml));
+/// let tracing = config.tracing.unwrap();
+/// assert_eq!(tracing.format, TraceFormat::Json);
+/// assert_eq!(tracing.filter.as_deref(), Some("info,hyper=off"));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(nightly, doc(cfg(feature = "tracing")))]
+pub struct Tracing {
+    /// The output format.
+    ///
+    /// **default: `full`**
+    #[serde(default)]
+    pub format: TraceFormat,
+    /// The filter directives, if not derived from the log level.
+    ///
+    /// **default: `None`**
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+/// The output format of Rocket's tracing subscriber.
+///
+/// See [`Tracing`] for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(nightly, doc(cfg(feature = "tracing")))]
+pub enum TraceFormat {
+    /// Human-readable, multi-field lines with the full span context.
+    Full,
+    /// Shorter human-readable lines.
+    Compact,
+    /// Newline-delimited JSON objects.
+    Json,
+}
+
+impl Default for TraceFormat {
+    fn default() -> Self {
+        TraceFormat::Full
+    }
+}
+
+impl fmt::Display for TraceFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceFormat::Full => "full".fmt(f),
+            TraceFormat::Compact => "compact".fmt(f),
+            TraceFormat::Json => "json".fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Tracing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format)?;
+        if let Some(filter) = &self.filter {
+            write!(f, " ({})", filter)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::http::private::UnixOptions;
+
+/// Unix domain socket configuration for a listener.
+///
+/// When a listener's address is a `unix://` path, `UnixSocket` controls how its
+/// socket file is created. The file is created with permissions derived from
+/// the process's umask or, when set, with `mode`, `owner`, and `group`: the
+/// socket is then bound in a private directory next to its path, changed there,
+/// and only then linked into place, so it is never accessible with other
+/// permissions. Changing the owner typically requires privileges. `owner` and
+/// `group` are names or numeric IDs.
+///
+/// A socket file left behind by a process that didn't shut down gracefully
+/// makes binding to its path fail. With `remove_stale`, such a file is removed
+/// before binding as long as nothing is listening on it. Regardless, the socket
+/// file is removed on graceful shutdown.
+///
+/// A `unix://@name` address, on Linux, names a socket in the abstract
+/// namespace. Such sockets have no file, so `mode`, `owner`, and `group` must
+/// not be set for them. Access to any Unix socket can also be checked per
+/// request with the [`PeerCredentials`](crate::request::PeerCredentials)
+/// request guard.
+///
+/// # Example
+///
+/// To make the socket of the primary address accessible to the `www-data`
+/// group, in `Rocket.toml`:
+///
+/// ```toml
+/// [default]
+/// address = "unix:///run/app/app.sock"
+/// unix_socket = { mode = "0660", group = "www-data", remove_stale = true }
+/// ```
+///
+/// Or, equivalently, programmatically:
+///
+/// ```rust
+/// use rocket::config::{Config, UnixSocket};
+///
+/// let unix_socket = UnixSocket {
+///     mode: Some(0o660),
+///     group: Some("www-data".into()),
+///     remove_stale: true,
+///     ..UnixSocket::default()
+/// };
+///
+/// let figment = Config::figment()
+///     .merge((Config::ADDRESS, "unix:///run/app/app.sock"))
+///     .merge((Config::UNIX_SOCKET, unix_socket));
+///
+/// let config = Config::from(figment);
+/// let unix_socket = config.unix_socket.unwrap();
+/// assert_eq!(unix_socket.mode, Some(0o660));
+/// assert_eq!(unix_socket.owner, None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct UnixSocket {
+    /// The permissions of the socket file, such as `0o660`, if any. Can be
+    /// written as an integer or as an octal string like `"0660"`.
+    ///
+    /// **default: `None`**
+    #[serde(default, deserialize_with = "deserialize_mode")]
+    pub mode: Option<u32>,
+    /// The user, by name or ID, to own the socket file, if any.
+    ///
+    /// **default: `None`**
+    #[serde(default, deserialize_with = "deserialize_id")]
+    pub owner: Option<String>,
+    /// The group, by name or ID, of the socket file, if any.
+    ///
+    /// **default: `None`**
+    #[serde(default, deserialize_with = "deserialize_id")]
+    pub group: Option<String>,
+    /// Whether to remove an existing socket file that nothing is listening on
+    /// before binding.
+    ///
+    /// **default: `false`**
+    #[serde(default)]
+    pub remove_stale: bool,
+}
+
+impl UnixSocket {
+    pub(crate) fn to_options(&self) -> UnixOptions {
+        UnixOptions {
+            mode: self.mode,
+            owner: self.owner.clone(),
+            group: self.group.clone(),
+            remove_stale: self.remove_stale,
+        }
+    }
+}
+
+fn deserialize_mode<'de, D>(de: D) -> Result<Option<u32>, D::Error>
+    where D: Deserializer<'de>
+{
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = Option<u32>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            formatter.write_str("a file mode integer or octal string")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            match v {
+                0..=0o7777 => Ok(Some(v as u32)),
+                _ => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+            }
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            match u64::try_from(v) {
+                Ok(v) => self.visit_u64(v),
+                Err(_) => Err(E::invalid_value(de::Unexpected::Signed(v), &self)),
+            }
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            let digits = v.strip_prefix("0o").unwrap_or(v);
+            match u64::from_str_radix(digits, 8) {
+                Ok(mode) => self.visit_u64(mode),
+                Err(_) => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+            }
+        }
+
+        fn visit_some<D>(self, de: D) -> Result<Self::Value, D::Error>
+            where D: Deserializer<'de>
+        {
+            de.deserialize_any(self)
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+    }
+
+    de.deserialize_option(Visitor)
+}
+
+fn deserialize_id<'de, D>(de: D) -> Result<Option<String>, D::Error>
+    where D: Deserializer<'de>
+{
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = Option<String>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            formatter.write_str("a name or numeric ID")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(Some(v.to_string()))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            match u64::try_from(v) {
+                Ok(v) => self.visit_u64(v),
+                Err(_) => Err(E::invalid_value(de::Unexpected::Signed(v), &self)),
+            }
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            Ok(Some(v.to_string()))
+        }
+
+        fn visit_some<D>(self, de: D) -> Result<Self::Value, D::Error>
+            where D: Deserializer<'de>
+        {
+            de.deserialize_any(self)
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+    }
+
+    de.deserialize_option(Visitor)
+}
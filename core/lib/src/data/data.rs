@@ -1,7 +1,14 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
 use crate::tokio::io::AsyncReadExt;
 use crate::data::data_stream::DataStream;
 use crate::data::{ByteUnit, StreamReader};
 
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+use crate::{Request, compression::Encoding, http::uncased::eq};
+
 /// The number of bytes to read into the "peek" buffer.
 pub const PEEK_BYTES: usize = 512;
 
@@ -41,18 +48,40 @@ pub struct Data<'r> {
     buffer: Vec<u8>,
     is_complete: bool,
     stream: StreamReader<'r>,
+    timed_out: Option<Arc<AtomicBool>>,
+}
+
+/// Whether a read of the body of a `Data` timed out.
+pub(crate) struct TimedOut(Option<Arc<AtomicBool>>);
+
+impl TimedOut {
+    pub(crate) fn get(&self) -> bool {
+        self.0.as_ref().map_or(false, |expired| expired.load(Ordering::Acquire))
+    }
 }
 
 impl<'r> Data<'r> {
     /// Create a `Data` from a recognized `stream`.
     pub(crate) fn from<S: Into<StreamReader<'r>>>(stream: S) -> Data<'r> {
-        // TODO.async: This used to also set the read timeout to 5 seconds.
-        // Such a short read timeout is likely no longer necessary, but some
-        // kind of idle timeout should be implemented.
-
         let stream = stream.into();
         let buffer = Vec::with_capacity(PEEK_BYTES / 8);
-        Data { buffer, stream, is_complete: false }
+        Data { buffer, stream, is_complete: false, timed_out: None }
+    }
+
+    /// Fails reads of the body with an error of kind `TimedOut` when a chunk
+    /// isn't received within `timeout`. See [`Data::timed_out()`].
+    pub(crate) fn with_timeout(mut self, timeout: Duration) -> Data<'r> {
+        let expired = Arc::new(AtomicBool::new(false));
+        let stream = std::mem::replace(&mut self.stream, StreamReader::empty());
+        self.stream = stream.with_timeout(timeout, expired.clone());
+        self.timed_out = Some(expired);
+        self
+    }
+
+    /// Returns a handle that reports whether a read of the body timed out,
+    /// even after `self` has been consumed.
+    pub(crate) fn timed_out(&self) -> TimedOut {
+        TimedOut(self.timed_out.clone())
     }
 
     /// This creates a `data` object from a local data source `data`.
@@ -62,9 +91,41 @@ impl<'r> Data<'r> {
             buffer: data,
             stream: StreamReader::empty(),
             is_complete: true,
+            timed_out: None,
         }
     }
 
+    /// Decodes the remainder of the body, including anything already in the
+    /// peek buffer, according to the content codings in `req`'s
+    /// `Content-Encoding` header, if they're all supported. If they are,
+    /// `Content-Encoding` and `Content-Length` are removed from `req` as they
+    /// no longer describe the body. Otherwise, the body is left as-is.
+    #[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+    pub(crate) fn decode(&mut self, req: &mut Request<'_>) {
+        let encodings = req.headers().get("Content-Encoding")
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|coding| !coding.is_empty() && !eq(coding, "identity"))
+            .map(Encoding::from_name)
+            .collect::<Option<Vec<_>>>();
+
+        let encodings = match encodings {
+            Some(encodings) if !encodings.is_empty() => encodings,
+            _ => return,
+        };
+
+        // Codings are listed in the order they were applied.
+        for encoding in encodings.into_iter().rev() {
+            let stream = std::mem::replace(&mut self.stream, StreamReader::empty());
+            let buffer = std::mem::take(&mut self.buffer);
+            self.stream = stream.decoded(buffer, encoding);
+        }
+
+        self.is_complete = false;
+        req._remove_header("Content-Encoding");
+        req._remove_header("Content-Length");
+    }
+
     /// Returns the raw data stream, limited to `limit` bytes.
     ///
     /// The stream contains all of the data in the body of the request,
@@ -72,6 +133,21 @@ impl<'r> Data<'r> {
     /// instance. This ensures that a `Data` type _always_ represents _all_ of
     /// the data in a request.
     ///
+    /// # Content Encoding
+    ///
+    /// When any of the `gzip`, `deflate`, `brotli`, or `zstd` features are
+    /// enabled and the request's `Content-Encoding` only names content codings
+    /// that are enabled, the stream contains the _decoded_ body and `limit`
+    /// applies to the decoded data. This includes the data in the `peek`
+    /// buffer. The encoded body is limited to `limit` bytes as well: reading
+    /// beyond that fails with an I/O error of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData). The
+    /// `Content-Encoding` and `Content-Length` headers are
+    /// removed from the request before any request fairings run. If a coding
+    /// is not supported, the body is left as-is. A malformed body results in
+    /// an I/O error of kind [`InvalidData`](std::io::ErrorKind::InvalidData)
+    /// when it is read.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -2,9 +2,14 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::path::Path;
 use std::io::{self, Cursor};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use tokio::fs::File;
 use tokio::io::{AsyncRead, AsyncWrite, AsyncReadExt, ReadBuf, Take};
+use tokio::time::{sleep, Sleep};
+use futures::future::Future;
 use futures::stream::Stream;
 use futures::ready;
 
@@ -12,6 +17,18 @@ use crate::http::hyper;
 use crate::ext::{PollExt, Chain};
 use crate::data::{Capped, N};
 
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+use {
+    std::sync::atomic::AtomicU64,
+    tokio::io::BufReader,
+    crate::compression::{Decompressor, Encoding},
+    crate::ext::{AsyncReadExt as _, ReaderStream},
+};
+
+/// The size of the chunks read from a decoded stream.
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+const DECODED_CHUNK_SIZE: usize = 8 * 1024;
+
 /// Raw data stream of a request body.
 ///
 /// This stream can only be obtained by calling
@@ -47,12 +64,23 @@ pub struct DataStream<'r> {
 pub struct StreamReader<'r> {
     state: State,
     inner: StreamKind<'r>,
+    timeout: Option<ChunkTimeout>,
+}
+
+/// A deadline for each chunk read from a `StreamReader`.
+struct ChunkTimeout {
+    duration: Duration,
+    timer: Option<Pin<Box<Sleep>>>,
+    expired: Arc<AtomicBool>,
 }
 
 /// The current state of `StreamReader` `AsyncRead` adapter.
 enum State {
     Pending,
     Partial(Cursor<hyper::body::Bytes>),
+    /// A read failed. The error is returned by every subsequent read so that
+    /// it isn't lost to a `Data::peek()`.
+    Failed(io::ErrorKind, String),
     Done,
 }
 
@@ -60,11 +88,49 @@ enum State {
 enum StreamKind<'r> {
     Empty,
     Body(&'r mut hyper::Body),
-    Multipart(multer::Field<'r>)
+    Multipart(multer::Field<'r>),
+    /// A decoded stream and the limit on its encoded input, which is shared
+    /// with the streams it decodes, if any.
+    #[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+    Decoded(Box<ReaderStream<Decompressor<BufReader<Encoded<'r>>>>>, Arc<EncodedLimit>),
+}
+
+/// The encoded input of a decoded stream: the data already read followed by
+/// the rest of the stream.
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+struct Encoded<'r> {
+    chain: Chain<Cursor<Vec<u8>>, StreamReader<'r>>,
+    read: u64,
+    limit: Arc<EncodedLimit>,
+}
+
+/// The limit on the bytes read from the encoded input of each decoded stream
+/// in a chain, set when the `DataStream` is opened, and the most bytes read
+/// from any one input. Reads fail once the latter exceeds the former.
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+struct EncodedLimit {
+    limit: AtomicU64,
+    read: AtomicU64,
+}
+
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+impl EncodedLimit {
+    fn check(&self) -> io::Result<()> {
+        if self.read.load(Ordering::Acquire) > self.limit.load(Ordering::Acquire) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "encoded body exceeds limit"));
+        }
+
+        Ok(())
+    }
 }
 
 impl<'r> DataStream<'r> {
     pub(crate) fn new(buf: Vec<u8>, stream: StreamReader<'r>, limit: u64) -> Self {
+        #[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+        if let StreamKind::Decoded(_, encoded) = &stream.inner {
+            encoded.limit.store(limit, Ordering::Release);
+        }
+
         let chain = Chain::new(Cursor::new(buf), stream).take(limit);
         Self { chain }
     }
@@ -227,21 +293,73 @@ impl<'r> DataStream<'r> {
 
 // TODO.async: Consider implementing `AsyncBufRead`.
 
-impl StreamReader<'_> {
+impl<'r> StreamReader<'r> {
     pub fn empty() -> Self {
-        Self { inner: StreamKind::Empty, state: State::Done }
+        Self { inner: StreamKind::Empty, state: State::Done, timeout: None }
+    }
+
+    /// Fails a read with an error of kind `TimedOut` and sets `expired` when
+    /// the next chunk of `self` isn't available within `duration`.
+    pub(crate) fn with_timeout(mut self, duration: Duration, expired: Arc<AtomicBool>) -> Self {
+        self.timeout = Some(ChunkTimeout { duration, timer: None, expired });
+        self
+    }
+
+    /// Polls for the next chunk, subject to the chunk timeout, if any.
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<io::Result<hyper::body::Bytes>>> {
+        let next = Pin::new(&mut self.inner).poll_next(cx);
+        let timeout = match self.timeout.as_mut() {
+            Some(timeout) => timeout,
+            None => return next,
+        };
+
+        if next.is_ready() {
+            timeout.timer = None;
+            return next;
+        }
+
+        let duration = timeout.duration;
+        let timer = timeout.timer.get_or_insert_with(|| Box::pin(sleep(duration)));
+        ready!(timer.as_mut().poll(cx));
+        timeout.expired.store(true, Ordering::Release);
+        let error = io::Error::new(io::ErrorKind::TimedOut, "request body read timed out");
+        Poll::Ready(Some(Err(error)))
+    }
+
+    /// A stream of the data in `buf` followed by `self`, decoded according to
+    /// `encoding`.
+    #[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+    pub(crate) fn decoded(self, buf: Vec<u8>, encoding: Encoding) -> Self {
+        let limit = match &self.inner {
+            StreamKind::Decoded(_, limit) => limit.clone(),
+            _ => {
+                let (limit, read) = (AtomicU64::new(u64::MAX), AtomicU64::new(0));
+                Arc::new(EncodedLimit { limit, read })
+            }
+        };
+
+        let chain = Chain::new(Cursor::new(buf), self);
+        let encoded = Encoded { chain, read: 0, limit: limit.clone() };
+        let decoder = Decompressor::new(BufReader::new(encoded), encoding)
+            .into_bytes_stream(DECODED_CHUNK_SIZE);
+
+        let inner = StreamKind::Decoded(Box::new(decoder), limit);
+        Self { inner, state: State::Pending, timeout: None }
     }
 }
 
 impl<'r> From<&'r mut hyper::Body> for StreamReader<'r> {
     fn from(body: &'r mut hyper::Body) -> Self {
-        Self { inner: StreamKind::Body(body), state: State::Pending }
+        Self { inner: StreamKind::Body(body), state: State::Pending, timeout: None }
     }
 }
 
 impl<'r> From<multer::Field<'r>> for StreamReader<'r> {
     fn from(field: multer::Field<'r>) -> Self {
-        Self { inner: StreamKind::Multipart(field), state: State::Pending }
+        Self { inner: StreamKind::Multipart(field), state: State::Pending, timeout: None }
     }
 }
 
@@ -268,6 +386,11 @@ impl Stream for StreamKind<'_> {
                 .map_err_ext(|e| io::Error::new(io::ErrorKind::Other, e)),
             StreamKind::Multipart(mp) => Pin::new(mp).poll_next(cx)
                 .map_err_ext(|e| io::Error::new(io::ErrorKind::Other, e)),
+            #[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+            StreamKind::Decoded(decoder, limit) => match limit.check() {
+                Ok(()) => Pin::new(decoder).poll_next(cx),
+                Err(e) => Poll::Ready(Some(Err(e))),
+            },
             StreamKind::Empty => Poll::Ready(None),
         }
     }
@@ -276,11 +399,28 @@ impl Stream for StreamKind<'_> {
         match self {
             StreamKind::Body(body) => body.size_hint(),
             StreamKind::Multipart(mp) => mp.size_hint(),
+            #[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+            StreamKind::Decoded(..) => (0, None),
             StreamKind::Empty => (0, Some(0)),
         }
     }
 }
 
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+impl AsyncRead for Encoded<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut self.chain).poll_read(cx, buf))?;
+        self.read += (buf.filled().len() - filled) as u64;
+        self.limit.read.fetch_max(self.read, Ordering::AcqRel);
+        Poll::Ready(self.limit.check())
+    }
+}
+
 impl AsyncRead for StreamReader<'_> {
     fn poll_read(
         mut self: Pin<&mut Self>,
@@ -290,8 +430,11 @@ impl AsyncRead for StreamReader<'_> {
         loop {
             self.state = match self.state {
                 State::Pending => {
-                    match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
-                        Some(Err(e)) => return Poll::Ready(Err(e)),
+                    match ready!(self.poll_next_chunk(cx)) {
+                        Some(Err(e)) => {
+                            self.state = State::Failed(e.kind(), e.to_string());
+                            return Poll::Ready(Err(e));
+                        }
                         Some(Ok(bytes)) => State::Partial(Cursor::new(bytes)),
                         None => State::Done,
                     }
@@ -303,6 +446,9 @@ impl AsyncRead for StreamReader<'_> {
                         result => return Poll::Ready(result),
                     }
                 }
+                State::Failed(kind, ref msg) => {
+                    return Poll::Ready(Err(io::Error::new(kind, msg.clone())));
+                }
                 State::Done => return Poll::Ready(Ok(())),
             }
         }
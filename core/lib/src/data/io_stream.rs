@@ -0,0 +1,154 @@
+use std::io;
+use std::task::{Context, Poll};
+use std::pin::Pin;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf, DuplexStream};
+
+use crate::http::hyper::upgrade::Upgraded;
+
+/// A bidirectional, raw stream to the client.
+///
+/// An instance of `IoStream` is passed to an [`IoHandler`] once a connection
+/// has been successfully upgraded. Bytes written to the stream are sent
+/// directly to the client. Bytes read from the stream are those sent directly
+/// _by_ the client. See [`IoHandler`] for one use of an `IoStream`.
+///
+/// The stream is subject to Rocket's graceful [shutdown] like any other
+/// connection: once shutdown is requested and the grace period elapses, the
+/// stream is closed for writing; once the mercy period elapses, all I/O on the
+/// stream fails.
+///
+/// [shutdown]: crate::config::Shutdown
+pub struct IoStream {
+    kind: IoStreamKind,
+}
+
+/// The kinds of raw streams we can hand to an `IoHandler`.
+enum IoStreamKind {
+    Upgraded(Upgraded),
+    Local(DuplexStream),
+}
+
+/// An upgraded connection I/O handler.
+///
+/// An I/O handler performs raw I/O via the passed in [`IoStream`], which is
+/// [`AsyncRead`], [`AsyncWrite`], and `Unpin`. Handlers are registered for a
+/// protocol on a [`Response`](crate::Response) via
+/// [`Response::add_upgrade()`](crate::Response::add_upgrade()) or
+/// [`Builder::upgrade()`](crate::response::Builder::upgrade()). If the
+/// request asked to upgrade the connection to that protocol, Rocket responds
+/// with `101 Switching Protocols` and, once the connection has been upgraded,
+/// calls [`IoHandler::io()`] with the raw connection.
+///
+/// # Example
+///
+/// A responder that echoes every byte it receives back to the client after
+/// upgrading to a fictional `echo` protocol:
+///
+/// ```rust
+/// use std::pin::Pin;
+///
+/// use rocket::tokio::io;
+/// use rocket::data::{IoHandler, IoStream};
+/// use rocket::request::Request;
+/// use rocket::response::{self, Responder, Response};
+///
+/// struct EchoHandler;
+///
+/// #[rocket::async_trait]
+/// impl IoHandler for EchoHandler {
+///     async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+///         let (mut reader, mut writer) = io::split(io);
+///         io::copy(&mut reader, &mut writer).await?;
+///         Ok(())
+///     }
+/// }
+///
+/// impl<'r> Responder<'r, 'static> for EchoHandler {
+///     fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+///         Response::build()
+///             .upgrade("echo", self)
+///             .ok()
+///     }
+/// }
+/// ```
+#[crate::async_trait]
+pub trait IoHandler: Send {
+    /// Performs the raw I/O.
+    ///
+    /// An error returned by this method is logged. Rocket closes the
+    /// connection once this method returns.
+    async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()>;
+}
+
+#[doc(hidden)]
+impl From<Upgraded> for IoStream {
+    fn from(io: Upgraded) -> Self {
+        IoStream { kind: IoStreamKind::Upgraded(io) }
+    }
+}
+
+#[doc(hidden)]
+impl From<DuplexStream> for IoStream {
+    fn from(io: DuplexStream) -> Self {
+        IoStream { kind: IoStreamKind::Local(io) }
+    }
+}
+
+impl AsyncRead for IoStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut().kind {
+            IoStreamKind::Upgraded(ref mut io) => Pin::new(io).poll_read(cx, buf),
+            IoStreamKind::Local(ref mut io) => Pin::new(io).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for IoStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut().kind {
+            IoStreamKind::Upgraded(ref mut io) => Pin::new(io).poll_write(cx, buf),
+            IoStreamKind::Local(ref mut io) => Pin::new(io).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut().kind {
+            IoStreamKind::Upgraded(ref mut io) => Pin::new(io).poll_flush(cx),
+            IoStreamKind::Local(ref mut io) => Pin::new(io).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut().kind {
+            IoStreamKind::Upgraded(ref mut io) => Pin::new(io).poll_shutdown(cx),
+            IoStreamKind::Local(ref mut io) => Pin::new(io).poll_shutdown(cx),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut().kind {
+            IoStreamKind::Upgraded(ref mut io) => Pin::new(io).poll_write_vectored(cx, bufs),
+            IoStreamKind::Local(ref mut io) => Pin::new(io).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self.kind {
+            IoStreamKind::Upgraded(ref io) => io.is_write_vectored(),
+            IoStreamKind::Local(ref io) => io.is_write_vectored(),
+        }
+    }
+}
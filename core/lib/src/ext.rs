@@ -1,6 +1,8 @@
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::{io, time::Duration};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use bytes::{Bytes, BytesMut};
 use pin_project_lite::pin_project;
@@ -9,6 +11,7 @@ use tokio::time::{sleep, Sleep};
 
 use futures::future::{self, Future, FutureExt};
 use futures::stream::Stream;
+use futures::task::AtomicWaker;
 
 use crate::http::bindable::BindableAddr;
 
@@ -277,7 +280,8 @@ impl<F: Future, I: AsyncWrite> AsyncWrite for CancellableIo<F, I> {
     }
 }
 
-use crate::http::private::{Certificates, Connection, Listener};
+use crate::http::private::{Certificates, Connection, Handshake, Listener, PeerCredentials};
+use crate::http::proxy_protocol::ProxyHeader;
 
 impl<F: Future, C: Connection> Connection for CancellableIo<F, C> {
     fn peer_address(&self) -> Option<BindableAddr> {
@@ -288,6 +292,18 @@ impl<F: Future, C: Connection> Connection for CancellableIo<F, C> {
         self.io().and_then(|io| io.peer_certificates())
     }
 
+    fn tls_handshake(&self) -> Option<Handshake> {
+        self.io().and_then(|io| io.tls_handshake())
+    }
+
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        self.io().and_then(|io| io.proxy_header())
+    }
+
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        self.io().and_then(|io| io.peer_credentials())
+    }
+
     fn enable_nodelay(&self) -> io::Result<()> {
         match self.io() {
             Some(io) => io.enable_nodelay(),
@@ -335,6 +351,316 @@ impl<L: Listener, F: Future + Clone> Listener for CancellableListener<F, L> {
     }
 }
 
+/// The requests being served on one connection, shared between the
+/// connection's [`TimeoutIo`] and the tasks serving its requests.
+#[derive(Default)]
+pub struct Activity {
+    started: AtomicUsize,
+    in_flight: AtomicUsize,
+    waker: AtomicWaker,
+}
+
+impl Activity {
+    /// Marks the start of a request, which ends when the guard is dropped.
+    pub fn enter(self: &Arc<Self>) -> ActivityGuard {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        self.started.fetch_add(1, Ordering::AcqRel);
+        ActivityGuard(self.clone())
+    }
+}
+
+/// Marks a request as in-flight until it is dropped.
+pub struct ActivityGuard(Arc<Activity>);
+
+impl Drop for ActivityGuard {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::AcqRel);
+        self.0.waker.wake();
+    }
+}
+
+/// A timer which can be restarted.
+struct Deadline {
+    timer: Pin<Box<Sleep>>,
+    period: Duration,
+}
+
+impl Deadline {
+    fn new(period: Option<Duration>) -> Option<Self> {
+        period.map(|period| Deadline { timer: Box::pin(sleep(period)), period })
+    }
+
+    fn restart(&mut self) {
+        let deadline = tokio::time::Instant::now() + self.period;
+        self.timer.as_mut().reset(deadline);
+    }
+}
+
+enum Phase {
+    /// Waiting for the first byte of a request.
+    Idle(Option<Deadline>),
+    /// Waiting for the rest of a request's head.
+    Head(Option<Deadline>),
+    /// At least one request is being served.
+    Busy,
+    /// The head timed out. Writing a `408` response from the given offset.
+    Rejecting(usize),
+    /// The connection timed out. Reads return EOF.
+    Closed,
+    /// The connection speaks HTTP/2, which we don't time out.
+    Http2,
+}
+
+/// The start of the HTTP/2 connection preface.
+const HTTP2_PREFACE: &[u8] = b"PRI ";
+
+/// Sent when a request's head isn't received in time.
+const REQUEST_TIMEOUT: &[u8] =
+    b"HTTP/1.1 408 Request Timeout\r\nconnection: close\r\ncontent-length: 0\r\n\r\n";
+
+pin_project! {
+    /// HTTP/1 I/O that times out slow request heads and idle connections.
+    ///
+    /// The I/O is idle until the first byte of a request arrives. The head
+    /// must then be received, which is when hyper calls into our service and
+    /// [`Activity::enter()`] is called, within the `header` timeout, or a
+    /// `408` is sent and the connection is closed. Once the last request in
+    /// flight completes, the I/O is idle again and is closed if a new request
+    /// doesn't start within the `idle` timeout. A new connection must start
+    /// its first request within the `header` timeout.
+    pub struct TimeoutIo<I> {
+        #[pin]
+        io: I,
+        activity: Arc<Activity>,
+        phase: Phase,
+        header: Option<Duration>,
+        idle: Option<Duration>,
+        // The value of `activity.started` as of the last update.
+        started: usize,
+        // Whether any data was written since the current head began.
+        written: bool,
+        // The length of the HTTP/2 preface matched so far, while it may be.
+        preface: Option<usize>,
+    }
+}
+
+impl<I: AsyncRead + AsyncWrite> TimeoutIo<I> {
+    pub fn new(io: I, header: Option<Duration>, idle: Option<Duration>) -> Self {
+        TimeoutIo {
+            io,
+            activity: Arc::default(),
+            phase: Phase::Idle(Deadline::new(header)),
+            header,
+            idle,
+            started: 0,
+            written: false,
+            preface: Some(0),
+        }
+    }
+
+    /// The requests being served on this connection.
+    pub fn activity(&self) -> &Arc<Activity> {
+        &self.activity
+    }
+}
+
+impl<I: AsyncRead + AsyncWrite> AsyncRead for TimeoutIo<I> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let mut me = self.project();
+
+        // Move between idle and busy as requests start and complete.
+        if let Phase::Idle(_) | Phase::Head(_) | Phase::Busy = me.phase {
+            me.activity.waker.register(cx.waker());
+            let in_flight = me.activity.in_flight.load(Ordering::Acquire);
+            let started = me.activity.started.load(Ordering::Acquire);
+            if in_flight > 0 {
+                *me.phase = Phase::Busy;
+                *me.started = started;
+                *me.preface = None;
+            } else if started != *me.started || matches!(me.phase, Phase::Busy) {
+                *me.phase = Phase::Idle(Deadline::new(*me.idle));
+                *me.started = started;
+                *me.preface = None;
+            }
+        }
+
+        loop {
+            match me.phase {
+                Phase::Busy | Phase::Http2 => return me.io.poll_read(cx, buf),
+                Phase::Closed => return Poll::Ready(Ok(())),
+                Phase::Rejecting(offset) => {
+                    while *offset < REQUEST_TIMEOUT.len() {
+                        let rest = &REQUEST_TIMEOUT[*offset..];
+                        match futures::ready!(me.io.as_mut().poll_write(cx, rest)) {
+                            Ok(0) | Err(_) => break,
+                            Ok(n) => *offset += n,
+                        }
+                    }
+
+                    let _ = futures::ready!(me.io.as_mut().poll_flush(cx));
+                    *me.phase = Phase::Closed;
+                }
+                Phase::Idle(deadline) | Phase::Head(deadline) => {
+                    let filled = buf.filled().len();
+                    if let Poll::Ready(result) = me.io.as_mut().poll_read(cx, buf) {
+                        let received = &buf.filled()[filled..];
+                        if let (Some(matched), false) = (me.preface.as_mut(), received.is_empty()) {
+                            let rest = &HTTP2_PREFACE[*matched..];
+                            let n = received.len().min(rest.len());
+                            if received[..n] != rest[..n] {
+                                *me.preface = None;
+                            } else if *matched + n == HTTP2_PREFACE.len() {
+                                *me.preface = None;
+                                *me.phase = Phase::Http2;
+                                return Poll::Ready(result);
+                            } else {
+                                *matched += n;
+                            }
+                        }
+
+                        if matches!(me.phase, Phase::Idle(_)) && !received.is_empty() {
+                            *me.phase = Phase::Head(Deadline::new(*me.header));
+                            *me.written = false;
+                        }
+
+                        return Poll::Ready(result);
+                    }
+
+                    let expired = match deadline {
+                        Some(deadline) => deadline.timer.as_mut().poll(cx).is_ready(),
+                        None => false,
+                    };
+
+                    if !expired {
+                        return Poll::Pending;
+                    }
+
+                    *me.phase = match me.phase {
+                        Phase::Head(_) if !*me.written => Phase::Rejecting(0),
+                        _ => Phase::Closed,
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl<I: AsyncWrite> AsyncWrite for TimeoutIo<I> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let me = self.project();
+        let result = futures::ready!(me.io.poll_write(cx, buf));
+        if let Ok(n) = result {
+            written(me.phase, me.written, n);
+        }
+
+        Poll::Ready(result)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().io.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().io.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let me = self.project();
+        let result = futures::ready!(me.io.poll_write_vectored(cx, bufs));
+        if let Ok(n) = result {
+            written(me.phase, me.written, n);
+        }
+
+        Poll::Ready(result)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+}
+
+/// Records that `n` bytes were written. A connection that is still writing a
+/// response isn't idle, so this restarts an idle deadline.
+fn written(phase: &mut Phase, written: &mut bool, n: usize) {
+    if n > 0 {
+        *written = true;
+        if let Phase::Idle(Some(deadline)) = phase {
+            deadline.restart();
+        }
+    }
+}
+
+impl<C: Connection> Connection for TimeoutIo<C> {
+    fn peer_address(&self) -> Option<BindableAddr> {
+        self.io.peer_address()
+    }
+
+    fn peer_certificates(&self) -> Option<Certificates> {
+        self.io.peer_certificates()
+    }
+
+    fn tls_handshake(&self) -> Option<Handshake> {
+        self.io.tls_handshake()
+    }
+
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        self.io.proxy_header()
+    }
+
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        self.io.peer_credentials()
+    }
+
+    fn enable_nodelay(&self) -> io::Result<()> {
+        self.io.enable_nodelay()
+    }
+}
+
+pin_project! {
+    /// A listener whose connections are [`TimeoutIo`]s.
+    pub struct TimeoutListener<L> {
+        #[pin]
+        listener: L,
+        header: Option<Duration>,
+        idle: Option<Duration>,
+    }
+}
+
+impl<L> TimeoutListener<L> {
+    pub fn new(listener: L, header: Option<Duration>, idle: Option<Duration>) -> Self {
+        TimeoutListener { listener, header, idle }
+    }
+}
+
+impl<L: Listener> Listener for TimeoutListener<L> {
+    type Connection = TimeoutIo<L::Connection>;
+
+    fn local_addr(&self) -> Option<BindableAddr> {
+        self.listener.local_addr()
+    }
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<Self::Connection>> {
+        let me = self.project();
+        me.listener.poll_accept(cx)
+            .map(|res| res.map(|conn| TimeoutIo::new(conn, *me.header, *me.idle)))
+    }
+}
+
 pub trait StreamExt: Sized + Stream {
     fn join<U>(self, other: U) -> Join<Self, U>
     where
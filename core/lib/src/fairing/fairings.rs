@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use crate::{Rocket, Request, Response, Data, Build, Orbit};
 use crate::fairing::{Fairing, Info, Kind};
 use crate::log::PaintExt;
+use crate::tracing;
 
 use yansi::Paint;
 
@@ -146,14 +147,16 @@ impl Fairings {
     #[inline(always)]
     pub async fn handle_request(&self, req: &mut Request<'_>, data: &mut Data<'_>) {
         for fairing in iter!(self.request) {
-            fairing.on_request(req, data).await
+            let span = tracing::fairing("request", fairing.info().name);
+            tracing::instrument(fairing.on_request(req, data), span).await
         }
     }
 
     #[inline(always)]
     pub async fn handle_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
         for fairing in iter!(self.response) {
-            fairing.on_response(req, res).await;
+            let span = tracing::fairing("response", fairing.info().name);
+            tracing::instrument(fairing.on_response(req, res), span).await;
         }
     }
 
@@ -1,11 +1,12 @@
 use std::io;
 use std::path::{Path, PathBuf};
 use std::ops::{Deref, DerefMut};
+use std::time::UNIX_EPOCH;
 
 use tokio::fs::File;
 
 use crate::request::Request;
-use crate::response::{self, Responder};
+use crate::response::{self, Responder, Response, apply_conditions, apply_ranges, format_date};
 use crate::http::ContentType;
 
 /// A [`Responder`] that sends file data with a Content-Type based on its
@@ -146,19 +147,70 @@ impl NamedFile {
 /// recognized. See [`ContentType::from_extension()`] for more information. If
 /// you would like to stream a file with a different Content-Type than that
 /// implied by its extension, use a [`File`] directly.
+///
+/// The response's `Last-Modified` and `ETag` are set from the file's metadata,
+/// and conditional requests are evaluated against them as described in
+/// [`Conditional`](crate::response::Conditional). `Range` requests are then
+/// honored as described in [`Ranged`](crate::response::Ranged): parts of the
+/// file, relative to its current position, are sent as requested.
 impl<'r> Responder<'r, 'static> for NamedFile {
     fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
-        let mut response = self.1.respond_to(req)?;
-        if let Some(ext) = self.0.extension() {
+        let NamedFile(path, file) = self;
+
+        // Size the file now, synchronously, so that ranges can be applied. If
+        // an operation on the file is in-flight, the file is sized later and
+        // no validators are set.
+        let mut response = Response::new();
+        match file.try_into_std() {
+            Ok(mut file) => {
+                if let Ok(metadata) = file.metadata() {
+                    set_validators(&mut response, &metadata);
+                }
+
+                let size = remaining_len(&mut file).ok();
+                response.set_sized_body(size, File::from_std(file));
+            }
+            Err(file) => response.set_sized_body(None, file),
+        }
+
+        if let Some(ext) = path.extension() {
             if let Some(ct) = ContentType::from_extension(&ext.to_string_lossy()) {
                 response.set_header(ct);
             }
         }
 
-        Ok(response)
+        let response = apply_conditions(req, response)?;
+        Ok(apply_ranges(req, response))
+    }
+}
+
+/// Sets the `Last-Modified` and `ETag` headers in `response` from `metadata`.
+/// The entity-tag is derived from the file's modification time and length.
+fn set_validators(response: &mut Response<'_>, metadata: &std::fs::Metadata) {
+    // The latest time that can be represented as an HTTP-date.
+    const MAX_SECS: u64 = 253402300799;
+
+    let modified = metadata.modified().ok()
+        .and_then(|time| Some((time, time.duration_since(UNIX_EPOCH).ok()?)))
+        .filter(|(_, since_epoch)| since_epoch.as_secs() <= MAX_SECS);
+
+    if let Some((time, since_epoch)) = modified {
+        let (secs, nanos) = (since_epoch.as_secs(), since_epoch.subsec_nanos());
+        let etag = format!("\"{:x}.{:x}-{:x}\"", secs, nanos, metadata.len());
+        response.set_raw_header("ETag", etag);
+        response.set_raw_header("Last-Modified", format_date(time));
     }
 }
 
+/// The number of bytes from `file`'s current position to its end.
+fn remaining_len(file: &mut std::fs::File) -> io::Result<usize> {
+    use std::io::Seek;
+
+    let len = file.metadata()?.len();
+    let pos = file.stream_position()?;
+    Ok(len.saturating_sub(pos) as usize)
+}
+
 impl Deref for NamedFile {
     type Target = File;
 
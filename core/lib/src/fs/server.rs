@@ -17,6 +17,12 @@ use crate::fs::NamedFile;
 /// routes is `10`. To customize route ranking, use the [`FileServer::rank()`]
 /// method.
 ///
+/// Files are served via [`NamedFile`], so responses carry `ETag` and
+/// `Last-Modified` validators, conditional requests are answered as described
+/// in [`Conditional`](crate::response::Conditional), and `Range` requests are
+/// honored as described in [`Ranged`](crate::response::Ranged). A failed
+/// precondition results in a `412 Precondition Failed` error, not a forward.
+///
 /// # Options
 ///
 /// The handler's functionality can be customized by passing an [`Options`] to
@@ -205,7 +211,7 @@ impl Handler for FileServer {
 
             if segments.is_empty() {
                 let file = NamedFile::open(&self.root).await.ok();
-                return Outcome::from_or_forward(req, data, file);
+                return respond_with(req, data, file);
             } else {
                 return Outcome::forward(data);
             }
@@ -233,14 +239,23 @@ impl Handler for FileServer {
                 }
 
                 let index = NamedFile::open(p.join("index.html")).await.ok();
-                Outcome::from_or_forward(req, data, index)
+                respond_with(req, data, index)
             },
-            Some(p) => Outcome::from_or_forward(req, data, NamedFile::open(p).await.ok()),
+            Some(p) => respond_with(req, data, NamedFile::open(p).await.ok()),
             None => Outcome::forward(data),
         }
     }
 }
 
+/// Responds with `file` if it could be opened and forwards otherwise. Unlike
+/// with [`Outcome::from_or_forward()`], a failed precondition is not a forward.
+fn respond_with<'r>(req: &'r Request<'_>, data: Data<'r>, file: Option<NamedFile>) -> Outcome<'r> {
+    match file {
+        Some(file) => Outcome::from(req, file),
+        None => Outcome::forward(data),
+    }
+}
+
 /// A bitset representing configurable options for [`FileServer`].
 ///
 /// The valid options are:
@@ -0,0 +1,240 @@
+//! Lifecycle state, health endpoints, and readiness checks.
+//!
+//! A launched instance of Rocket is in one of three [`Lifecycle`] states,
+//! available via [`Rocket::lifecycle()`]: it is [`Starting`] while liftoff
+//! fairings run, [`Ready`] once they have completed, and [`Draining`] as soon
+//! as [`Shutdown`](crate::Shutdown) is requested, through the end of the
+//! graceful shutdown period. Requests continue to be served while draining, so
+//! a load balancer needs to be told to stop routing new ones.
+//!
+//! The opt-in [`Health`] fairing does just that. It mounts two routes:
+//!
+//!   * `GET /health/live` always responds `200 OK` while Rocket is serving
+//!     requests, draining or not.
+//!
+//!   * `GET /health/ready` responds `200 OK` when Rocket is [`Ready`] and all
+//!     readiness checks pass and `503 Service Unavailable` otherwise. The body
+//!     of a `503` response describes why Rocket isn't ready.
+//!
+//! # Readiness Checks
+//!
+//! Anything that Rocket depends on to serve requests, such as a database pool,
+//! can contribute a readiness check with [`Rocket::readiness_check()`], usually
+//! from an ignite fairing. A check is a function or closure from
+//! `&Rocket<Orbit>` to a boxed future resolving to `Ok(())` when ready and to an
+//! `Err` with a short reason otherwise. Checks are run concurrently on each
+//! request to `/health/ready`, and only then, and only when Rocket is `Ready`.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! use std::sync::atomic::{AtomicBool, Ordering};
+//!
+//! use rocket::fairing::AdHoc;
+//! use rocket::health::Health;
+//!
+//! struct Database { connected: AtomicBool }
+//!
+//! #[launch]
+//! fn rocket() -> _ {
+//!     rocket::build()
+//!         .manage(Database { connected: AtomicBool::new(true) })
+//!         .attach(Health::default())
+//!         .attach(AdHoc::on_ignite("Database Check", |rocket| async move {
+//!             rocket.readiness_check("database", |rocket| Box::pin(async move {
+//!                 let db = rocket.state::<Database>().unwrap();
+//!                 match db.connected.load(Ordering::Acquire) {
+//!                     true => Ok(()),
+//!                     false => Err("not connected".into()),
+//!                 }
+//!             }))
+//!         }))
+//! }
+//! ```
+//!
+//! [`Starting`]: Lifecycle::Starting
+//! [`Ready`]: Lifecycle::Ready
+//! [`Draining`]: Lifecycle::Draining
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use futures::future::{join_all, BoxFuture};
+
+use crate::{Rocket, Request, Response, Data, Route, Build, Orbit};
+use crate::fairing::{self, Fairing, Info, Kind};
+use crate::http::{ContentType, Method, Status};
+use crate::route::{Handler, Outcome};
+
+/// The lifecycle state of a launched instance of Rocket.
+///
+/// See the [module level docs](self) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lifecycle {
+    /// Liftoff fairings are running; requests are not yet being served.
+    Starting,
+    /// Liftoff has completed and shutdown has not been requested.
+    Ready,
+    /// Shutdown has been requested. Pending requests, and any arriving during
+    /// the grace period, are still served.
+    Draining,
+}
+
+impl fmt::Display for Lifecycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lifecycle::Starting => f.write_str("starting"),
+            Lifecycle::Ready => f.write_str("ready"),
+            Lifecycle::Draining => f.write_str("draining"),
+        }
+    }
+}
+
+/// A readiness check, registered with [`Rocket::readiness_check()`].
+pub(crate) type Check = dyn for<'a> Fn(&'a Rocket<Orbit>) -> BoxFuture<'a, Result<(), String>>
+    + Send + Sync + 'static;
+
+/// The registered readiness checks.
+#[derive(Clone, Default)]
+pub(crate) struct Checks(Vec<(Cow<'static, str>, Arc<Check>)>);
+
+impl Checks {
+    pub(crate) fn add(&mut self, name: Cow<'static, str>, check: Arc<Check>) {
+        self.0.push((name, check));
+    }
+
+    /// Runs every check, returning the name and reason of those that fail.
+    async fn failures(&self, rocket: &Rocket<Orbit>) -> Vec<(&str, String)> {
+        let checks = self.0.iter().map(|(name, check)| async move {
+            check(rocket).await.err().map(|reason| (&**name, reason))
+        });
+
+        join_all(checks).await.into_iter().flatten().collect()
+    }
+}
+
+impl fmt::Debug for Checks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.iter().map(|(name, _)| name)).finish()
+    }
+}
+
+/// A fairing that mounts liveness and readiness endpoints.
+///
+/// The endpoints are mounted at `/health` by default; use [`Health::at()`] to
+/// mount them elsewhere. See the [module level docs](self) for details.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::health::Health;
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     // Serves `/status/live` and `/status/ready`.
+///     rocket::build().attach(Health::at("/status"))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Health {
+    base: Cow<'static, str>,
+}
+
+impl Health {
+    /// Returns a `Health` fairing which mounts its endpoints at `base`.
+    ///
+    /// # Panics
+    ///
+    /// Panics at ignition if `base` isn't a valid origin URI.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::health::Health;
+    ///
+    /// let health = Health::at("/_internal/health");
+    /// ```
+    pub fn at<B: Into<Cow<'static, str>>>(base: B) -> Self {
+        Health { base: base.into() }
+    }
+}
+
+impl Default for Health {
+    /// Returns a `Health` fairing which mounts its endpoints at `/health`.
+    fn default() -> Self {
+        Health::at("/health")
+    }
+}
+
+#[crate::async_trait]
+impl Fairing for Health {
+    fn info(&self) -> Info {
+        Info {
+            name: "Health",
+            kind: Kind::Ignite | Kind::Singleton,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let routes = [(Probe::Live, "/live"), (Probe::Ready, "/ready")]
+            .into_iter()
+            .map(|(probe, path)| {
+                let mut route = Route::new(Method::Get, path, probe);
+                route.name = Some(format!("Health: {}", path.trim_start_matches('/')).into());
+                route
+            })
+            .collect::<Vec<_>>();
+
+        Ok(rocket.mount(&*self.base, routes))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Probe {
+    Live,
+    Ready,
+}
+
+impl Probe {
+    async fn probe(self, rocket: &Rocket<Orbit>) -> (Status, String) {
+        if let Probe::Live = self {
+            return (Status::Ok, "live".into());
+        }
+
+        match rocket.lifecycle() {
+            Lifecycle::Ready => {},
+            state => return (Status::ServiceUnavailable, state.to_string()),
+        }
+
+        let failures = rocket.checks.failures(rocket).await;
+        if failures.is_empty() {
+            return (Status::Ok, Lifecycle::Ready.to_string());
+        }
+
+        let mut body = String::from("not ready");
+        for (name, reason) in failures {
+            body.push_str(&format!("\n{}: {}", name, reason));
+        }
+
+        (Status::ServiceUnavailable, body)
+    }
+}
+
+#[crate::async_trait]
+impl Handler for Probe {
+    async fn handle<'r>(&self, req: &'r Request<'_>, _: Data<'r>) -> Outcome<'r> {
+        let (status, body) = self.probe(req.rocket()).await;
+        let response = Response::build()
+            .status(status)
+            .header(ContentType::Plain)
+            .raw_header("Cache-Control", "no-store")
+            .sized_body(body.len(), Cursor::new(body))
+            .finalize();
+
+        Outcome::Success(response)
+    }
+}
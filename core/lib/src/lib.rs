@@ -68,6 +68,12 @@
 //! | `json`    | Support for [JSON (de)serialization].                   |
 //! | `msgpack` | Support for [MessagePack (de)serialization].            |
 //! | `uuid`    | Support for [UUID value parsing and (de)serialization]. |
+//! | `ws`      | Support for [WebSockets].                               |
+//! | `gzip`    | Support for `gzip` [(de)compression].                   |
+//! | `deflate` | Support for `deflate` [(de)compression].                |
+//! | `brotli`  | Support for `br` (Brotli) [(de)compression].            |
+//! | `zstd`    | Support for `zstd` (Zstandard) [(de)compression].       |
+//! | `tracing` | Support for [`tracing`] spans and subscriber setup.     |
 //!
 //! Disabled features can be selectively enabled in `Cargo.toml`:
 //!
@@ -86,9 +92,12 @@
 //! [JSON (de)serialization]: crate::serde::json
 //! [MessagePack (de)serialization]: crate::serde::msgpack
 //! [UUID value parsing and (de)serialization]: crate::serde::uuid
+//! [`tracing`]: crate::config::Tracing
 //! [private cookies]: https://rocket.rs/v0.5-rc/guide/requests/#private-cookies
 //! [TLS]: https://rocket.rs/v0.5-rc/guide/configuration/#tls
 //! [mutual TLS]: crate::mtls
+//! [WebSockets]: crate::ws
+//! [(de)compression]: crate::compression
 //!
 //! ## Configuration
 //!
@@ -132,7 +141,10 @@ pub mod catcher;
 pub mod route;
 pub mod serde;
 pub mod shield;
+pub mod health;
+pub mod access_log;
 pub mod fs;
+pub mod listener;
 
 // Reexport of HTTP everything.
 pub mod http {
@@ -164,11 +176,22 @@ pub mod http {
 #[cfg_attr(nightly, doc(cfg(feature = "mtls")))]
 pub mod mtls;
 
+#[cfg(feature = "ws")]
+#[cfg_attr(nightly, doc(cfg(feature = "ws")))]
+pub mod ws;
+
+#[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+#[cfg_attr(nightly, doc(cfg(any(
+    feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"
+))))]
+pub mod compression;
+
 /// TODO: We need a futures mod or something.
 mod trip_wire;
 mod shutdown;
 mod server;
 mod ext;
+mod tracing;
 mod state;
 mod cookies;
 mod rocket;
@@ -0,0 +1,54 @@
+//! Listeners that accept connections for Rocket to serve requests on.
+//!
+//! [`Rocket::launch()`](crate::Rocket::launch()) serves requests on the
+//! addresses in the application's configuration. To serve on any other
+//! transport, such as in-process pipes, tunnels, or sockets prepared ahead of
+//! time, implement [`Listener`] and [`Connection`] and pass the listener to
+//! [`Rocket::launch_on()`](crate::Rocket::launch_on()).
+//!
+//! # Example
+//!
+//! A listener that yields connections established elsewhere, for instance
+//! dialed out to a relay, and sent over a channel:
+//!
+//! ```rust,no_run
+//! use std::io;
+//! use std::pin::Pin;
+//! use std::task::{Context, Poll};
+//!
+//! use rocket::listener::Listener;
+//! use rocket::http::bindable::BindableAddr;
+//! use rocket::tokio::net::TcpStream;
+//! use rocket::tokio::sync::mpsc;
+//!
+//! struct Relayed(mpsc::Receiver<TcpStream>);
+//!
+//! impl Listener for Relayed {
+//!     type Connection = TcpStream;
+//!
+//!     fn local_addr(&self) -> Option<BindableAddr> {
+//!         None
+//!     }
+//!
+//!     fn poll_accept(
+//!         mut self: Pin<&mut Self>,
+//!         cx: &mut Context<'_>
+//!     ) -> Poll<io::Result<TcpStream>> {
+//!         // Once the channel is closed, no more connections will arrive.
+//!         self.0.poll_recv(cx).map(|conn| conn.ok_or_else(|| io::ErrorKind::BrokenPipe.into()))
+//!     }
+//! }
+//!
+//! #[rocket::main]
+//! async fn main() -> Result<(), rocket::Error> {
+//!     let (tx, rx) = mpsc::channel(16);
+//!     # drop(tx);
+//!     // ... hand `tx` to the task that establishes connections ...
+//!
+//!     let _rocket = rocket::build().launch_on(Relayed(rx)).await?;
+//!     Ok(())
+//! }
+//! ```
+
+#[doc(inline)]
+pub use crate::http::private::{Connection, Listener, TcpListener};
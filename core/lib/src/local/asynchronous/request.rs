@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{Request, Data};
+use crate::{tracing, Request, Data};
 use crate::http::{Status, Method};
 use crate::http::uri::Origin;
 
@@ -75,7 +75,7 @@ impl<'c> LocalRequest<'c> {
     }
 
     // Performs the actual dispatch.
-    async fn _dispatch(mut self) -> LocalResponse<'c> {
+    async fn _dispatch(self) -> LocalResponse<'c> {
         // First, revalidate the URI, returning an error response (generated
         // from an error catcher) immediately if it's invalid. If it's valid,
         // then `request` already contains a correct URI.
@@ -92,11 +92,16 @@ impl<'c> LocalRequest<'c> {
         }
 
         // Actually dispatch the request.
-        let mut data = Data::local(self.data);
-        let token = rocket.preprocess_request(&mut self.request, &mut data).await;
-        let response = LocalResponse::new(self.request, move |req| {
-            rocket.dispatch(token, req, data)
-        }).await;
+        let span = tracing::request(None, &self.request.method(), self.request.uri());
+        let (timer, request_span) = (tracing::Timer::start(), span.clone());
+        tracing::record_request_id(&span, self.request.request_id());
+        let (mut request, mut data) = (self.request, Data::local(self.data));
+        let response = tracing::instrument(async move {
+            let token = rocket.preprocess_request(&mut request, &mut data).await;
+            LocalResponse::new(request, move |req| rocket.dispatch(token, req, data)).await
+        }, request_span).await;
+
+        tracing::record_response(&span, response.status(), timer);
 
         // If the client is tracking cookies, updates the internal cookie jar
         // with the changes reflected by `response`.
@@ -2,9 +2,10 @@ use std::io;
 use std::future::Future;
 use std::{pin::Pin, task::{Context, Poll}};
 
-use tokio::io::{AsyncRead, ReadBuf};
+use tokio::io::{AsyncRead, ReadBuf, DuplexStream};
 
 use crate::http::CookieJar;
+use crate::response::Upgrade;
 use crate::{Request, Response};
 
 /// An `async` response from a dispatched [`LocalRequest`](super::LocalRequest).
@@ -56,6 +57,7 @@ pub struct LocalResponse<'c> {
     _request: Box<Request<'c>>,
     response: Response<'c>,
     cookies: CookieJar<'c>,
+    upgrade: Option<Upgrade<'c>>,
 }
 
 impl<'c> LocalResponse<'c> {
@@ -87,13 +89,14 @@ impl<'c> LocalResponse<'c> {
         let request: &'c Request<'c> = unsafe { &*(&*boxed_req as *const _) };
 
         async move {
-            let response: Response<'c> = f(request).await;
+            let mut response: Response<'c> = f(request).await;
+            let upgrade = request.rocket().prepare_upgrade(request, &mut response);
             let mut cookies = CookieJar::new(request.rocket().config());
             for cookie in response.cookies() {
                 cookies.add_original(cookie.into_owned());
             }
 
-            LocalResponse { cookies, _request: boxed_req, response, }
+            LocalResponse { cookies, _request: boxed_req, response, upgrade }
         }
     }
 }
@@ -185,6 +188,29 @@ impl LocalResponse<'_> {
         reader.await.ok()
     }
 
+    /// Runs the I/O handler for the upgraded connection, if any, against an
+    /// in-memory connection whose client end is passed to `client`.
+    ///
+    /// If the request named a protocol in its `Upgrade` header for which the
+    /// response registered an I/O handler, the response's status is `101
+    /// Switching Protocols` and this method drives that handler and the future
+    /// returned by `client` to completion, concurrently, returning the latter's
+    /// output. Dropping the client's end of the connection closes it for the
+    /// handler and vice-versa. If the connection was not upgraded, returns
+    /// `None` without calling `client`.
+    ///
+    /// For an example, see the [`ws`](crate::ws) module's testing
+    /// documentation, available when the `ws` feature is enabled.
+    pub async fn upgrade<F, Fut>(mut self, client: F) -> Option<Fut::Output>
+        where F: FnOnce(DuplexStream) -> Fut, Fut: Future
+    {
+        let (proto, handler) = self.upgrade.take()?;
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let server = crate::server::run_io_handler(&proto, handler, server_io.into());
+        let (_, output) = futures::future::join(server, client(client_io)).await;
+        Some(output)
+    }
+
     // Generates the public API methods, which call the private methods above.
     pub_response_impl!("# use rocket::local::asynchronous::Client;\n\
         use rocket::local::asynchronous::LocalResponse;" async await);
@@ -10,6 +10,10 @@ use yansi::Paint;
 /// Reexport the `log` crate as `private`.
 pub use log as private;
 
+// Instruments request and data guards in a span; used by codegen.
+#[doc(hidden)]
+pub use crate::tracing::guard as trace_guard;
+
 // Expose logging macros (hidden) for use by core/contrib codegen.
 macro_rules! define_log_macro {
     ($name:ident: $kind:ident, $target:expr, $d:tt) => (
@@ -100,6 +104,11 @@ impl log::Log for RocketLogger {
             return;
         }
 
+        // With a tracing subscriber configured, it prints (or filters) instead.
+        if crate::tracing::forward(record) {
+            return;
+        }
+
         // Don't print Hyper, Rustls or r2d2 messages unless debug is enabled.
         let max = log::max_level();
         let from = |path| record.module_path().map_or(false, |m| m.starts_with(path));
@@ -180,6 +189,16 @@ pub(crate) fn init(config: &crate::Config) {
 
         log::set_max_level(config.log_level.into());
     }
+
+    // Install the tracing subscriber, if configured. A filter may enable more
+    // than the log level, so let it, not `log`, decide what's filtered.
+    #[cfg(feature = "tracing")]
+    if let Some(tracing) = &config.tracing {
+        crate::tracing::init(config, tracing);
+        if ROCKET_LOGGER_SET.load(Ordering::Acquire) && tracing.filter.is_some() {
+            log::set_max_level(log::LevelFilter::Trace);
+        }
+    }
 }
 
 impl From<LogLevel> for log::LevelFilter {
@@ -4,11 +4,16 @@
 //! [`MutualTls`](crate::config::MutualTls) and the [TLS
 //! guide](https://rocket.rs/v0.5-rc/guide/configuration/#tls). See
 //! [`Certificate`] for a request guard that validated, verifies, and retrieves
-//! client certificates.
+//! client certificates and [`Identity`] for one that identifies clients by
+//! them.
 
 #[doc(inline)]
 pub use crate::http::tls::mtls::*;
 
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::config::IdentityAttribute;
 use crate::request::{Request, FromRequest, Outcome};
 use crate::outcome::{try_outcome, IntoOutcome};
 use crate::http::Status;
@@ -23,3 +28,146 @@ impl<'r> FromRequest<'r> for Certificate<'r> {
         Certificate::parse(data).into_outcome(Status::Unauthorized)
     }
 }
+
+/// A request guard that identifies a client by its validated and verified
+/// client certificate.
+///
+/// The identity is the value of the first of the listener's configured
+/// [`MutualTls`](crate::config::MutualTls) `identity` attributes, by default
+/// only `common_name`, that the certificate has. See [`IdentityAttribute`] for
+/// how each attribute's value is formatted.
+///
+/// Like [`Certificate`], this guard forwards if the client did not present a
+/// certificate and fails with a status of `401 Unauthorized` if the certificate
+/// is invalid. It also fails, with [`Error::NoIdentity`], if the certificate
+/// has none of the identifying attributes.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # use rocket::get;
+/// use rocket::mtls::Identity;
+///
+/// #[get("/whoami")]
+/// fn whoami(client: Identity<'_>) -> String {
+///     format!("{} ({})", client, client.attribute())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Identity<'r> {
+    attribute: IdentityAttribute,
+    value: Cow<'r, str>,
+    certificate: Certificate<'r>,
+}
+
+impl<'r> Identity<'r> {
+    /// Identifies the holder of `certificate` by the first of `attributes`
+    /// that it has.
+    fn new(certificate: Certificate<'r>, attributes: &[IdentityAttribute]) -> Result<Self> {
+        let value = |attribute: &IdentityAttribute| -> Option<Cow<'r, str>> {
+            let cert = &certificate;
+            match attribute {
+                IdentityAttribute::Spiffe => cert.spiffe_id().map(Cow::Borrowed),
+                IdentityAttribute::Uri => cert.uris().next().map(Cow::Borrowed),
+                IdentityAttribute::Dns => cert.dns_names().next().map(Cow::Borrowed),
+                IdentityAttribute::Ip => cert.ip_addrs().next().map(|ip| ip.to_string().into()),
+                IdentityAttribute::Email => cert.alt_emails().next()
+                    .or_else(|| cert.subject().email())
+                    .map(Cow::Borrowed),
+                IdentityAttribute::CommonName => cert.subject().common_name().map(Cow::Borrowed),
+                IdentityAttribute::Serial => Some(cert.serial().to_string().into()),
+                IdentityAttribute::Fingerprint => {
+                    let hex = cert.fingerprint().iter().map(|b| format!("{:02x}", b)).collect();
+                    Some(Cow::Owned(hex))
+                }
+            }
+        };
+
+        let (attribute, value) = attributes.iter()
+            .find_map(|attr| Some((*attr, value(attr)?)))
+            .ok_or(Error::NoIdentity)?;
+
+        Ok(Identity { attribute, value, certificate })
+    }
+
+    /// Returns the attribute that identified the client.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::config::IdentityAttribute;
+    /// use rocket::mtls::Identity;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(client: Identity<'_>) {
+    ///     if client.attribute() == IdentityAttribute::Spiffe {
+    ///         println!("Hello, workload!");
+    ///     }
+    /// }
+    /// ```
+    pub fn attribute(&self) -> IdentityAttribute {
+        self.attribute
+    }
+
+    /// Returns the identity: the value of [`attribute()`](Self::attribute()).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Identity;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(client: Identity<'_>) {
+    ///     println!("Hello, {}!", client.as_str());
+    /// }
+    /// ```
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the client certificate the identity was found in.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Identity;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(client: Identity<'_>) {
+    ///     let expires = client.certificate().not_after();
+    /// }
+    /// ```
+    pub fn certificate(&self) -> &Certificate<'r> {
+        &self.certificate
+    }
+}
+
+impl fmt::Display for Identity<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[crate::async_trait]
+impl<'r> FromRequest<'r> for Identity<'r> {
+    type Error = Error;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let cert = try_outcome!(req.guard::<Certificate<'r>>().await);
+        let attributes = match &req.connection.identity {
+            Some(attributes) => &attributes[..],
+            None => req.rocket().config().tls.as_ref()
+                .and_then(|tls| tls.mutual())
+                .map_or(&[IdentityAttribute::CommonName][..], |mtls| mtls.identity()),
+        };
+
+        Identity::new(cert, attributes).into_outcome(Status::Unauthorized)
+    }
+}
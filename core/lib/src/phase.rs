@@ -1,9 +1,14 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+
 use state::Container;
 use figment::Figment;
 
 use crate::{Catcher, Config, Rocket, Route, Shutdown};
 use crate::router::Router;
 use crate::fairing::Fairings;
+use crate::health::Checks;
+use crate::http::private::Connections;
 
 mod private {
     pub trait Sealed {  }
@@ -84,6 +89,7 @@ phases! {
         pub(crate) fairings: Fairings,
         pub(crate) figment: Figment,
         pub(crate) state: Container![Send + Sync],
+        pub(crate) checks: Checks,
     }
 
     /// The second launch [`Phase`]: post-build but pre-orbit. See
@@ -99,6 +105,7 @@ phases! {
         pub(crate) config: Config,
         pub(crate) state: Container![Send + Sync],
         pub(crate) shutdown: Shutdown,
+        pub(crate) checks: Checks,
     }
 
     /// The final launch [`Phase`]. See [Rocket#orbit](`Rocket#orbit`) for
@@ -113,5 +120,11 @@ phases! {
         pub(crate) config: Config,
         pub(crate) state: Container![Send + Sync],
         pub(crate) shutdown: Shutdown,
+        pub(crate) checks: Checks,
+        pub(crate) lifted_off: AtomicBool,
+        pub(crate) connections: Arc<Connections>,
+        pub(crate) requests: Arc<AtomicUsize>,
+        #[cfg(feature = "tls")]
+        pub(crate) tls_reloaders: Vec<crate::server::TlsReloader>,
     }
 }
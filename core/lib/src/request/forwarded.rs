@@ -0,0 +1,217 @@
+//! Parsing of the `Forwarded` and `X-Forwarded-*` headers set by proxies and
+//! resolution of the client through a chain of trusted proxies.
+
+use std::net::{IpAddr, SocketAddr};
+
+use crate::http::HeaderMap;
+use crate::http::proxy_protocol::{canonical_ip, IpNet};
+
+/// One hop in a chain of proxies, as recorded by the proxy that received it.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Hop<'a> {
+    /// The address of the peer the proxy received the request from, if known.
+    pub(crate) client: Option<IpAddr>,
+    /// The protocol the proxy received the request over, if known.
+    pub(crate) proto: Option<&'a str>,
+    /// The `Host` of the request the proxy received, if known.
+    pub(crate) host: Option<&'a str>,
+}
+
+/// Splits `string` at each `delim` that isn't in a quoted string.
+fn split(string: &str, delim: char) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    let mut escaped = false;
+    string.split(move |c| {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c == delim && !quoted => return true,
+            _ => {}
+        }
+
+        false
+    })
+}
+
+/// Removes surrounding whitespace and quotes from `value`.
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => value,
+    }
+}
+
+/// Parses a node: an IP address, optionally with a port and, for IPv6, in
+/// brackets. Obfuscated and `unknown` nodes yield `None`.
+fn node(value: &str) -> Option<IpAddr> {
+    let value = unquote(value);
+    if let Some(rest) = value.strip_prefix('[') {
+        return rest.split(']').next()?.parse().ok();
+    }
+
+    value.parse::<IpAddr>().ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+/// Returns `true` if `ip` is in `trusted`. Every address is trusted when
+/// `trusted` is `None`.
+fn is_trusted(trusted: Option<&[IpNet]>, ip: IpAddr) -> bool {
+    let ip = canonical_ip(ip);
+    trusted.map_or(true, |trusted| trusted.iter().any(|net| net.contains(&ip)))
+}
+
+/// Returns `true` if the peer at `remote` may set forwarding headers. Peers
+/// without an IP address, like those connected over a Unix socket, may.
+pub(crate) fn is_trusted_peer(trusted: Option<&[IpNet]>, remote: Option<IpAddr>) -> bool {
+    remote.map_or(true, |ip| is_trusted(trusted, ip))
+}
+
+/// Parses the hops in all of the RFC 7239 `Forwarded` headers in `headers`,
+/// from the first to the last proxy.
+pub(crate) fn forwarded<'a>(headers: &'a HeaderMap<'_>) -> Vec<Hop<'a>> {
+    headers.get("Forwarded")
+        .flat_map(|value| split(value, ','))
+        .filter(|element| !element.trim().is_empty())
+        .map(|element| {
+            let mut hop = Hop::default();
+            for pair in split(element, ';') {
+                let (key, value) = match pair.split_once('=') {
+                    Some((key, value)) => (key.trim(), unquote(value)),
+                    None => continue,
+                };
+
+                if key.eq_ignore_ascii_case("for") {
+                    hop.client = node(value);
+                } else if key.eq_ignore_ascii_case("proto") {
+                    hop.proto = Some(value);
+                } else if key.eq_ignore_ascii_case("host") {
+                    hop.host = Some(value);
+                }
+            }
+
+            hop
+        })
+        .collect()
+}
+
+/// Parses the hops in all of the `X-Forwarded-For`-style headers named `name`
+/// in `headers`, from the first to the last proxy.
+pub(crate) fn forwarded_for<'a>(headers: &'a HeaderMap<'_>, name: &str) -> Vec<Hop<'a>> {
+    headers.get(name)
+        .flat_map(|value| value.split(','))
+        .filter(|node| !node.trim().is_empty())
+        .map(|value| Hop { client: node(value), ..Hop::default() })
+        .collect()
+}
+
+/// Walks `hops` right-to-left, starting at the peer `remote`, and returns the
+/// index of the first hop recorded by a trusted proxy whose own client is
+/// untrusted or unknown. This is the hop that describes the client's request.
+/// If every proxy is trusted, this is the first hop.
+///
+/// Returns `None` if there are no hops or if `remote` isn't trusted.
+pub(crate) fn resolve(
+    hops: &[Hop<'_>],
+    remote: Option<IpAddr>,
+    trusted: Option<&[IpNet]>,
+) -> Option<usize> {
+    if !is_trusted_peer(trusted, remote) {
+        return None;
+    }
+
+    for (i, hop) in hops.iter().enumerate().rev() {
+        match hop.client {
+            Some(ip) if i > 0 && is_trusted(trusted, ip) => continue,
+            _ => return Some(i),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Header;
+
+    fn headers(name: &'static str, values: &[&'static str]) -> HeaderMap<'static> {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.add(Header::new(name, *value));
+        }
+
+        headers
+    }
+
+    fn ip(string: &str) -> Option<IpAddr> {
+        Some(string.parse().unwrap())
+    }
+
+    #[test]
+    fn test_parse_nodes() {
+        assert_eq!(node("192.0.2.43"), ip("192.0.2.43"));
+        assert_eq!(node("\"192.0.2.43:47011\""), ip("192.0.2.43"));
+        assert_eq!(node(" 2001:db8:cafe::17 "), ip("2001:db8:cafe::17"));
+        assert_eq!(node("\"[2001:db8:cafe::17]\""), ip("2001:db8:cafe::17"));
+        assert_eq!(node("\"[2001:db8:cafe::17]:4711\""), ip("2001:db8:cafe::17"));
+        assert_eq!(node("unknown"), None);
+        assert_eq!(node("_hidden"), None);
+        assert_eq!(node("[bad"), None);
+    }
+
+    #[test]
+    fn test_parse_forwarded() {
+        let map = headers("Forwarded", &[
+            r#"for=192.0.2.60;proto=https;host="example.com:8443";by=203.0.113.43"#,
+            r#"For="[2001:db8:cafe::17]:4711", for=unknown;PROTO=http"#,
+            r#"for="a,b;c"; host=quoted.example, , for=198.51.100.17"#,
+        ]);
+
+        assert_eq!(forwarded(&map), vec![
+            Hop { client: ip("192.0.2.60"), proto: Some("https"), host: Some("example.com:8443") },
+            Hop { client: ip("2001:db8:cafe::17"), ..Hop::default() },
+            Hop { client: None, proto: Some("http"), ..Hop::default() },
+            Hop { client: None, host: Some("quoted.example"), ..Hop::default() },
+            Hop { client: ip("198.51.100.17"), ..Hop::default() },
+        ]);
+
+        let map = headers("X-Forwarded-For", &["203.0.113.195, 2001:db8::1", "198.51.100.7,"]);
+        let clients: Vec<_> = forwarded_for(&map, "x-forwarded-for").into_iter()
+            .map(|hop| hop.client)
+            .collect();
+
+        assert_eq!(clients, vec![ip("203.0.113.195"), ip("2001:db8::1"), ip("198.51.100.7")]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let trusted: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap(), "fd00::/8".parse().unwrap()];
+        let trusted = Some(&trusted[..]);
+        let map = headers("X-Forwarded-For", &["1.1.1.1, 2.2.2.2, 10.0.0.2, 10.0.0.1"]);
+        let hops = forwarded_for(&map, "X-Forwarded-For");
+
+        // The walk stops at the first untrusted client from the right.
+        assert_eq!(resolve(&hops, ip("10.0.0.9"), trusted), Some(1));
+        assert_eq!(resolve(&hops, ip("::ffff:10.0.0.9"), trusted), Some(1));
+        assert_eq!(resolve(&hops, None, trusted), Some(1));
+
+        // Nothing is resolved when the peer itself isn't trusted.
+        assert_eq!(resolve(&hops, ip("2.2.2.2"), trusted), None);
+        assert_eq!(resolve(&hops, ip("fe00::1"), trusted), None);
+        assert_eq!(resolve(&[], ip("10.0.0.9"), trusted), None);
+
+        // Without a list of trusted proxies, everyone is trusted.
+        assert_eq!(resolve(&hops, ip("2.2.2.2"), None), Some(0));
+
+        // When everyone in the chain is trusted, the first hop is the client.
+        let map = headers("X-Forwarded-For", &["10.1.1.1, fd00::1"]);
+        let hops = forwarded_for(&map, "X-Forwarded-For");
+        assert_eq!(resolve(&hops, ip("fd00::2"), trusted), Some(0));
+
+        // An unknown client ends the walk.
+        let map = headers("Forwarded", &["for=1.1.1.1, for=_gateway;proto=https, for=10.0.0.1"]);
+        assert_eq!(resolve(&forwarded(&map), ip("10.0.0.9"), trusted), Some(1));
+    }
+}
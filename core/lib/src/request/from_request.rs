@@ -7,6 +7,7 @@ use crate::outcome::Outcome::*;
 
 use crate::http::{Status, ContentType, Accept, Method, CookieJar};
 use crate::http::uri::{Host, Origin};
+use crate::http::proxy_protocol::ProxyHeader;
 
 /// Type alias for the `Outcome` of a `FromRequest` conversion.
 pub type Outcome<S, E> = outcome::Outcome<S, (Status, E), ()>;
@@ -135,6 +136,13 @@ impl<S, E> IntoOutcome<S, (Status, E), ()> for Result<S, E> {
 ///     Extracts the [`Host`] from the incoming request, if it exists. See
 ///     [`Request::host()`] for details.
 ///
+///   * **RequestId**
+///
+///     Extracts the [`RequestId`](crate::request::RequestId) of the incoming
+///     request. See [`Request::request_id()`] for details.
+///
+///     _This implementation always returns successfully._
+///
 ///   * **&Route**
 ///
 ///     Extracts the [`Route`] from the request if one is available. If a route
@@ -174,6 +182,14 @@ impl<S, E> IntoOutcome<S, (Status, E), ()> for Result<S, E> {
 ///
 ///     _This implementation always returns successfully._
 ///
+///   * **&ProxyHeader**
+///
+///     Extracts the [`ProxyHeader`] received on the request's connection. If
+///     there is none, the request is forwarded. See
+///     [`ProxyProtocol`](crate::config::ProxyProtocol).
+///
+///     _This implementation always returns successfully._
+///
 ///   * **Option&lt;T>** _where_ **T: FromRequest**
 ///
 ///     The type `T` is derived from the incoming request using `T`'s
@@ -488,6 +504,18 @@ impl<'r> FromRequest<'r> for &'r crate::http::bindable::BindableAddr {
     }
 }
 
+#[crate::async_trait]
+impl<'r> FromRequest<'r> for &'r ProxyHeader {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.proxy_header() {
+            Some(header) => Success(header),
+            None => Forward(())
+        }
+    }
+}
+
 #[crate::async_trait]
 impl<'r, T: FromRequest<'r>> FromRequest<'r> for Result<T, T::Error> {
     type Error = std::convert::Infallible;
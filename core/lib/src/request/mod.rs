@@ -3,6 +3,12 @@
 mod request;
 mod from_param;
 mod from_request;
+mod forwarded;
+mod server_name;
+mod peer_credentials;
+mod request_id;
+#[cfg(feature = "tls")]
+mod tls_info;
 
 #[cfg(test)]
 mod tests;
@@ -10,6 +16,11 @@ mod tests;
 pub use self::request::Request;
 pub use self::from_request::{FromRequest, Outcome};
 pub use self::from_param::{FromParam, FromSegments};
+pub use self::server_name::ServerName;
+pub use self::peer_credentials::{PeerCredentials, NoPeerCredentials};
+pub use self::request_id::RequestId;
+#[cfg(feature = "tls")]
+pub use self::tls_info::TlsInfo;
 
 #[doc(inline)]
 pub use crate::response::flash::FlashMessage;
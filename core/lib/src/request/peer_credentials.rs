@@ -0,0 +1,169 @@
+use std::fmt;
+
+use crate::http::Status;
+use crate::request::{FromRequest, Outcome, Request};
+
+/// Request guard for the credentials of the process that sent a request over a
+/// Unix socket.
+///
+/// The operating system reports the user ID, group ID, and, on most platforms,
+/// the process ID of the peer when Rocket accepts a Unix socket connection.
+/// Unlike the client's address, these can't be forged by the client, so they
+/// can be used to authorize requests to, say, an administrative socket.
+///
+/// The guard fails with a status of `401 Unauthorized` and a
+/// [`NoPeerCredentials`] error if the request didn't arrive directly over a
+/// Unix socket or its peer's credentials are unavailable. Requests over TCP,
+/// with a PROXY protocol header, or from a local client have no peer
+/// credentials. See also [`Request::peer_credentials()`].
+///
+/// # Example
+///
+/// Only serve requests from processes running as `root` or as the server's
+/// own user:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::http::Status;
+/// use rocket::request::{self, FromRequest, PeerCredentials, Request};
+///
+/// struct Admin;
+///
+/// #[rocket::async_trait]
+/// impl<'r> FromRequest<'r> for Admin {
+///     type Error = ();
+///
+///     async fn from_request(req: &'r Request<'_>) -> request::Outcome<Admin, ()> {
+///         let server_uid = 1000; // for example, from `libc::geteuid()`
+///         match req.peer_credentials() {
+///             Some(peer) if peer.uid() == 0 || peer.uid() == server_uid => {
+///                 request::Outcome::Success(Admin)
+///             }
+///             _ => request::Outcome::Failure((Status::Forbidden, ())),
+///         }
+///     }
+/// }
+///
+/// #[post("/reload")]
+/// fn reload(_admin: Admin) -> &'static str {
+///     "Reloading."
+/// }
+///
+/// #[get("/whoami")]
+/// fn whoami(peer: PeerCredentials) -> String {
+///     format!("uid {}, gid {}", peer.uid(), peer.gid())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PeerCredentials {
+    uid: u32,
+    gid: u32,
+    pid: Option<i32>,
+}
+
+impl PeerCredentials {
+    /// The effective user ID of the peer process.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::request::PeerCredentials;
+    ///
+    /// #[get("/")]
+    /// fn index(peer: PeerCredentials) -> &'static str {
+    ///     match peer.uid() {
+    ///         0 => "Hello, root!",
+    ///         _ => "Hello!",
+    ///     }
+    /// }
+    /// ```
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// The effective group ID of the peer process.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::request::PeerCredentials;
+    ///
+    /// #[get("/")]
+    /// fn index(peer: PeerCredentials) -> String {
+    ///     format!("Your group is {}.", peer.gid())
+    /// }
+    /// ```
+    pub fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    /// The ID of the peer process, if the platform reports it. Linux, Android,
+    /// and macOS do, among others.
+    ///
+    /// Note that the process may have exited, and its ID been reused, by the
+    /// time a request is handled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::request::PeerCredentials;
+    ///
+    /// #[get("/")]
+    /// fn index(peer: PeerCredentials) -> String {
+    ///     match peer.pid() {
+    ///         Some(pid) => format!("Hello, process {}!", pid),
+    ///         None => "Hello, process!".into(),
+    ///     }
+    /// }
+    /// ```
+    pub fn pid(&self) -> Option<i32> {
+        self.pid
+    }
+}
+
+impl From<crate::http::private::PeerCredentials> for PeerCredentials {
+    fn from(cred: crate::http::private::PeerCredentials) -> Self {
+        PeerCredentials { uid: cred.uid, gid: cred.gid, pid: cred.pid }
+    }
+}
+
+impl fmt::Display for PeerCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "uid={} gid={}", self.uid, self.gid)?;
+        if let Some(pid) = self.pid {
+            write!(f, " pid={}", pid)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The error returned by the [`PeerCredentials`] request guard when a request
+/// has no peer credentials: it didn't arrive directly over a Unix socket, or the
+/// platform didn't report them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoPeerCredentials;
+
+impl fmt::Display for NoPeerCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("peer credentials are only available for requests made directly over a \
+            Unix socket")
+    }
+}
+
+impl std::error::Error for NoPeerCredentials {}
+
+#[crate::async_trait]
+impl<'r> FromRequest<'r> for PeerCredentials {
+    type Error = NoPeerCredentials;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.peer_credentials() {
+            Some(credentials) => Outcome::Success(credentials),
+            None => Outcome::Failure((Status::Unauthorized, NoPeerCredentials)),
+        }
+    }
+}
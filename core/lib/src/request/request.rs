@@ -10,6 +10,7 @@ use atomic::{Atomic, Ordering};
 
 use crate::{Rocket, Route, Orbit};
 use crate::request::{FromParam, FromSegments, FromRequest, Outcome};
+use crate::request::{forwarded, RequestId};
 use crate::form::{self, ValueField, FromForm};
 use crate::data::Limits;
 
@@ -17,7 +18,8 @@ use crate::http::bindable::BindableAddr;
 use crate::http::{hyper, Method, Header, HeaderMap};
 use crate::http::{ContentType, Accept, MediaType, CookieJar, Cookie};
 use crate::http::uncased::UncasedStr;
-use crate::http::private::Certificates;
+use crate::http::private::{Certificates, Handshake};
+use crate::http::proxy_protocol::ProxyHeader;
 use crate::http::uri::{fmt::Path, Origin, Segments, Host, Authority};
 
 /// The type of an incoming web request.
@@ -40,6 +42,13 @@ pub(crate) struct ConnectionMeta {
     pub remote: Option<BindableAddr>,
     #[cfg_attr(not(feature = "mtls"), allow(dead_code))]
     pub client_certificates: Option<Certificates>,
+    pub proxy_header: Option<Arc<ProxyHeader>>,
+    pub tls: Option<Handshake>,
+    pub peer_credentials: Option<crate::http::private::PeerCredentials>,
+    /// The attributes identifying mutual TLS clients on this connection's
+    /// listener, if it has mutual TLS enabled.
+    #[cfg(feature = "mtls")]
+    pub identity: Option<Arc<[crate::config::IdentityAttribute]>>,
 }
 
 /// Information derived from the request.
@@ -51,6 +60,8 @@ pub(crate) struct RequestState<'r> {
     pub content_type: Storage<Option<ContentType>>,
     pub cache: Arc<Container![Send + Sync]>,
     pub host: Option<Host<'r>>,
+    pub version: Option<hyper::Version>,
+    pub request_id: Storage<String>,
 }
 
 impl Request<'_> {
@@ -75,6 +86,8 @@ impl RequestState<'_> {
             content_type: self.content_type.clone(),
             cache: self.cache.clone(),
             host: self.host.clone(),
+            version: self.version,
+            request_id: self.request_id.clone(),
         }
     }
 }
@@ -94,6 +107,11 @@ impl<'r> Request<'r> {
             connection: ConnectionMeta {
                 remote: None,
                 client_certificates: None,
+                proxy_header: None,
+                tls: None,
+                peer_credentials: None,
+                #[cfg(feature = "mtls")]
+                identity: None,
             },
             state: RequestState {
                 rocket,
@@ -103,6 +121,8 @@ impl<'r> Request<'r> {
                 content_type: Storage::new(),
                 cache: Arc::new(<Container![Send + Sync]>::new()),
                 host: None,
+                version: None,
+                request_id: Storage::new(),
             }
         }
     }
@@ -308,9 +328,10 @@ impl<'r> Request<'r> {
     ///
     /// Because it is common for proxies to forward connections for clients, the
     /// remote address may contain information about the proxy instead of the
-    /// client. For this reason, proxies typically set the "X-Real-IP" header
-    /// with the client's true IP. To extract this IP from the request, use the
-    /// [`real_ip()`] or [`client_ip()`] methods.
+    /// client. For this reason, proxies typically set a header like
+    /// "X-Real-IP" or "X-Forwarded-For" with the client's true IP. To extract
+    /// this IP from the request, use the [`real_ip()`] or [`client_ip()`]
+    /// methods.
     ///
     /// [`real_ip()`]: #method.real_ip
     /// [`client_ip()`]: #method.client_ip
@@ -358,8 +379,180 @@ impl<'r> Request<'r> {
         self.connection.remote = Some(address);
     }
 
-    /// Returns the IP address in the "X-Real-IP" header of the request if such
-    /// a header exists and contains a valid IP address.
+    /// Returns the PROXY protocol header received on the connection this
+    /// request arrived on, if any.
+    ///
+    /// A header is only received on listeners configured with
+    /// [`ProxyProtocol`](crate::config::ProxyProtocol), in which case the
+    /// client address in the header is the [`remote()`](Request::remote())
+    /// address. Local requests never have a PROXY protocol header.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let c = rocket::local::blocking::Client::debug_with(vec![]).unwrap();
+    /// # let request = c.get("/");
+    /// if let Some(header) = request.proxy_header() {
+    ///     println!("proxied for {:?} via {:?}", header.source(), header.authority());
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn proxy_header(&self) -> Option<&ProxyHeader> {
+        self.connection.proxy_header.as_deref()
+    }
+
+    /// Returns the server name the client requested via SNI (Server Name
+    /// Indication) in the TLS handshake on the connection this request arrived
+    /// on, if any. This is the name that selected the certificate served, as
+    /// configured via [`TlsConfig`](crate::config::TlsConfig), and may differ
+    /// from the request's [`host()`](Request::host()).
+    ///
+    /// Requests received without TLS and local requests never have a server
+    /// name. The [`ServerName`](crate::request::ServerName) request guard
+    /// retrieves the name in routes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let c = rocket::local::blocking::Client::debug_with(vec![]).unwrap();
+    /// # let request = c.get("/");
+    /// assert_eq!(request.server_name(), None);
+    ///
+    /// if let Some(name) = request.server_name() {
+    ///     println!("TLS client requested {}", name);
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn server_name(&self) -> Option<&str> {
+        self.connection.tls.as_ref()
+            .and_then(|tls| tls.data())
+            .and_then(|data| data.server_name.as_deref())
+    }
+
+    /// Returns the parameters negotiated in the TLS handshake on the
+    /// connection this request arrived on: the protocol version, cipher suite,
+    /// ALPN protocol, and SNI server name. Returns `None` if the request wasn't
+    /// received over TLS, as is the case for local requests.
+    ///
+    /// The [`TlsInfo`](crate::request::TlsInfo) request guard retrieves the
+    /// same information in routes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::TlsVersion;
+    ///
+    /// # let c = rocket::local::blocking::Client::debug_with(vec![]).unwrap();
+    /// # let request = c.get("/");
+    /// assert!(request.tls_info().is_none());
+    ///
+    /// if let Some(tls) = request.tls_info() {
+    ///     let modern = tls.version() >= TlsVersion::TLSv1_3;
+    ///     println!("{} via {}; modern: {}", tls.version(), tls.cipher_suite(), modern);
+    /// }
+    /// ```
+    #[cfg(feature = "tls")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tls")))]
+    pub fn tls_info(&self) -> Option<crate::request::TlsInfo<'_>> {
+        self.connection.tls.as_ref()
+            .and_then(|tls| tls.data())
+            .and_then(crate::request::TlsInfo::from_handshake)
+    }
+
+    /// Returns the credentials of the process on the other end of the Unix
+    /// socket this request arrived on: its user ID, group ID, and, where the
+    /// platform reports it, process ID. Returns `None` if the request didn't
+    /// arrive directly over a Unix socket, as is the case for TCP connections,
+    /// connections with a PROXY protocol header, and local requests.
+    ///
+    /// The [`PeerCredentials`](crate::request::PeerCredentials) request guard
+    /// retrieves the same information in routes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let c = rocket::local::blocking::Client::debug_with(vec![]).unwrap();
+    /// # let request = c.get("/");
+    /// assert!(request.peer_credentials().is_none());
+    ///
+    /// if let Some(peer) = request.peer_credentials() {
+    ///     println!("request from uid {}", peer.uid());
+    /// }
+    /// ```
+    pub fn peer_credentials(&self) -> Option<crate::request::PeerCredentials> {
+        self.connection.peer_credentials.map(crate::request::PeerCredentials::from)
+    }
+
+    /// Returns the ID identifying this request in logs.
+    ///
+    /// If the header named by
+    /// [`Config::request_id_header`](crate::Config::request_id_header),
+    /// `X-Request-Id` by default, is present, the remote peer is trusted to set
+    /// it, and its first value is a valid ID, that value is returned.
+    /// Otherwise, a random ID is generated. As for forwarding headers, every
+    /// peer is trusted unless
+    /// [`Config::trusted_proxies`](crate::Config::trusted_proxies) is set, in
+    /// which case only peers in the trusted networks, or without an IP address,
+    /// are. Either
+    /// way, the ID is computed once and is the same for the lifetime of the
+    /// request, unless the header is later modified. See
+    /// [`RequestId`](crate::request::RequestId) for what makes an ID valid.
+    ///
+    /// The [`RequestId`](crate::request::RequestId) request guard retrieves the
+    /// ID in routes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::Header;
+    ///
+    /// # let c = rocket::local::blocking::Client::debug_with(vec![]).unwrap();
+    /// # let req = c.get("/");
+    /// let id = req.request_id().to_string();
+    /// assert_eq!(id.len(), 32);
+    /// assert_eq!(req.request_id(), id);
+    ///
+    /// let req = req.header(Header::new("X-Request-Id", "checkout-17"));
+    /// assert_eq!(req.request_id(), "checkout-17");
+    ///
+    /// # let req = c.get("/");
+    /// let req = req.header(Header::new("X-Request-Id", "not valid"));
+    /// assert_ne!(req.request_id(), "not valid");
+    /// ```
+    pub fn request_id(&self) -> &str {
+        self.state.request_id.get_or_set(|| {
+            let config = self.rocket().config();
+            let remote = self.remote().and_then(|r| r.ip());
+            config.request_id_header.as_ref()
+                .filter(|_| forwarded::is_trusted_peer(config.trusted_proxies.as_deref(), remote))
+                .and_then(|name| self.headers().get_one(name.as_str()))
+                .filter(|id| RequestId::is_valid(id))
+                .map(|id| id.to_string())
+                .unwrap_or_else(RequestId::generate)
+        })
+    }
+
+    /// Returns the client's IP address as reported by proxies in the header
+    /// named by [`Config::ip_header`](crate::Config::ip_header), `X-Real-IP`
+    /// by default, if there is such a header and the address can be resolved.
+    ///
+    /// The `Forwarded` ([RFC 7239]) and `X-Forwarded-For` headers are read as
+    /// chains of proxies, each of which appends the address of the peer it
+    /// received the request from. When
+    /// [`Config::trusted_proxies`](crate::Config::trusted_proxies) is set, the
+    /// chain is walked right-to-left, starting at the
+    /// [`remote()`](Request::remote()) peer, for as long as the addresses are
+    /// of trusted proxies; the first untrusted address is the client's. If the
+    /// walk reaches an `unknown` or obfuscated address, `None` is returned. If
+    /// every proxy is trusted, the leftmost address is returned. Any other
+    /// header must contain a single IP address, which is returned if the
+    /// remote peer is trusted.
+    ///
+    /// When `trusted_proxies` is not set, every peer is trusted, so the header
+    /// can be set by any client. Peers without an IP address, like those
+    /// connected via a Unix socket, are always trusted.
+    ///
+    /// [RFC 7239]: https://datatracker.ietf.org/doc/html/rfc7239
     ///
     /// # Example
     ///
@@ -374,20 +567,61 @@ impl<'r> Request<'r> {
     /// let req = req.header(Header::new("X-Real-IP", "127.0.0.1"));
     /// assert_eq!(req.real_ip(), Some(Ipv4Addr::LOCALHOST.into()));
     /// ```
+    ///
+    /// With `X-Forwarded-For` and trusted proxies:
+    ///
+    /// ```rust
+    /// use rocket::http::Header;
+    /// use rocket::local::blocking::Client;
+    ///
+    /// let figment = rocket::Config::figment()
+    ///     .merge(("ip_header", "X-Forwarded-For"))
+    ///     .merge(("trusted_proxies", ["10.0.0.0/8"]));
+    ///
+    /// let client = Client::debug(rocket::custom(figment)).unwrap();
+    /// let req = client.get("/")
+    ///     .remote("tcp://10.0.0.1:8000".parse().unwrap())
+    ///     .header(Header::new("X-Forwarded-For", "192.0.2.1, 198.51.100.7, 10.0.0.2"));
+    ///
+    /// // `10.0.0.2` is trusted, but `198.51.100.7` isn't.
+    /// assert_eq!(req.real_ip(), Some("198.51.100.7".parse().unwrap()));
+    ///
+    /// // Headers from untrusted peers are ignored.
+    /// let req = req.remote("tcp://192.0.2.1:8000".parse().unwrap());
+    /// assert_eq!(req.real_ip(), None);
+    /// ```
     pub fn real_ip(&self) -> Option<IpAddr> {
-        self.headers()
-            .get_one("X-Real-IP")
-            .and_then(|ip| {
-                ip.parse()
-                    .map_err(|_| warn_!("'X-Real-IP' header is malformed: {}", ip))
-                    .ok()
-            })
+        let config = self.rocket().config();
+        let name = config.ip_header.as_ref()?;
+        let trusted = config.trusted_proxies.as_deref();
+        let remote = self.remote().and_then(|r| r.ip());
+
+        let hops = if name == "Forwarded" {
+            forwarded::forwarded(self.headers())
+        } else if name == "X-Forwarded-For" {
+            forwarded::forwarded_for(self.headers(), name.as_str())
+        } else {
+            if !forwarded::is_trusted_peer(trusted, remote) {
+                return None;
+            }
+
+            return self.headers()
+                .get_one(name.as_str())
+                .and_then(|ip| {
+                    ip.trim().parse()
+                        .map_err(|_| warn_!("'{}' header is malformed: {}", name, ip))
+                        .ok()
+                });
+        };
+
+        forwarded::resolve(&hops, remote, trusted).and_then(|i| hops[i].client)
     }
 
     /// Attempts to return the client's IP address by first inspecting the
-    /// "X-Real-IP" header and then using the remote connection's IP address.
+    /// configured IP header via [`real_ip()`](Request::real_ip()) and then
+    /// using the remote connection's IP address.
     ///
-    /// If the "X-Real-IP" header exists and contains a valid IP address, that
+    /// If the header exists and an IP address can be resolved from it, that
     /// address is returned. Otherwise, if the address of the remote connection
     /// is known, that address is returned. Otherwise, `None` is returned.
     ///
@@ -416,6 +650,121 @@ impl<'r> Request<'r> {
         self.real_ip().or_else(|| self.remote().and_then(|r| r.ip()))
     }
 
+    /// Returns the protocol, like `https`, that the client used to make this
+    /// request to the outermost trusted proxy, if it's known.
+    ///
+    /// Forwarding headers are only considered when
+    /// [`Config::trusted_proxies`](crate::Config::trusted_proxies) is set and
+    /// the [`remote()`](Request::remote()) peer is trusted. If the request has
+    /// a `Forwarded` header, the `proto` of the hop that the client's address
+    /// is resolved from is returned, as described in
+    /// [`real_ip()`](Request::real_ip()). Otherwise, the last value of the
+    /// `X-Forwarded-Proto` header, as set by the nearest proxy, is returned.
+    /// Values that aren't valid URI schemes are ignored.
+    ///
+    /// Together with [`forwarded_host()`](Request::forwarded_host()), this
+    /// allows absolute URIs and redirects to be constructed correctly behind a
+    /// TLS-terminating proxy.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::Header;
+    /// use rocket::local::blocking::Client;
+    ///
+    /// let figment = rocket::Config::figment().merge(("trusted_proxies", ["127.0.0.1/32"]));
+    /// let client = Client::debug(rocket::custom(figment)).unwrap();
+    /// let req = client.get("/")
+    ///     .remote("tcp://127.0.0.1:8000".parse().unwrap())
+    ///     .header(Header::new("Forwarded", "for=192.0.2.1;proto=https;host=rocket.rs"));
+    ///
+    /// assert_eq!(req.forwarded_proto(), Some("https"));
+    /// assert_eq!(req.forwarded_host().unwrap().domain(), "rocket.rs");
+    ///
+    /// let req = client.get("/")
+    ///     .remote("tcp://127.0.0.1:8000".parse().unwrap())
+    ///     .header(Header::new("X-Forwarded-Proto", "https"));
+    ///
+    /// assert_eq!(req.forwarded_proto(), Some("https"));
+    /// assert_eq!(req.forwarded_host(), None);
+    /// ```
+    pub fn forwarded_proto(&self) -> Option<&str> {
+        let is_scheme = |proto: &&str| {
+            let mut chars = proto.chars();
+            chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+                && chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        };
+
+        self.forwarded_value(|hop| hop.proto, "X-Forwarded-Proto").filter(is_scheme)
+    }
+
+    /// Returns the [`Host`] that the client requested from the outermost
+    /// trusted proxy, if it's known.
+    ///
+    /// The host is read from the `Forwarded` header's `host` or the
+    /// `X-Forwarded-Host` header under the same conditions as the protocol is
+    /// read by [`forwarded_proto()`](Request::forwarded_proto()). Values that
+    /// aren't valid hosts are ignored. Like [`host()`](Request::host()), the
+    /// value is controlled by the client and should be checked against a
+    /// whitelist before use.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::http::Header;
+    /// use rocket::http::uri::Host;
+    /// use rocket::local::blocking::Client;
+    ///
+    /// #[get("/token?<secret>")]
+    /// fn token(secret: String) { /* .. */ }
+    ///
+    /// let figment = rocket::Config::figment().merge(("trusted_proxies", ["127.0.0.1/32"]));
+    /// let client = Client::debug(rocket::custom(figment)).unwrap();
+    /// let req = client.get("/")
+    ///     .remote("tcp://127.0.0.1:8000".parse().unwrap())
+    ///     .header(Header::new("X-Forwarded-Proto", "https"))
+    ///     .header(Header::new("X-Forwarded-Host", "rocket.rs"));
+    ///
+    /// const WHITELIST: [Host<'static>; 1] = [Host::new(uri!("rocket.rs"))];
+    ///
+    /// let host = req.forwarded_host().or_else(|| req.host().cloned()).unwrap();
+    /// let proto = req.forwarded_proto().unwrap_or("http");
+    /// let prefix = host.to_absolute(proto, &WHITELIST).unwrap();
+    /// assert_eq!(uri!(prefix, token("abc")), "https://rocket.rs/token?secret=abc");
+    /// ```
+    pub fn forwarded_host(&self) -> Option<Host<'_>> {
+        self.forwarded_value(|hop| hop.host, "X-Forwarded-Host")
+            .and_then(|host| Host::parse(host).ok())
+    }
+
+    /// Returns the field of the `Forwarded` hop that describes the client's
+    /// request or the last value of the `fallback` header when there's no
+    /// `Forwarded` header, if the remote peer is a trusted proxy.
+    fn forwarded_value<'a, F>(&'a self, field: F, fallback: &str) -> Option<&'a str>
+        where F: Fn(&forwarded::Hop<'a>) -> Option<&'a str>
+    {
+        let trusted = self.rocket().config().trusted_proxies.as_deref()?;
+        let remote = self.remote().and_then(|r| r.ip());
+
+        let hops = forwarded::forwarded(self.headers());
+        if !hops.is_empty() {
+            let i = forwarded::resolve(&hops, remote, Some(trusted))?;
+            return field(&hops[i]).filter(|value| !value.is_empty());
+        }
+
+        if !forwarded::is_trusted_peer(Some(trusted), remote) {
+            return None;
+        }
+
+        self.headers()
+            .get(fallback)
+            .flat_map(|value| value.split(','))
+            .last()
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
     /// Returns a wrapped borrow to the cookies in `self`.
     ///
     /// [`CookieJar`] implements internal mutability, so this method allows you
@@ -917,6 +1266,8 @@ impl<'r> Request<'r> {
             if self.accept().is_none() || replace {
                 self.state.accept = Storage::new();
             }
+        } else if self.rocket().config.request_id_header.as_deref() == Some(name) {
+            self.state.request_id = Storage::new();
         }
     }
 
@@ -961,6 +1312,13 @@ impl<'r> Request<'r> {
         self.method.store(method, Ordering::Release)
     }
 
+    /// Removes all headers named `name` from `self`.
+    #[inline(always)]
+    pub(crate) fn _remove_header(&mut self, name: &str) {
+        self.bust_header_cache(name.into(), true);
+        self.headers.remove(name);
+    }
+
     pub(crate) fn cookies_mut(&mut self) -> &mut CookieJar<'r> {
         &mut self.state.cookies
     }
@@ -976,34 +1334,46 @@ impl<'r> Request<'r> {
             .ok_or(Error::BadMethod(&hyper.method))?;
 
         // TODO: Keep around not just the path/query, but the rest, if there?
-        let uri = hyper.uri.path_and_query().ok_or(Error::InvalidUri(&hyper.uri))?;
+        // `CONNECT` requests target an authority, not a path. Route them as if
+        // they were to `/`; the target is available as the request's host.
+        let is_connect = method == Method::Connect && hyper.uri.path_and_query().is_none();
+        let (path, query) = match hyper.uri.path_and_query() {
+            Some(uri) => (uri.path(), uri.query()),
+            None if is_connect => ("/", None),
+            None => return Err(Error::InvalidUri(&hyper.uri)),
+        };
 
         // In debug, make sure we agree with Hyper that the URI is valid. If we
         // disagree, print a warning but continue anyway seeing as if this is a
         // security issue with Hyper, there isn't much we can do.
         #[cfg(debug_assertions)]
-        if Origin::parse(uri.as_str()).is_err() {
-            warn!("Hyper/Rocket URI validity discord: {:?}", uri.as_str());
-            info_!("Hyper believes the URI is valid while Rocket disagrees.");
-            info_!("This is likely a Hyper bug with potential security implications.");
-            warn_!("Please report this warning to Rocket's GitHub issue tracker.");
+        if let Some(uri) = hyper.uri.path_and_query() {
+            if Origin::parse(uri.as_str()).is_err() {
+                warn!("Hyper/Rocket URI validity discord: {:?}", uri.as_str());
+                info_!("Hyper believes the URI is valid while Rocket disagrees.");
+                info_!("This is likely a Hyper bug with potential security implications.");
+                warn_!("Please report this warning to Rocket's GitHub issue tracker.");
+            }
         }
 
         // Construct the request object.
-        let uri = Origin::new(uri.path(), uri.query().map(Cow::Borrowed));
+        let uri = Origin::new(path, query.map(Cow::Borrowed));
         let mut request = Request::new(rocket, method, uri);
         if let Some(connection) = connection {
             request.connection = connection;
         }
 
-        // Determine the host. On HTTP < 2, use the `HOST` header. Otherwise,
-        // use the `:authority` pseudo-header which hyper makes part of the URI.
-        request.state.host = if hyper.version < hyper::Version::HTTP_2 {
+        // Determine the host. On HTTP < 2, use the `HOST` header unless this is
+        // a `CONNECT`. Otherwise, use the authority which hyper makes part of
+        // the URI: the `CONNECT` target or the `:authority` pseudo-header.
+        request.state.host = if hyper.version < hyper::Version::HTTP_2 && !is_connect {
             hyper.headers.get("host").and_then(|h| Host::parse_bytes(h.as_bytes()).ok())
         } else {
             hyper.uri.host().map(|h| Host::new(Authority::new(None, h, hyper.uri.port_u16())))
         };
 
+        request.state.version = Some(hyper.version);
+
         // Set the request cookies, if they exist.
         for header in hyper.headers.get_all("Cookie") {
             let raw_str = match std::str::from_utf8(header.as_bytes()) {
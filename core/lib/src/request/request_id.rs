@@ -0,0 +1,103 @@
+use std::fmt;
+use std::ops::Deref;
+
+use crate::request::{FromRequest, Outcome, Request};
+
+/// Request guard for the ID that identifies a request in logs.
+///
+/// Every request has an ID. When [`Config::request_id_header`] names a header
+/// and the request carries a valid ID in it, typically set by a proxy or an
+/// upstream service, that ID is used. Otherwise, Rocket generates a random one.
+/// An ID is valid when it is between 1 and 128 characters long, all of which
+/// are ASCII alphanumerics or one of `-`, `_`, `.`, `:`, `+`, `/`, or `=`.
+///
+/// Rocket includes the ID in its own log messages about the request and, when
+/// a header is configured, echoes it in that header on the response. This
+/// guard never fails. See [`Request::request_id()`] for details.
+///
+/// [`Config::request_id_header`]: crate::Config::request_id_header
+///
+/// # Example
+///
+/// Pass the ID along to another service:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::request::RequestId;
+///
+/// #[get("/")]
+/// fn index(id: RequestId<'_>) -> String {
+///     // client.get(url).header("X-Request-Id", id.as_str()).send().await;
+///     format!("handling request {}", id)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RequestId<'r>(&'r str);
+
+impl<'r> RequestId<'r> {
+    /// The maximum length of an accepted ID, in bytes.
+    const MAX_LEN: usize = 128;
+
+    /// Returns the ID as a string slice.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::request::RequestId;
+    ///
+    /// #[get("/")]
+    /// fn index(id: RequestId<'_>) -> String {
+    ///     id.as_str().to_string()
+    /// }
+    /// ```
+    pub fn as_str(&self) -> &'r str {
+        self.0
+    }
+
+    /// Returns `true` if `id` may be used as a request ID.
+    pub(crate) fn is_valid(id: &str) -> bool {
+        let valid = |b: u8| b.is_ascii_alphanumeric() || b"-_.:+/=".contains(&b);
+        !id.is_empty() && id.len() <= Self::MAX_LEN && id.bytes().all(valid)
+    }
+
+    /// Generates a new, random, 32 character hexadecimal ID.
+    pub(crate) fn generate() -> String {
+        format!("{:032x}", rand::random::<u128>())
+    }
+}
+
+impl Deref for RequestId<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Display for RequestId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq<str> for RequestId<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for RequestId<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[crate::async_trait]
+impl<'r> FromRequest<'r> for RequestId<'r> {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(RequestId(request.request_id()))
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt;
+use std::ops::Deref;
+
+use crate::request::{FromRequest, Outcome, Request};
+
+/// Request guard for the server name a TLS client requested via SNI (Server
+/// Name Indication).
+///
+/// The server name selects the certificate served when several are configured
+/// via [`TlsConfig`](crate::config::TlsConfig). It is known only for requests
+/// received over TLS when the client sent one, which most do. Otherwise, the
+/// guard forwards. See [`Request::server_name()`] for details.
+///
+/// # Example
+///
+/// Serve a route only to clients that connected to a particular domain:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::request::{self, FromRequest, Request, ServerName};
+///
+/// struct Api;
+///
+/// #[rocket::async_trait]
+/// impl<'r> FromRequest<'r> for Api {
+///     type Error = std::convert::Infallible;
+///
+///     async fn from_request(req: &'r Request<'_>) -> request::Outcome<Api, Self::Error> {
+///         match req.guard::<ServerName<'_>>().await.succeeded() {
+///             Some(name) if name == "api.example.com" => request::Outcome::Success(Api),
+///             _ => request::Outcome::Forward(()),
+///         }
+///     }
+/// }
+///
+/// #[get("/")]
+/// fn api_index(_api: Api) -> &'static str {
+///     "API"
+/// }
+///
+/// #[get("/", rank = 2)]
+/// fn index(name: Option<ServerName<'_>>) -> String {
+///     match name {
+///         Some(name) => format!("Welcome to {}!", name),
+///         None => "Welcome!".into(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ServerName<'r>(&'r str);
+
+impl<'r> ServerName<'r> {
+    /// Returns the server name as a string slice, exactly as the client sent
+    /// it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::request::ServerName;
+    ///
+    /// #[get("/")]
+    /// fn index(name: ServerName<'_>) -> String {
+    ///     name.as_str().to_ascii_lowercase()
+    /// }
+    /// ```
+    pub fn as_str(&self) -> &'r str {
+        self.0
+    }
+}
+
+impl Deref for ServerName<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Display for ServerName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq<str> for ServerName<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for ServerName<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[crate::async_trait]
+impl<'r> FromRequest<'r> for ServerName<'r> {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.server_name() {
+            Some(name) => Outcome::Success(ServerName(name)),
+            None => Outcome::Forward(()),
+        }
+    }
+}
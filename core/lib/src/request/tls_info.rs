@@ -0,0 +1,171 @@
+use std::fmt;
+
+use crate::config::{CipherSuite, TlsVersion};
+use crate::http::private::HandshakeData;
+use crate::request::{FromRequest, Outcome, Request};
+
+/// Request guard for the parameters negotiated in the TLS handshake of the
+/// connection a request arrived on.
+///
+/// The protocol version, cipher suite, ALPN (Application-Layer Protocol
+/// Negotiation) protocol, and SNI (Server Name Indication) server name are
+/// available. The guard forwards if the request wasn't received over TLS, as is
+/// the case for local requests. See also [`Request::tls_info()`].
+///
+/// # Example
+///
+/// Require TLS 1.3 for admin routes:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::config::TlsVersion;
+/// use rocket::http::Status;
+/// use rocket::request::{self, FromRequest, Request, TlsInfo};
+///
+/// struct Tls13;
+///
+/// #[rocket::async_trait]
+/// impl<'r> FromRequest<'r> for Tls13 {
+///     type Error = TlsVersion;
+///
+///     async fn from_request(req: &'r Request<'_>) -> request::Outcome<Tls13, TlsVersion> {
+///         match req.tls_info() {
+///             Some(tls) if tls.version() >= TlsVersion::TLSv1_3 => request::Outcome::Success(Tls13),
+///             Some(tls) => request::Outcome::Failure((Status::Forbidden, tls.version())),
+///             None => request::Outcome::Forward(()),
+///         }
+///     }
+/// }
+///
+/// #[get("/admin")]
+/// fn admin(_tls: Tls13) -> &'static str {
+///     "Welcome, admin."
+/// }
+///
+/// #[get("/")]
+/// fn index(tls: Option<TlsInfo<'_>>) -> String {
+///     match tls {
+///         Some(tls) => format!("Connected via {} with {}.", tls.version(), tls.cipher_suite()),
+///         None => "Connected without TLS.".into(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(nightly, doc(cfg(feature = "tls")))]
+pub struct TlsInfo<'r> {
+    version: TlsVersion,
+    cipher_suite: CipherSuite,
+    alpn_protocol: Option<&'r [u8]>,
+    server_name: Option<&'r str>,
+}
+
+impl<'r> TlsInfo<'r> {
+    /// Returns `None` if the version or cipher suite is unknown to Rocket,
+    /// which can't happen for connections accepted by Rocket's own listeners.
+    pub(crate) fn from_handshake(data: &'r HandshakeData) -> Option<Self> {
+        Some(TlsInfo {
+            version: TlsVersion::from_u16(data.version)?,
+            cipher_suite: CipherSuite::from_u16(data.cipher_suite)?,
+            alpn_protocol: data.alpn_protocol.as_deref(),
+            server_name: data.server_name.as_deref(),
+        })
+    }
+
+    /// The negotiated version of the TLS protocol.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::config::TlsVersion;
+    /// use rocket::request::TlsInfo;
+    ///
+    /// #[get("/")]
+    /// fn index(tls: TlsInfo<'_>) -> &'static str {
+    ///     match tls.version() {
+    ///         TlsVersion::TLSv1_3 => "TLS 1.3",
+    ///         _ => "not TLS 1.3",
+    ///     }
+    /// }
+    /// ```
+    pub fn version(&self) -> TlsVersion {
+        self.version
+    }
+
+    /// The negotiated cipher suite.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::config::CipherSuite;
+    /// use rocket::request::TlsInfo;
+    ///
+    /// #[get("/")]
+    /// fn index(tls: TlsInfo<'_>) -> String {
+    ///     let suite: CipherSuite = tls.cipher_suite();
+    ///     suite.to_string()
+    /// }
+    /// ```
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    /// The protocol agreed on via ALPN, if any. This is `b"h2"` for HTTP/2
+    /// and `b"http/1.1"` for HTTP/1.1 when the client uses ALPN.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::request::TlsInfo;
+    ///
+    /// #[get("/")]
+    /// fn index(tls: TlsInfo<'_>) -> &'static str {
+    ///     match tls.alpn_protocol() {
+    ///         Some(b"h2") => "HTTP/2",
+    ///         Some(_) => "some other protocol",
+    ///         None => "no protocol negotiated",
+    ///     }
+    /// }
+    /// ```
+    pub fn alpn_protocol(&self) -> Option<&'r [u8]> {
+        self.alpn_protocol
+    }
+
+    /// The server name requested by the client via SNI, if any. This is the
+    /// same as [`Request::server_name()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::request::TlsInfo;
+    ///
+    /// #[get("/")]
+    /// fn index(tls: TlsInfo<'_>) -> String {
+    ///     tls.server_name().unwrap_or("unknown").to_string()
+    /// }
+    /// ```
+    pub fn server_name(&self) -> Option<&'r str> {
+        self.server_name
+    }
+}
+
+impl fmt::Display for TlsInfo<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.version, self.cipher_suite)
+    }
+}
+
+#[crate::async_trait]
+impl<'r> FromRequest<'r> for TlsInfo<'r> {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.tls_info() {
+            Some(info) => Outcome::Success(info),
+            None => Outcome::Forward(()),
+        }
+    }
+}
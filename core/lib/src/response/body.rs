@@ -75,7 +75,7 @@ pub trait AsyncReadSeek: AsyncRead + AsyncSeek { }
 impl<T: AsyncRead + AsyncSeek> AsyncReadSeek for T {  }
 
 /// A pinned `AsyncRead + AsyncSeek` body type.
-type SizedBody<'r> = Pin<Box<dyn AsyncReadSeek + Send + 'r>>;
+pub(crate) type SizedBody<'r> = Pin<Box<dyn AsyncReadSeek + Send + 'r>>;
 
 /// A pinned `AsyncRead` (not `AsyncSeek`) body type.
 type UnsizedBody<'r> = Pin<Box<dyn AsyncRead + Send + 'r>>;
@@ -127,6 +127,22 @@ impl<'r> Body<'r> {
         }
     }
 
+    /// Returns the body's preset size if the body is seekable.
+    pub(crate) fn seekable_size(&self) -> Option<usize> {
+        match self.inner {
+            Inner::Seekable(_) => self.size,
+            _ => None,
+        }
+    }
+
+    /// Returns the body's reader if the body is seekable.
+    pub(crate) fn into_seekable(self) -> Option<SizedBody<'r>> {
+        match self.inner {
+            Inner::Seekable(body) => Some(body),
+            _ => None,
+        }
+    }
+
     pub(crate) fn set_max_chunk_size(&mut self, max_chunk: usize) {
         self.max_chunk = max_chunk;
     }
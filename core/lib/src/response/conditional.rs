@@ -0,0 +1,313 @@
+use std::borrow::Cow;
+use std::time::SystemTime;
+
+use crate::request::Request;
+use crate::response::{self, Responder, Response};
+use crate::http::{Method, Status, StatusClass};
+
+/// Evaluates conditional request headers against the validators of a wrapped
+/// responder's response.
+///
+/// `Conditional` lets clients revalidate cached responses and make requests
+/// contingent on the state of a resource. The validators are the `ETag` and
+/// `Last-Modified` headers set by the wrapped responder; [`Etagged`] sets the
+/// former for dynamic content, and [`NamedFile`], and thus [`FileServer`], sets
+/// both from file metadata and evaluates conditions internally.
+///
+/// Only successful (`2xx`) responses are considered; all others are passed
+/// through unchanged. Otherwise, the preconditions in the request are
+/// evaluated in the following order:
+///
+///   1. **`If-Match`**: if the header is not `*` and none of its entity-tags
+///      strongly matches the response's `ETag`, the response is `412
+///      Precondition Failed`.
+///   2. **`If-Unmodified-Since`**: if `If-Match` is not present and the
+///      response's `Last-Modified` is later than the header's date, the
+///      response is `412 Precondition Failed`.
+///   3. **`If-None-Match`**: if any entity-tag in the header weakly matches the
+///      response's `ETag`, or the header is `*`, the response is `304 Not
+///      Modified` for `GET` and `HEAD` requests and `412 Precondition Failed`
+///      otherwise.
+///   4. **`If-Modified-Since`**: if `If-None-Match` is not present, the request
+///      is a `GET` or `HEAD`, and the response's `Last-Modified` is no later
+///      than the header's date, the response is `304 Not Modified`.
+///
+/// A `304 Not Modified` response retains the headers of the original response
+/// with the exception of `Content-Type` and `Content-Length` and has no body.
+/// A `412 Precondition Failed` is forwarded to the corresponding error
+/// catcher. Malformed headers, and dates in a response or request that can't
+/// be parsed, are ignored.
+///
+/// Because `Conditional` only sees the headers set by the responder it wraps,
+/// it should be the outermost responder. Note also that responders are only
+/// invoked once a handler has run: for requests with side effects, like `PUT`,
+/// `Conditional` can report that a precondition failed but cannot prevent the
+/// handler from running.
+///
+/// [`NamedFile`]: crate::fs::NamedFile
+/// [`FileServer`]: crate::fs::FileServer
+///
+/// # Example
+///
+/// ```rust
+/// # use rocket::get;
+/// use rocket::http::Header;
+/// use rocket::response::Conditional;
+///
+/// #[derive(rocket::Responder)]
+/// struct Article(String, Header<'static>);
+///
+/// #[get("/article")]
+/// fn article() -> Conditional<Article> {
+///     let modified = Header::new("Last-Modified", "Sun, 06 Nov 1994 08:49:37 GMT");
+///     Conditional(Article("...".into(), modified))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conditional<R>(pub R);
+
+/// Evaluates the preconditions in the request as described in [`Conditional`].
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Conditional<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        let response = self.0.respond_to(req)?;
+        apply_conditions(req, response)
+    }
+}
+
+/// Sets the `ETag` of a wrapped responder's response and evaluates conditional
+/// request headers against it.
+///
+/// An entity-tag identifies a particular representation of a resource: it
+/// should change whenever the response's body does. A _strong_ entity-tag, as
+/// created by [`Etagged::strong()`], promises that two responses with the same
+/// tag are byte-for-byte identical, while a _weak_ one, as created by
+/// [`Etagged::weak()`], only promises that they are semantically equivalent.
+/// Only strong entity-tags can be used to resume downloads via `If-Range`.
+///
+/// Once the `ETag` is set, the response is processed as by [`Conditional`].
+/// Any `ETag` set by the wrapped responder is overwritten.
+///
+/// An entity-tag may consist of any visible ASCII character except `"` as
+/// well as any non-ASCII characters. If the tag is invalid, an error is logged
+/// and an `Err` of `Status::InternalServerError` is returned.
+///
+/// # Example
+///
+/// ```rust
+/// # use rocket::get;
+/// use rocket::response::Etagged;
+///
+/// #[get("/version")]
+/// fn version() -> Etagged<&'static str> {
+///     Etagged::strong("v1.2", "Version 1.2")
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Etagged<R> {
+    responder: R,
+    tag: Cow<'static, str>,
+    weak: bool,
+}
+
+impl<R> Etagged<R> {
+    /// Wraps `responder` so that its response has the strong entity-tag `tag`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::Etagged;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let response = Etagged::strong("abc123", "Hello, world!");
+    /// ```
+    pub fn strong<T: Into<Cow<'static, str>>>(tag: T, responder: R) -> Self {
+        Etagged { responder, tag: tag.into(), weak: false }
+    }
+
+    /// Wraps `responder` so that its response has the weak entity-tag `tag`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::Etagged;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let response = Etagged::weak("abc123", "Hello, world!");
+    /// ```
+    pub fn weak<T: Into<Cow<'static, str>>>(tag: T, responder: R) -> Self {
+        Etagged { responder, tag: tag.into(), weak: true }
+    }
+}
+
+/// Sets the `ETag` header and evaluates preconditions as described in
+/// [`Etagged`].
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Etagged<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        if !self.tag.chars().all(|c| c == '!' || ('#'..='~').contains(&c) || !c.is_ascii()) {
+            error_!("Invalid entity-tag used in `Etagged`: {:?}.", self.tag);
+            return Err(Status::InternalServerError);
+        }
+
+        let mut response = self.responder.respond_to(req)?;
+        let prefix = if self.weak { "W/" } else { "" };
+        response.set_raw_header("ETag", format!("{}\"{}\"", prefix, self.tag));
+        apply_conditions(req, response)
+    }
+}
+
+/// Evaluates the preconditions in `req` against `response` as described in
+/// [`Conditional`].
+pub(crate) fn apply_conditions<'o>(
+    req: &Request<'_>,
+    mut response: Response<'o>
+) -> response::Result<'o> {
+    if response.status().class() != StatusClass::Success {
+        return Ok(response);
+    }
+
+    let headers = req.headers();
+    let etag = response.headers().get_one("ETag").and_then(EntityTag::parse);
+    let last_modified = response.headers().get_one("Last-Modified").and_then(parse_date);
+
+    if let Some(if_match) = headers.get_one("If-Match") {
+        if !list_matches(if_match, etag, true) {
+            return Err(Status::PreconditionFailed);
+        }
+    } else if let Some(since) = headers.get_one("If-Unmodified-Since").and_then(parse_date) {
+        if last_modified.map_or(false, |modified| modified > since) {
+            return Err(Status::PreconditionFailed);
+        }
+    }
+
+    let safe = matches!(req.method(), Method::Get | Method::Head);
+    let not_modified = if let Some(if_none_match) = headers.get_one("If-None-Match") {
+        if list_matches(if_none_match, etag, false) {
+            if !safe {
+                return Err(Status::PreconditionFailed);
+            }
+
+            true
+        } else {
+            false
+        }
+    } else if let Some(since) = headers.get_one("If-Modified-Since").and_then(parse_date) {
+        safe && last_modified.map_or(false, |modified| modified <= since)
+    } else {
+        false
+    };
+
+    if not_modified {
+        response.set_status(Status::NotModified);
+        response.remove_header("Content-Type");
+        response.remove_header("Content-Length");
+        response.body_mut().take();
+    }
+
+    Ok(response)
+}
+
+/// Formats `time` as an HTTP-date, suitable for a `Last-Modified` header.
+pub(crate) fn format_date(time: SystemTime) -> String {
+    httpdate::fmt_http_date(time)
+}
+
+/// Parses the HTTP-date `value`, truncated to whole seconds.
+fn parse_date(value: &str) -> Option<SystemTime> {
+    httpdate::parse_http_date(value.trim()).ok()
+}
+
+/// Returns `true` if `value`, an `If-Match` or `If-None-Match` header, is `*`
+/// and there is a representation, or if any entity-tag in `value` matches
+/// `etag`, compared strongly if `strong`. A malformed `value` never matches.
+fn list_matches(value: &str, etag: Option<EntityTag<'_>>, strong: bool) -> bool {
+    if value.trim() == "*" {
+        return true;
+    }
+
+    let etag = match etag {
+        Some(etag) => etag,
+        None => return false,
+    };
+
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|c| c == ',' || c == ' ' || c == '\t');
+        if rest.is_empty() {
+            return false;
+        }
+
+        match EntityTag::parse_prefix(rest) {
+            Some((tag, _)) if tag.matches(&etag, strong) => return true,
+            Some((_, remaining)) => rest = remaining,
+            None => return false,
+        }
+    }
+}
+
+/// A parsed entity-tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EntityTag<'a> {
+    /// The opaque tag, without quotes.
+    tag: &'a str,
+    weak: bool,
+}
+
+impl<'a> EntityTag<'a> {
+    /// Parses `value`, which must consist of exactly one entity-tag.
+    fn parse(value: &'a str) -> Option<Self> {
+        match EntityTag::parse_prefix(value.trim())? {
+            (tag, "") => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// Parses the entity-tag at the start of `value`, returning it along with
+    /// the remainder of `value`.
+    fn parse_prefix(value: &'a str) -> Option<(Self, &'a str)> {
+        let (weak, value) = match value.strip_prefix("W/") {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+
+        let value = value.strip_prefix('"')?;
+        let end = value.find('"')?;
+        Some((EntityTag { tag: &value[..end], weak }, &value[(end + 1)..]))
+    }
+
+    /// Compares `self` to `other` using the strong comparison function if
+    /// `strong` and the weak comparison function otherwise.
+    fn matches(&self, other: &EntityTag<'_>, strong: bool) -> bool {
+        self.tag == other.tag && (!strong || (!self.weak && !other.weak))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entity_tags() {
+        let tag = |tag, weak| EntityTag { tag, weak };
+
+        assert_eq!(EntityTag::parse("\"abc\""), Some(tag("abc", false)));
+        assert_eq!(EntityTag::parse(" W/\"abc\" "), Some(tag("abc", true)));
+        assert_eq!(EntityTag::parse("\"\""), Some(tag("", false)));
+        assert_eq!(EntityTag::parse("\"a,b\""), Some(tag("a,b", false)));
+        assert_eq!(EntityTag::parse("abc"), None);
+        assert_eq!(EntityTag::parse("w/\"abc\""), None);
+        assert_eq!(EntityTag::parse("\"abc"), None);
+        assert_eq!(EntityTag::parse("\"abc\"def"), None);
+
+        let strong = EntityTag::parse("\"a\"");
+        let weak = EntityTag::parse("W/\"a\"");
+        assert!(list_matches("\"b\", \"a\"", strong, true));
+        assert!(list_matches("\"x,y\", \"a\"", strong, true));
+        assert!(!list_matches("W/\"a\"", strong, true));
+        assert!(!list_matches("\"a\"", weak, true));
+        assert!(list_matches("W/\"a\"", strong, false));
+        assert!(list_matches("\"b\",W/\"a\"", weak, false));
+        assert!(!list_matches("\"b\", garbage, \"a\"", strong, false));
+        assert!(list_matches("*", None, false));
+        assert!(!list_matches("\"a\"", None, false));
+    }
+}
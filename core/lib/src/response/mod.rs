@@ -19,6 +19,8 @@ mod redirect;
 mod response;
 mod debug;
 mod body;
+mod ranged;
+mod conditional;
 
 pub(crate) mod flash;
 
@@ -35,6 +37,12 @@ pub use self::responder::Responder;
 pub use self::redirect::Redirect;
 pub use self::flash::Flash;
 pub use self::debug::Debug;
+pub use self::ranged::Ranged;
+pub use self::conditional::{Conditional, Etagged};
+
+pub(crate) use self::response::Upgrade;
+pub(crate) use self::ranged::apply_ranges;
+pub(crate) use self::conditional::{apply_conditions, format_date};
 
 /// Type alias for the `Result` of a [`Responder::respond_to()`] call.
 pub type Result<'r> = std::result::Result<Response<'r>, crate::http::Status>;
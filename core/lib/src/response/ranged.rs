@@ -0,0 +1,416 @@
+use std::io::{self, SeekFrom};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::ready;
+use rand::{Rng, distributions::Alphanumeric};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+use crate::request::Request;
+use crate::response::{self, Responder, Response};
+use crate::response::body::SizedBody;
+use crate::http::{ContentType, Method, Status};
+
+/// Serves the byte ranges requested via a `Range` header from the response of
+/// a wrapped responder.
+///
+/// `Ranged` makes any responder whose body is seekable and of known size
+/// resumable and seekable by clients. This includes responders for `&[u8]`,
+/// `Vec<u8>`, `&str`, and `String`, as well as [`NamedFile`] and thus
+/// [`FileServer`], which use `Ranged` internally. Responses that aren't `200
+/// OK` or whose body is not seekable with a known size, like streams, are
+/// passed through unchanged.
+///
+/// Otherwise, the response includes an `Accept-Ranges: bytes` header. If the
+/// request is a `GET` with a valid `Range` header in bytes, and any
+/// `If-Range` precondition holds, the response is one of:
+///
+///   * **`206 Partial Content`** with a `Content-Range` and the data for the
+///     requested range if exactly one satisfiable range was requested.
+///   * **`206 Partial Content`** with a `multipart/byteranges` body holding a
+///     part for each range if several satisfiable ranges were requested.
+///     Overlapping ranges are coalesced first.
+///   * **`416 Range Not Satisfiable`** with a `Content-Range` of `bytes
+///     */size` and no body if none of the requested ranges are satisfiable.
+///
+/// An `If-Range` containing an entity-tag holds only if it strongly matches
+/// the response's `ETag`. One containing a date holds only if it is exactly
+/// the response's `Last-Modified`. If `If-Range` does not hold, if the `Range`
+/// header is malformed or isn't in bytes, or if it requests more than 32
+/// ranges after coalescing, the full response is sent as if no range was
+/// requested.
+///
+/// Ranges are relative to the position of the body when it is returned by the
+/// wrapped responder. Because `Ranged` only sees the headers set by the
+/// responder it wraps, it should be the outermost responder: a `Content-Type`,
+/// `ETag`, or `Last-Modified` added by an enclosing responder is not
+/// considered.
+///
+/// [`NamedFile`]: crate::fs::NamedFile
+/// [`FileServer`]: crate::fs::FileServer
+///
+/// # Example
+///
+/// ```rust
+/// # use rocket::get;
+/// use rocket::response::Ranged;
+///
+/// #[get("/data")]
+/// fn data() -> Ranged<Vec<u8>> {
+///     # /*
+///     let data: Vec<u8> = /* a large amount of data */;
+///     # */
+///     # let data = vec![0; 1024];
+///     Ranged(data)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranged<R>(pub R);
+
+/// The maximum number of ranges to serve in a single response.
+const MAX_RANGES: usize = 32;
+
+/// Serves the response in `Ranged<R>`.
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Ranged<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        let response = self.0.respond_to(req)?;
+        Ok(apply_ranges(req, response))
+    }
+}
+
+/// Applies the `Range` in `req`, if any, to `response` as described in
+/// [`Ranged`].
+pub(crate) fn apply_ranges<'o>(req: &Request<'_>, mut response: Response<'o>) -> Response<'o> {
+    if response.status() != Status::Ok || response.headers().contains("Content-Range") {
+        return response;
+    }
+
+    let size = match response.body().seekable_size() {
+        Some(size) => size as u64,
+        None => return response,
+    };
+
+    response.set_raw_header("Accept-Ranges", "bytes");
+    if req.method() != Method::Get || !if_range_holds(req, &response) {
+        return response;
+    }
+
+    let ranges = match req.headers().get_one("Range").and_then(|r| parse_ranges(r, size)) {
+        Some(ranges) if ranges.len() <= MAX_RANGES => ranges,
+        _ => return response,
+    };
+
+    if ranges.is_empty() {
+        response.set_status(Status::RangeNotSatisfiable);
+        response.set_raw_header("Content-Range", format!("bytes */{}", size));
+        response.body_mut().take();
+        return response;
+    }
+
+    let segments = match ranges[..] {
+        [(start, end)] => {
+            let content_range = format!("bytes {}-{}/{}", start, end, size);
+            response.set_raw_header("Content-Range", content_range);
+            vec![Segment::Body { start, len: end - start + 1 }]
+        }
+        _ => {
+            let boundary: String = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(24)
+                .map(char::from)
+                .collect();
+
+            let content_type = response.headers().get_one("Content-Type").map(String::from);
+            let mut segments = vec![];
+            for (i, &(start, end)) in ranges.iter().enumerate() {
+                let mut head = if i == 0 { String::new() } else { String::from("\r\n") };
+                head += &format!("--{}\r\n", boundary);
+                if let Some(ref content_type) = content_type {
+                    head += &format!("Content-Type: {}\r\n", content_type);
+                }
+
+                head += &format!("Content-Range: bytes {}-{}/{}\r\n\r\n", start, end, size);
+                segments.push(Segment::Bytes(head.into_bytes()));
+                segments.push(Segment::Body { start, len: end - start + 1 });
+            }
+
+            segments.push(Segment::Bytes(format!("\r\n--{}--\r\n", boundary).into_bytes()));
+            let multipart = ContentType::new("multipart", "byteranges");
+            response.set_header(multipart.with_params(("boundary", boundary)));
+            segments
+        }
+    };
+
+    response.set_status(Status::PartialContent);
+    let max_chunk = response.body().max_chunk_size();
+    if let Some(body) = response.body_mut().take().into_seekable() {
+        let parts = Parts::new(body, segments);
+        response.set_sized_body(parts.len as usize, parts);
+        response.set_max_chunk_size(max_chunk);
+    }
+
+    response
+}
+
+/// Returns `true` if `req` has no `If-Range` or if it matches `response`.
+fn if_range_holds(req: &Request<'_>, response: &Response<'_>) -> bool {
+    let if_range = match req.headers().get_one("If-Range") {
+        Some(if_range) => if_range.trim(),
+        None => return true,
+    };
+
+    // Weak entity-tags never match; strong ones must match exactly.
+    if if_range.starts_with("W/") {
+        false
+    } else if if_range.starts_with('"') {
+        response.headers().get_one("ETag").map_or(false, |etag| etag.trim() == if_range)
+    } else {
+        let last_modified = response.headers().get_one("Last-Modified");
+        last_modified.map_or(false, |date| date.trim() == if_range)
+    }
+}
+
+/// Parses the `Range` header `value` into the inclusive byte ranges it
+/// requests which are satisfiable for a body of `size` bytes. Returns `None`
+/// if the header is malformed or not in bytes, and so must be ignored.
+fn parse_ranges(value: &str, size: u64) -> Option<Vec<(u64, u64)>> {
+    fn number(s: &str) -> Option<u64> {
+        s.bytes().all(|b| b.is_ascii_digit()).then(|| s.parse().ok()).flatten()
+    }
+
+    let (unit, specs) = value.split_once('=')?;
+    if !unit.trim().eq_ignore_ascii_case("bytes") {
+        return None;
+    }
+
+    let mut ranges = vec![];
+    let mut any = false;
+    for spec in specs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        any = true;
+        let (first, last) = spec.split_once('-')?;
+        let (first, last) = (first.trim(), last.trim());
+        if first.is_empty() {
+            // A suffix range: the last `n` bytes.
+            let n = number(last)?;
+            if n > 0 && size > 0 {
+                ranges.push((size - std::cmp::min(n, size), size - 1));
+            }
+        } else {
+            let first = number(first)?;
+            let last = match last {
+                "" => u64::MAX,
+                last => number(last).filter(|&last| last >= first)?,
+            };
+
+            if first < size {
+                ranges.push((first, std::cmp::min(last, size - 1)));
+            }
+        }
+    }
+
+    if !any {
+        return None;
+    }
+
+    // Coalesce overlapping ranges, keeping the requested order otherwise.
+    let mut sorted = ranges.clone();
+    sorted.sort_unstable();
+    let mut coalesced: Vec<(u64, u64)> = Vec::with_capacity(sorted.len());
+    for (start, end) in sorted {
+        match coalesced.last_mut() {
+            Some(last) if start <= last.1 => last.1 = std::cmp::max(last.1, end),
+            _ => coalesced.push((start, end)),
+        }
+    }
+
+    match coalesced.len() < ranges.len() {
+        true => Some(coalesced),
+        false => Some(ranges),
+    }
+}
+
+/// A piece of a ranged response body.
+enum Segment {
+    /// Bytes generated here: `multipart/byteranges` delimiters and headers.
+    Bytes(Vec<u8>),
+    /// `len` bytes of the original body, starting at `start`.
+    Body { start: u64, len: u64 },
+}
+
+impl Segment {
+    fn len(&self) -> u64 {
+        match self {
+            Segment::Bytes(bytes) => bytes.len() as u64,
+            Segment::Body { len, .. } => *len,
+        }
+    }
+}
+
+/// A ranged response body: a sequence of segments, some read from the
+/// original body. Seeking is virtual; `body` is only sought when read from.
+struct Parts<'r> {
+    body: SizedBody<'r>,
+    segments: Vec<Segment>,
+    /// The sum of the lengths of all segments.
+    len: u64,
+    /// The position in the ranged body.
+    pos: u64,
+    /// The position of `body` when it was taken, once known.
+    base: Option<u64>,
+    /// The current position of `body`, if known.
+    body_pos: Option<u64>,
+    /// Whether a seek of `body` is in progress.
+    seeking: bool,
+}
+
+impl<'r> Parts<'r> {
+    fn new(body: SizedBody<'r>, segments: Vec<Segment>) -> Self {
+        let len = segments.iter().map(|s| s.len()).sum();
+        Parts { body, segments, len, pos: 0, base: None, body_pos: None, seeking: false }
+    }
+
+    /// Returns the index of the segment containing `self.pos` and the offset
+    /// of `self.pos` in it.
+    fn locate(&self) -> Option<(usize, u64)> {
+        let mut offset = self.pos;
+        for (i, segment) in self.segments.iter().enumerate() {
+            if offset < segment.len() {
+                return Some((i, offset));
+            }
+
+            offset -= segment.len();
+        }
+
+        None
+    }
+
+    fn poll_seek_body(&mut self, cx: &mut Context<'_>, to: SeekFrom) -> Poll<io::Result<u64>> {
+        if !self.seeking {
+            self.body.as_mut().start_seek(to)?;
+            self.seeking = true;
+        }
+
+        let result = ready!(self.body.as_mut().poll_complete(cx));
+        self.seeking = false;
+        self.body_pos = result.as_ref().ok().copied();
+        Poll::Ready(result)
+    }
+}
+
+impl AsyncRead for Parts<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            let (i, offset) = match this.locate() {
+                Some(location) => location,
+                None => return Poll::Ready(Ok(())),
+            };
+
+            let (start, len) = match this.segments[i] {
+                Segment::Body { start, len } => (start, len),
+                Segment::Bytes(ref bytes) => {
+                    let bytes = &bytes[offset as usize..];
+                    let n = std::cmp::min(buf.remaining(), bytes.len());
+                    buf.put_slice(&bytes[..n]);
+                    this.pos += n as u64;
+                    return Poll::Ready(Ok(()));
+                }
+            };
+
+            let base = match this.base {
+                Some(base) => base,
+                None => {
+                    this.base = Some(ready!(this.poll_seek_body(cx, SeekFrom::Current(0)))?);
+                    continue;
+                }
+            };
+
+            let target = base + start + offset;
+            if this.body_pos != Some(target) {
+                ready!(this.poll_seek_body(cx, SeekFrom::Start(target)))?;
+                continue;
+            }
+
+            let max = std::cmp::min(buf.remaining() as u64, len - offset) as usize;
+            let mut limited = ReadBuf::new(buf.initialize_unfilled_to(max));
+            ready!(this.body.as_mut().poll_read(cx, &mut limited))?;
+            let n = limited.filled().len();
+            if n == 0 && max > 0 {
+                let error = "response body ended before the requested range";
+                return Poll::Ready(Err(io::Error::new(io::ErrorKind::UnexpectedEof, error)));
+            }
+
+            buf.advance(n);
+            this.pos += n as u64;
+            this.body_pos = Some(target + n as u64);
+            return Poll::Ready(Ok(()));
+        }
+    }
+}
+
+impl AsyncSeek for Parts<'_> {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        fn offset(base: u64, delta: i64) -> Option<u64> {
+            match delta >= 0 {
+                true => base.checked_add(delta as u64),
+                false => base.checked_sub(delta.unsigned_abs()),
+            }
+        }
+
+        let this = self.get_mut();
+        let pos = match position {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => offset(this.len, n),
+            SeekFrom::Current(n) => offset(this.pos, n),
+        };
+
+        this.pos = pos.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative position")
+        })?;
+
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_ranges;
+
+    #[test]
+    fn test_parse_ranges() {
+        assert_eq!(parse_ranges("bytes=0-499", 1000), Some(vec![(0, 499)]));
+        assert_eq!(parse_ranges("bytes=500-999", 1000), Some(vec![(500, 999)]));
+        assert_eq!(parse_ranges("bytes=500-", 1000), Some(vec![(500, 999)]));
+        assert_eq!(parse_ranges("bytes=-500", 1000), Some(vec![(500, 999)]));
+        assert_eq!(parse_ranges("bytes=-5000", 1000), Some(vec![(0, 999)]));
+        assert_eq!(parse_ranges("bytes=0-5000", 1000), Some(vec![(0, 999)]));
+        assert_eq!(parse_ranges("Bytes = 0-0 , -1", 1000), Some(vec![(0, 0), (999, 999)]));
+        assert_eq!(parse_ranges("bytes=9-9,0-0", 10), Some(vec![(9, 9), (0, 0)]));
+        assert_eq!(parse_ranges("bytes=0-4,2-6,8-9", 10), Some(vec![(0, 6), (8, 9)]));
+        assert_eq!(parse_ranges("bytes=5-9,0-5", 10), Some(vec![(0, 9)]));
+
+        // Unsatisfiable ranges are dropped.
+        assert_eq!(parse_ranges("bytes=1000-", 1000), Some(vec![]));
+        assert_eq!(parse_ranges("bytes=-0", 1000), Some(vec![]));
+        assert_eq!(parse_ranges("bytes=0-0", 0), Some(vec![]));
+        assert_eq!(parse_ranges("bytes=2000-3000,0-1", 1000), Some(vec![(0, 1)]));
+
+        // Malformed headers are ignored.
+        assert_eq!(parse_ranges("bytes=", 1000), None);
+        assert_eq!(parse_ranges("bytes=5-1", 1000), None);
+        assert_eq!(parse_ranges("bytes=-", 1000), None);
+        assert_eq!(parse_ranges("bytes=a-b", 1000), None);
+        assert_eq!(parse_ranges("bytes=+1-2", 1000), None);
+        assert_eq!(parse_ranges("bytes=0-1,junk", 1000), None);
+        assert_eq!(parse_ranges("items=0-1", 1000), None);
+        assert_eq!(parse_ranges("0-1", 1000), None);
+    }
+}
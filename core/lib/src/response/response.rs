@@ -1,9 +1,13 @@
 use std::{fmt, str};
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::pin::Pin;
 
 use tokio::io::{AsyncRead, AsyncSeek};
 
 use crate::http::{Header, HeaderMap, Status, ContentType, Cookie};
+use crate::http::uncased::{Uncased, AsUncased};
+use crate::data::IoHandler;
 use crate::response::Body;
 
 /// Builder for the [`Response`] type.
@@ -282,6 +286,39 @@ impl<'r> Builder<'r> {
         self
     }
 
+    /// Registers `handler` as the I/O handler for upgrade protocol `protocol`.
+    ///
+    /// See [`Response::add_upgrade()`] for notes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::pin::Pin;
+    /// # use rocket::data::{IoHandler, IoStream};
+    /// # use rocket::tokio::io;
+    /// # struct EchoHandler;
+    /// # #[rocket::async_trait]
+    /// # impl IoHandler for EchoHandler {
+    /// #     async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// use rocket::Response;
+    ///
+    /// let mut response = Response::build()
+    ///     .upgrade("raw-echo", EchoHandler)
+    ///     .finalize();
+    ///
+    /// assert!(response.upgrade("raw-echo").is_some());
+    /// ```
+    #[inline(always)]
+    pub fn upgrade<P, H>(&mut self, protocol: P, handler: H) -> &mut Builder<'r>
+        where P: Into<Uncased<'r>>, H: IoHandler + 'r
+    {
+        self.response.add_upgrade(protocol.into(), handler);
+        self
+    }
+
     /// Merges the `other` `Response` into `self` by setting any fields in
     /// `self` to the corresponding value in `other` if they are set in `other`.
     /// Fields in `self` are unchanged if they are not set in `other`. If a
@@ -404,6 +441,9 @@ impl<'r> Builder<'r> {
     }
 }
 
+/// A pending upgrade: the negotiated protocol and its I/O handler.
+pub(crate) type Upgrade<'r> = (Uncased<'r>, Pin<Box<dyn IoHandler + 'r>>);
+
 /// A response, as returned by types implementing
 /// [`Responder`](crate::response::Responder).
 ///
@@ -413,6 +453,7 @@ pub struct Response<'r> {
     status: Option<Status>,
     headers: HeaderMap<'r>,
     body: Body<'r>,
+    upgrade: HashMap<Uncased<'r>, Pin<Box<dyn IoHandler + 'r>>>,
 }
 
 impl<'r> Response<'r> {
@@ -837,6 +878,115 @@ impl<'r> Response<'r> {
         self.body_mut().set_max_chunk_size(size);
     }
 
+    /// Returns a mutable reference to the I/O handler registered for the
+    /// upgrade protocol `proto`, if any. Protocol names are compared
+    /// case-insensitively.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::pin::Pin;
+    /// # use rocket::data::{IoHandler, IoStream};
+    /// # use rocket::tokio::io;
+    /// # struct EchoHandler;
+    /// # #[rocket::async_trait]
+    /// # impl IoHandler for EchoHandler {
+    /// #     async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// use rocket::Response;
+    ///
+    /// let mut response = Response::new();
+    /// assert!(response.upgrade("raw-echo").is_none());
+    ///
+    /// response.add_upgrade("raw-echo", EchoHandler);
+    /// assert!(response.upgrade("raw-echo").is_some());
+    /// assert!(response.upgrade("RAW-ECHO").is_some());
+    /// ```
+    pub fn upgrade(&mut self, proto: &str) -> Option<Pin<&mut (dyn IoHandler + 'r)>> {
+        self.upgrade.get_mut(proto.as_uncased()).map(|h| h.as_mut())
+    }
+
+    /// Registers `handler` as the I/O handler for upgrade protocol `protocol`.
+    ///
+    /// If the request this response is for asks to upgrade the connection to
+    /// `protocol` via its `Upgrade` header, Rocket responds with a `101
+    /// Switching Protocols` and, once the connection has been upgraded, hands
+    /// the raw I/O stream to `handler`. Any body set in the response is
+    /// discarded. If the request does not ask to upgrade to `protocol`,
+    /// `handler` is dropped and the response is sent as usual. If a handler
+    /// for `protocol` was already registered, it is replaced. Protocol names
+    /// are compared case-insensitively.
+    ///
+    /// # `CONNECT`
+    ///
+    /// `CONNECT` requests, which ask to establish a tunnel rather than to
+    /// upgrade to a named protocol, are handled by the handler registered for
+    /// the protocol `CONNECT`, if any. If the response's status is successful,
+    /// the status is preserved, the body is discarded, and the handler is
+    /// handed the raw connection once the response has been sent. The target
+    /// of the `CONNECT` request is available via
+    /// [`Request::host()`](crate::Request::host()). `CONNECT` routes must be
+    /// created manually via [`Route::new()`](crate::Route::new()).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::pin::Pin;
+    ///
+    /// use rocket::Response;
+    /// use rocket::data::{IoHandler, IoStream};
+    /// use rocket::tokio::io;
+    ///
+    /// struct EchoHandler;
+    ///
+    /// #[rocket::async_trait]
+    /// impl IoHandler for EchoHandler {
+    ///     async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+    ///         let (mut reader, mut writer) = io::split(io);
+    ///         io::copy(&mut reader, &mut writer).await?;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut response = Response::new();
+    /// response.add_upgrade("raw-echo", EchoHandler);
+    /// assert!(response.upgrade("raw-echo").is_some());
+    /// ```
+    pub fn add_upgrade<N, H>(&mut self, protocol: N, handler: H)
+        where N: Into<Uncased<'r>>, H: IoHandler + 'r
+    {
+        self.upgrade.insert(protocol.into(), Box::pin(handler));
+    }
+
+    /// Returns the first protocol in the comma-separated list(s) `protocols`
+    /// for which an upgrade handler is registered, removing and returning the
+    /// handler alongside it.
+    ///
+    /// Returns `Ok(None)` if no handlers are registered or if `protocols` is
+    /// empty and `Err(())` if `protocols` names no registered protocol.
+    pub(crate) fn take_upgrade<'a, I: Iterator<Item = &'a str>>(
+        &mut self,
+        protocols: I
+    ) -> Result<Option<Upgrade<'r>>, ()> {
+        if self.upgrade.is_empty() {
+            return Ok(None);
+        }
+
+        let mut protocols = protocols.peekable();
+        let have_protocols = protocols.peek().is_some();
+        let found = protocols
+            .flat_map(|v| v.split(',').map(str::trim))
+            .find_map(|p| self.upgrade.remove_entry(p.as_uncased()));
+
+        match found {
+            Some((proto, handler)) => Ok(Some((proto, handler))),
+            None if have_protocols => Err(()),
+            None => Ok(None),
+        }
+    }
+
     /// Replaces this response's status and body with that of `other`, if they
     /// exist in `other`. Any headers that exist in `other` replace the ones in
     /// `self`. Any in `self` that aren't in `other` remain in `self`.
@@ -880,6 +1030,8 @@ impl<'r> Response<'r> {
         for (name, values) in other.headers.into_iter_raw() {
             self.headers.replace_all(name.into_cow(), values);
         }
+
+        self.upgrade.extend(other.upgrade);
     }
 
     /// Sets `self`'s status and body to that of `other` if they are not already
@@ -925,6 +1077,10 @@ impl<'r> Response<'r> {
         for (name, mut values) in other.headers.into_iter_raw() {
             self.headers.add_all(name.into_cow(), &mut values);
         }
+
+        for (proto, handler) in other.upgrade {
+            self.upgrade.entry(proto).or_insert(handler);
+        }
     }
 }
 
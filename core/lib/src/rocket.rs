@@ -1,15 +1,22 @@
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::fmt;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use either::Either;
+use futures::future::BoxFuture;
 use figment::{Figment, Provider};
 use yansi::Paint;
 
 use crate::error::{Error, ErrorKind};
 use crate::fairing::{Fairing, Fairings};
+use crate::health::Lifecycle;
 use crate::http::ext::IntoOwned;
+use crate::http::private::Connections;
 use crate::http::uri::{self, Origin};
+use crate::listener::Listener;
 use crate::log::PaintExt;
 use crate::phase::{Build, Building, Ignite, Igniting, Orbit, Orbiting, Phase};
 use crate::phase::{State, StateRef, Stateful};
@@ -449,6 +456,40 @@ impl Rocket<Build> {
         self
     }
 
+    /// Registers a readiness check named `name`. Once launched, Rocket is only
+    /// considered ready by the [`Health`](crate::health::Health) fairing when
+    /// every registered check resolves to `Ok(())`. This is typically called
+    /// by an ignite fairing which manages a resource, like a database pool,
+    /// that requests depend on. See [`health`](crate::health) for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::health::Health;
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     rocket::build()
+    ///         .attach(Health::default())
+    ///         .readiness_check("temp dir", |rocket| Box::pin(async move {
+    ///             match rocket.config().temp_dir.relative().exists() {
+    ///                 true => Ok(()),
+    ///                 false => Err("temporary directory is missing".into()),
+    ///             }
+    ///         }))
+    /// }
+    /// ```
+    pub fn readiness_check<N, F>(mut self, name: N, check: F) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        F: for<'a> Fn(&'a Rocket<Orbit>) -> BoxFuture<'a, Result<(), String>>,
+        F: Send + Sync + 'static,
+    {
+        self.checks.add(name.into(), Arc::new(check));
+        self
+    }
+
     /// Attaches a fairing to this instance of Rocket. No fairings are eagerly
     /// excuted; fairings are executed at their appropriate time.
     ///
@@ -573,6 +614,7 @@ impl Rocket<Build> {
             figment: self.0.figment,
             fairings: self.0.fairings,
             state: self.0.state,
+            checks: self.0.checks,
         });
 
         // Query the sentinels, abort if requested.
@@ -657,6 +699,7 @@ impl Rocket<Ignite> {
     }
 
     fn into_orbit(self) -> Rocket<Orbit> {
+        let connections = Connections::new(self.0.config.max_connections);
         Rocket(Orbiting {
             router: self.0.router,
             fairings: self.0.fairings,
@@ -664,12 +707,19 @@ impl Rocket<Ignite> {
             config: self.0.config,
             state: self.0.state,
             shutdown: self.0.shutdown,
+            checks: self.0.checks,
+            lifted_off: AtomicBool::new(false),
+            connections: Arc::new(connections),
+            requests: Arc::default(),
+            #[cfg(feature = "tls")]
+            tls_reloaders: vec![],
         })
     }
 
     async fn _local_launch(self) -> Rocket<Orbit> {
         let rocket = self.into_orbit();
         rocket.fairings.handle_liftoff(&rocket).await;
+        rocket.lifted_off.store(true, Ordering::Release);
         launch_info!(
             "{}{}",
             Paint::emoji("🚀 "),
@@ -685,8 +735,43 @@ impl Rocket<Ignite> {
                 Box::pin(async move {
                     rkt.fairings.handle_liftoff(&rkt).await;
 
-                    let proto = rkt.config.tls_enabled().then(|| "https").unwrap_or("http");
-                    let addr = format!("{}://{}", proto, rkt.config.address);
+                    let addrs = rkt.config.endpoints()
+                        .map(|(addr, tls)| {
+                            let proto = if tls { "https" } else { "http" };
+                            Paint::default(format!("{}://{}", proto, addr)).bold().underline()
+                        })
+                        .map(|addr| addr.to_string())
+                        .collect::<Vec<_>>();
+
+                    launch_info!(
+                        "{}{} {}",
+                        Paint::emoji("🚀 "),
+                        Paint::default("Rocket has launched from").bold(),
+                        addrs.join(", ")
+                    );
+                })
+            })
+            .await
+            .map(|rocket| rocket.into_ignite())
+    }
+
+    async fn _launch_on<L>(self, listener: L) -> Result<Rocket<Ignite>, Error>
+        where L: Listener + Send + Unpin + 'static,
+              <L as Listener>::Connection: Send + Unpin + 'static,
+    {
+        let address = listener.local_addr();
+        self.into_orbit()
+            .serve_on(listener, |rkt| {
+                let address = address.clone();
+                Box::pin(async move {
+                    rkt.fairings.handle_liftoff(rkt).await;
+
+                    let proto = if rkt.config.tls_enabled() { "https" } else { "http" };
+                    let addr = match address {
+                        Some(addr) => format!("{}://{}", proto, addr),
+                        None => format!("a custom {} listener", proto),
+                    };
+
                     launch_info!(
                         "{}{} {}",
                         Paint::emoji("🚀 "),
@@ -709,6 +794,7 @@ impl Rocket<Orbit> {
             config: self.0.config,
             state: self.0.state,
             shutdown: self.0.shutdown,
+            checks: self.0.checks,
         })
     }
 
@@ -764,6 +850,117 @@ impl Rocket<Orbit> {
     pub fn shutdown(&self) -> Shutdown {
         self.shutdown.clone()
     }
+
+    /// Returns the number of connections currently open across all
+    /// listeners. See [`Config::max_connections`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     rocket::build()
+    ///         .attach(AdHoc::on_request("Load", |req, _| Box::pin(async move {
+    ///             let rocket = req.rocket();
+    ///             let (conns, reqs) = (rocket.open_connections(), rocket.in_flight_requests());
+    ///             println!("{} connections, {} requests in flight", conns, reqs);
+    ///         })))
+    /// }
+    /// ```
+    pub fn open_connections(&self) -> usize {
+        self.connections.open()
+    }
+
+    /// Returns the number of requests received by the server which are
+    /// currently being served, including the current request, if any.
+    /// Requests that are shed because of [`Config::max_in_flight_requests`]
+    /// aren't counted. Neither are requests dispatched by a local
+    /// [`Client`](crate::local).
+    ///
+    /// # Example
+    ///
+    /// See [`Rocket::open_connections()`].
+    pub fn in_flight_requests(&self) -> usize {
+        self.requests.load(Ordering::Acquire)
+    }
+
+    /// Returns the current [`Lifecycle`] state: `Starting` while liftoff
+    /// fairings run, `Ready` once they complete, and `Draining` as soon as
+    /// [`Shutdown`] is requested. See [`health`](crate::health) for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::fairing::AdHoc;
+    /// use rocket::health::Lifecycle;
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     rocket::build()
+    ///         .attach(AdHoc::on_response("Draining", |req, res| Box::pin(async move {
+    ///             if req.rocket().lifecycle() == Lifecycle::Draining {
+    ///                 res.set_raw_header("Connection", "close");
+    ///             }
+    ///         })))
+    /// }
+    /// ```
+    pub fn lifecycle(&self) -> Lifecycle {
+        if self.shutdown.0.tripped() {
+            Lifecycle::Draining
+        } else if self.lifted_off.load(Ordering::Acquire) {
+            Lifecycle::Ready
+        } else {
+            Lifecycle::Starting
+        }
+    }
+
+    /// Reloads the certificate chain and key of every TLS listener, and the
+    /// certificate revocation lists for mutual TLS, if any, from its configured
+    /// paths. The new certificates are used for new TLS handshakes;
+    /// established connections are unaffected. See
+    /// [`TlsConfig`](crate::config::TlsConfig) for reloading periodically and
+    /// on `SIGHUP` instead.
+    ///
+    /// If any listener's certificates fail to load, that listener continues
+    /// to use its previous certificates, the remaining listeners are still
+    /// reloaded, and the first error is returned. When no TLS listeners are
+    /// being served, as with a local [`Client`](crate::local), this method
+    /// does nothing and returns `Ok`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::fairing::AdHoc;
+    /// use rocket::http::{Method, Status};
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     rocket::build()
+    ///         .attach(AdHoc::on_response("Reload TLS", |req, res| Box::pin(async move {
+    ///             if req.method() == Method::Post && req.uri().path() == "/admin/reload-tls" {
+    ///                 match req.rocket().reload_tls() {
+    ///                     Ok(()) => res.set_status(Status::NoContent),
+    ///                     Err(_) => res.set_status(Status::InternalServerError),
+    ///                 }
+    ///             }
+    ///         })))
+    /// }
+    /// ```
+    #[cfg(feature = "tls")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tls")))]
+    pub fn reload_tls(&self) -> std::io::Result<()> {
+        let mut result = Ok(());
+        for reloader in &self.tls_reloaders {
+            result = result.and(reloader.reload());
+        }
+
+        result
+    }
 }
 
 impl<P: Phase> Rocket<P> {
@@ -928,6 +1125,57 @@ impl<P: Phase> Rocket<P> {
             State::Orbit(s) => Ok(Rocket::from(s).into_ignite()),
         }
     }
+
+    /// Like [`Rocket::launch()`] but serves requests on connections accepted
+    /// from `listener` instead of on the configured addresses.
+    ///
+    /// This allows serving over any transport that implements [`Listener`],
+    /// such as in-process pipes, tunnels, or sockets set up in advance. As
+    /// with `launch()`, an instance in any phase can be launched: a `Rocket`
+    /// in the _build_ phase is first ignited, while one in the _ignite_ phase
+    /// is launched directly. Connections from `listener` are otherwise handled
+    /// exactly like those from a configured address: they are wrapped in TLS
+    /// if [`Config::tls`](crate::Config::tls) is configured, make their
+    /// [`Connection::peer_address()`](crate::listener::Connection::peer_address())
+    /// available as the remote address of requests, and are closed gracefully
+    /// on [`Shutdown`].
+    ///
+    /// The configured [`address`](crate::Config::address) and
+    /// [`listeners`](crate::Config::listeners) are not bound. Instead, in the
+    /// configuration seen by liftoff fairings and beyond, the `address` is
+    /// replaced by [`Listener::local_addr()`], if it returns `Some`, and
+    /// `listeners` is empty.
+    ///
+    /// # Error
+    ///
+    /// Returns an `Err` in the same cases as [`Rocket::launch()`], except that
+    /// no address is bound, and additionally if TLS is configured but could
+    /// not be set up.
+    ///
+    /// # Example
+    ///
+    /// Serve on a TCP listener bound ahead of time:
+    ///
+    /// ```rust,no_run
+    /// use rocket::listener::TcpListener;
+    ///
+    /// #[rocket::main]
+    /// async fn main() -> Result<(), rocket::Error> {
+    ///     let listener = TcpListener::bind("127.0.0.1:8000").await.expect("bind");
+    ///     let _rocket = rocket::build().launch_on(listener).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn launch_on<L>(self, listener: L) -> Result<Rocket<Ignite>, Error>
+        where L: Listener + Send + Unpin + 'static,
+              <L as Listener>::Connection: Send + Unpin + 'static,
+    {
+        match self.0.into_state() {
+            State::Build(s) => Rocket::from(s).ignite().await?._launch_on(listener).await,
+            State::Ignite(s) => Rocket::from(s)._launch_on(listener).await,
+            State::Orbit(s) => Ok(Rocket::from(s).into_ignite()),
+        }
+    }
 }
 
 #[doc(hidden)]
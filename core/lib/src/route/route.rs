@@ -188,6 +188,10 @@ pub struct Route {
     pub rank: isize,
     /// The media type this route matches against, if any.
     pub format: Option<MediaType>,
+    /// The number of seconds this route's handler may run for, overriding the
+    /// configured [`Timeouts::handler`](crate::config::Timeouts::handler), if
+    /// any. A value of `0` disables the deadline.
+    pub timeout: Option<u32>,
     /// The discovered sentinels.
     pub(crate) sentinels: Vec<Sentry>,
 }
@@ -252,6 +256,7 @@ impl Route {
         Route {
             name: None,
             format: None,
+            timeout: None,
             sentinels: Vec::new(),
             handler: Box::new(handler),
             rank, uri, method,
@@ -325,6 +330,7 @@ impl fmt::Debug for Route {
             .field("uri", &self.uri)
             .field("rank", &self.rank)
             .field("format", &self.format)
+            .field("timeout", &self.timeout)
             .finish()
     }
 }
@@ -344,6 +350,8 @@ pub struct StaticInfo {
     pub handler: for<'r> fn(&'r crate::Request<'_>, crate::Data<'r>) -> BoxFuture<'r>,
     /// The route's rank, if any.
     pub rank: Option<isize>,
+    /// The route's handler timeout, if any.
+    pub timeout: Option<u32>,
     /// Route-derived sentinels, if any.
     /// This isn't `&'static [SentryInfo]` because `type_name()` isn't `const`.
     pub sentinels: Vec<Sentry>,
@@ -361,6 +369,7 @@ impl From<StaticInfo> for Route {
             handler: Box::new(info.handler),
             rank: info.rank.unwrap_or_else(|| uri.default_rank()),
             format: info.format,
+            timeout: info.timeout,
             sentinels: info.sentinels.into_iter().collect(),
             uri,
         }
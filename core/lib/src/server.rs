@@ -1,31 +1,43 @@
 use std::io;
+use std::pin::Pin;
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
-use futures::future::{BoxFuture, Future, FutureExt};
+use futures::future::{BoxFuture, Future, FutureExt, TryFutureExt};
 use futures::stream::StreamExt;
-use rocket_http::private::{bind_tcp, bind_unix};
+use rocket_http::private::{bind_inherited, bind_tcp, bind_unix, Inherited};
 #[cfg(feature = "tls")]
-use rocket_http::tls::TlsListener;
+use rocket_http::tls::{CertResolver, TlsListener};
+#[cfg(feature = "mtls")]
+use rocket_http::tls::CrlVerifier;
 use tokio::sync::oneshot;
 use tokio::time::sleep;
 use yansi::Paint;
 
+use crate::data::{IoHandler, IoStream};
 use crate::error::{Error, ErrorKind};
-use crate::ext::{AsyncReadExt, CancellableIo, CancellableListener};
+use crate::ext::{ActivityGuard, AsyncReadExt, CancellableIo, CancellableListener};
+use crate::ext::{TimeoutIo, TimeoutListener};
 use crate::form::Form;
+use crate::tracing;
 use crate::outcome::Outcome;
 use crate::request::ConnectionMeta;
+use crate::response::{Body, Upgrade};
 use crate::{route, Config, Data, Orbit, Request, Response, Rocket};
+use crate::config::{ProxyProtocol, Timeouts, UnixSocket};
 
-use crate::http::private::{Connection, Incoming, Listener};
+use crate::http::private::{Connection, Connections, Counted, Incoming, Listener};
+use crate::http::uncased::Uncased;
 use crate::http::{hyper, uri::Origin, Header, Method, Status};
+use crate::http::bindable::BindableAddr;
+use crate::http::proxy_protocol::ProxyListener;
 
 // A token returned to force the execution of one method before another.
 pub(crate) struct RequestToken;
 
-async fn handle<Fut, T, F>(name: Option<&str>, run: F) -> Option<T>
+async fn handle<Fut, T, F>(name: Option<&str>, request_id: &str, run: F) -> Option<T>
 where
     F: FnOnce() -> Fut,
     Fut: Future<Output = T>,
@@ -35,8 +47,9 @@ where
     macro_rules! panic_info {
         ($name:expr, $e:expr) => {{
             match $name {
-                Some(name) => error_!("Handler {} panicked.", Paint::white(name)),
-                None => error_!("A handler panicked."),
+                Some(name) => error_!("Handler {} panicked (request {}).",
+                    Paint::white(name), request_id),
+                None => error_!("A handler panicked (request {}).", request_id),
             };
 
             info_!("This is an application bug.");
@@ -70,6 +83,8 @@ where
 async fn hyper_service_fn(
     rocket: Arc<Rocket<Orbit>>,
     conn: ConnectionMeta,
+    conn_span: tracing::Span,
+    activity: Option<ActivityGuard>,
     hyp_req: hyper::Request<hyper::Body>,
 ) -> Result<hyper::Response<hyper::Body>, io::Error> {
     // This future must return a hyper::Response, but the response body might
@@ -77,16 +92,44 @@ async fn hyper_service_fn(
     // sends the response metadata (and a body channel) prior.
     let (tx, rx) = oneshot::channel();
 
-    tokio::spawn(async move {
-        // Convert a Hyper request into a Rocket request.
-        let (h_parts, mut h_body) = hyp_req.into_parts();
+    let span = tracing::request(Some(&conn_span), hyp_req.method(), hyp_req.uri());
+    let (timer, task_span) = (tracing::Timer::start(), span.clone());
+    tokio::spawn(tracing::instrument(async move {
+        // Convert a Hyper request into a Rocket request. We move the parts
+        // into the request next, so take the pending upgrade, if any, now.
+        let (mut h_parts, mut h_body) = hyp_req.into_parts();
+        let pending_upgrade = h_parts.extensions.remove::<hyper::upgrade::OnUpgrade>();
         match Request::from_hyp(&rocket, &h_parts, Some(conn)) {
             Ok(mut req) => {
+                tracing::record_request_id(&span, req.request_id());
+                let max = rocket.config.max_in_flight_requests;
+                let _in_flight = match InFlight::enter(&rocket.requests, max) {
+                    Some(in_flight) => in_flight,
+                    None => return rocket.shed(&req, tx).await,
+                };
+
                 // Convert into Rocket `Data`, dispatch request, write response.
                 let mut data = Data::from(&mut h_body);
+                if let Some(timeout) = Timeouts::duration(rocket.config.timeouts.body) {
+                    data = data.with_timeout(timeout);
+                }
+
                 let token = rocket.preprocess_request(&mut req, &mut data).await;
-                let response = rocket.dispatch(token, &mut req, data).await;
-                rocket.send_response(response, tx).await;
+                let mut response = rocket.dispatch(token, &req, data).await;
+                tracing::record_response(&span, response.status(), timer);
+
+                // Hyper only gives us a pending upgrade if one was requested.
+                let upgrade = pending_upgrade.and_then(|pending| {
+                    rocket.prepare_upgrade(&req, &mut response)
+                        .map(|(proto, handler)| (proto, handler, pending))
+                });
+
+                match upgrade {
+                    Some((proto, handler, pending)) => {
+                        rocket.handle_upgrade(response, proto, handler, pending, tx).await
+                    }
+                    None => rocket.send_response(response, tx).await,
+                }
             }
             Err(e) => {
                 // TODO: We don't have a request to pass in, so we fabricate
@@ -95,16 +138,64 @@ async fn hyper_service_fn(
                 error!("Bad incoming request: {}", e);
                 let dummy = Request::new(&rocket, Method::Get, Origin::ROOT);
                 let response = rocket.handle_error(Status::BadRequest, &dummy).await;
+                tracing::record_response(&span, response.status(), timer);
                 rocket.send_response(response, tx).await;
             }
         }
-    });
+
+        // The request, including any upgraded I/O, is now complete.
+        drop(activity);
+    }, task_span));
 
     // Receive the response written to `tx` by the task above.
     rx.await
         .map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))
 }
 
+/// Counts a request as in flight until it is dropped.
+struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    /// Counts a request in `requests` unless `max` are already in flight, in
+    /// which case the request should be shed.
+    fn enter(requests: &Arc<AtomicUsize>, max: Option<usize>) -> Option<InFlight> {
+        let mut count = requests.load(Ordering::Acquire);
+        loop {
+            if max.map_or(false, |max| count >= max) {
+                return None;
+            }
+
+            let (success, failure) = (Ordering::AcqRel, Ordering::Acquire);
+            match requests.compare_exchange_weak(count, count + 1, success, failure) {
+                Ok(_) => return Some(InFlight(requests.clone())),
+                Err(actual) => count = actual,
+            }
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Runs the I/O handler `io_handler` for upgraded protocol `proto` on `io`,
+/// logging its failure, if any.
+pub(crate) async fn run_io_handler(
+    proto: &Uncased<'_>,
+    io_handler: Pin<Box<dyn IoHandler + '_>>,
+    io: IoStream,
+) {
+    if let Err(e) = io_handler.io(io).await {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            warn!("Upgraded {} I/O handler was closed.", proto);
+        } else {
+            error!("Upgraded {} I/O handler failed: {}", proto, e);
+        }
+    }
+}
+
 impl Rocket<Orbit> {
     /// Wrapper around `_send_response` to log a success or failure.
     #[inline]
@@ -170,8 +261,102 @@ impl Rocket<Orbit> {
         Ok(())
     }
 
+    /// Responds to `request`, without routing it, with a `503` asking the
+    /// client to retry after `retry_after` seconds. Response fairings run as
+    /// for any other response, so the request is logged by them.
+    async fn shed<'s, 'r: 's>(
+        &'s self,
+        request: &'r Request<'s>,
+        tx: oneshot::Sender<hyper::Response<hyper::Body>>,
+    ) {
+        warn!("{} [{}]:", request, request.request_id());
+        warn_!("Too many requests in flight. Shedding request.");
+        let mut response = self.handle_error(Status::ServiceUnavailable, request).await;
+        response.set_raw_header("Retry-After", self.config.retry_after.to_string());
+        self.echo_request_id(request, &mut response);
+        self.fairings.handle_response(request, &mut response).await;
+        self.send_response(response, tx).await;
+    }
+
+    /// Sets the `request_id_header`, if any, to `request`'s ID in `response`
+    /// unless `response` already has it.
+    fn echo_request_id<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if let Some(name) = request.rocket().config.request_id_header.as_ref() {
+            if !response.headers().contains(name.as_str()) {
+                response.set_header(Header::new(name.as_str(), request.request_id()));
+            }
+        }
+    }
+
+    /// If `request` asks to upgrade the connection to a protocol for which
+    /// `response` has registered an I/O handler, turns `response` into a `101
+    /// Switching Protocols` response and returns the protocol and its handler.
+    ///
+    /// `CONNECT` requests instead use the handler registered for `CONNECT`,
+    /// if any, and only if `response` is successful. Its status is kept.
+    pub(crate) fn prepare_upgrade<'r>(
+        &self,
+        request: &Request<'_>,
+        response: &mut Response<'r>,
+    ) -> Option<Upgrade<'r>> {
+        if request.method() == Method::Connect {
+            if !response.status().class().is_success() {
+                return None;
+            }
+
+            let (proto, handler) = response.take_upgrade(std::iter::once("CONNECT")).ok()??;
+            // A `2xx` to a `CONNECT` must not have a `Content-Length`, which
+            // we'd send for a sized body, even an empty one. Use an unsized one.
+            info_!("Tunneling connection via {}.", Paint::white("CONNECT").bold());
+            *response.body_mut() = Body::with_unsized(tokio::io::empty());
+            return Some((proto, handler));
+        }
+
+        match response.take_upgrade(request.headers().get("Upgrade")) {
+            Ok(Some((proto, handler))) => {
+                info_!("Upgrading connection to {}.", Paint::white(&proto).bold());
+                response.set_status(Status::SwitchingProtocols);
+                response.set_raw_header("Connection", "Upgrade");
+                response.set_raw_header("Upgrade", proto.clone().into_cow());
+                *response.body_mut() = Body::default();
+                Some((proto, handler))
+            }
+            Ok(None) => None,
+            Err(()) => {
+                warn_!("Request wants upgrade but no I/O handler matched.");
+                info_!("Request is not being upgraded.");
+                None
+            }
+        }
+    }
+
+    /// Sends the `101` `response`, waits for hyper to hand over the upgraded
+    /// connection, and then runs `io_handler` to completion on it.
+    async fn handle_upgrade<'r>(
+        &self,
+        response: Response<'r>,
+        proto: Uncased<'r>,
+        io_handler: Pin<Box<dyn IoHandler + 'r>>,
+        pending_upgrade: hyper::upgrade::OnUpgrade,
+        tx: oneshot::Sender<hyper::Response<hyper::Body>>,
+    ) {
+        self.send_response(response, tx).await;
+
+        match pending_upgrade.await {
+            Ok(io_stream) => {
+                info_!("Upgrade successful.");
+                run_io_handler(&proto, io_handler, io_stream.into()).await;
+            }
+            Err(e) => {
+                warn!("Response indicated upgrade, but upgrade failed.");
+                warn_!("Upgrade error: {}", e);
+            }
+        }
+    }
+
     /// Preprocess the request for Rocket things. Currently, this means:
     ///
+    ///   * Decoding the body if it has a supported `Content-Encoding`.
     ///   * Rewriting the method in the request if _method form field exists.
     ///   * Run the request fairings.
     ///
@@ -181,6 +366,9 @@ impl Rocket<Orbit> {
         req: &mut Request<'_>,
         data: &mut Data<'_>,
     ) -> RequestToken {
+        #[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+        data.decode(req);
+
         // Check if this is a form and if the form contains the special _method
         // field which we use to reinterpret the request's method.
         let (min_len, max_len) = ("_method=get".len(), "_method=delete".len());
@@ -212,7 +400,12 @@ impl Rocket<Orbit> {
         request: &'r Request<'s>,
         data: Data<'r>,
     ) -> Response<'r> {
-        info!("{}:", request);
+        info!("{} [{}]:", request, request.request_id());
+
+        #[cfg(feature = "tls")]
+        if let Some(tls) = request.tls_info() {
+            info_!("{} {}", Paint::default("TLS:").bold(), tls);
+        }
 
         // Remember if the request is `HEAD` for later body stripping.
         let was_head_request = request.method() == Method::Head;
@@ -228,6 +421,9 @@ impl Rocket<Orbit> {
             }
         }
 
+        // Echo the request's ID if it isn't already there.
+        self.echo_request_id(request, &mut response);
+
         // Run the response fairings.
         self.fairings.handle_response(request, &mut response).await;
 
@@ -244,6 +440,7 @@ impl Rocket<Orbit> {
         request: &'r Request<'s>,
         data: Data<'r>,
     ) -> Response<'r> {
+        let timed_out = data.timed_out();
         let mut response = match self.route(request, data).await {
             Outcome::Success(response) => response,
             Outcome::Forward(data) if request.method() == Method::Head => {
@@ -261,6 +458,14 @@ impl Rocket<Orbit> {
             Outcome::Failure(status) => self.handle_error(status, request).await,
         };
 
+        // A client that's too slow to send the body gets a `408`, whatever the
+        // handler made of the failed read, and the connection is closed.
+        if timed_out.get() {
+            warn_!("Request body read timed out.");
+            response = self.handle_error(Status::RequestTimeout, request).await;
+            response.set_raw_header("Connection", "close");
+        }
+
         // Set the cookies. Note that error responses will only include cookies
         // set by the error handler. See `handle_error` for more.
         let delta_jar = request.cookies().take_delta_jar();
@@ -287,11 +492,25 @@ impl Rocket<Orbit> {
             // Retrieve and set the requests parameters.
             info_!("Matched: {}", route);
             request.set_route(route);
+            tracing::record_route(route);
 
             let name = route.name.as_deref();
-            let outcome = handle(name, || route.handler.handle(request, data))
-                .await
-                .unwrap_or(Outcome::Failure(Status::InternalServerError));
+            let id = request.request_id();
+            let handler = handle(name, id, || route.handler.handle(request, data));
+            let handler = tracing::instrument(handler, tracing::handler(route));
+            let timeout = route.timeout.unwrap_or(self.config.timeouts.handler);
+            let outcome = match Timeouts::duration(timeout) {
+                Some(duration) => match tokio::time::timeout(duration, handler).await {
+                    Ok(outcome) => outcome,
+                    Err(_) => {
+                        error_!("Handler exceeded its {}s deadline (request {}).", timeout, id);
+                        Some(Outcome::Failure(self.config.timeouts.handler_status))
+                    }
+                },
+                None => handler.await,
+            };
+
+            let outcome = outcome.unwrap_or(Outcome::Failure(Status::InternalServerError));
 
             // Check if the request processing completed (Some) or if the
             // request needs to be forwarded. If it does, continue the loop
@@ -303,7 +522,7 @@ impl Rocket<Orbit> {
             }
         }
 
-        error_!("No matching routes for {}.", request);
+        error_!("No matching routes for {} (request {}).", request, request.request_id());
         Outcome::Forward(data)
     }
 
@@ -330,7 +549,8 @@ impl Rocket<Orbit> {
         if let Some(catcher) = self.router.catch(status, req) {
             warn_!("Responding with registered {} catcher.", catcher);
             let name = catcher.name.as_deref();
-            handle(name, || catcher.handler.handle(status, req))
+            let handler = handle(name, req.request_id(), || catcher.handler.handle(status, req));
+            tracing::instrument(handler, tracing::catcher(catcher, status))
                 .await
                 .map(|result| result.map_err(Some))
                 .unwrap_or_else(|| Err(None))
@@ -357,7 +577,7 @@ impl Rocket<Orbit> {
 
         // If it fails and it's not a 500, try the 500 catcher.
         if status != Status::InternalServerError {
-            error_!("Catcher failed. Attemping 500 error catcher.");
+            error_!("Catcher failed (request {}). Attemping 500 error catcher.", req.request_id());
             status = Status::InternalServerError;
             if let Ok(r) = self.invoke_catcher(status, req).await {
                 return r;
@@ -365,80 +585,93 @@ impl Rocket<Orbit> {
         }
 
         // If it failed again or if it was already a 500, use Rocket's default.
-        error_!("{} catcher failed. Using Rocket default 500.", status.code);
+        error_!("{} catcher failed (request {}). Using Rocket default 500.",
+            status.code, req.request_id());
         crate::catcher::default_handler(Status::InternalServerError, req)
     }
 
+    /// Binds to every address in the configuration, runs `ready`, and then
+    /// serves requests on all of them until shutdown.
+    ///
+    /// All addresses are bound before `ready` runs so that a failure to bind
+    /// any one of them is reported before any request is served. The bound
+    /// addresses are written back into the configuration: a TCP address with
+    /// port `0` is replaced by the address actually bound to.
     pub(crate) async fn default_server_for_address<C>(mut self, ready: C) -> Result<Self, Error>
     where
         C: for<'a> Fn(&'a Self) -> BoxFuture<'a, ()>,
     {
-        use crate::http::bindable::BindableAddr;
-
-        // If TLS is enabled, we have to try to add TLS and run the server with it if it succeeded.
-        // Otherwise we just run the server as normal.
         #[cfg(feature = "tls")]
-        macro_rules! tls_then_run {
-            ($l:ident) => {
-                match self.maybe_add_tls($l).await? {
-                    Ok(l) => {
-                        ready(&mut self).await;
-                        self.http_server(l).await
-                    }
-                    Err(l) => {
-                        ready(&mut self).await;
-                        self.http_server(l).await
-                    }
-                }
-            };
-        }
-        #[cfg(not(feature = "tls"))]
-        macro_rules! tls_then_run {
-            ($l:ident) => {{
-                ready(&mut self).await;
-                self.http_server($l).await
-            }};
+        let primary_tls = self.config.tls.as_ref().filter(|_| self.config.tls_enabled());
+
+        let mut bound = Vec::with_capacity(1 + self.config.listeners.len());
+        bound.push(Bound::bind(
+            &self.config.address,
+            #[cfg(feature = "tls")] primary_tls,
+            self.config.proxy_protocol.as_ref(),
+            self.config.unix_socket.as_ref(),
+            &self.connections,
+        ).await?);
+
+        for listener in &self.config.listeners {
+            bound.push(Bound::bind(
+                &listener.address,
+                #[cfg(feature = "tls")] listener.tls.as_ref().filter(|_| listener.tls_enabled()),
+                listener.proxy_protocol.as_ref(),
+                listener.unix_socket.as_ref(),
+                &self.connections,
+            ).await?);
         }
 
-        match &mut self.config.address {
-            BindableAddr::Tcp(ref mut addr) => {
-                let l = bind_tcp(addr.clone()).await.map_err(ErrorKind::Bind)?;
-                if let Ok(new_addr) = l.local_addr() {
-                    *addr = new_addr;
-                }
-                tls_then_run!(l)
-            }
-            BindableAddr::Unix(path) => {
-                let l = bind_unix(&path).map_err(ErrorKind::Bind)?;
-                tls_then_run!(l)
+        let config = &mut self.config;
+        let addresses = std::iter::once(&mut config.address)
+            .chain(config.listeners.iter_mut().map(|l| &mut l.address));
+
+        for (address, bound) in addresses.zip(&bound) {
+            if let Some(actual) = bound.address.clone() {
+                *address = actual;
             }
         }
+
+        ready(&self).await;
+        self.lifted_off.store(true, Ordering::Release);
+        self.http_server(bound).await
     }
 
-    #[cfg(feature = "tls")]
-    async fn maybe_add_tls<C, L>(&self, raw: L) -> Result<Result<TlsListener<L>, L>, Error>
+    /// Serves on `listener`, with TLS and PROXY protocol if they are
+    /// configured, instead of on the configured addresses. Calls `ready` once
+    /// the listener is set up.
+    ///
+    /// The configured `address` is replaced by the address of `listener`, if
+    /// it has one, and the configured `listeners` are removed.
+    pub(crate) async fn serve_on<L, C>(mut self, listener: L, ready: C) -> Result<Self, Error>
     where
-        C: Connection + Unpin,
-        L: Listener<Connection = C> + Unpin,
+        L: Listener + Send + Unpin + 'static,
+        <L as Listener>::Connection: Send + Unpin + 'static,
+        C: for<'a> Fn(&'a Self) -> BoxFuture<'a, ()>,
     {
-        if self.config.tls_enabled() {
-            if let Some(ref config) = self.config.tls {
-                let config = config.to_native_config().map_err(ErrorKind::Io)?;
-                let l = TlsListener::bind(raw, config)
-                    .await
-                    .map_err(ErrorKind::Bind)?;
-                return Ok(Ok(l));
-            }
+        #[cfg(feature = "tls")]
+        let tls = self.config.tls.as_ref().filter(|_| self.config.tls_enabled());
+
+        let proxy_protocol = self.config.proxy_protocol.as_ref();
+        let bound = Bound::layered(
+            listener,
+            #[cfg(feature = "tls")] tls,
+            proxy_protocol,
+            &self.connections,
+        ).await?;
+        if let Some(address) = bound.address.clone() {
+            self.config.address = address;
         }
-        return Ok(Err(raw));
+
+        self.config.listeners.clear();
+        ready(&self).await;
+        self.lifted_off.store(true, Ordering::Release);
+        self.http_server(vec![bound]).await
     }
 
     // TODO.async: Solidify the Listener APIs and make this function public
-    pub(crate) async fn http_server<L>(self, listener: L) -> Result<Self, Error>
-    where
-        L: Listener + Send,
-        <L as Listener>::Connection: Send + Unpin + 'static,
-    {
+    pub(crate) async fn http_server(mut self, listeners: Vec<Bound>) -> Result<Self, Error> {
         // Emit a warning if we're not running inside of Rocket's async runtime.
         if self.config.profile == Config::DEBUG_PROFILE {
             tokio::task::spawn_blocking(|| {
@@ -454,7 +687,7 @@ impl Rocket<Orbit> {
             });
         }
 
-        // Set up cancellable I/O from the given listener. Shutdown occurs when
+        // Set up cancellable I/O from the given listeners. Shutdown occurs when
         // `Shutdown` (`TripWire`) resolves. This can occur directly through a
         // notification or indirectly through an external signal which, when
         // received, results in triggering the notify.
@@ -479,40 +712,20 @@ impl Rocket<Orbit> {
             });
         }
 
-        // Save the keep-alive value for later use; we're about to move `self`.
-        let keep_alive = self.config.keep_alive;
-
-        // Create the Hyper `Service`.
-        let rocket = Arc::new(self);
-        let service_fn = |conn: &CancellableIo<_, L::Connection>| {
-            let rocket = rocket.clone();
-            let connection = ConnectionMeta {
-                remote: conn.peer_address(),
-                client_certificates: conn.peer_certificates(),
-            };
-
-            async move {
-                Ok::<_, std::convert::Infallible>(hyper::service::service_fn(move |req| {
-                    hyper_service_fn(rocket.clone(), connection.clone(), req)
-                }))
+        // Reload TLS certificates periodically and on `SIGHUP`, as configured.
+        #[cfg(feature = "tls")] {
+            self.tls_reloaders = listeners.iter().filter_map(|b| b.tls.clone()).collect();
+            for reloader in &self.tls_reloaders {
+                reloader.spawn(shutdown.clone());
             }
-        };
-
-        // NOTE: `hyper` uses `tokio::spawn()` as the default executor.
-        let listener = CancellableListener::new(shutdown.clone(), listener, grace, mercy);
-        let builder = hyper::server::Server::builder(Incoming::new(listener).nodelay(true));
-
-        #[cfg(feature = "http2")]
-        let builder = builder.http2_keep_alive_interval(match keep_alive {
-            0 => None,
-            n => Some(Duration::from_secs(n as u64)),
-        });
+        }
 
-        let server = builder
-            .http1_keepalive(keep_alive != 0)
-            .http1_preserve_header_case(true)
-            .serve(hyper::service::make_service_fn(service_fn))
-            .with_graceful_shutdown(shutdown.clone());
+        // Start a server for each listener, all sharing one `Rocket`. The
+        // combined `server` future resolves when every server has, or as soon
+        // as any one fails.
+        let rocket = Arc::new(self);
+        let servers = listeners.into_iter().map(|bound| (bound.serve)(rocket.clone()));
+        let server = futures::future::try_join_all(servers).map_ok(|_| ());
 
         // This deserves some exaplanation.
         //
@@ -529,7 +742,10 @@ impl Rocket<Orbit> {
         // signal. That signal is the `shutdown` future. When the future
         // resolves, `CancellableIo` begins to terminate in grace, mercy, and
         // finally force close phases. Since all connections are wrapped in
-        // `CancellableIo`, this eventually ends all I/O.
+        // `CancellableIo`, this eventually ends all I/O. This includes upgraded
+        // connections: the `IoStream` handed to an `IoHandler` wraps the same
+        // `CancellableIo`, and the handler runs in the task serving the
+        // request, which holds a reference to the `Arc<Rocket>` below.
         //
         // At that point, unless a user spawned an infinite, stand-alone task
         // that isn't monitoring `Shutdown`, all tasks should resolve. This
@@ -604,3 +820,295 @@ impl Rocket<Orbit> {
         }
     }
 }
+
+/// Serves requests from a listener to a `Rocket` until shutdown.
+type Serve = Box<
+    dyn FnOnce(Arc<Rocket<Orbit>>) -> BoxFuture<'static, Result<(), hyper::Error>> + Send
+>;
+
+/// A listener bound to an address, ready to be served by [`Rocket::http_server()`].
+pub(crate) struct Bound {
+    /// The address the listener is bound to, if it is known.
+    pub(crate) address: Option<BindableAddr>,
+    serve: Serve,
+    /// The reloader of the listener's TLS certificates, if it uses TLS.
+    #[cfg(feature = "tls")]
+    tls: Option<TlsReloader>,
+}
+
+impl Bound {
+    pub(crate) fn new<L>(listener: L) -> Self
+    where
+        L: Listener + Send + 'static,
+        <L as Listener>::Connection: Send + Unpin + 'static,
+    {
+        Bound::serving(listener, #[cfg(feature = "mtls")] None)
+    }
+
+    /// Like [`Bound::new()`], identifying mutual TLS clients by `identity`.
+    fn serving<L>(
+        listener: L,
+        #[cfg(feature = "mtls")] identity: Option<Arc<[crate::config::IdentityAttribute]>>,
+    ) -> Self
+    where
+        L: Listener + Send + 'static,
+        <L as Listener>::Connection: Send + Unpin + 'static,
+    {
+        Bound {
+            address: listener.local_addr(),
+            serve: Box::new(move |rocket| {
+                serve(rocket, listener, #[cfg(feature = "mtls")] identity).boxed()
+            }),
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// Binds to `address`, with TLS if `tls` is `Some` and reading PROXY
+    /// protocol headers if `proxy_protocol` is `Some`. A Unix socket is bound
+    /// as configured by `unix_socket`, if it is `Some`. Connections whose
+    /// PROXY headers are being read are counted in `connections`.
+    async fn bind(
+        address: &BindableAddr,
+        #[cfg(feature = "tls")] tls: Option<&crate::config::TlsConfig>,
+        proxy_protocol: Option<&ProxyProtocol>,
+        unix_socket: Option<&UnixSocket>,
+        connections: &Arc<Connections>,
+    ) -> Result<Bound, Error> {
+        macro_rules! bound {
+            ($listener:expr) => {
+                Bound::layered(
+                    $listener,
+                    #[cfg(feature = "tls")] tls,
+                    proxy_protocol,
+                    connections,
+                ).await
+            };
+        }
+
+        match address {
+            BindableAddr::Tcp(addr) => bound!(bind_tcp(*addr).await.map_err(ErrorKind::Bind)?),
+            BindableAddr::Unix(path) => {
+                let options = unix_socket.map(|u| u.to_options()).unwrap_or_default();
+                bound!(bind_unix(path, &options).map_err(ErrorKind::Bind)?)
+            }
+            BindableAddr::Fd(_) | BindableAddr::Systemd(_) => {
+                match bind_inherited(address).map_err(ErrorKind::Bind)? {
+                    Inherited::Tcp(listener) => bound!(listener),
+                    Inherited::Unix(listener) => bound!(listener),
+                }
+            }
+        }
+    }
+
+    /// Wraps `listener` in a PROXY protocol listener if `proxy_protocol` is
+    /// `Some` and then in a TLS listener if `tls` is `Some`. Connections whose
+    /// PROXY headers are being read are counted in `connections`.
+    pub(crate) async fn layered<L>(
+        listener: L,
+        #[cfg(feature = "tls")] tls: Option<&crate::config::TlsConfig>,
+        proxy_protocol: Option<&ProxyProtocol>,
+        connections: &Arc<Connections>,
+    ) -> Result<Bound, Error>
+    where
+        L: Listener + Send + Unpin + 'static,
+        <L as Listener>::Connection: Send + Unpin + 'static,
+    {
+        if let Some(config) = proxy_protocol {
+            let timeout = Duration::from_secs(config.timeout as u64);
+            let listener = ProxyListener::new(listener, config.trusted.clone(), timeout)
+                .connections(connections.clone());
+            return Bound::secured(listener, #[cfg(feature = "tls")] tls).await;
+        }
+
+        Bound::secured(listener, #[cfg(feature = "tls")] tls).await
+    }
+
+    /// Wraps `listener` in a TLS listener if `tls` is `Some`.
+    async fn secured<L>(
+        listener: L,
+        #[cfg(feature = "tls")] tls: Option<&crate::config::TlsConfig>,
+    ) -> Result<Bound, Error>
+    where
+        L: Listener + Send + Unpin + 'static,
+        <L as Listener>::Connection: Send + Unpin + 'static,
+    {
+        #[cfg(feature = "tls")]
+        if let Some(tls) = tls {
+            let config = tls.to_native_config().map_err(ErrorKind::Io)?;
+            let listener = TlsListener::bind(listener, config)
+                .await
+                .map_err(ErrorKind::Bind)?;
+
+            let reloader = TlsReloader {
+                address: listener.local_addr(),
+                config: tls.clone(),
+                resolver: listener.resolver().clone(),
+                #[cfg(feature = "mtls")]
+                crl_verifier: listener.crl_verifier().cloned(),
+            };
+
+            #[cfg(feature = "mtls")]
+            let identity = tls.mutual().map(|mtls| mtls.identity().into());
+            let bound = Bound::serving(listener, #[cfg(feature = "mtls")] identity);
+            return Ok(Bound { tls: Some(reloader), ..bound });
+        }
+
+        Ok(Bound::new(listener))
+    }
+}
+
+/// Reloads a TLS listener's certificate chain and key, and its certificate
+/// revocation lists, if any, from its configuration.
+#[cfg(feature = "tls")]
+#[derive(Debug, Clone)]
+pub(crate) struct TlsReloader {
+    address: Option<BindableAddr>,
+    config: crate::config::TlsConfig,
+    resolver: Arc<CertResolver>,
+    #[cfg(feature = "mtls")]
+    crl_verifier: Option<Arc<CrlVerifier>>,
+}
+
+#[cfg(feature = "tls")]
+impl TlsReloader {
+    /// Reloads the certificate chains and keys and the CRLs, logging the
+    /// outcome. On error, the previous certificate chains, keys, and CRLs
+    /// remain in use.
+    pub(crate) fn reload(&self) -> io::Result<()> {
+        let address = match &self.address {
+            Some(address) => address.to_string(),
+            None => "a custom listener".into(),
+        };
+
+        match self.load() {
+            Ok(()) => {
+                info!("Reloaded TLS certificates for {}.", Paint::white(address));
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to reload TLS certificates for {}.", Paint::white(address));
+                info_!("{}", e);
+                info_!("Continuing to use the previous certificates.");
+                Err(e)
+            }
+        }
+    }
+
+    /// Reads and parses everything to reload and then, if all of it is valid,
+    /// replaces the certificate chains, keys, and CRLs in use.
+    fn load(&self) -> io::Result<()> {
+        let (default, names) = self.config.load_certified_keys()?;
+
+        #[cfg(feature = "mtls")]
+        if let Some(verifier) = &self.crl_verifier {
+            verifier.set(self.config.load_crls()?)?;
+        }
+
+        self.resolver.set(default, names);
+        Ok(())
+    }
+
+    /// Spawns tasks that reload on the configured interval and on `SIGHUP`,
+    /// if enabled, until `shutdown` resolves.
+    fn spawn(&self, shutdown: crate::Shutdown) {
+        if self.config.reload_interval() > 0 {
+            let period = Duration::from_secs(self.config.reload_interval() as u64);
+            let (reloader, shutdown) = (self.clone(), shutdown.clone());
+            tokio::spawn(async move {
+                let start = tokio::time::Instant::now() + period;
+                let mut ticks = tokio::time::interval_at(start, period);
+                tokio::pin!(shutdown);
+                loop {
+                    tokio::select! {
+                        _ = ticks.tick() => { let _ = reloader.reload(); },
+                        _ = &mut shutdown => break,
+                    }
+                }
+            });
+        }
+
+        #[cfg(unix)]
+        if self.config.reload_on_sighup() {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let mut hangups = match signal(SignalKind::hangup()) {
+                Ok(signal) => signal,
+                Err(e) => return warn!("Failed to enable `SIGHUP` TLS reloading: {}", e),
+            };
+
+            let reloader = self.clone();
+            tokio::spawn(async move {
+                tokio::pin!(shutdown);
+                loop {
+                    tokio::select! {
+                        Some(_) = hangups.recv() => { let _ = reloader.reload(); },
+                        _ = &mut shutdown => break,
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Returns a hyper server serving requests from `listener` to `rocket` which
+/// shuts down gracefully when `rocket`'s `Shutdown` is triggered. Mutual TLS
+/// clients are identified by `identity`, if it is `Some`.
+fn serve<L>(
+    rocket: Arc<Rocket<Orbit>>,
+    listener: L,
+    #[cfg(feature = "mtls")] identity: Option<Arc<[crate::config::IdentityAttribute]>>,
+) -> impl Future<Output = Result<(), hyper::Error>>
+where
+    L: Listener + Send + 'static,
+    <L as Listener>::Connection: Send + Unpin + 'static,
+{
+    let shutdown = rocket.shutdown();
+    let grace = rocket.config.shutdown.grace as u64;
+    let mercy = rocket.config.shutdown.mercy as u64;
+    let keep_alive = rocket.config.keep_alive;
+    let header = Timeouts::duration(rocket.config.timeouts.header);
+    let idle = Timeouts::duration(rocket.config.timeouts.idle.unwrap_or(keep_alive));
+    let connections = rocket.connections.clone();
+
+    // Create the Hyper `Service`.
+    let service_fn = move |conn: &Counted<CancellableIo<_, TimeoutIo<L::Connection>>>| {
+        let rocket = rocket.clone();
+        let connection = ConnectionMeta {
+            remote: conn.peer_address(),
+            client_certificates: conn.peer_certificates(),
+            proxy_header: conn.proxy_header(),
+            tls: conn.tls_handshake(),
+            peer_credentials: conn.peer_credentials(),
+            #[cfg(feature = "mtls")]
+            identity: identity.clone(),
+        };
+
+        let span = tracing::connection(connection.remote.as_ref());
+        let activity = conn.get_ref().io().map(|io| io.activity().clone());
+        async move {
+            Ok::<_, std::convert::Infallible>(hyper::service::service_fn(move |req| {
+                let guard = activity.as_ref().map(|activity| activity.enter());
+                hyper_service_fn(rocket.clone(), connection.clone(), span.clone(), guard, req)
+            }))
+        }
+    };
+
+    // NOTE: `hyper` uses `tokio::spawn()` as the default executor.
+    let listener = TimeoutListener::new(listener, header, idle);
+    let listener = CancellableListener::new(shutdown.clone(), listener, grace, mercy);
+    let incoming = Incoming::new(listener).nodelay(true).connections(connections);
+    let builder = hyper::server::Server::builder(incoming);
+
+    #[cfg(feature = "http2")]
+    let builder = builder.http2_keep_alive_interval(match keep_alive {
+        0 => None,
+        n => Some(Duration::from_secs(n as u64)),
+    });
+
+    builder
+        .http1_keepalive(keep_alive != 0)
+        .http1_preserve_header_case(true)
+        .serve(hyper::service::make_service_fn(service_fn))
+        .with_graceful_shutdown(shutdown)
+}
@@ -0,0 +1,256 @@
+//! Spans for connections, requests, fairings, guards, and handlers.
+//!
+//! With the `tracing` feature enabled, Rocket emits a `connection` span for
+//! every accepted connection and, within it, a `request` span for every
+//! request. Request fairings, response fairings, request and data guards, and
+//! route and catcher handlers run in child spans of the request span. Without
+//! the feature, every function here compiles to nothing.
+
+#[cfg(feature = "tracing")]
+pub use enabled::*;
+
+#[cfg(not(feature = "tracing"))]
+pub use disabled::*;
+
+#[cfg(feature = "tracing")]
+mod enabled {
+    use std::fmt::Display;
+    use std::time::Instant;
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use tracing_::{field::Empty, info_span};
+    use tracing_subscriber::{fmt, Layer, Registry};
+    use tracing_subscriber::filter::{LevelFilter, Targets};
+    use tracing_subscriber::layer::SubscriberExt;
+    use yansi::Paint;
+
+    pub use tracing_::Span;
+    pub use tracing_::instrument::Instrumented;
+
+    use crate::{Catcher, Config, Route};
+    use crate::config::{LogLevel, TraceFormat, Tracing};
+    use crate::http::Status;
+    use crate::http::bindable::BindableAddr;
+
+    /// Whether `log` records are forwarded to the tracing subscriber.
+    static FORWARD: AtomicBool = AtomicBool::new(false);
+
+    /// Installs a global default subscriber, once, and begins forwarding
+    /// Rocket's log records to it. If another default subscriber is already
+    /// installed, records are forwarded to it instead.
+    pub fn init(config: &Config, tracing: &Tracing) {
+        if FORWARD.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let targets = match tracing.filter.as_deref().map(str::parse::<Targets>) {
+            Some(Ok(targets)) => targets,
+            Some(Err(e)) => {
+                warn!("invalid tracing filter: {}", e);
+                default_targets(config.log_level)
+            }
+            None => default_targets(config.log_level),
+        };
+
+        // JSON output shouldn't contain terminal escape sequences.
+        if tracing.format == TraceFormat::Json {
+            Paint::disable();
+        }
+
+        let ansi = config.cli_colors && atty::is(atty::Stream::Stdout);
+        let fmt = fmt::layer().with_writer(std::io::stdout).with_ansi(ansi);
+        let layer: Box<dyn Layer<Registry> + Send + Sync> = match tracing.format {
+            TraceFormat::Full => fmt.boxed(),
+            TraceFormat::Compact => fmt.compact().boxed(),
+            TraceFormat::Json => fmt.json().boxed(),
+        };
+
+        let subscriber = Registry::default().with(layer.with_filter(targets));
+        let _ = tracing_::subscriber::set_global_default(subscriber);
+    }
+
+    /// The filter to use when none is configured, mirroring what Rocket's
+    /// default logger prints at `level`.
+    fn default_targets(level: LogLevel) -> Targets {
+        let targets = match level {
+            LogLevel::Critical => Targets::new()
+                .with_default(LevelFilter::WARN)
+                .with_target("rocket::launch", LevelFilter::INFO),
+            LogLevel::Normal => Targets::new().with_default(LevelFilter::INFO),
+            LogLevel::Debug => return Targets::new().with_default(LevelFilter::TRACE),
+            LogLevel::Off => return Targets::new().with_default(LevelFilter::OFF),
+        };
+
+        targets.with_target("hyper", LevelFilter::OFF)
+            .with_target("rustls", LevelFilter::OFF)
+            .with_target("r2d2", LevelFilter::OFF)
+    }
+
+    /// Forwards `record` to the tracing subscriber if forwarding is enabled.
+    /// Returns `false` if it isn't.
+    pub fn forward(record: &log::Record<'_>) -> bool {
+        if !FORWARD.load(Ordering::Acquire) {
+            return false;
+        }
+
+        // Rocket's "_" suffixed targets indicate indentation; drop it.
+        let target = match record.target() {
+            "_" => "rocket",
+            "rocket::launch_" => "rocket::launch",
+            target => target,
+        };
+
+        let _ = tracing_log::format_trace(&log::Record::builder()
+            .args(*record.args())
+            .level(record.level())
+            .target(target)
+            .module_path(record.module_path())
+            .file(record.file())
+            .line(record.line())
+            .build());
+        true
+    }
+
+    /// When a request began, for its span's `latency_ms`.
+    pub struct Timer(Instant);
+
+    impl Timer {
+        pub fn start() -> Timer {
+            Timer(Instant::now())
+        }
+    }
+
+    pub fn instrument<F>(fut: F, span: Span) -> Instrumented<F> {
+        tracing_::Instrument::instrument(fut, span)
+    }
+
+    pub fn connection(remote: Option<&BindableAddr>) -> Span {
+        match remote {
+            Some(remote) => info_span!("connection", remote = %remote),
+            None => info_span!("connection", remote = Empty),
+        }
+    }
+
+    /// A request span, a child of `connection` or else of the current span.
+    pub fn request(connection: Option<&Span>, method: &dyn Display, uri: &dyn Display) -> Span {
+        macro_rules! request_span {
+            ($($parent:tt)*) => (info_span!($($parent)* "request",
+                method = %method, uri = %uri, request_id = Empty,
+                route = Empty, status = Empty, latency_ms = Empty))
+        }
+
+        match connection {
+            Some(connection) => request_span!(parent: connection,),
+            None => request_span!(),
+        }
+    }
+
+    pub fn record_request_id(request: &Span, id: &str) {
+        request.record("request_id", id);
+    }
+
+    /// Records the route that matched the request of the current span.
+    pub fn record_route(route: &Route) {
+        match &route.name {
+            Some(name) => Span::current().record("route", &**name),
+            None => Span::current().record("route", tracing_::field::display(&route.uri)),
+        };
+    }
+
+    pub fn record_response(request: &Span, status: Status, timer: Timer) {
+        let latency_ms = timer.0.elapsed().as_secs_f64() * 1000.0;
+        request.record("status", status.code);
+        request.record("latency_ms", latency_ms);
+        tracing_::info!(target: "rocket::server", parent: request, "completed");
+    }
+
+    pub fn fairing(kind: &'static str, name: &'static str) -> Span {
+        info_span!("fairing", kind, name)
+    }
+
+    pub fn handler(route: &Route) -> Span {
+        info_span!("handler", route = %route.name.as_deref().unwrap_or("-"), uri = %route.uri)
+    }
+
+    pub fn catcher(catcher: &Catcher, status: Status) -> Span {
+        let name = catcher.name.as_deref().unwrap_or("-");
+        info_span!("catcher", catcher = %name, status = status.code)
+    }
+
+    /// Runs the request or data guard future `fut` of type `ty` in a span.
+    pub fn guard<F>(kind: &'static str, ty: &'static str, fut: F) -> Instrumented<F> {
+        instrument(fut, info_span!("guard", kind, ty))
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod disabled {
+    use std::fmt::Display;
+
+    use crate::{Catcher, Route};
+    use crate::http::Status;
+    use crate::http::bindable::BindableAddr;
+
+    #[derive(Clone)]
+    pub struct Span;
+
+    #[inline(always)]
+    pub fn forward(_: &log::Record<'_>) -> bool {
+        false
+    }
+
+    pub struct Timer;
+
+    impl Timer {
+        #[inline(always)]
+        pub fn start() -> Timer {
+            Timer
+        }
+    }
+
+    #[inline(always)]
+    pub fn instrument<F>(fut: F, _: Span) -> F {
+        fut
+    }
+
+    #[inline(always)]
+    pub fn connection(_: Option<&BindableAddr>) -> Span {
+        Span
+    }
+
+    #[inline(always)]
+    pub fn request(_: Option<&Span>, _: &dyn Display, _: &dyn Display) -> Span {
+        Span
+    }
+
+    #[inline(always)]
+    pub fn record_request_id(_: &Span, _: &str) {}
+
+    #[inline(always)]
+    pub fn record_route(_: &Route) {}
+
+    #[inline(always)]
+    pub fn record_response(_: &Span, _: Status, _: Timer) {}
+
+    #[inline(always)]
+    pub fn fairing(_: &'static str, _: &'static str) -> Span {
+        Span
+    }
+
+    #[inline(always)]
+    pub fn handler(_: &Route) -> Span {
+        Span
+    }
+
+    #[inline(always)]
+    pub fn catcher(_: &Catcher, _: Status) -> Span {
+        Span
+    }
+
+    /// Runs the request or data guard future `fut` of type `ty` in a span.
+    #[inline(always)]
+    pub fn guard<F>(_: &'static str, _: &'static str, fut: F) -> F {
+        fut
+    }
+}
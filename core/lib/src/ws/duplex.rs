@@ -0,0 +1,134 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::future::Future;
+
+use futures::{Stream, Sink, ready};
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::protocol::frame::{CloseFrame, coding::CloseCode};
+
+use crate::Shutdown;
+use crate::data::IoStream;
+use crate::ws::{Config, Message, Result};
+
+/// A readable and writeable WebSocket [`Message`] `async` stream.
+///
+/// This struct implements [`Stream`] and [`Sink`], allowing for `async` reading
+/// and writing of [`Message`]s. The [`StreamExt`] and [`SinkExt`] traits can be
+/// imported to provide additional functionality for streams and sinks:
+///
+/// ```rust
+/// # use rocket::get;
+/// # use rocket::ws::{WebSocket, Channel};
+/// use rocket::futures::{SinkExt, StreamExt};
+///
+/// #[get("/echo/manual")]
+/// fn echo_manual<'r>(ws: WebSocket) -> Channel<'r> {
+///     ws.channel(move |mut stream| Box::pin(async move {
+///         while let Some(message) = stream.next().await {
+///             let _ = stream.send(message?).await;
+///         }
+///
+///         Ok(())
+///     }))
+/// }
+/// ```
+///
+/// Once [`Shutdown`] is triggered, the stream sends a `Close` frame to the
+/// client the next time it is polled for a message, initiating the closing
+/// handshake. The stream ends once the client acknowledges.
+///
+/// [`StreamExt`]: futures::StreamExt
+/// [`SinkExt`]: futures::SinkExt
+pub struct DuplexStream {
+    inner: WebSocketStream<IoStream>,
+    state: State,
+}
+
+/// The state of the server-initiated closing handshake.
+enum State {
+    /// Waiting for shutdown to be triggered.
+    Open(Shutdown),
+    /// Shutdown was triggered; we need to send a `Close` frame.
+    Closing,
+    /// The `Close` frame has been queued; we need to flush it.
+    Flushing,
+    /// The `Close` frame was sent or can't be sent.
+    Closed,
+}
+
+impl DuplexStream {
+    pub(crate) async fn new(stream: IoStream, config: Config, shutdown: Shutdown) -> Self {
+        let inner = WebSocketStream::from_raw_socket(stream, Role::Server, Some(config)).await;
+        DuplexStream { inner, state: State::Open(shutdown) }
+    }
+
+    /// Close the stream now. This does not typically need to be called.
+    pub async fn close(&mut self, msg: Option<CloseFrame<'_>>) -> Result<()> {
+        self.state = State::Closed;
+        self.inner.close(msg).await
+    }
+
+    /// Drives the server-initiated closing handshake, if shutdown has been
+    /// triggered, until the `Close` frame has been sent.
+    fn poll_shutdown(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        loop {
+            match self.state {
+                State::Open(ref mut shutdown) => match Pin::new(shutdown).poll(cx) {
+                    Poll::Ready(()) => self.state = State::Closing,
+                    Poll::Pending => return Poll::Ready(()),
+                },
+                State::Closing => {
+                    let mut inner = Pin::new(&mut self.inner);
+                    let frame = CloseFrame { code: CloseCode::Away, reason: "".into() };
+                    self.state = match ready!(inner.as_mut().poll_ready(cx)) {
+                        Ok(()) => match inner.start_send(Message::Close(Some(frame))) {
+                            Ok(()) => State::Flushing,
+                            Err(_) => State::Closed,
+                        },
+                        Err(_) => State::Closed,
+                    };
+                }
+                State::Flushing => {
+                    let _ = ready!(Pin::new(&mut self.inner).poll_flush(cx));
+                    self.state = State::Closed;
+                }
+                State::Closed => return Poll::Ready(()),
+            }
+        }
+    }
+}
+
+impl Stream for DuplexStream {
+    type Item = Result<Message>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        ready!(this.poll_shutdown(cx));
+        Pin::new(&mut this.inner).poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl Sink<Message> for DuplexStream {
+    type Error = crate::ws::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Message) -> Result<()> {
+        Pin::new(&mut self.get_mut().inner).start_send(item)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
@@ -0,0 +1,143 @@
+//! WebSocket support via HTTP connection upgrades.
+//!
+//! This module, enabled via the `ws` feature, implements the [WebSocket
+//! protocol] on top of Rocket's support for upgrading connections. The
+//! [`WebSocket`] request guard succeeds for well-formed WebSocket handshake
+//! requests. It can be converted into one of two responders which perform the
+//! `101 Switching Protocols` handshake and then speak WebSocket:
+//!
+//!   * [`Channel`] - a full-duplex channel: a handler is passed a
+//!     [`DuplexStream`] which is both a [`Stream`] of incoming [`Message`]s
+//!     and a [`Sink`] of outgoing ones.
+//!   * [`MessageStream`] - a stream of outgoing messages, produced from the
+//!     stream of incoming ones.
+//!
+//! [WebSocket protocol]: https://www.rfc-editor.org/rfc/rfc6455.html
+//! [`Stream`]: https://docs.rs/futures/0.3/futures/stream/trait.Stream.html
+//! [`Sink`]: https://docs.rs/futures/0.3/futures/sink/trait.Sink.html
+//!
+//! # Example
+//!
+//! The routes below implement an echo server, once as a `Channel` and once as
+//! a `MessageStream`:
+//!
+//! ```rust
+//! # use rocket::get;
+//! use rocket::futures::{SinkExt, StreamExt, stream::SplitStream};
+//! use rocket::ws::{WebSocket, Channel, MessageStream, DuplexStream};
+//!
+//! #[get("/echo/channel")]
+//! fn echo_channel(ws: WebSocket) -> Channel<'static> {
+//!     ws.channel(move |mut stream| Box::pin(async move {
+//!         while let Some(message) = stream.next().await {
+//!             let _ = stream.send(message?).await;
+//!         }
+//!
+//!         Ok(())
+//!     }))
+//! }
+//!
+//! #[get("/echo/stream")]
+//! fn echo_stream(ws: WebSocket) -> MessageStream<'static, SplitStream<DuplexStream>> {
+//!     ws.stream(|incoming| incoming)
+//! }
+//! ```
+//!
+//! # Graceful Shutdown
+//!
+//! WebSocket connections are long-lived and typically outlive the requests
+//! that initiated them. As with all connections, upgraded connections are
+//! subject to the configured [shutdown](crate::config::Shutdown) grace and
+//! mercy periods. Additionally, once [`Shutdown`](crate::Shutdown) is
+//! triggered, Rocket initiates the WebSocket closing handshake on every open
+//! connection by sending a `Close` frame with status code [`Away`] the next
+//! time the connection is read from. A well-behaved client responds with its
+//! own `Close` frame, at which point the incoming message stream ends and the
+//! handler completes.
+//!
+//! [`Away`]: frame::CloseCode::Away
+//!
+//! # Testing
+//!
+//! The [`asynchronous`](crate::local::asynchronous) local client can dispatch
+//! WebSocket handshake requests. The client's end of the upgraded connection
+//! is made available via [`LocalResponse::upgrade()`], where it can be wrapped
+//! in a client-role stream:
+//!
+//! [`LocalResponse::upgrade()`]: crate::local::asynchronous::LocalResponse::upgrade()
+//!
+//! ```rust
+//! # use rocket::{get, routes};
+//! # use rocket::futures::{SinkExt, StreamExt};
+//! # use rocket::ws::{WebSocket, Channel};
+//! # #[get("/echo")]
+//! # fn echo(ws: WebSocket) -> Channel<'static> {
+//! #     ws.channel(move |mut stream| Box::pin(async move {
+//! #         while let Some(message) = stream.next().await {
+//! #             let _ = stream.send(message?).await;
+//! #         }
+//! #
+//! #         Ok(())
+//! #     }))
+//! # }
+//! use rocket::local::asynchronous::Client;
+//! use rocket::http::{Header, Status};
+//! use rocket::ws::Message;
+//! use rocket::ws::tokio_tungstenite::WebSocketStream;
+//! use rocket::ws::tungstenite::protocol::Role;
+//!
+//! # rocket::async_test(async {
+//! let client = Client::tracked(rocket::build().mount("/", routes![echo])).await.unwrap();
+//! let response = client.get("/echo")
+//!     .header(Header::new("Connection", "Upgrade"))
+//!     .header(Header::new("Upgrade", "websocket"))
+//!     .header(Header::new("Sec-WebSocket-Version", "13"))
+//!     .header(Header::new("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+//!     .dispatch()
+//!     .await;
+//!
+//! assert_eq!(response.status(), Status::SwitchingProtocols);
+//! let reply = response.upgrade(|io| async move {
+//!     let mut ws = WebSocketStream::from_raw_socket(io, Role::Client, None).await;
+//!     ws.send(Message::text("hi")).await.unwrap();
+//!     let reply = ws.next().await;
+//!     ws.close(None).await.unwrap();
+//!     reply
+//! }).await;
+//!
+//! assert_eq!(reply.unwrap().unwrap().unwrap(), Message::text("hi"));
+//! # });
+//! ```
+
+mod websocket;
+mod duplex;
+
+pub use self::websocket::{WebSocket, Channel, MessageStream};
+pub use self::duplex::DuplexStream;
+
+/// The underlying WebSocket implementation.
+pub use tokio_tungstenite;
+
+/// The underlying WebSocket protocol implementation.
+pub use tokio_tungstenite::tungstenite;
+
+/// A WebSocket message.
+pub use tokio_tungstenite::tungstenite::Message;
+
+/// WebSocket connection configuration.
+pub use tokio_tungstenite::tungstenite::protocol::WebSocketConfig as Config;
+
+/// WebSocket frame types.
+pub mod frame {
+    #[doc(inline)]
+    pub use tokio_tungstenite::tungstenite::protocol::frame::{CloseFrame, Frame};
+
+    #[doc(inline)]
+    pub use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+}
+
+/// A WebSocket error.
+pub use tokio_tungstenite::tungstenite::error::Error;
+
+/// A result type with a default error type of [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,264 @@
+use std::io;
+use std::pin::Pin;
+
+use futures::future::BoxFuture;
+use futures::{SinkExt, StreamExt, Stream};
+use futures::stream::SplitStream;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::frame::{CloseFrame, coding::CloseCode};
+
+use crate::{Request, Response, Shutdown};
+use crate::data::{IoHandler, IoStream};
+use crate::request::{FromRequest, Outcome};
+use crate::response::{self, Responder};
+use crate::ws::{Config, DuplexStream, Error, Message, Result};
+
+/// A request guard identifying WebSocket requests. Converts into a [`Channel`]
+/// or [`MessageStream`].
+///
+/// For example usage, see the [module level docs](crate::ws).
+///
+/// # Request Guard
+///
+/// This type succeeds for requests which are well-formed WebSocket handshake
+/// requests: those with an `Upgrade: websocket` header, a `Connection` header
+/// including `Upgrade`, a `Sec-WebSocket-Version` of `13`, and a
+/// `Sec-WebSocket-Key`. Otherwise, it forwards.
+pub struct WebSocket {
+    config: Config,
+    key: String,
+    shutdown: Shutdown,
+}
+
+impl WebSocket {
+    /// Change the default connection configuration to `config`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::get;
+    /// # use rocket::ws::{WebSocket, Channel};
+    /// use rocket::ws::Config;
+    ///
+    /// #[get("/echo")]
+    /// fn echo(ws: WebSocket) -> Channel<'static> {
+    ///     let ws = ws.config(Config {
+    ///         max_message_size: Some(1 << 20),
+    ///         ..Default::default()
+    ///     });
+    ///
+    ///     ws.channel(|stream| Box::pin(async move {
+    ///         /* ... */
+    ///         # let _ = stream;
+    ///         Ok(())
+    ///     }))
+    /// }
+    /// ```
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Create a read/write channel to the client and call `handler` with it.
+    ///
+    /// This method takes a `FnOnce`, `handler`, that consumes a read/write
+    /// WebSocket channel, [`DuplexStream`] to the client. See [`DuplexStream`]
+    /// for details on how to make use of the channel.
+    ///
+    /// The `handler` must return a `Box`ed and `Pin`ned future: calling
+    /// [`Box::pin()`] with a future does just this as is the preferred
+    /// mechanism to create a `Box<Pin<Future>>`. The future must return a
+    /// [`Result<()>`](crate::ws::Result). The WebSocket connection is closed
+    /// successfully if the future returns `Ok` and with an error code if the
+    /// future returns `Err`.
+    ///
+    /// # Lifetimes
+    ///
+    /// The `Channel` may borrow from the request. If it does, the lifetime
+    /// should be specified as something other than `'static`. Otherwise, the
+    /// `'static` lifetime should be used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::get;
+    /// use rocket::futures::{SinkExt, StreamExt};
+    /// use rocket::ws::{WebSocket, Channel};
+    ///
+    /// #[get("/echo")]
+    /// fn echo(ws: WebSocket) -> Channel<'static> {
+    ///     ws.channel(move |mut stream| Box::pin(async move {
+    ///         while let Some(message) = stream.next().await {
+    ///             let _ = stream.send(message?).await;
+    ///         }
+    ///
+    ///         Ok(())
+    ///     }))
+    /// }
+    /// ```
+    pub fn channel<'r, F>(self, handler: F) -> Channel<'r>
+        where F: FnOnce(DuplexStream) -> BoxFuture<'r, Result<()>> + Send + 'r
+    {
+        Channel { ws: self, handler: Box::new(handler), }
+    }
+
+    /// Create a stream that consumes client [`Message`]s and emits its own.
+    ///
+    /// This method takes a `FnOnce` `stream` that consumes a read-only stream
+    /// and returns a stream of [`Message`]s. Each message emitted by the
+    /// returned stream is sent to the client. The connection is closed once
+    /// the returned stream is exhausted or yields an error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::get;
+    /// use rocket::futures::stream::SplitStream;
+    /// use rocket::ws::{WebSocket, MessageStream, DuplexStream};
+    ///
+    /// #[get("/echo")]
+    /// fn echo(ws: WebSocket) -> MessageStream<'static, SplitStream<DuplexStream>> {
+    ///     ws.stream(|incoming| incoming)
+    /// }
+    /// ```
+    pub fn stream<'r, F, S>(self, stream: F) -> MessageStream<'r, S>
+        where F: FnOnce(SplitStream<DuplexStream>) -> S + Send + 'r,
+              S: Stream<Item = Result<Message>> + Send + 'r
+    {
+        MessageStream { ws: self, handler: Box::new(stream), }
+    }
+}
+
+/// A raw, low-level WebSocket channel.
+///
+/// This type is not typically used directly. Instead, it is created via
+/// [`WebSocket::channel()`] and returned from a route.
+///
+/// # Responder
+///
+/// The responder performs the WebSocket handshake and then calls the channel's
+/// handler with the upgraded connection. When the request isn't a request to
+/// upgrade to a WebSocket, the handler is never called.
+pub struct Channel<'r> {
+    ws: WebSocket,
+    handler: Box<dyn FnOnce(DuplexStream) -> BoxFuture<'r, Result<()>> + Send + 'r>,
+}
+
+/// A [`Stream`](futures::Stream) of [`Message`]s sent to the client.
+///
+/// This type is not typically used directly. Instead, it is created via
+/// [`WebSocket::stream()`] and returned from a route.
+///
+/// # Responder
+///
+/// The responder performs the WebSocket handshake and then sends every message
+/// emitted by the stream to the client. When the request isn't a request to
+/// upgrade to a WebSocket, the stream is never created.
+pub struct MessageStream<'r, S> {
+    ws: WebSocket,
+    handler: Box<dyn FnOnce(SplitStream<DuplexStream>) -> S + Send + 'r>
+}
+
+#[crate::async_trait]
+impl<'r> FromRequest<'r> for WebSocket {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        use crate::http::uncased::eq;
+
+        let headers = req.headers();
+        let is_upgrade = headers.get("Connection")
+            .any(|h| h.split(',').any(|v| eq(v.trim(), "upgrade")));
+
+        let is_ws = headers.get("Upgrade")
+            .any(|h| h.split(',').any(|v| eq(v.trim(), "websocket")));
+
+        let is_13 = headers.get_one("Sec-WebSocket-Version") == Some("13");
+        let key = headers.get_one("Sec-WebSocket-Key").map(|k| derive_accept_key(k.as_bytes()));
+        match key {
+            Some(key) if is_upgrade && is_ws && is_13 => {
+                let shutdown = req.rocket().shutdown();
+                Outcome::Success(WebSocket { key, shutdown, config: Config::default() })
+            },
+            Some(_) | None => Outcome::Forward(())
+        }
+    }
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Channel<'o> {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
+        Response::build()
+            .raw_header("Sec-WebSocket-Accept", self.ws.key.clone())
+            .upgrade("websocket", self)
+            .ok()
+    }
+}
+
+impl<'r, 'o: 'r, S> Responder<'r, 'o> for MessageStream<'o, S>
+    where S: futures::Stream<Item = Result<Message>> + Send + 'o
+{
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
+        Response::build()
+            .raw_header("Sec-WebSocket-Accept", self.ws.key.clone())
+            .upgrade("websocket", self)
+            .ok()
+    }
+}
+
+#[crate::async_trait]
+impl IoHandler for Channel<'_> {
+    async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+        let channel = Pin::into_inner(self);
+        let WebSocket { config, shutdown, .. } = channel.ws;
+        let stream = DuplexStream::new(io, config, shutdown).await;
+        let result = (channel.handler)(stream).await;
+        handle_result(result).map(|_| ())
+    }
+}
+
+#[crate::async_trait]
+impl<'r, S> IoHandler for MessageStream<'r, S>
+    where S: futures::Stream<Item = Result<Message>> + Send + 'r
+{
+    async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+        let MessageStream { ws, handler } = *Pin::into_inner(self);
+        let WebSocket { config, mut shutdown, .. } = ws;
+        let (mut sink, source) = DuplexStream::new(io, config, shutdown.clone()).await.split();
+        let stream = handler(source);
+        tokio::pin!(stream);
+
+        loop {
+            let message = tokio::select! {
+                message = stream.next() => message,
+                _ = &mut shutdown => {
+                    let frame = CloseFrame { code: CloseCode::Away, reason: "".into() };
+                    let result = sink.send(Message::Close(Some(frame))).await;
+                    return handle_result(result).map(|_| ());
+                }
+            };
+
+            let result = match message {
+                Some(Ok(message)) => sink.send(message).await,
+                Some(Err(e)) => Err(e),
+                None => break,
+            };
+
+            if !handle_result(result)? {
+                return Ok(());
+            }
+        }
+
+        handle_result(sink.close().await).map(|_| ())
+    }
+}
+
+/// Returns `Ok(true)` if processing should continue, `Ok(false)` if processing
+/// has terminated without error, and `Err(e)` if an error has occurred.
+fn handle_result(result: Result<()>) -> io::Result<bool> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(Error::ConnectionClosed) | Err(Error::AlreadyClosed) => Ok(false),
+        Err(Error::Io(e)) => Err(e),
+        Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+    }
+}
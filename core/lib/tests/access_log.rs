@@ -0,0 +1,106 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rocket::{Rocket, Build};
+use rocket::access_log::{AccessLog, Format};
+use rocket::error::ErrorKind;
+use rocket::http::Header;
+use rocket::local::asynchronous::Client;
+
+#[get("/hello?<name>")]
+fn hello(name: &str) -> String {
+    format!("Hello, {}!", name)
+}
+
+fn rocket(access_log: AccessLog) -> Rocket<Build> {
+    rocket::build().mount("/", routes![hello]).attach(access_log)
+}
+
+/// Waits for `done` to return `Some`, as records are written by another thread.
+fn wait_for<T>(mut done: impl FnMut() -> Option<T>) -> T {
+    let start = Instant::now();
+    loop {
+        match done() {
+            Some(value) => return value,
+            None if start.elapsed() > Duration::from_secs(5) => panic!("records weren't written"),
+            None => std::thread::sleep(Duration::from_millis(5)),
+        }
+    }
+}
+
+async fn log(format: Format) -> Vec<String> {
+    let records = Arc::new(Mutex::new(vec![]));
+    let sink = records.clone();
+    let access_log = AccessLog::new(format)
+        .to_sink(move |record| sink.lock().unwrap().push(record.to_string()));
+
+    let client = Client::debug(rocket(access_log)).await.unwrap();
+    client.get("/hello?name=Rocket")
+        .header(Header::new("User-Agent", "agent \"007\""))
+        .header(Header::new("Referer", "https://rocket.rs/"))
+        .header(Header::new("X-Request-Id", "abc123"))
+        .dispatch().await;
+
+    client.get("/missing").dispatch().await;
+
+    wait_for(|| Some(records.lock().unwrap().clone()).filter(|r| r.len() == 2))
+}
+
+#[rocket::async_test]
+async fn writes_common_and_combined_records() {
+    let records = log(Format::Common).await;
+    assert_eq!(records.len(), 2);
+
+    let (prefix, rest) = records[0].split_once(" [").unwrap();
+    assert_eq!(prefix, "- - -");
+    assert!(rest.contains(" +0000] \"GET /hello?name=Rocket\" 200 14"), "{}", records[0]);
+    assert!(records[1].contains("] \"GET /missing\" 404 "), "{}", records[1]);
+
+    let records = log(Format::Combined).await;
+    let expected = "\" 200 14 \"https://rocket.rs/\" \"agent \\\"007\\\"\"";
+    assert!(records[0].ends_with(expected), "{}", records[0]);
+    assert!(records[1].ends_with(" \"-\" \"-\""), "{}", records[1]);
+}
+
+#[rocket::async_test]
+async fn writes_json_records() {
+    let records = log(Format::Json).await;
+    let record = &records[0];
+    assert!(record.starts_with("{\"time\":\""), "{}", record);
+    assert!(record.contains("Z\",\"remote\":null,\"client_ip\":null"), "{}", record);
+    assert!(record.contains(",\"method\":\"GET\",\"uri\":\"/hello?name=Rocket\""), "{}", record);
+    assert!(record.contains(",\"route\":\"hello\",\"status\":200,\"size\":14,"), "{}", record);
+    assert!(record.contains(",\"latency_ms\":"), "{}", record);
+    let expected = ",\"user_agent\":\"agent \\\"007\\\"\",\"referer\":\"https://rocket.rs/\",\
+        \"request_id\":\"abc123\",\"tls_version\":null,\"tls_cipher\":null}";
+    assert!(record.ends_with(expected), "{}", record);
+
+    assert!(records[1].contains(",\"route\":null,\"status\":404,"), "{}", records[1]);
+}
+
+#[rocket::async_test]
+async fn appends_records_to_files() {
+    let path = std::env::temp_dir().join(format!("rocket-access-{}.log", std::process::id()));
+    std::fs::write(&path, "existing\n").unwrap();
+
+    let access_log = AccessLog::new(Format::Common).to_file(&path);
+    let client = Client::debug(rocket(access_log)).await.unwrap();
+    client.get("/hello?name=File").dispatch().await;
+
+    let contents = wait_for(|| {
+        Some(std::fs::read_to_string(&path).unwrap()).filter(|c| c.lines().count() == 2)
+    });
+
+    let lines: Vec<_> = contents.lines().collect();
+    assert_eq!(lines[0], "existing");
+    assert!(lines[1].ends_with("\"GET /hello?name=File\" 200 12"), "{}", lines[1]);
+    std::fs::remove_file(&path).unwrap();
+
+    let missing = std::env::temp_dir().join("rocket-access-missing-dir").join("access.log");
+    match Client::debug(rocket(AccessLog::new(Format::Common).to_file(missing))).await {
+        Err(e) => assert!(matches!(e.kind(), ErrorKind::FailedFairings(_))),
+        Ok(_) => panic!("ignited with an unopenable access log"),
+    }
+}
@@ -0,0 +1,169 @@
+//! Helpers shared by the TLS integration tests. Each test uses only some.
+
+#![allow(dead_code)]
+
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rocket::{Build, Config, Rocket, Shutdown};
+use rocket::config::{BindableAddr, TlsConfig};
+use rocket::fairing::AdHoc;
+use rocket::fs::relative;
+use rocket::futures::channel::oneshot;
+use rocket::http::Status;
+use rocket::http::tls::CertResolver;
+use rocket::http::tls::rustls::{self, ClientConfig, ClientConnection, PrivateKey, RootCertStore};
+use rustls_pemfile::Item;
+
+pub const RSA: (&str, &str) = (
+    relative!("../../examples/tls/private/rsa_sha256_cert.pem"),
+    relative!("../../examples/tls/private/rsa_sha256_key.pem"),
+);
+
+pub const ECDSA: (&str, &str) = (
+    relative!("../../examples/tls/private/ecdsa_nistp256_sha256_cert.pem"),
+    relative!("../../examples/tls/private/ecdsa_nistp256_sha256_key_pkcs8.pem"),
+);
+
+pub const CA: (&str, &str) = (
+    relative!("../../examples/tls/private/ca_cert.pem"),
+    relative!("../../examples/tls/private/ca_key.pem"),
+);
+
+/// Returns the DER-encoded leaf certificate in the PEM file at `pair.0`.
+pub fn leaf((certs, key): (&str, &str)) -> rustls::Certificate {
+    let mut certs = io::BufReader::new(std::fs::File::open(certs).unwrap());
+    let mut key = io::BufReader::new(std::fs::File::open(key).unwrap());
+    CertResolver::load(&mut certs, &mut key).unwrap().cert.remove(0)
+}
+
+/// Returns a root store trusting the example certificate authority.
+pub fn roots() -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    roots.add(&leaf(CA)).unwrap();
+    roots
+}
+
+/// Returns a client configuration trusting the example certificate authority.
+pub fn client_config() -> ClientConfig {
+    ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots())
+        .with_no_client_auth()
+}
+
+/// Returns a client configuration trusting the example certificate authority
+/// and authenticating with the certificate chain and key in `pair`.
+pub fn client_auth_config((certs, key): (&str, &str)) -> ClientConfig {
+    let open = |path| io::BufReader::new(std::fs::File::open(path).unwrap());
+    let certs = rustls_pemfile::certs(&mut open(certs)).unwrap();
+    let key = match rustls_pemfile::read_one(&mut open(key)).unwrap() {
+        Some(Item::RSAKey(key)) | Some(Item::PKCS8Key(key)) => key,
+        item => panic!("unexpected key: {:?}", item),
+    };
+
+    ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots())
+        .with_single_cert(certs.into_iter().map(rustls::Certificate).collect(), PrivateKey(key))
+        .unwrap()
+}
+
+/// A temporary directory, removed when dropped.
+pub struct TempDir(PathBuf);
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Creates a fresh temporary directory unique to this process and `name`.
+pub fn temp_dir(name: &str) -> TempDir {
+    let id = std::process::id();
+    let dir = std::env::temp_dir().join(format!("rocket-test-{}-{}", name, id));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    TempDir(dir)
+}
+
+/// Returns a debug configuration serving `tls` on a free port on localhost.
+pub fn config(tls: TlsConfig) -> Config {
+    Config {
+        address: BindableAddr::Tcp(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)),
+        tls: Some(tls),
+        ..Config::debug_default()
+    }
+}
+
+/// Returns a fairing that reloads TLS on requests to `/reload`, responding
+/// with `204 No Content` on success and `500 Internal Server Error` on failure.
+pub fn reload() -> AdHoc {
+    AdHoc::on_response("Reload", |req, res| Box::pin(async move {
+        if req.uri().path() == "/reload" {
+            match req.rocket().reload_tls() {
+                Ok(()) => res.set_status(Status::NoContent),
+                Err(_) => res.set_status(Status::InternalServerError),
+            }
+        }
+    }))
+}
+
+/// Launches `rocket` in the background. Returns the port it's listening on
+/// once it has lifted off and a handle to shut it down.
+pub async fn launch(rocket: Rocket<Build>) -> (u16, Shutdown) {
+    let (tx, rx) = oneshot::channel();
+    let rocket = rocket.attach(AdHoc::on_liftoff("Send Config", move |rocket| Box::pin(async move {
+        tx.send((rocket.config().clone(), rocket.shutdown())).unwrap();
+    })));
+
+    rocket::tokio::spawn(rocket.launch());
+    let (config, shutdown) = rx.await.unwrap();
+    (config.address.port().unwrap(), shutdown)
+}
+
+/// Sends a `GET` request for `path` over a new TLS connection to `localhost`
+/// configured by `config`. Returns the leaf certificate the server presented
+/// and the response, or the error if the handshake fails.
+pub async fn get(
+    port: u16,
+    path: &str,
+    config: ClientConfig,
+) -> io::Result<(rustls::Certificate, String)> {
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+    rocket::tokio::task::spawn_blocking(move || {
+        let name = "localhost".try_into().unwrap();
+        let conn = ClientConnection::new(Arc::new(config), name).unwrap();
+        let sock = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let mut stream = rustls::StreamOwned::new(conn, sock);
+        stream.write_all(request.as_bytes())?;
+
+        // The server may close without a `close_notify`; that's only an error
+        // if it did so without responding, i.e., if it rejected the handshake.
+        let mut response = String::new();
+        if let Err(e) = stream.read_to_string(&mut response) {
+            if response.is_empty() {
+                return Err(e);
+            }
+        }
+
+        let leaf = stream.conn.peer_certificates().unwrap()[0].clone();
+        Ok((leaf, response))
+    }).await.unwrap()
+}
+
+/// Returns the body of the HTTP `response`.
+pub fn body(response: &str) -> &str {
+    response.split("\r\n\r\n").nth(1).unwrap_or_default()
+}
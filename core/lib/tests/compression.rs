@@ -0,0 +1,184 @@
+#![cfg(all(feature = "gzip", feature = "brotli"))]
+
+#[macro_use] extern crate rocket;
+
+use rocket::{Rocket, Build, Config};
+use rocket::error::ErrorKind;
+use rocket::compression::{Compression, CompressionConfig, Encoding};
+use rocket::data::ToByteUnit;
+use rocket::figment::Figment;
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::asynchronous::{Client, LocalResponse};
+use rocket::response::stream::TextStream;
+use rocket::tokio::io::AsyncReadExt;
+
+const TEXT: &str = "Rocket is a web framework for Rust. ";
+
+fn text() -> String {
+    TEXT.repeat(100)
+}
+
+#[get("/text")]
+fn big() -> String {
+    text()
+}
+
+#[get("/small")]
+fn small() -> &'static str {
+    TEXT
+}
+
+#[get("/png")]
+fn png() -> (ContentType, String) {
+    (ContentType::PNG, text())
+}
+
+#[get("/stream")]
+fn stream() -> TextStream![&'static str] {
+    TextStream! {
+        for _ in 0..100 {
+            yield TEXT;
+            rocket::tokio::task::yield_now().await;
+        }
+    }
+}
+
+fn rocket(compression: Compression) -> Rocket<Build> {
+    rocket::build()
+        .mount("/", routes![big, small, png, stream])
+        .attach(compression)
+}
+
+async fn get<'c>(client: &'c Client, uri: &'static str, accept: &str) -> LocalResponse<'c> {
+    let mut request = client.get(uri);
+    if !accept.is_empty() {
+        request.add_header(Header::new("Accept-Encoding", accept.to_string()));
+    }
+
+    let response = request.dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+    response
+}
+
+async fn decompress(response: LocalResponse<'_>) -> String {
+    use async_compression::tokio::bufread::{GzipDecoder, BrotliDecoder};
+
+    let encoding = response.headers().get_one("Content-Encoding").map(String::from);
+    let bytes = response.into_bytes().await.unwrap();
+    let mut string = String::new();
+    match encoding.as_deref() {
+        Some("gzip") => GzipDecoder::new(&bytes[..]).read_to_string(&mut string).await,
+        Some("br") => BrotliDecoder::new(&bytes[..]).read_to_string(&mut string).await,
+        Some(other) => panic!("unexpected encoding: {}", other),
+        None => return String::from_utf8(bytes).unwrap(),
+    }.unwrap();
+
+    string
+}
+
+#[async_test]
+async fn test_negotiation() {
+    let client = Client::debug(rocket(Compression::fairing())).await.unwrap();
+
+    let response = get(&client, "/text", "gzip").await;
+    assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+    assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+    assert!(response.headers().get_one("Content-Length").is_none());
+    assert_eq!(decompress(response).await, text());
+
+    let response = get(&client, "/text", "gzip, deflate, br").await;
+    assert_eq!(response.headers().get_one("Content-Encoding"), Some("br"));
+    assert_eq!(decompress(response).await, text());
+
+    let response = get(&client, "/text", "gzip;q=1.0, br;q=0.5").await;
+    assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+    assert_eq!(decompress(response).await, text());
+
+    let response = get(&client, "/text", "br;q=0, zstd;q=0, deflate;q=0, *").await;
+    assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+
+    for accept in ["", "identity", "gzip;q=0, br;q=0", "*;q=0"] {
+        let response = get(&client, "/text", accept).await;
+        assert!(response.headers().get_one("Content-Encoding").is_none());
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+        assert_eq!(response.into_string().await.unwrap(), text());
+    }
+}
+
+#[async_test]
+async fn test_skipped() {
+    let client = Client::debug(rocket(Compression::fairing())).await.unwrap();
+
+    let response = get(&client, "/small", "gzip").await;
+    assert!(response.headers().get_one("Content-Encoding").is_none());
+    assert!(response.headers().get_one("Vary").is_none());
+    assert_eq!(response.into_string().await.unwrap(), TEXT);
+
+    let response = get(&client, "/png", "gzip").await;
+    assert!(response.headers().get_one("Content-Encoding").is_none());
+    assert!(response.headers().get_one("Vary").is_none());
+    assert_eq!(response.into_string().await.unwrap(), text());
+}
+
+#[async_test]
+async fn test_streamed() {
+    let client = Client::debug(rocket(Compression::fairing())).await.unwrap();
+    for accept in ["gzip", "br"] {
+        let response = get(&client, "/stream", accept).await;
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some(accept));
+        assert_eq!(decompress(response).await, text());
+    }
+}
+
+#[async_test]
+async fn test_custom_config() {
+    let config = CompressionConfig {
+        min_size: 0.bytes(),
+        encodings: vec![Encoding::Gzip],
+        exclude: vec![ContentType::Plain],
+        ..Default::default()
+    };
+
+    let client = Client::debug(rocket(Compression::custom(config))).await.unwrap();
+    let response = get(&client, "/text", "br, gzip").await;
+    assert!(response.headers().get_one("Content-Encoding").is_none());
+
+    let response = get(&client, "/png", "br, gzip").await;
+    assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+    assert_eq!(decompress(response).await, text());
+}
+
+#[async_test]
+async fn test_figment_config() {
+    let figment = Figment::from(Config::debug_default())
+        .merge(("compression.min_size", "10KiB"))
+        .merge(("compression.encodings", ["gzip"]));
+
+    let rocket = rocket::custom(figment)
+        .mount("/", routes![big, stream])
+        .attach(Compression::fairing());
+
+    let client = Client::debug(rocket).await.unwrap();
+    let response = get(&client, "/text", "br").await;
+    assert!(response.headers().get_one("Content-Encoding").is_none());
+
+    let response = get(&client, "/stream", "br, gzip").await;
+    assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+    assert_eq!(decompress(response).await, text());
+}
+
+#[async_test]
+async fn test_invalid_config() {
+    let figments = [
+        Figment::from(Config::debug_default()).merge(("compression.level", "slow")),
+        Figment::from(Config::debug_default()).merge(("compression.min_size", "big")),
+        Figment::from(Config::debug_default()).merge(("compression.encodings", ["lzma"])),
+        Figment::from(Config::debug_default()).merge(("compression.exclude", ["nope"])),
+    ];
+
+    for figment in figments {
+        let rocket = rocket::custom(figment).attach(Compression::fairing());
+        let err = rocket.ignite().await.unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::FailedFairings(_)));
+    }
+}
@@ -0,0 +1,164 @@
+#[macro_use] extern crate rocket;
+
+use rocket::http::{Header, Method, Status};
+use rocket::local::blocking::{Client, LocalResponse};
+use rocket::response::{Conditional, Etagged};
+
+const MODIFIED: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+
+#[derive(Responder)]
+struct Dated(&'static str, Header<'static>);
+
+#[get("/strong")]
+fn strong() -> Etagged<&'static str> {
+    Etagged::strong("v1", "strong")
+}
+
+#[get("/weak")]
+fn weak() -> Etagged<&'static str> {
+    Etagged::weak("v1", "weak")
+}
+
+#[put("/strong")]
+fn put() -> Etagged<&'static str> {
+    Etagged::strong("v1", "put")
+}
+
+#[get("/invalid")]
+fn invalid() -> Etagged<&'static str> {
+    Etagged::strong("a\"b", "invalid")
+}
+
+#[get("/dated")]
+fn dated() -> Conditional<Dated> {
+    Conditional(Dated("dated", Header::new("Last-Modified", MODIFIED)))
+}
+
+#[get("/missing")]
+fn missing() -> Etagged<Option<&'static str>> {
+    Etagged::strong("v1", None)
+}
+
+fn client() -> Client {
+    let routes = routes![strong, weak, put, invalid, dated, missing];
+    Client::debug(rocket::build().mount("/", routes)).unwrap()
+}
+
+fn request<'c>(
+    client: &'c Client,
+    method: Method,
+    uri: &'static str,
+    headers: &[(&'static str, &'static str)]
+) -> LocalResponse<'c> {
+    let mut request = client.req(method, uri);
+    for &(name, value) in headers {
+        request.add_header(Header::new(name, value));
+    }
+
+    request.dispatch()
+}
+
+#[test]
+fn test_etagged() {
+    let client = client();
+    let response = request(&client, Method::Get, "/strong", &[]);
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.headers().get_one("ETag"), Some("\"v1\""));
+    assert_eq!(response.into_string().unwrap(), "strong");
+
+    let response = request(&client, Method::Get, "/weak", &[]);
+    assert_eq!(response.headers().get_one("ETag"), Some("W/\"v1\""));
+
+    let response = request(&client, Method::Get, "/invalid", &[]);
+    assert_eq!(response.status(), Status::InternalServerError);
+}
+
+#[test]
+fn test_if_none_match() {
+    let client = client();
+    for value in ["\"v1\"", "W/\"v1\"", "\"v0\", \"v1\"", "*"] {
+        for (method, uri) in [(Method::Get, "/strong"), (Method::Head, "/weak")] {
+            let response = request(&client, method, uri, &[("If-None-Match", value)]);
+            assert_eq!(response.status(), Status::NotModified, "{} {}", value, uri);
+            assert!(response.headers().get_one("ETag").is_some());
+            assert!(response.content_type().is_none());
+            assert!(response.body().is_none());
+        }
+    }
+
+    for value in ["\"v2\"", "v1", "\"V1\""] {
+        let response = request(&client, Method::Get, "/strong", &[("If-None-Match", value)]);
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().unwrap(), "strong");
+    }
+
+    // Unsafe methods fail the precondition instead.
+    let response = request(&client, Method::Put, "/strong", &[("If-None-Match", "*")]);
+    assert_eq!(response.status(), Status::PreconditionFailed);
+}
+
+#[test]
+fn test_if_match() {
+    let client = client();
+    for value in ["\"v1\"", "\"v0\", \"v1\"", "*"] {
+        let response = request(&client, Method::Put, "/strong", &[("If-Match", value)]);
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().unwrap(), "put");
+    }
+
+    for value in ["\"v2\"", "W/\"v1\""] {
+        let response = request(&client, Method::Put, "/strong", &[("If-Match", value)]);
+        assert_eq!(response.status(), Status::PreconditionFailed);
+    }
+
+    // Weak entity-tags never match strongly.
+    let response = request(&client, Method::Get, "/weak", &[("If-Match", "W/\"v1\"")]);
+    assert_eq!(response.status(), Status::PreconditionFailed);
+
+    // `If-Match` takes precedence over `If-Unmodified-Since`.
+    let headers = [
+        ("If-Match", "\"v1\""),
+        ("If-Unmodified-Since", "Sat, 01 Jan 1994 00:00:00 GMT"),
+    ];
+
+    let response = request(&client, Method::Get, "/strong", &headers);
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[test]
+fn test_dates() {
+    let client = client();
+    let later = "Mon, 07 Nov 1994 08:49:37 GMT";
+    let earlier = "Sat, 05 Nov 1994 08:49:37 GMT";
+
+    let cases = [
+        ("If-Modified-Since", MODIFIED, Status::NotModified),
+        ("If-Modified-Since", later, Status::NotModified),
+        ("If-Modified-Since", earlier, Status::Ok),
+        ("If-Modified-Since", "yesterday", Status::Ok),
+        ("If-Unmodified-Since", MODIFIED, Status::Ok),
+        ("If-Unmodified-Since", later, Status::Ok),
+        ("If-Unmodified-Since", earlier, Status::PreconditionFailed),
+        ("If-Unmodified-Since", "yesterday", Status::Ok),
+    ];
+
+    for (name, value, status) in cases {
+        let response = request(&client, Method::Get, "/dated", &[(name, value)]);
+        assert_eq!(response.status(), status, "{}: {}", name, value);
+        if status != Status::PreconditionFailed {
+            assert_eq!(response.headers().get_one("Last-Modified"), Some(MODIFIED));
+        }
+    }
+
+    // `If-None-Match` takes precedence over `If-Modified-Since`.
+    let headers = [("If-None-Match", "\"v1\""), ("If-Modified-Since", MODIFIED)];
+    let response = request(&client, Method::Get, "/dated", &headers);
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[test]
+fn test_passthrough() {
+    let client = client();
+    let response = request(&client, Method::Get, "/missing", &[("If-Match", "\"v2\"")]);
+    assert_eq!(response.status(), Status::NotFound);
+}
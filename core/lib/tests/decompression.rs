@@ -0,0 +1,261 @@
+#![cfg(all(feature = "gzip", feature = "brotli"))]
+
+#[macro_use] extern crate rocket;
+
+use rocket::{Request, Config};
+use rocket::data::{Capped, Limits, ToByteUnit};
+use rocket::figment::Figment;
+use rocket::form::Form;
+use rocket::fs::TempFile;
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::asynchronous::Client;
+use rocket::tokio::io::AsyncReadExt;
+
+use async_compression::tokio::bufread::{GzipEncoder, BrotliEncoder};
+
+async fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![];
+    GzipEncoder::new(data).read_to_end(&mut encoded).await.unwrap();
+    encoded
+}
+
+async fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![];
+    BrotliEncoder::new(data).read_to_end(&mut encoded).await.unwrap();
+    encoded
+}
+
+struct Headers(String);
+
+#[rocket::async_trait]
+impl<'r> rocket::request::FromRequest<'r> for Headers {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> rocket::request::Outcome<Self, ()> {
+        let encoding = req.headers().get_one("Content-Encoding").unwrap_or("none");
+        let length = req.headers().get_one("Content-Length").unwrap_or("none");
+        rocket::request::Outcome::Success(Headers(format!("{}/{}", encoding, length)))
+    }
+}
+
+#[post("/string", data = "<body>")]
+fn string(body: String, headers: Headers) -> String {
+    format!("{}: {}", headers.0, body)
+}
+
+#[post("/capped", data = "<body>")]
+fn capped(body: Capped<String>) -> String {
+    format!("{}: {}", body.is_complete(), body.len())
+}
+
+#[post("/bytes", data = "<body>")]
+fn bytes(body: Capped<Vec<u8>>) -> String {
+    format!("{}: {}", body.is_complete(), body.len())
+}
+
+#[derive(FromForm)]
+struct Message<'r> {
+    text: &'r str,
+}
+
+#[post("/form", data = "<form>")]
+fn form(form: Form<Message<'_>>) -> String {
+    form.text.into()
+}
+
+#[put("/form", data = "<form>")]
+fn form_put(form: Form<Message<'_>>) -> String {
+    format!("put: {}", form.text)
+}
+
+#[post("/file", data = "<file>")]
+async fn file(mut file: TempFile<'_>) -> std::io::Result<String> {
+    let path = std::env::temp_dir().join(format!("rocket-decompression-{}", std::process::id()));
+    file.persist_to(&path).await?;
+    let contents = rocket::tokio::fs::read_to_string(&path).await;
+    let _ = rocket::tokio::fs::remove_file(&path).await;
+    contents
+}
+
+#[cfg(feature = "json")]
+#[post("/json", data = "<json>")]
+fn json(json: rocket::serde::json::Json<Vec<String>>) -> String {
+    json.join(", ")
+}
+
+async fn client() -> Client {
+    let figment = Figment::from(Config::debug_default())
+        .merge(("limits", Limits::default()
+            .limit("string", 1.kibibytes())
+            .limit("bytes", 1.kibibytes())));
+
+    let rocket = rocket::custom(figment)
+        .mount("/", routes![string, capped, bytes, form, form_put, file]);
+
+    #[cfg(feature = "json")]
+    let rocket = rocket.mount("/", routes![json]);
+
+    Client::debug(rocket).await.unwrap()
+}
+
+#[async_test]
+async fn test_decoded() {
+    let client = client().await;
+    let body = gzip(b"Hello, gzip!").await;
+    let response = client.post("/string")
+        .header(Header::new("Content-Encoding", "gzip"))
+        .header(Header::new("Content-Length", body.len().to_string()))
+        .body(body)
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "none/none: Hello, gzip!");
+
+    let body = brotli(b"Hello, brotli!").await;
+    let response = client.post("/string")
+        .header(Header::new("Content-Encoding", "br"))
+        .body(body)
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "none/none: Hello, brotli!");
+
+    let body = brotli(&gzip(b"Hello, both!").await).await;
+    let response = client.post("/string")
+        .header(Header::new("Content-Encoding", "identity, gzip, br"))
+        .body(body)
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "none/none: Hello, both!");
+}
+
+#[async_test]
+async fn test_unsupported_untouched() {
+    let client = client().await;
+    let response = client.post("/string")
+        .header(Header::new("Content-Encoding", "lzma"))
+        .body("raw")
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "lzma/none: raw");
+
+    let response = client.post("/string")
+        .header(Header::new("Content-Encoding", "identity"))
+        .body("raw")
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "identity/none: raw");
+
+    // A single unsupported coding leaves the entire body untouched.
+    let body = gzip(b"raw").await;
+    let response = client.post("/string")
+        .header(Header::new("Content-Encoding", "gzip, lzma"))
+        .body(&body)
+        .dispatch().await;
+
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[async_test]
+async fn test_malformed() {
+    let client = client().await;
+    let response = client.post("/string")
+        .header(Header::new("Content-Encoding", "gzip"))
+        .body("this isn't gzip")
+        .dispatch().await;
+
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[async_test]
+async fn test_limit_applies_to_decoded_size() {
+    let client = client().await;
+    let zeros = vec![b'0'; 64 * 1024];
+    let body = gzip(&zeros).await;
+    assert!(body.len() < 1024);
+
+    let response = client.post("/capped")
+        .header(Header::new("Content-Encoding", "gzip"))
+        .body(&body)
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "false: 1024");
+
+    let response = client.post("/string")
+        .header(Header::new("Content-Encoding", "gzip"))
+        .body(&body)
+        .dispatch().await;
+
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[async_test]
+async fn test_limit_applies_to_encoded_size() {
+    let client = client().await;
+    let mut state = 0x9E3779B9u32;
+    let noise: Vec<u8> = (0..1020).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as u8
+    }).collect();
+
+    let body = gzip(&noise).await;
+    assert!(body.len() > 1024);
+
+    let response = client.post("/bytes")
+        .body(&noise)
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "true: 1020");
+
+    let response = client.post("/bytes")
+        .header(Header::new("Content-Encoding", "gzip"))
+        .body(&body)
+        .dispatch().await;
+
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[async_test]
+async fn test_form_and_method_override() {
+    let client = client().await;
+    let response = client.post("/form")
+        .header(ContentType::Form)
+        .header(Header::new("Content-Encoding", "gzip"))
+        .body(gzip(b"text=Hello%20form").await)
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "Hello form");
+
+    let response = client.post("/form")
+        .header(ContentType::Form)
+        .header(Header::new("Content-Encoding", "br"))
+        .body(brotli(b"_method=put&text=override").await)
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "put: override");
+}
+
+#[async_test]
+async fn test_temp_file() {
+    let client = client().await;
+    let contents = "Hello, file! ".repeat(1000);
+    let response = client.post("/file")
+        .header(Header::new("Content-Encoding", "gzip"))
+        .body(gzip(contents.as_bytes()).await)
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), contents);
+}
+
+#[cfg(feature = "json")]
+#[async_test]
+async fn test_json() {
+    let client = client().await;
+    let response = client.post("/json")
+        .header(ContentType::JSON)
+        .header(Header::new("Content-Encoding", "gzip"))
+        .body(gzip(br#"["a", "b", "c"]"#).await)
+        .dispatch().await;
+
+    assert_eq!(response.into_string().await.unwrap(), "a, b, c");
+}
@@ -186,3 +186,89 @@ fn test_redirection() {
     assert_eq!(response.status(), Status::PermanentRedirect);
     assert_eq!(response.headers().get("Location").next(), Some("/redir_index/"));
 }
+
+#[test]
+fn test_ranges() {
+    use rocket::http::Header;
+
+    let client = Client::debug(rocket()).expect("valid rocket");
+    let contents = std::fs::read_to_string(static_root().join("index.html")).unwrap();
+    let len = contents.len();
+
+    let response = client.get("/default/index.html").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.headers().get_one("Accept-Ranges"), Some("bytes"));
+
+    let response = client.get("/default/index.html")
+        .header(Header::new("Range", "bytes=5-8"))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::PartialContent);
+    let content_range = format!("bytes 5-8/{}", len);
+    assert_eq!(response.headers().get_one("Content-Range"), Some(&*content_range));
+    assert_eq!(response.into_string().unwrap(), &contents[5..=8]);
+
+    let response = client.get("/default/index.html")
+        .header(Header::new("Range", "bytes=-11"))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::PartialContent);
+    assert_eq!(response.into_string().unwrap(), &contents[(len - 11)..]);
+
+    let response = client.get("/default/index.html")
+        .header(Header::new("Range", format!("bytes={}-", len)))
+        .dispatch();
+
+    let content_range = format!("bytes */{}", len);
+    assert_eq!(response.status(), Status::RangeNotSatisfiable);
+    assert_eq!(response.headers().get_one("Content-Range"), Some(&*content_range));
+    assert!(response.body().is_none());
+}
+
+#[test]
+fn test_conditional() {
+    use rocket::http::Header;
+
+    let client = Client::debug(rocket()).expect("valid rocket");
+    let response = client.get("/default/index.html").dispatch();
+    let etag = response.headers().get_one("ETag").expect("ETag").to_string();
+    let modified = response.headers().get_one("Last-Modified").expect("Last-Modified").to_string();
+    assert!(etag.starts_with('"') && etag.ends_with('"'));
+
+    for method in [rocket::http::Method::Get, rocket::http::Method::Head] {
+        let response = client.req(method, "/default/index.html")
+            .header(Header::new("If-None-Match", etag.clone()))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotModified);
+        assert_eq!(response.headers().get_one("ETag"), Some(&*etag));
+        assert!(response.content_type().is_none());
+        assert!(response.body().is_none());
+    }
+
+    let response = client.get("/default/index.html")
+        .header(Header::new("If-Modified-Since", modified.clone()))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::NotModified);
+
+    let response = client.get("/default/index.html")
+        .header(Header::new("If-None-Match", "\"other\""))
+        .header(Header::new("If-Modified-Since", modified.clone()))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.get("/default/index.html")
+        .header(Header::new("If-Match", "\"other\""))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::PreconditionFailed);
+
+    let response = client.get("/default/index.html")
+        .header(Header::new("If-Range", etag))
+        .header(Header::new("Range", "bytes=0-0"))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::PartialContent);
+}
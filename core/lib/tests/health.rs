@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rocket::{Rocket, Build, Orbit};
+use rocket::fairing::AdHoc;
+use rocket::futures::channel::oneshot;
+use rocket::health::{Health, Lifecycle};
+use rocket::http::Status;
+use rocket::local::asynchronous::Client;
+
+struct Database {
+    connected: AtomicBool,
+}
+
+fn rocket() -> Rocket<Build> {
+    rocket::build()
+        .manage(Database { connected: AtomicBool::new(true) })
+        .attach(Health::default())
+        .attach(AdHoc::on_ignite("Database Check", |rocket| async move {
+            rocket.readiness_check("database", |rocket| Box::pin(async move {
+                let db = rocket.state::<Database>().unwrap();
+                match db.connected.load(Ordering::Acquire) {
+                    true => Ok(()),
+                    false => Err("not connected".into()),
+                }
+            }))
+        }))
+}
+
+async fn probe(client: &Client, path: &str) -> (Status, String) {
+    let response = client.get(path).dispatch().await;
+    (response.status(), response.into_string().await.unwrap())
+}
+
+#[rocket::async_test]
+async fn readiness_reflects_checks_and_shutdown() {
+    let client = Client::debug(rocket()).await.unwrap();
+    assert_eq!(client.rocket().lifecycle(), Lifecycle::Ready);
+    assert_eq!(probe(&client, "/health/live").await, (Status::Ok, "live".into()));
+    assert_eq!(probe(&client, "/health/ready").await, (Status::Ok, "ready".into()));
+
+    let db = client.rocket().state::<Database>().unwrap();
+    db.connected.store(false, Ordering::Release);
+    let (status, body) = probe(&client, "/health/ready").await;
+    assert_eq!(status, Status::ServiceUnavailable);
+    assert_eq!(body, "not ready\ndatabase: not connected");
+
+    db.connected.store(true, Ordering::Release);
+    assert_eq!(probe(&client, "/health/ready").await.0, Status::Ok);
+
+    client.rocket().shutdown().notify();
+    assert_eq!(client.rocket().lifecycle(), Lifecycle::Draining);
+    let expected = (Status::ServiceUnavailable, "draining".into());
+    assert_eq!(probe(&client, "/health/ready").await, expected);
+    assert_eq!(probe(&client, "/health/live").await, (Status::Ok, "live".into()));
+}
+
+#[rocket::async_test]
+async fn lifecycle_is_starting_during_liftoff() {
+    let (tx, rx) = oneshot::channel();
+    let rocket = rocket::build()
+        .attach(AdHoc::on_liftoff("Lifecycle", |rocket: &Rocket<Orbit>| Box::pin(async move {
+            tx.send(rocket.lifecycle()).unwrap();
+        })));
+
+    let client = Client::debug(rocket).await.unwrap();
+    assert_eq!(rx.await.unwrap(), Lifecycle::Starting);
+    assert_eq!(client.rocket().lifecycle(), Lifecycle::Ready);
+}
+
+#[rocket::async_test]
+async fn health_endpoints_can_be_mounted_elsewhere() {
+    let client = Client::debug(rocket::build().attach(Health::at("/status"))).await.unwrap();
+    assert_eq!(probe(&client, "/status/ready").await, (Status::Ok, "ready".into()));
+    assert_eq!(client.get("/health/ready").dispatch().await.status(), Status::NotFound);
+
+    let response = client.get("/status/live").dispatch().await;
+    assert_eq!(response.headers().get_one("Cache-Control"), Some("no-store"));
+}
@@ -0,0 +1,108 @@
+#[macro_use] extern crate rocket;
+
+use std::io;
+use std::net::Ipv4Addr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use rocket::Config;
+use rocket::config::{BindableAddr, ListenerConfig};
+use rocket::fairing::AdHoc;
+use rocket::futures::channel::oneshot;
+use rocket::listener::{Listener, TcpListener};
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::mpsc;
+
+#[get("/")]
+fn index(remote: Option<&BindableAddr>) -> String {
+    let protocol = remote.map(|r| r.protocol_name()).unwrap_or("none");
+    format!("Hello from {}!", protocol)
+}
+
+/// Yields the connections sent over a channel.
+struct Channel(mpsc::Receiver<TcpStream>);
+
+impl Listener for Channel {
+    type Connection = TcpStream;
+
+    fn local_addr(&self) -> Option<BindableAddr> {
+        None
+    }
+
+    fn poll_accept(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<TcpStream>> {
+        self.0.poll_recv(cx).map(|conn| conn.ok_or_else(|| io::ErrorKind::BrokenPipe.into()))
+    }
+}
+
+async fn get(mut stream: TcpStream) -> String {
+    let request = "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+fn config() -> Config {
+    let mut config = Config::debug_default();
+    config.address = "tcp://127.0.0.1:1".parse().unwrap();
+    config.listeners.push(ListenerConfig::new("tcp://127.0.0.1:2".parse().unwrap()));
+    config
+}
+
+#[rocket::async_test]
+async fn launch_on_custom_listener() {
+    let (tx, rx) = oneshot::channel();
+    let rocket = rocket::custom(config())
+        .mount("/", routes![index])
+        .attach(AdHoc::on_liftoff("Send Config", move |rocket| Box::pin(async move {
+            tx.send((rocket.config().clone(), rocket.shutdown())).unwrap();
+        })));
+
+    let (connections, incoming) = mpsc::channel(1);
+    let server = rocket::tokio::spawn(rocket.launch_on(Channel(incoming)));
+    let (config, shutdown) = rx.await.unwrap();
+
+    // The configured listeners are not bound, and the address is left as-is.
+    assert_eq!(config.address, "tcp://127.0.0.1:1".parse().unwrap());
+    assert!(config.listeners.is_empty());
+
+    // Connections from the listener are served with their remote address.
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+    let (conn, _) = listener.accept().await.unwrap();
+    connections.send(conn).await.unwrap();
+    assert!(get(client).await.ends_with("Hello from tcp!"));
+
+    shutdown.notify();
+    assert!(server.await.unwrap().is_ok());
+}
+
+#[rocket::async_test]
+async fn launch_ignited_on_bound_listener() {
+    let (tx, rx) = oneshot::channel();
+    let rocket = rocket::custom(config())
+        .mount("/", routes![index])
+        .attach(AdHoc::on_liftoff("Send Config", move |rocket| Box::pin(async move {
+            tx.send((rocket.config().clone(), rocket.shutdown())).unwrap();
+        })))
+        .ignite()
+        .await
+        .unwrap();
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = rocket::tokio::spawn(rocket.launch_on(listener));
+    let (config, shutdown) = rx.await.unwrap();
+
+    // The address is replaced by the listener's.
+    assert_eq!(config.address, BindableAddr::Tcp(address));
+    assert!(config.listeners.is_empty());
+
+    let client = TcpStream::connect(address).await.unwrap();
+    assert!(get(client).await.ends_with("Hello from tcp!"));
+
+    shutdown.notify();
+    assert!(server.await.unwrap().is_ok());
+}
@@ -0,0 +1,152 @@
+#[macro_use] extern crate rocket;
+
+use std::net::{Ipv4Addr, SocketAddr};
+
+use rocket::{Config, Shutdown};
+use rocket::config::{BindableAddr, ListenerConfig};
+use rocket::fairing::AdHoc;
+use rocket::futures::channel::oneshot;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::time::{sleep, timeout, Duration};
+
+#[get("/")]
+fn index() -> &'static str {
+    "index"
+}
+
+#[get("/slow")]
+async fn slow() -> &'static str {
+    sleep(Duration::from_millis(500)).await;
+    "slow"
+}
+
+async fn launch(config: Config) -> (Config, Shutdown) {
+    let config = Config {
+        address: localhost(),
+        ..config
+    };
+
+    let (tx, rx) = oneshot::channel();
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index, slow])
+        .attach(AdHoc::on_response("Load", |req, res| Box::pin(async move {
+            let rocket = req.rocket();
+            let load = format!("{} {}", rocket.open_connections(), rocket.in_flight_requests());
+            res.set_raw_header("X-Load", load);
+        })))
+        .attach(AdHoc::on_liftoff("Send Config", move |rocket| Box::pin(async move {
+            tx.send((rocket.config().clone(), rocket.shutdown())).unwrap();
+        })));
+
+    rocket::tokio::spawn(rocket.launch());
+    rx.await.unwrap()
+}
+
+fn localhost() -> BindableAddr {
+    BindableAddr::Tcp(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0))
+}
+
+async fn request(stream: &mut TcpStream, path: &str) -> String {
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut buf = [0; 1024];
+    let n = stream.read(&mut buf).await.unwrap();
+    String::from_utf8(buf[..n].to_vec()).unwrap()
+}
+
+#[rocket::async_test]
+async fn connections_are_limited() {
+    let config = Config { max_connections: Some(1), ..Config::debug_default() };
+    let (config, shutdown) = launch(config).await;
+    let port = config.address.port().unwrap();
+
+    let mut first = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await.unwrap();
+    let response = request(&mut first, "/").await;
+    assert!(response.contains("x-load: 1 1\r\n"), "{}", response);
+
+    // The second connection waits to be accepted until the first closes.
+    let mut second = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await.unwrap();
+    let waiting = timeout(Duration::from_millis(250), request(&mut second, "/"));
+    assert!(waiting.await.is_err());
+
+    drop(first);
+    let mut buf = [0; 1024];
+    let n = timeout(Duration::from_secs(5), second.read(&mut buf)).await.unwrap().unwrap();
+    let response = std::str::from_utf8(&buf[..n]).unwrap();
+    assert!(response.contains("x-load: 1 1\r\n"), "{}", response);
+
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn idle_listeners_hold_no_connections() {
+    let config = Config {
+        max_connections: Some(1),
+        listeners: vec![ListenerConfig::new(localhost())],
+        ..Config::debug_default()
+    };
+
+    let (config, shutdown) = launch(config).await;
+    let primary = config.address.port().unwrap();
+    let secondary = config.listeners[0].address.port().unwrap();
+
+    // Either listener can take the one slot while the other is idle.
+    for port in [secondary, primary, secondary] {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await.unwrap();
+        let response = timeout(Duration::from_secs(5), request(&mut stream, "/")).await.unwrap();
+        assert!(response.contains("x-load: 1 1\r\n"), "{}", response);
+    }
+
+    // A connection to one listener waits while the other's is open.
+    let mut first = TcpStream::connect((Ipv4Addr::LOCALHOST, secondary)).await.unwrap();
+    let response = request(&mut first, "/").await;
+    assert!(response.contains("x-load: 1 1\r\n"), "{}", response);
+
+    let mut second = TcpStream::connect((Ipv4Addr::LOCALHOST, primary)).await.unwrap();
+    let waiting = timeout(Duration::from_millis(250), request(&mut second, "/"));
+    assert!(waiting.await.is_err());
+
+    drop(first);
+    let mut buf = [0; 1024];
+    let n = timeout(Duration::from_secs(5), second.read(&mut buf)).await.unwrap().unwrap();
+    let response = std::str::from_utf8(&buf[..n]).unwrap();
+    assert!(response.contains("x-load: 1 1\r\n"), "{}", response);
+
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn excess_requests_are_shed() {
+    let config = Config {
+        max_in_flight_requests: Some(1),
+        retry_after: 7,
+        ..Config::debug_default()
+    };
+
+    let (config, shutdown) = launch(config).await;
+    let port = config.address.port().unwrap();
+    let mut first = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await.unwrap();
+    let mut second = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await.unwrap();
+
+    let slow = rocket::tokio::spawn(async move {
+        let response = request(&mut first, "/slow").await;
+        (first, response)
+    });
+    sleep(Duration::from_millis(100)).await;
+
+    let response = request(&mut second, "/").await;
+    assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"), "{}", response);
+    assert!(response.contains("retry-after: 7\r\n"), "{}", response);
+    assert!(response.contains("x-load: 2 1\r\n"), "{}", response);
+
+    let (_first, response) = slow.await.unwrap();
+    assert!(response.ends_with("\r\n\r\nslow"), "{}", response);
+
+    // Once the slow request completes, requests are served again.
+    let response = request(&mut second, "/").await;
+    assert!(response.contains("x-load: 2 1\r\n"), "{}", response);
+
+    shutdown.notify();
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "mtls")]
+
+#[macro_use] extern crate rocket;
+
+mod common;
+
+use rocket::Shutdown;
+use rocket::config::{MutualTls, TlsConfig};
+use rocket::fs::relative;
+use rocket::mtls::Certificate;
+
+use common::{RSA, ECDSA, temp_dir, client_auth_config, config};
+
+const CA_CERT: &str = common::CA.0;
+// `ECDSA` is revoked by both `CRL` and `STALE_CRL`.
+const CRL: &str = relative!("../../examples/tls/private/crl.pem");
+const STALE_CRL: &str = relative!("../../examples/tls/private/stale_crl.pem");
+
+#[get("/")]
+fn index(cert: Certificate<'_>) -> String {
+    cert.serial().to_string()
+}
+
+async fn launch(mtls: MutualTls) -> (u16, Shutdown) {
+    let tls = TlsConfig::from_paths(RSA.0, RSA.1).with_mutual(mtls);
+    let rocket = rocket::custom(config(tls)).mount("/", routes![index]).attach(common::reload());
+    common::launch(rocket).await
+}
+
+/// Requests `path` over TLS, authenticating with the client certificate and
+/// key in `client`. Returns the response or the handshake error.
+async fn get(port: u16, path: &str, client: (&str, &str)) -> std::io::Result<String> {
+    let config = client_auth_config(client);
+    common::get(port, path, config).await.map(|(_, response)| response)
+}
+
+#[rocket::async_test]
+async fn revoked_client_certificates_are_rejected() {
+    let (port, shutdown) = launch(MutualTls::from_path(CA_CERT).with_crl_path(CRL)).await;
+    let response = get(port, "/", RSA).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+    assert!(get(port, "/", ECDSA).await.is_err());
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn stale_crls_fail_closed_unless_allowed() {
+    let (port, shutdown) = launch(MutualTls::from_path(CA_CERT).with_crl_path(STALE_CRL)).await;
+    assert!(get(port, "/", RSA).await.is_err());
+    shutdown.notify();
+
+    let mtls = MutualTls::from_path(CA_CERT).with_crl_path(STALE_CRL).allow_stale_crls(true);
+    let (port, shutdown) = launch(mtls).await;
+    assert!(get(port, "/", RSA).await.unwrap().starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(get(port, "/", ECDSA).await.is_err());
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn crls_are_reloaded() {
+    let dir = temp_dir("mtls-crl-reload");
+    let crl = dir.join("crl.pem");
+    std::fs::copy(CRL, &crl).unwrap();
+
+    let (port, shutdown) = launch(MutualTls::from_path(CA_CERT).with_crl_path(&crl)).await;
+    assert!(get(port, "/", RSA).await.unwrap().starts_with("HTTP/1.1 200 OK\r\n"));
+
+    // A failed reload leaves the previous CRLs in place.
+    std::fs::write(&crl, "not a CRL").unwrap();
+    let response = get(port, "/reload", RSA).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 500 Internal Server Error\r\n"), "{}", response);
+    assert!(get(port, "/", RSA).await.unwrap().starts_with("HTTP/1.1 200 OK\r\n"));
+
+    std::fs::copy(STALE_CRL, &crl).unwrap();
+    let response = get(port, "/reload", RSA).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 204 No Content\r\n"), "{}", response);
+    assert!(get(port, "/", RSA).await.is_err());
+
+    shutdown.notify();
+}
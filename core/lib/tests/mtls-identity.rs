@@ -0,0 +1,50 @@
+#![cfg(feature = "mtls")]
+
+#[macro_use] extern crate rocket;
+
+mod common;
+
+use rocket::config::{IdentityAttribute, MutualTls, TlsConfig};
+use rocket::mtls::Identity;
+
+use common::{RSA, CA, client_auth_config, config, body};
+
+#[get("/")]
+fn index(id: Identity<'_>) -> String {
+    format!("{} {}", id.attribute(), id)
+}
+
+/// Identifies a client authenticating with the `RSA` certificate by the first
+/// of `attributes` that it has. Returns the response.
+async fn identify<const N: usize>(attributes: [IdentityAttribute; N]) -> String {
+    let mtls = MutualTls::from_path(CA.0).with_identity(attributes);
+    let tls = TlsConfig::from_paths(RSA.0, RSA.1).with_mutual(mtls);
+    let rocket = rocket::custom(config(tls)).mount("/", routes![index]);
+    let (port, shutdown) = common::launch(rocket).await;
+    let (_, response) = common::get(port, "/", client_auth_config(RSA)).await.unwrap();
+    shutdown.notify();
+    response
+}
+
+#[rocket::async_test]
+async fn identifies_by_first_present_attribute() {
+    use IdentityAttribute::*;
+
+    assert_eq!(body(&identify([CommonName]).await), "common_name localhost");
+
+    let response = identify([Spiffe, Uri, Ip, Email, Dns, CommonName]).await;
+    assert_eq!(body(&response), "dns localhost");
+
+    let response = identify([Serial]).await;
+    assert_eq!(body(&response), "serial 301438261598342027628437991669560131935683596135");
+
+    let response = identify([Fingerprint]).await;
+    let fingerprint = "adabd1d1fe48a77e3053a506c0430b0ada93d8883a932c6c6f484df25ff4efd2";
+    assert_eq!(body(&response), format!("fingerprint {}", fingerprint));
+
+    let response = identify([Spiffe, Email]).await;
+    assert!(response.starts_with("HTTP/1.1 401 Unauthorized\r\n"), "{}", response);
+
+    let response = identify([]).await;
+    assert!(response.starts_with("HTTP/1.1 401 Unauthorized\r\n"), "{}", response);
+}
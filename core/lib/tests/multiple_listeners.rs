@@ -0,0 +1,99 @@
+#[macro_use] extern crate rocket;
+
+use std::net::{Ipv4Addr, SocketAddr};
+
+use rocket::Config;
+use rocket::config::{BindableAddr, ListenerConfig};
+use rocket::fairing::AdHoc;
+use rocket::futures::channel::oneshot;
+use rocket::tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+
+#[get("/")]
+fn index(remote: Option<&BindableAddr>) -> String {
+    let protocol = remote.map(|r| r.protocol_name()).unwrap_or("none");
+    format!("Hello from {}!", protocol)
+}
+
+fn localhost() -> BindableAddr {
+    BindableAddr::Tcp(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0))
+}
+
+async fn get(mut stream: impl AsyncRead + AsyncWrite + Unpin) -> String {
+    let request = "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[rocket::async_test]
+async fn serves_on_all_listeners() {
+    #[cfg(unix)]
+    let socket = std::env::temp_dir()
+        .join(format!("rocket-listeners-{}.sock", std::process::id()));
+
+    let mut config = Config::debug_default();
+    config.address = localhost();
+    config.listeners.push(ListenerConfig::new(localhost()));
+    #[cfg(unix)]
+    config.listeners.push(ListenerConfig::new(BindableAddr::Unix(socket.clone())));
+
+    let (tx, rx) = oneshot::channel();
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index])
+        .attach(AdHoc::on_liftoff("Send Config", move |rocket| Box::pin(async move {
+            tx.send((rocket.config().clone(), rocket.shutdown())).unwrap();
+        })));
+
+    let server = rocket::tokio::spawn(rocket.launch());
+    let (config, shutdown) = rx.await.unwrap();
+
+    // Every TCP listener bound to port `0` reports the port it was assigned.
+    let ports = std::iter::once(&config.address)
+        .chain(config.listeners.iter().map(|l| &l.address))
+        .filter_map(|address| address.port())
+        .collect::<Vec<_>>();
+
+    assert_eq!(ports.len(), 2);
+    assert!(ports.iter().all(|&port| port != 0));
+    assert_ne!(ports[0], ports[1]);
+
+    for port in ports {
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await.unwrap();
+        assert!(get(stream).await.ends_with("Hello from tcp!"));
+    }
+
+    #[cfg(unix)] {
+        let stream = rocket::tokio::net::UnixStream::connect(&socket).await.unwrap();
+        assert!(get(stream).await.ends_with("Hello from none!"));
+    }
+
+    // A single `Shutdown` stops every listener.
+    shutdown.notify();
+    assert!(server.await.unwrap().is_ok());
+
+    #[cfg(unix)]
+    assert!(!socket.exists());
+}
+
+#[rocket::async_test]
+async fn fails_if_any_listener_fails_to_bind() {
+    let taken = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let mut config = Config::debug_default();
+    config.address = localhost();
+    config.listeners.push(ListenerConfig::new(BindableAddr::Tcp(taken.local_addr().unwrap())));
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let rocket = rocket::custom(config)
+        .attach(AdHoc::on_liftoff("Liftoff", move |_| Box::pin(async move {
+            let _ = tx.send(());
+        })));
+
+    let error = rocket.launch().await.unwrap_err();
+    assert!(matches!(error.kind(), rocket::error::ErrorKind::Bind(_)));
+
+    // Liftoff never happens.
+    assert!(rx.await.is_err());
+}
@@ -0,0 +1,151 @@
+#[macro_use] extern crate rocket;
+
+use std::net::{Ipv4Addr, SocketAddr};
+
+use rocket::{Config, Shutdown};
+use rocket::config::{BindableAddr, ListenerConfig, ProxyProtocol};
+use rocket::fairing::AdHoc;
+use rocket::futures::channel::oneshot;
+use rocket::http::proxy_protocol::ProxyHeader;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::time::{sleep, timeout, Duration};
+
+#[get("/")]
+fn index(remote: Option<&BindableAddr>, header: Option<&ProxyHeader>) -> String {
+    let remote = remote.map(|r| r.to_string());
+    let authority = header.and_then(|h| h.authority());
+    format!("{} {}", remote.as_deref().unwrap_or("none"), authority.unwrap_or("none"))
+}
+
+const REQUEST: &[u8] = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+
+async fn send(port: u16, chunks: &[&[u8]]) -> String {
+    let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await.unwrap();
+    for chunk in chunks {
+        stream.write_all(chunk).await.unwrap();
+        stream.flush().await.unwrap();
+        sleep(Duration::from_millis(10)).await;
+    }
+
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response).await;
+    response
+}
+
+fn v2(command: u8, family: u8, body: &[u8]) -> Vec<u8> {
+    let mut header = b"\r\n\r\n\0\r\nQUIT\n".to_vec();
+    header.extend_from_slice(&[command, family]);
+    header.extend_from_slice(&(body.len() as u16).to_be_bytes());
+    header.extend_from_slice(body);
+    header
+}
+
+async fn launch(mut config: Config) -> (u16, u16, Shutdown) {
+    let localhost = BindableAddr::Tcp(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0));
+    config.address = localhost.clone();
+    config.proxy_protocol = Some(ProxyProtocol {
+        timeout: 1,
+        ..ProxyProtocol::new(vec!["127.0.0.0/8".parse().unwrap()])
+    });
+
+    // The peers of this listener, on localhost, aren't trusted.
+    let untrusted = ProxyProtocol::new(vec!["10.0.0.0/8".parse().unwrap()]);
+    config.listeners.push(ListenerConfig::new(localhost).with_proxy_protocol(untrusted));
+
+    let (tx, rx) = oneshot::channel();
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index])
+        .attach(AdHoc::on_liftoff("Send Config", move |rocket| Box::pin(async move {
+            tx.send((rocket.config().clone(), rocket.shutdown())).unwrap();
+        })));
+
+    rocket::tokio::spawn(rocket.launch());
+    let (config, shutdown) = rx.await.unwrap();
+    (config.address.port().unwrap(), config.listeners[0].address.port().unwrap(), shutdown)
+}
+
+#[rocket::async_test]
+async fn rewrites_remote_from_trusted_peers() {
+    let (trusted, untrusted, shutdown) = launch(Config::debug_default()).await;
+
+    let v1 = b"PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\n";
+    let response = send(trusted, &[v1, REQUEST]).await;
+    assert!(response.ends_with("tcp://192.0.2.1:56324 none"), "{}", response);
+
+    // The header may arrive in pieces and share a packet with the request.
+    let response = send(trusted, &[
+        b"PROXY TCP6 2001:db8::1 ",
+        b"::1 80 443\r\nGET / HTTP/1.1\r\n",
+        b"Connection: close\r\n\r\n",
+    ]).await;
+    assert!(response.ends_with("tcp://[2001:db8::1]:80 none"), "{}", response);
+
+    let mut body = vec![198, 51, 100, 7, 192, 0, 2, 2, 0x1f, 0x90, 0x01, 0xbb];
+    body.extend_from_slice(&[0x02, 0, 11]);
+    body.extend_from_slice(b"example.com");
+    let header = v2(0x21, 0x11, &body);
+    let response = send(trusted, &[&header[..5], &header[5..], REQUEST]).await;
+    assert!(response.ends_with("tcp://198.51.100.7:8080 example.com"), "{}", response);
+
+    // A `LOCAL` header keeps the real peer's address.
+    let response = send(trusted, &[&v2(0x20, 0x00, &[]), REQUEST]).await;
+    assert!(response.contains("\r\n\r\ntcp://127.0.0.1:"), "{}", response);
+    assert!(response.ends_with(" none"), "{}", response);
+
+    // A trusted peer must send a valid header, or the connection is closed.
+    assert_eq!(send(trusted, &[REQUEST]).await, "");
+    assert_eq!(send(trusted, &[b"PROXY TCP4 nope\r\n", REQUEST]).await, "");
+
+    // Headers aren't read from untrusted peers.
+    let response = send(untrusted, &[REQUEST]).await;
+    assert!(response.contains("\r\n\r\ntcp://127.0.0.1:"), "{}", response);
+    assert!(send(untrusted, &[v1, REQUEST]).await.starts_with("HTTP/1.1 400"));
+
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn slow_peers_do_not_block_others() {
+    let (trusted, _, shutdown) = launch(Config::debug_default()).await;
+
+    // This peer never sends its header...
+    let mut stalled = TcpStream::connect((Ipv4Addr::LOCALHOST, trusted)).await.unwrap();
+    sleep(Duration::from_millis(50)).await;
+
+    // ...but others are served in the meantime.
+    let v1 = b"PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\n";
+    let response = send(trusted, &[v1, REQUEST]).await;
+    assert!(response.ends_with("tcp://192.0.2.1:56324 none"), "{}", response);
+
+    // Until its connection is closed after the timeout.
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(5), stalled.read_to_end(&mut buf));
+    assert!(read.await.is_ok());
+    assert!(buf.is_empty());
+
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn unread_headers_count_as_open_connections() {
+    let config = Config { max_connections: Some(1), ..Config::debug_default() };
+    let (trusted, untrusted, shutdown) = launch(config).await;
+
+    // This peer never sends its header, taking the only connection slot...
+    let mut stalled = TcpStream::connect((Ipv4Addr::LOCALHOST, trusted)).await.unwrap();
+    sleep(Duration::from_millis(50)).await;
+
+    // ...so others wait until its connection is closed after the timeout.
+    let waiting = timeout(Duration::from_millis(250), send(untrusted, &[REQUEST]));
+    assert!(waiting.await.is_err());
+
+    let response = timeout(Duration::from_secs(5), send(untrusted, &[REQUEST])).await.unwrap();
+    assert!(response.contains("\r\n\r\ntcp://127.0.0.1:"), "{}", response);
+
+    let mut buf = vec![];
+    assert!(stalled.read_to_end(&mut buf).await.is_ok());
+    assert!(buf.is_empty());
+
+    shutdown.notify();
+}
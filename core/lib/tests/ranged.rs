@@ -0,0 +1,194 @@
+#[macro_use] extern crate rocket;
+
+use rocket::http::{Header, Status};
+use rocket::local::blocking::{Client, LocalResponse};
+use rocket::response::{Ranged, Responder};
+use rocket::response::stream::ByteStream;
+
+const DATA: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+#[derive(Responder)]
+#[response(content_type = "text/plain")]
+struct Validated(&'static [u8], Header<'static>, Header<'static>);
+
+#[get("/")]
+fn data() -> Ranged<&'static [u8]> {
+    Ranged(DATA)
+}
+
+#[get("/validated")]
+fn validated() -> Ranged<Validated> {
+    let etag = Header::new("ETag", "\"v1\"");
+    let date = Header::new("Last-Modified", "Sun, 06 Nov 1994 08:49:37 GMT");
+    Ranged(Validated(DATA, etag, date))
+}
+
+#[get("/stream")]
+fn stream() -> Ranged<ByteStream![&'static [u8]]> {
+    Ranged(ByteStream! { yield DATA; })
+}
+
+#[get("/missing")]
+fn missing() -> Ranged<Option<&'static [u8]>> {
+    Ranged(None)
+}
+
+#[post("/")]
+fn post() -> Ranged<&'static [u8]> {
+    Ranged(DATA)
+}
+
+fn client() -> Client {
+    let routes = routes![data, validated, stream, missing, post];
+    Client::debug(rocket::build().mount("/", routes)).unwrap()
+}
+
+fn get<'c>(
+    client: &'c Client,
+    uri: &'static str,
+    headers: &[(&'static str, &'static str)],
+) -> LocalResponse<'c> {
+    let mut request = client.get(uri);
+    for &(name, value) in headers {
+        request.add_header(Header::new(name, value));
+    }
+
+    request.dispatch()
+}
+
+#[test]
+fn test_full() {
+    let client = client();
+    let response = get(&client, "/", &[]);
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.headers().get_one("Accept-Ranges"), Some("bytes"));
+    assert!(response.headers().get_one("Content-Range").is_none());
+    assert_eq!(response.into_bytes().unwrap(), DATA);
+
+    // Malformed and non-byte ranges are ignored.
+    for range in ["bytes=z-1", "bytes=9-1", "lines=0-1", "bytes="] {
+        let response = get(&client, "/", &[("Range", range)]);
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_bytes().unwrap(), DATA);
+    }
+
+    // Ranges are only served for `GET` requests.
+    let response = client.post("/").header(Header::new("Range", "bytes=0-1")).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_bytes().unwrap(), DATA);
+
+    // `HEAD` requests see the same headers as the equivalent `GET`.
+    let response = client.head("/").header(Header::new("Range", "bytes=0-1")).dispatch();
+    assert_eq!(response.status(), Status::PartialContent);
+    assert_eq!(response.headers().get_one("Content-Range"), Some("bytes 0-1/36"));
+    assert!(response.into_bytes().unwrap_or_default().is_empty());
+}
+
+#[test]
+fn test_single_range() {
+    let client = client();
+    let cases: &[(&str, &str, &[u8])] = &[
+        ("bytes=0-0", "bytes 0-0/36", b"0"),
+        ("bytes=10-15", "bytes 10-15/36", b"abcdef"),
+        ("bytes=30-", "bytes 30-35/36", b"uvwxyz"),
+        ("bytes=30-1000", "bytes 30-35/36", b"uvwxyz"),
+        ("bytes=-3", "bytes 33-35/36", b"xyz"),
+        ("bytes=-100", "bytes 0-35/36", DATA),
+        ("bytes=0-3,2-5", "bytes 0-5/36", b"012345"),
+        ("bytes=100-200,1-1", "bytes 1-1/36", b"1"),
+    ];
+
+    for &(range, content_range, expected) in cases {
+        let response = get(&client, "/", &[("Range", range)]);
+        assert_eq!(response.status(), Status::PartialContent, "{}", range);
+        assert_eq!(response.headers().get_one("Content-Range"), Some(content_range));
+        assert_eq!(response.body().preset_size(), Some(expected.len()));
+        assert_eq!(response.into_bytes().unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_multiple_ranges() {
+    let client = client();
+    let response = get(&client, "/validated", &[("Range", "bytes=0-1, 10-12, -2")]);
+    assert_eq!(response.status(), Status::PartialContent);
+    assert!(response.headers().get_one("Content-Range").is_none());
+
+    let content_type = response.content_type().unwrap();
+    assert_eq!(content_type.top(), "multipart");
+    assert_eq!(content_type.sub(), "byteranges");
+    let boundary = content_type.param("boundary").unwrap().to_string();
+
+    let size = response.body().preset_size();
+    let body = response.into_string().unwrap();
+    assert_eq!(size, Some(body.len()));
+
+    let expected = format!("--{b}\r\n\
+        Content-Type: text/plain\r\n\
+        Content-Range: bytes 0-1/36\r\n\r\n01\r\n\
+        --{b}\r\n\
+        Content-Type: text/plain\r\n\
+        Content-Range: bytes 10-12/36\r\n\r\nabc\r\n\
+        --{b}\r\n\
+        Content-Type: text/plain\r\n\
+        Content-Range: bytes 34-35/36\r\n\r\nyz\r\n\
+        --{b}--\r\n", b = boundary);
+
+    assert_eq!(body, expected);
+}
+
+#[test]
+fn test_unsatisfiable() {
+    let client = client();
+    for range in ["bytes=36-", "bytes=100-200", "bytes=-0", "bytes=40-50, 36-"] {
+        let response = get(&client, "/", &[("Range", range)]);
+        assert_eq!(response.status(), Status::RangeNotSatisfiable);
+        assert_eq!(response.headers().get_one("Content-Range"), Some("bytes */36"));
+        assert!(response.body().is_none());
+    }
+}
+
+#[test]
+fn test_if_range() {
+    let client = client();
+    let holds = [
+        "\"v1\"",
+        "Sun, 06 Nov 1994 08:49:37 GMT",
+    ];
+
+    for if_range in holds {
+        let headers = [("Range", "bytes=0-1"), ("If-Range", if_range)];
+        let response = get(&client, "/validated", &headers);
+        assert_eq!(response.status(), Status::PartialContent);
+        assert_eq!(response.into_bytes().unwrap(), b"01");
+    }
+
+    let fails = [
+        "\"v2\"",
+        "W/\"v1\"",
+        "Sun, 06 Nov 1994 08:49:38 GMT",
+    ];
+
+    for if_range in fails {
+        let headers = [("Range", "bytes=0-1"), ("If-Range", if_range)];
+        let response = get(&client, "/validated", &headers);
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_bytes().unwrap(), DATA);
+    }
+
+    // Without validators, `If-Range` never holds.
+    let response = get(&client, "/", &[("Range", "bytes=0-1"), ("If-Range", "\"v1\"")]);
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[test]
+fn test_passthrough() {
+    let client = client();
+    let response = get(&client, "/stream", &[("Range", "bytes=0-1")]);
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.headers().get_one("Accept-Ranges").is_none());
+    assert_eq!(response.into_bytes().unwrap(), DATA);
+
+    let response = get(&client, "/missing", &[("Range", "bytes=0-1")]);
+    assert_eq!(response.status(), Status::NotFound);
+}
@@ -0,0 +1,91 @@
+#[macro_use] extern crate rocket;
+
+use rocket::{Rocket, Build};
+use rocket::http::Header;
+use rocket::local::blocking::Client;
+use rocket::request::RequestId;
+
+#[get("/")]
+fn id(id: RequestId<'_>) -> String {
+    id.to_string()
+}
+
+fn rocket(request_id_header: &'static str) -> Rocket<Build> {
+    let figment = rocket::Config::figment().merge(("request_id_header", request_id_header));
+    rocket::custom(figment).mount("/", routes![id])
+}
+
+#[test]
+fn generates_and_echoes_ids() {
+    let client = Client::debug(rocket("X-Request-Id")).unwrap();
+    let response = client.get("/").dispatch();
+    let echoed = response.headers().get_one("X-Request-Id").unwrap().to_string();
+    assert_eq!(response.into_string().unwrap(), echoed);
+    assert_eq!(echoed.len(), 32);
+    assert!(echoed.bytes().all(|b| b.is_ascii_hexdigit()));
+
+    let response = client.get("/").dispatch();
+    assert_ne!(response.headers().get_one("X-Request-Id").unwrap(), echoed);
+}
+
+#[test]
+fn accepts_only_valid_incoming_ids() {
+    let client = Client::debug(rocket("X-Request-Id")).unwrap();
+    let long = "a".repeat(128);
+    for id in ["abc", "7d9b1c2e-0f4a-4a57-9a1b-3c2d4e5f6a7b", "trace:a/b+c=_.", &long] {
+        let header = Header::new("X-Request-Id", id.to_string());
+        let response = client.get("/").header(header).dispatch();
+        assert_eq!(response.headers().get_one("X-Request-Id"), Some(id));
+        assert_eq!(response.into_string().unwrap(), id);
+    }
+
+    let too_long = "a".repeat(129);
+    for id in ["", "has space", "back\\slash", "ünicode", &too_long] {
+        let header = Header::new("X-Request-Id", id.to_string());
+        let response = client.get("/").header(header).dispatch();
+        let echoed = response.headers().get_one("X-Request-Id").unwrap();
+        assert_ne!(echoed, id);
+        assert_eq!(echoed.len(), 32);
+    }
+}
+
+#[test]
+fn header_is_configurable() {
+    let client = Client::debug(rocket("X-Correlation-Id")).unwrap();
+    let response = client.get("/")
+        .header(Header::new("X-Request-Id", "ignored"))
+        .header(Header::new("X-Correlation-Id", "used"))
+        .dispatch();
+
+    assert_eq!(response.headers().get_one("X-Correlation-Id"), Some("used"));
+    assert!(!response.headers().contains("X-Request-Id"));
+    assert_eq!(response.into_string().unwrap(), "used");
+
+    let figment = rocket::Config::figment().merge(("request_id_header", false));
+    let client = Client::debug(rocket::custom(figment).mount("/", routes![id])).unwrap();
+    let response = client.get("/").header(Header::new("X-Request-Id", "ignored")).dispatch();
+    assert!(!response.headers().contains("X-Request-Id"));
+    assert_eq!(response.into_string().unwrap().len(), 32);
+}
+
+#[test]
+fn accepts_ids_only_from_trusted_peers() {
+    let figment = rocket::Config::figment().merge(("trusted_proxies", ["10.0.0.0/8"]));
+    let client = Client::debug(rocket::custom(figment).mount("/", routes![id])).unwrap();
+
+    let response = client.get("/")
+        .remote("tcp://10.0.0.1:8000".parse().unwrap())
+        .header(Header::new("X-Request-Id", "from-proxy"))
+        .dispatch();
+
+    assert_eq!(response.into_string().unwrap(), "from-proxy");
+
+    let response = client.get("/")
+        .remote("tcp://192.0.2.1:8000".parse().unwrap())
+        .header(Header::new("X-Request-Id", "spoofed"))
+        .dispatch();
+
+    let echoed = response.headers().get_one("X-Request-Id").unwrap().to_string();
+    assert_ne!(echoed, "spoofed");
+    assert_eq!(response.into_string().unwrap(), echoed);
+}
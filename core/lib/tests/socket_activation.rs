@@ -0,0 +1,115 @@
+#![cfg(unix)]
+
+#[macro_use] extern crate rocket;
+
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, TcpStream};
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use rocket::{Config, Shutdown};
+use rocket::config::{BindableAddr, ListenerConfig};
+
+/// Set in the environment of the child process that adopts the sockets.
+const CHILD: &str = "SOCKET_ACTIVATION_CHILD";
+
+#[get("/")]
+fn index(remote: Option<&BindableAddr>) -> String {
+    let protocol = remote.map(|r| r.protocol_name()).unwrap_or("none");
+    format!("Hello from {}!", protocol)
+}
+
+#[get("/addresses")]
+fn addresses(config: &Config) -> String {
+    format!("{} {}", config.address, config.listeners[0].address)
+}
+
+#[get("/shutdown")]
+fn shutdown(shutdown: Shutdown) -> &'static str {
+    shutdown.notify();
+    "Shutting down."
+}
+
+fn get<S: Read + Write>(mut stream: S, path: &str) -> String {
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+/// Serves on the sockets passed by `serves_on_inherited_sockets()`.
+fn child() {
+    let mut config = Config::debug_default();
+    config.address = "systemd://web".parse().unwrap();
+    config.listeners.push(ListenerConfig::new("fd://4".parse().unwrap()));
+
+    let rocket = rocket::custom(config).mount("/", routes![index, addresses, shutdown]);
+    rocket::execute(rocket.launch()).expect("launch on inherited sockets");
+}
+
+#[test]
+fn serves_on_inherited_sockets() {
+    if std::env::var_os(CHILD).is_some() {
+        return child();
+    }
+
+    let tcp = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let port = tcp.local_addr().unwrap().port();
+    let path = std::env::temp_dir().join(format!("rocket-activation-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let unix = UnixListener::bind(&path).unwrap();
+
+    // The sockets are passed as the child's stdin and stdout, which `sh` moves
+    // to descriptors 3 and 4 before `exec`ing this test binary in its place,
+    // keeping the PID it reports in `LISTEN_PID`.
+    let script = r#"export LISTEN_PID=$$; exec "$0" "$@" 3<&0 4<&1 0</dev/null 1>&2"#;
+    let mut child = Command::new("sh")
+        .args(["-c", script])
+        .arg(std::env::current_exe().unwrap())
+        .args(["serves_on_inherited_sockets", "--exact", "--nocapture"])
+        .env(CHILD, "1")
+        .env("LISTEN_FDS", "2")
+        .env("LISTEN_FDNAMES", "web:internal")
+        .stdin(unsafe { Stdio::from_raw_fd(tcp.into_raw_fd()) })
+        .stdout(unsafe { Stdio::from_raw_fd(unix.into_raw_fd()) })
+        .spawn()
+        .unwrap();
+
+    let connect_tcp = || {
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(30))).unwrap();
+        stream
+    };
+
+    assert!(get(connect_tcp(), "/").ends_with("Hello from tcp!"));
+
+    let stream = UnixStream::connect(&path).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(30))).unwrap();
+    assert!(get(stream, "/").ends_with("Hello from none!"));
+
+    // The configured addresses are replaced by those of the adopted sockets.
+    let expected = format!("tcp://127.0.0.1:{} unix://{}", port, path.display());
+    assert!(get(connect_tcp(), "/addresses").ends_with(&expected));
+
+    assert!(get(connect_tcp(), "/shutdown").ends_with("Shutting down."));
+    assert!(child.wait().unwrap().success());
+
+    // Inherited Unix sockets belong to the parent and are not removed.
+    assert!(path.exists());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[rocket::async_test]
+async fn fails_without_inherited_sockets() {
+    for address in ["fd://3", "systemd://web"] {
+        let mut config = Config::debug_default();
+        config.address = address.parse().unwrap();
+
+        let error = rocket::custom(config).launch().await.unwrap_err();
+        assert!(matches!(error.kind(), rocket::error::ErrorKind::Bind(_)));
+    }
+}
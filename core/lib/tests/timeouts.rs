@@ -0,0 +1,166 @@
+#[macro_use] extern crate rocket;
+
+use std::net::{Ipv4Addr, SocketAddr};
+
+use rocket::{Config, Shutdown};
+use rocket::config::{BindableAddr, Timeouts};
+use rocket::data::{Data, ToByteUnit};
+use rocket::fairing::AdHoc;
+use rocket::futures::channel::oneshot;
+use rocket::http::Status;
+use rocket::local::asynchronous::Client;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::time::{sleep, timeout, Duration};
+
+#[get("/")]
+fn index() -> &'static str {
+    "index"
+}
+
+#[post("/", data = "<data>")]
+async fn upload(data: Data<'_>) -> String {
+    match data.open(1.kibibytes()).into_string().await {
+        Ok(string) => string.into_inner(),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[get("/slow")]
+async fn slow() -> &'static str {
+    sleep(Duration::from_secs(2)).await;
+    "slow"
+}
+
+#[get("/slow/unlimited", timeout = 0)]
+async fn unlimited() -> &'static str {
+    sleep(Duration::from_secs(2)).await;
+    "unlimited"
+}
+
+#[get("/slow/limited", timeout = 1)]
+async fn limited() -> &'static str {
+    sleep(Duration::from_secs(2)).await;
+    "limited"
+}
+
+async fn launch() -> (u16, Shutdown) {
+    let config = Config {
+        address: BindableAddr::Tcp(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)),
+        timeouts: Timeouts { header: 1, body: 1, idle: Some(1), ..Default::default() },
+        ..Config::debug_default()
+    };
+
+    let (tx, rx) = oneshot::channel();
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index, upload])
+        .attach(AdHoc::on_liftoff("Send Config", move |rocket| Box::pin(async move {
+            tx.send((rocket.config().clone(), rocket.shutdown())).unwrap();
+        })));
+
+    rocket::tokio::spawn(rocket.launch());
+    let (config, shutdown) = rx.await.unwrap();
+    (config.address.port().unwrap(), shutdown)
+}
+
+async fn connect(port: u16) -> TcpStream {
+    TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await.unwrap()
+}
+
+async fn read_to_close(stream: &mut TcpStream) -> String {
+    let mut response = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut response));
+    assert!(read.await.is_ok(), "connection was not closed");
+    String::from_utf8(response).unwrap()
+}
+
+#[rocket::async_test]
+async fn slow_heads_are_rejected() {
+    let (port, shutdown) = launch().await;
+
+    let mut stream = connect(port).await;
+    stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n").await.unwrap();
+    let response = read_to_close(&mut stream).await;
+    assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"), "{}", response);
+
+    // A connection that never sends a request is closed without a response.
+    let mut stream = connect(port).await;
+    assert_eq!(read_to_close(&mut stream).await, "");
+
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn idle_connections_are_closed() {
+    let (port, shutdown) = launch().await;
+
+    let mut stream = connect(port).await;
+    for _ in 0..2 {
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let mut buf = [0; 1024];
+        let n = stream.read(&mut buf).await.unwrap();
+        let response = std::str::from_utf8(&buf[..n]).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.ends_with("index"), "{}", response);
+
+        // Requests that start within the idle timeout are served.
+        sleep(Duration::from_millis(500)).await;
+    }
+
+    assert_eq!(read_to_close(&mut stream).await, "");
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn slow_bodies_are_rejected() {
+    let (port, shutdown) = launch().await;
+
+    let mut stream = connect(port).await;
+    let head = b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 10\r\n\r\n";
+    stream.write_all(head).await.unwrap();
+    for chunk in [&b"hel"[..], b"lo"] {
+        sleep(Duration::from_millis(500)).await;
+        stream.write_all(chunk).await.unwrap();
+    }
+
+    let response = read_to_close(&mut stream).await;
+    assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"), "{}", response);
+    assert!(response.contains("connection: close\r\n"), "{}", response);
+
+    // Bodies received in time are unaffected.
+    let mut stream = connect(port).await;
+    stream.write_all(head).await.unwrap();
+    for chunk in [&b"hello"[..], b" ", b"world"] {
+        sleep(Duration::from_millis(500)).await;
+        stream.write_all(chunk).await.unwrap();
+    }
+
+    let mut buf = [0; 1024];
+    let n = stream.read(&mut buf).await.unwrap();
+    let response = std::str::from_utf8(&buf[..n]).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+    assert!(response.ends_with("hello worl"), "{}", response);
+
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn handlers_exceeding_deadline_fail() {
+    async fn client_with(timeouts: Timeouts) -> Client {
+        let config = Config { timeouts, ..Config::debug_default() };
+        let rocket = rocket::custom(config).mount("/", routes![slow, unlimited, limited]);
+        Client::debug(rocket).await.unwrap()
+    }
+
+    let client = client_with(Timeouts { handler: 1, ..Default::default() }).await;
+    assert_eq!(client.get("/slow").dispatch().await.status(), Status::ServiceUnavailable);
+    assert_eq!(client.get("/slow/limited").dispatch().await.status(), Status::ServiceUnavailable);
+
+    let response = client.get("/slow/unlimited").dispatch().await;
+    assert_eq!(response.into_string().await.unwrap(), "unlimited");
+
+    let status = Status::GatewayTimeout;
+    let client = client_with(Timeouts { handler_status: status, ..Default::default() }).await;
+    assert_eq!(client.get("/slow").dispatch().await.into_string().await.unwrap(), "slow");
+    assert_eq!(client.get("/slow/limited").dispatch().await.status(), status);
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "tls")]
+
+#[macro_use] extern crate rocket;
+
+mod common;
+
+use rocket::Shutdown;
+use rocket::config::{TlsConfig, TlsVersion};
+use rocket::http::Status;
+use rocket::http::tls::rustls::{self, ClientConfig};
+use rocket::local::asynchronous::Client;
+use rocket::request::{self, FromRequest, Request, TlsInfo};
+
+use common::{RSA, roots, config};
+
+struct Tls13;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Tls13 {
+    type Error = TlsVersion;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, TlsVersion> {
+        match req.tls_info() {
+            Some(tls) if tls.version() >= TlsVersion::TLSv1_3 => request::Outcome::Success(Tls13),
+            Some(tls) => request::Outcome::Failure((Status::Forbidden, tls.version())),
+            None => request::Outcome::Forward(()),
+        }
+    }
+}
+
+#[get("/")]
+fn index(tls: Option<TlsInfo<'_>>) -> String {
+    match tls {
+        Some(tls) => {
+            let alpn = tls.alpn_protocol().map(String::from_utf8_lossy);
+            format!("{} {:?} {:?}", tls, alpn, tls.server_name())
+        }
+        None => "none".into(),
+    }
+}
+
+#[get("/admin")]
+fn admin(_tls: Tls13) -> &'static str {
+    "admin"
+}
+
+async fn launch() -> (u16, Shutdown) {
+    let tls = TlsConfig::from_paths(RSA.0, RSA.1).with_preferred_server_cipher_order(true);
+    common::launch(rocket::custom(config(tls)).mount("/", routes![index, admin])).await
+}
+
+/// Requests `path` over TLS using `version`, offering `alpn` protocols.
+async fn get(
+    port: u16,
+    path: &'static str,
+    version: &'static rustls::SupportedProtocolVersion,
+    alpn: &'static [&'static [u8]],
+) -> String {
+    let mut config = ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(&[version])
+        .unwrap()
+        .with_root_certificates(roots())
+        .with_no_client_auth();
+
+    config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+    common::get(port, path, config).await.unwrap().1
+}
+
+#[rocket::async_test]
+async fn tls_info_is_exposed() {
+    use rustls::version::{TLS12, TLS13};
+
+    let (port, shutdown) = launch().await;
+
+    let response = get(port, "/", &TLS13, &[b"http/1.1"]).await;
+    assert!(response.ends_with("\r\n\r\nTLSv1.3 TLS_CHACHA20_POLY1305_SHA256 \
+        Some(\"http/1.1\") Some(\"localhost\")"), "{}", response);
+
+    let response = get(port, "/", &TLS12, &[]).await;
+    assert!(response.ends_with("\r\n\r\nTLSv1.2 TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256 \
+        None Some(\"localhost\")"), "{}", response);
+
+    let response = get(port, "/admin", &TLS13, &[]).await;
+    assert!(response.ends_with("\r\n\r\nadmin"), "{}", response);
+
+    let response = get(port, "/admin", &TLS12, &[]).await;
+    assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"), "{}", response);
+
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn tls_info_is_absent_without_tls() {
+    let client = Client::debug_with(routes![index, admin]).await.unwrap();
+    assert_eq!(client.get("/").dispatch().await.into_string().await.unwrap(), "none");
+    assert_eq!(client.get("/admin").dispatch().await.status(), Status::NotFound);
+}
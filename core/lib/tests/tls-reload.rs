@@ -0,0 +1,71 @@
+#![cfg(feature = "tls")]
+
+mod common;
+
+use std::path::Path;
+
+use rocket::Shutdown;
+use rocket::config::TlsConfig;
+use rocket::http::tls::rustls::Certificate;
+use rocket::tokio::time::{sleep, Duration};
+
+use common::{RSA, ECDSA, leaf, temp_dir, client_config, config};
+
+/// Copies the certificate chain and key in `pair` to `dir`.
+fn install(dir: &Path, (certs, key): (&str, &str)) {
+    std::fs::copy(certs, dir.join("cert.pem")).unwrap();
+    std::fs::copy(key, dir.join("key.pem")).unwrap();
+}
+
+async fn launch(dir: &Path, reload_interval: u32) -> (u16, Shutdown) {
+    let tls = TlsConfig::from_paths(dir.join("cert.pem"), dir.join("key.pem"))
+        .with_reload_interval(reload_interval);
+
+    common::launch(rocket::custom(config(tls)).attach(common::reload())).await
+}
+
+/// Sends a `GET` request for `path` over a new TLS connection. Returns the
+/// leaf certificate the server presented and the response.
+async fn get(port: u16, path: &str) -> (Certificate, String) {
+    common::get(port, path, client_config()).await.unwrap()
+}
+
+#[rocket::async_test]
+async fn tls_reloads_on_demand() {
+    let dir = temp_dir("tls-reload-on-demand");
+    install(&dir, RSA);
+    let (port, shutdown) = launch(&dir, 0).await;
+    assert_eq!(get(port, "/").await.0, leaf(RSA));
+
+    // New certificates aren't used until they're reloaded.
+    install(&dir, ECDSA);
+    assert_eq!(get(port, "/").await.0, leaf(RSA));
+
+    let (cert, response) = get(port, "/reload").await;
+    assert_eq!(cert, leaf(RSA));
+    assert!(response.starts_with("HTTP/1.1 204 No Content\r\n"), "{}", response);
+    assert_eq!(get(port, "/").await.0, leaf(ECDSA));
+
+    // A failed reload leaves the previous certificates in place.
+    std::fs::write(dir.join("key.pem"), "not a key").unwrap();
+    let (cert, response) = get(port, "/reload").await;
+    assert_eq!(cert, leaf(ECDSA));
+    assert!(response.starts_with("HTTP/1.1 500 Internal Server Error\r\n"), "{}", response);
+    assert_eq!(get(port, "/").await.0, leaf(ECDSA));
+
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn tls_reloads_periodically() {
+    let dir = temp_dir("tls-reload-periodic");
+    install(&dir, RSA);
+    let (port, shutdown) = launch(&dir, 1).await;
+    assert_eq!(get(port, "/").await.0, leaf(RSA));
+
+    install(&dir, ECDSA);
+    sleep(Duration::from_millis(1500)).await;
+    assert_eq!(get(port, "/").await.0, leaf(ECDSA));
+
+    shutdown.notify();
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "tls")]
+
+#[macro_use] extern crate rocket;
+
+mod common;
+
+use rocket::Shutdown;
+use rocket::config::{SniCertificate, TlsConfig};
+use rocket::http::tls::rustls::Certificate;
+use rocket::request::ServerName;
+use rocket::tokio::time::{timeout, Duration};
+
+use common::{RSA, ECDSA, leaf, client_config, config, body};
+
+#[get("/")]
+fn index(name: Option<ServerName<'_>>) -> String {
+    name.map(|name| name.to_string()).unwrap_or_default()
+}
+
+async fn launch(tls: TlsConfig) -> (u16, Shutdown) {
+    common::launch(rocket::custom(config(tls)).mount("/", routes![index])).await
+}
+
+/// Requests `/` over TLS, sending `localhost` via SNI. Returns the leaf
+/// certificate the server presented and the response body.
+async fn get(port: u16) -> (Certificate, String) {
+    let (leaf, response) = common::get(port, "/", client_config()).await.unwrap();
+    (leaf, body(&response).to_string())
+}
+
+#[rocket::async_test]
+async fn serves_certificate_for_server_name() {
+    let tls = TlsConfig::from_paths(RSA.0, RSA.1)
+        .with_sni_certificate("LocalHost", SniCertificate::from_paths(ECDSA.0, ECDSA.1));
+
+    let (port, shutdown) = launch(tls).await;
+    assert_eq!(get(port).await, (leaf(ECDSA), "localhost".into()));
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn serves_default_certificate_for_other_names() {
+    let tls = TlsConfig::from_paths(RSA.0, RSA.1)
+        .with_sni_certificate("*.localhost", SniCertificate::from_paths(ECDSA.0, ECDSA.1));
+
+    let (port, shutdown) = launch(tls).await;
+    assert_eq!(get(port).await, (leaf(RSA), "localhost".into()));
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn bad_sni_certificates_fail_launch() {
+    let tls = TlsConfig::from_paths(RSA.0, RSA.1)
+        .with_sni_certificate("localhost", SniCertificate::from_paths(ECDSA.0, ECDSA.0));
+
+    let launch = timeout(Duration::from_secs(5), rocket::custom(config(tls)).launch());
+    let error = launch.await.expect("launch failed").unwrap_err();
+    assert!(error.to_string().contains("localhost"), "{}", error);
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "tls")]
+
+#[macro_use] extern crate rocket;
+
+mod common;
+
+use rocket::Shutdown;
+use rocket::config::{CipherSuite, TlsConfig, TlsVersion};
+use rocket::error::ErrorKind;
+use rocket::http::tls::rustls::{self, ClientConfig, SupportedProtocolVersion};
+use rocket::request::TlsInfo;
+
+use common::{RSA, roots, config, body};
+
+#[get("/")]
+fn index(tls: TlsInfo<'_>) -> String {
+    tls.version().to_string()
+}
+
+fn tls_config() -> TlsConfig {
+    TlsConfig::from_paths(RSA.0, RSA.1)
+}
+
+async fn launch(tls: TlsConfig) -> (u16, Shutdown) {
+    common::launch(rocket::custom(config(tls)).mount("/", routes![index])).await
+}
+
+/// Requests `/` over TLS using `version`. Returns the response body or the
+/// handshake error.
+async fn get(port: u16, version: &'static SupportedProtocolVersion) -> std::io::Result<String> {
+    let config = ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(&[version])
+        .unwrap()
+        .with_root_certificates(roots())
+        .with_no_client_auth();
+
+    common::get(port, "/", config).await.map(|(_, response)| body(&response).to_string())
+}
+
+#[rocket::async_test]
+async fn min_version_rejects_older_clients() {
+    use rustls::version::{TLS12, TLS13};
+
+    let tls = tls_config()
+        .with_min_version(TlsVersion::TLSv1_3)
+        .with_session_tickets(false)
+        .with_session_cache_size(0);
+
+    let (port, shutdown) = launch(tls).await;
+    assert_eq!(get(port, &TLS13).await.unwrap(), "TLSv1.3");
+    assert!(get(port, &TLS12).await.is_err());
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn max_version_rejects_newer_clients() {
+    use rustls::version::{TLS12, TLS13};
+
+    let tls = tls_config()
+        .with_max_version(TlsVersion::TLSv1_2)
+        .with_ticket_rotation(60);
+
+    let (port, shutdown) = launch(tls).await;
+    assert_eq!(get(port, &TLS12).await.unwrap(), "TLSv1.2");
+    assert!(get(port, &TLS13).await.is_err());
+    shutdown.notify();
+}
+
+#[rocket::async_test]
+async fn incompatible_tls_config_fails_ignite() {
+    let tls = tls_config()
+        .with_ciphers(CipherSuite::TLS_V13_SET)
+        .with_max_version(TlsVersion::TLSv1_2);
+
+    let error = rocket::custom(config(tls)).ignite().await.unwrap_err();
+    match error.kind() {
+        ErrorKind::Config(e) => assert_eq!(e.path, vec!["tls", "ciphers"]),
+        e => panic!("unexpected error: {:?}", e),
+    }
+
+    let tls = tls_config()
+        .with_min_version(TlsVersion::TLSv1_3)
+        .with_max_version(TlsVersion::TLSv1_2);
+
+    let error = rocket::custom(config(tls)).ignite().await.unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Config(e) if e.path == ["tls", "min_version"]));
+}
@@ -0,0 +1,155 @@
+#![cfg(feature = "tracing")]
+
+#[macro_use] extern crate rocket;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use rocket::fairing::AdHoc;
+use rocket::http::Status;
+use rocket::local::asynchronous::Client;
+use rocket::request::{self, FromRequest, Request};
+
+use tracing_::field::{Field, Visit};
+use tracing_::span::{Attributes, Id, Record};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, Registry};
+
+#[derive(Debug, Clone)]
+struct Span {
+    name: &'static str,
+    parent: Option<&'static str>,
+    fields: HashMap<&'static str, String>,
+}
+
+impl Span {
+    fn get(&self, field: &str) -> Option<&str> {
+        self.fields.get(field).map(|s| s.as_str())
+    }
+}
+
+struct Fields<'a>(&'a mut HashMap<&'static str, String>);
+
+impl Visit for Fields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name(), format!("{:?}", value));
+    }
+}
+
+/// Records every span, with its fields and parent, when it closes.
+struct Recorder(Arc<Mutex<Vec<Span>>>);
+
+impl<S: tracing_::Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let mut fields = HashMap::new();
+        attrs.record(&mut Fields(&mut fields));
+        span.extensions_mut().insert(Span {
+            name: span.name(),
+            parent: span.parent().map(|p| p.name()),
+            fields,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let mut extensions = span.extensions_mut();
+        values.record(&mut Fields(&mut extensions.get_mut::<Span>().unwrap().fields));
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = ctx.span(&id).unwrap();
+        let recorded = span.extensions().get::<Span>().cloned().unwrap();
+        self.0.lock().unwrap().push(recorded);
+    }
+}
+
+fn spans_for(closed: &Mutex<Vec<Span>>, uri: &str) -> Vec<Span> {
+    // Spans of a request close before its request span; collect them all.
+    let closed = closed.lock().unwrap();
+    let end = closed.iter()
+        .position(|s| s.name == "request" && s.get("uri") == Some(uri))
+        .unwrap();
+
+    let start = closed[..end].iter()
+        .rposition(|s| s.name == "request")
+        .map_or(0, |i| i + 1);
+
+    closed[start..=end].to_vec()
+}
+
+struct User;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for User {
+    type Error = ();
+
+    async fn from_request(_: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        request::Outcome::Success(User)
+    }
+}
+
+#[get("/hello/<name>")]
+fn hello(_user: User, name: &str) -> String {
+    format!("Hello, {}!", name)
+}
+
+#[catch(404)]
+fn not_found() -> &'static str {
+    "not found"
+}
+
+#[rocket::async_test]
+async fn spans_requests_fairings_guards_and_handlers() {
+    let closed = Arc::new(Mutex::new(vec![]));
+    let subscriber = Registry::default().with(Recorder(closed.clone()));
+    tracing_::subscriber::set_global_default(subscriber).unwrap();
+
+    let rocket = rocket::build()
+        .mount("/", routes![hello])
+        .register("/", catchers![not_found])
+        .attach(AdHoc::on_request("Tagger", |_, _| Box::pin(async {})));
+
+    let client = Client::debug(rocket).await.unwrap();
+    let response = client.get("/hello/Rocket").dispatch().await;
+    assert_eq!(response.into_string().await.unwrap(), "Hello, Rocket!");
+    assert_eq!(client.get("/missing").dispatch().await.status(), Status::NotFound);
+
+    let spans = spans_for(&closed, "/hello/Rocket");
+    let request = spans.last().unwrap();
+    assert_eq!(request.get("method"), Some("GET"));
+    assert_eq!(request.get("request_id").map(|id| id.len()), Some(32));
+    assert_eq!(request.get("route"), Some("hello"));
+    assert_eq!(request.get("status"), Some("200"));
+    assert!(request.get("latency_ms").is_some());
+
+    let find = |name: &str| spans.iter().find(|s| s.name == name).unwrap().clone();
+    let fairing = find("fairing");
+    assert_eq!(fairing.parent, Some("request"));
+    assert_eq!(fairing.get("kind"), Some("request"));
+    assert_eq!(fairing.get("name"), Some("Tagger"));
+
+    let handler = find("handler");
+    assert_eq!(handler.parent, Some("request"));
+    assert_eq!(handler.get("route"), Some("hello"));
+
+    let guard = find("guard");
+    assert_eq!(guard.parent, Some("handler"));
+    assert_eq!(guard.get("kind"), Some("request"));
+    assert_eq!(guard.get("ty"), Some("User"));
+
+    let spans = spans_for(&closed, "/missing");
+    let request = spans.last().unwrap();
+    assert_eq!(request.get("route"), None);
+    assert_eq!(request.get("status"), Some("404"));
+
+    let catcher = spans.iter().find(|s| s.name == "catcher").unwrap();
+    assert_eq!(catcher.parent, Some("request"));
+    assert_eq!(catcher.get("catcher"), Some("not_found"));
+    assert_eq!(catcher.get("status"), Some("404"));
+}
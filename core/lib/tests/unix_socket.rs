@@ -0,0 +1,197 @@
+#![cfg(unix)]
+
+#[macro_use] extern crate rocket;
+
+use std::io::{Read, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+use rocket::{Config, Rocket, Orbit, Shutdown};
+use rocket::config::{BindableAddr, UnixSocket};
+use rocket::error::ErrorKind;
+use rocket::fairing::AdHoc;
+use rocket::futures::channel::oneshot;
+use rocket::http::Status;
+use rocket::local::asynchronous::Client;
+use rocket::request::{PeerCredentials, NoPeerCredentials};
+
+#[get("/")]
+fn index() -> &'static str {
+    "Hello, socket!"
+}
+
+#[get("/whoami")]
+fn whoami(peer: Result<PeerCredentials, NoPeerCredentials>) -> String {
+    match peer {
+        Ok(peer) => format!("{} {} {:?}", peer.uid(), peer.gid(), peer.pid()),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[get("/creds")]
+fn creds(peer: PeerCredentials) -> String {
+    peer.to_string()
+}
+
+fn socket_path(name: &str) -> PathBuf {
+    let file = format!("rocket-unix-{}-{}.sock", name, std::process::id());
+    let path = std::env::temp_dir().join(file);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn get(stream: UnixStream) -> String {
+    get_path(stream, "/")
+}
+
+fn get_path(mut stream: UnixStream, path: &str) -> String {
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+fn rocket(address: BindableAddr, unix_socket: Option<UnixSocket>) -> Rocket<rocket::Build> {
+    let config = Config { address, unix_socket, ..Config::debug_default() };
+    rocket::custom(config).mount("/", routes![index, whoami, creds])
+}
+
+/// Launches a server on `address`, returning a handle to it and its shutdown
+/// handle once it has lifted off.
+async fn launch(
+    address: BindableAddr,
+    unix_socket: Option<UnixSocket>,
+) -> (rocket::tokio::task::JoinHandle<Result<Rocket<rocket::Ignite>, rocket::Error>>, Shutdown) {
+    let (tx, rx) = oneshot::channel();
+    let rocket = rocket(address, unix_socket)
+        .attach(AdHoc::on_liftoff("Send Shutdown", move |rocket: &Rocket<Orbit>| {
+            Box::pin(async move { tx.send(rocket.shutdown()).unwrap(); })
+        }));
+
+    let server = rocket::tokio::spawn(rocket.launch());
+    (server, rx.await.unwrap())
+}
+
+fn mode(path: &Path) -> u32 {
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o7777
+}
+
+#[rocket::async_test]
+async fn configures_socket_file_and_removes_it_on_shutdown() {
+    let path = socket_path("mode");
+    let gid = std::fs::metadata(std::env::temp_dir()).unwrap().gid();
+    let unix_socket = UnixSocket {
+        mode: Some(0o600),
+        group: Some(gid.to_string()),
+        ..UnixSocket::default()
+    };
+
+    let (server, shutdown) = launch(BindableAddr::Unix(path.clone()), Some(unix_socket)).await;
+    assert_eq!(mode(&path), 0o600);
+    assert_eq!(std::fs::metadata(&path).unwrap().gid(), gid);
+
+    let stream = UnixStream::connect(&path).unwrap();
+    let response = rocket::tokio::task::spawn_blocking(move || get(stream)).await.unwrap();
+    assert!(response.ends_with("Hello, socket!"), "{}", response);
+
+    shutdown.notify();
+    server.await.unwrap().unwrap();
+    assert!(!path.exists());
+}
+
+#[rocket::async_test]
+async fn removes_stale_sockets_only_when_configured() {
+    // A socket file whose listener is gone, as left behind by a crash.
+    let path = socket_path("stale");
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let error = rocket(BindableAddr::Unix(path.clone()), None).launch().await.unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Bind(_)));
+    assert!(path.exists());
+
+    let unix_socket = UnixSocket { remove_stale: true, ..UnixSocket::default() };
+    let (server, shutdown) = launch(BindableAddr::Unix(path.clone()), Some(unix_socket)).await;
+    let stream = UnixStream::connect(&path).unwrap();
+    let response = rocket::tokio::task::spawn_blocking(move || get(stream)).await.unwrap();
+    assert!(response.ends_with("Hello, socket!"), "{}", response);
+    shutdown.notify();
+    server.await.unwrap().unwrap();
+}
+
+#[rocket::async_test]
+async fn never_removes_sockets_in_use() {
+    let path = socket_path("in-use");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let address = BindableAddr::Unix(path.clone());
+    let unix_socket = UnixSocket { remove_stale: true, ..UnixSocket::default() };
+    let error = rocket(address.clone(), Some(unix_socket)).launch().await.unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Bind(_)));
+    assert!(path.exists());
+
+    // Nor when the socket is bound privately to then be linked into place.
+    let ino = std::fs::metadata(&path).unwrap().ino();
+    let unix_socket = UnixSocket { mode: Some(0o600), ..UnixSocket::default() };
+    let error = rocket(address, Some(unix_socket)).launch().await.unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Bind(_)));
+    assert_eq!(std::fs::metadata(&path).unwrap().ino(), ino);
+    assert!(UnixStream::connect(&path).is_ok());
+
+    drop(listener);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[rocket::async_test]
+async fn serves_on_abstract_sockets() {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::SocketAddr;
+
+    let name = format!("rocket-unix-abstract-{}", std::process::id());
+    let address: BindableAddr = format!("unix://@{}", name).parse().unwrap();
+
+    let unix_socket = UnixSocket { mode: Some(0o600), ..UnixSocket::default() };
+    let error = rocket(address.clone(), Some(unix_socket)).launch().await.unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Bind(_)));
+
+    let (server, shutdown) = launch(address, None).await;
+    let addr = SocketAddr::from_abstract_name(name.as_bytes()).unwrap();
+    let stream = UnixStream::connect_addr(&addr).unwrap();
+    let response = rocket::tokio::task::spawn_blocking(move || get(stream)).await.unwrap();
+    assert!(response.ends_with("Hello, socket!"), "{}", response);
+    shutdown.notify();
+    server.await.unwrap().unwrap();
+}
+
+#[rocket::async_test]
+async fn exposes_peer_credentials() {
+    let path = socket_path("creds");
+    let (server, shutdown) = launch(BindableAddr::Unix(path.clone()), None).await;
+
+    // This process is the peer: compare against the owner of a file it creates.
+    let file = socket_path("creds-owner");
+    std::fs::write(&file, "").unwrap();
+    let (uid, gid) = std::fs::metadata(&file).map(|m| (m.uid(), m.gid())).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    let stream = UnixStream::connect(&path).unwrap();
+    let response = rocket::tokio::task::spawn_blocking(move || get_path(stream, "/whoami"))
+        .await.unwrap();
+
+    assert!(response.contains(&format!("\r\n\r\n{} {} ", uid, gid)), "{}", response);
+    #[cfg(target_os = "linux")]
+    assert!(response.ends_with(&format!("Some({})", std::process::id())), "{}", response);
+
+    shutdown.notify();
+    server.await.unwrap().unwrap();
+
+    // Requests without a Unix socket peer, here local ones, have none.
+    let client = Client::debug(rocket(BindableAddr::Unix(path.clone()), None)).await.unwrap();
+    let response = client.get("/whoami").dispatch().await;
+    assert_eq!(response.into_string().await.unwrap(), NoPeerCredentials.to_string());
+    assert_eq!(client.get("/creds").dispatch().await.status(), Status::Unauthorized);
+}
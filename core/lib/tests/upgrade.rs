@@ -0,0 +1,196 @@
+#[macro_use] extern crate rocket;
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+
+use rocket::{route, Config, Data, Request, Response, Route};
+use rocket::config::BindableAddr;
+use rocket::data::{IoHandler, IoStream};
+use rocket::fairing::AdHoc;
+use rocket::futures::channel::oneshot;
+use rocket::http::{Header, Method, Status};
+use rocket::local::asynchronous::Client;
+use rocket::response::{self, Responder};
+use rocket::tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::task::JoinHandle;
+
+type LaunchResult = Result<rocket::Rocket<rocket::Ignite>, rocket::Error>;
+
+struct Echo(&'static str);
+
+#[rocket::async_trait]
+impl IoHandler for Echo {
+    async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+        let (mut reader, mut writer) = io::split(io);
+        io::copy(&mut reader, &mut writer).await?;
+        Ok(())
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Echo {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        Response::build()
+            .sized_body(self.0.len(), std::io::Cursor::new(self.0))
+            .upgrade(self.0, self)
+            .ok()
+    }
+}
+
+#[get("/")]
+fn echo() -> Echo {
+    Echo("echo")
+}
+
+// Codegen doesn't support `CONNECT` routes, so we write this one manually.
+fn connect<'r>(req: &'r Request<'_>, _: Data<'r>) -> route::BoxFuture<'r> {
+    let target = req.host().map(|h| h.to_string()).unwrap_or_default();
+    let response = Response::build_from(Echo("CONNECT").respond_to(req).unwrap())
+        .raw_header("X-Target", target)
+        .finalize();
+
+    route::Outcome::Success(response).pin()
+}
+
+fn routes() -> Vec<Route> {
+    let mut routes = routes![echo];
+    routes.push(Route::new(Method::Connect, "/", connect));
+    routes
+}
+
+async fn client() -> Client {
+    Client::debug(rocket::build().mount("/", routes())).await.unwrap()
+}
+
+async fn echo_over(mut io: impl io::AsyncRead + io::AsyncWrite + Unpin) -> Vec<u8> {
+    io.write_all(b"hello, world").await.unwrap();
+    io.shutdown().await.unwrap();
+
+    let mut buf = vec![];
+    io.read_to_end(&mut buf).await.unwrap();
+    buf
+}
+
+#[async_test]
+async fn local_upgrade() {
+    let client = client().await;
+    let response = client.get("/")
+        .header(Header::new("Connection", "Upgrade"))
+        .header(Header::new("Upgrade", "ECHO"))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    assert_eq!(response.headers().get_one("Upgrade"), Some("echo"));
+    assert_eq!(response.upgrade(echo_over).await.unwrap(), b"hello, world");
+}
+
+#[async_test]
+async fn local_no_upgrade() {
+    let client = client().await;
+    let response = client.get("/").dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().await.unwrap(), "echo");
+
+    let response = client.get("/")
+        .header(Header::new("Connection", "Upgrade"))
+        .header(Header::new("Upgrade", "h2c, unknown"))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.headers().get_one("Upgrade").is_none());
+    assert!(response.upgrade(|_| async {}).await.is_none());
+}
+
+#[async_test]
+async fn local_connect() {
+    let client = client().await;
+    let response = client.req(Method::Connect, "/")
+        .header(Header::new("Host", "example.com:443"))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.upgrade(echo_over).await.unwrap(), b"hello, world");
+}
+
+async fn launch(config: Config) -> (SocketAddr, rocket::Shutdown, JoinHandle<LaunchResult>) {
+    let (tx, rx) = oneshot::channel();
+    let config = Config {
+        address: BindableAddr::Tcp(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)),
+        ..config
+    };
+
+    let rocket = rocket::custom(config)
+        .mount("/", routes())
+        .attach(AdHoc::on_liftoff("Send Address", move |rocket| Box::pin(async move {
+            let port = rocket.config().address.port().unwrap();
+            let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port);
+            tx.send((addr, rocket.shutdown())).unwrap();
+        })));
+
+    let server = rocket::tokio::spawn(rocket.launch());
+    let (addr, shutdown) = rx.await.unwrap();
+    (addr, shutdown, server)
+}
+
+/// Reads an HTTP response head from `stream`, returning it as a string.
+async fn read_head(stream: &mut TcpStream) -> String {
+    let mut head = vec![];
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(stream.read_u8().await.unwrap());
+    }
+
+    String::from_utf8(head).unwrap()
+}
+
+#[async_test]
+async fn server_upgrade_and_connect() {
+    let (addr, shutdown, _) = launch(Config::debug_default()).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = "GET / HTTP/1.1\r\nHost: localhost\r\n\
+        Connection: upgrade\r\nUpgrade: echo\r\n\r\n";
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let head = read_head(&mut stream).await.to_lowercase();
+    assert!(head.starts_with("http/1.1 101 switching protocols\r\n"));
+    assert!(head.contains("\r\nupgrade: echo\r\n"));
+    assert_eq!(echo_over(stream).await, b"hello, world");
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = "CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\n";
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let head = read_head(&mut stream).await.to_lowercase();
+    assert!(head.starts_with("http/1.1 200 ok\r\n"));
+    assert!(head.contains("\r\nx-target: example.com:443\r\n"));
+    assert!(!head.contains("content-length"));
+    assert_eq!(echo_over(stream).await, b"hello, world");
+
+    shutdown.notify();
+}
+
+#[async_test]
+async fn upgraded_connections_are_drained_on_shutdown() {
+    let mut config = Config::debug_default();
+    config.shutdown.grace = 1;
+    config.shutdown.mercy = 1;
+
+    let (addr, shutdown, server) = launch(config).await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = "GET / HTTP/1.1\r\nHost: localhost\r\n\
+        Connection: upgrade\r\nUpgrade: echo\r\n\r\n";
+    stream.write_all(request.as_bytes()).await.unwrap();
+    read_head(&mut stream).await;
+
+    // The connection is upgraded and idle. The echo handler never finishes on
+    // its own as we never close our end, so shutdown must close it for us.
+    let start = std::time::Instant::now();
+    shutdown.notify();
+    let mut buf = vec![];
+    let _ = stream.read_to_end(&mut buf).await;
+    assert!(buf.is_empty());
+
+    assert!(server.await.unwrap().is_ok());
+    assert!(start.elapsed() < std::time::Duration::from_secs(4));
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "ws")]
+
+#[macro_use] extern crate rocket;
+
+use rocket::futures::{SinkExt, StreamExt};
+use rocket::futures::stream::SplitStream;
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::{Client, LocalRequest};
+use rocket::ws::{WebSocket, Channel, MessageStream, DuplexStream, Message};
+use rocket::ws::frame::CloseCode;
+use rocket::ws::tokio_tungstenite::WebSocketStream;
+use rocket::ws::tungstenite::protocol::Role;
+
+#[get("/channel")]
+fn channel(ws: WebSocket) -> Channel<'static> {
+    ws.channel(move |mut stream| Box::pin(async move {
+        while let Some(message) = stream.next().await {
+            let _ = stream.send(message?).await;
+        }
+
+        Ok(())
+    }))
+}
+
+#[get("/stream")]
+fn stream(ws: WebSocket) -> MessageStream<'static, SplitStream<DuplexStream>> {
+    ws.stream(|incoming| incoming)
+}
+
+#[get("/<_..>", rank = 2)]
+fn fallback() -> &'static str {
+    "not a websocket"
+}
+
+async fn client() -> Client {
+    let rocket = rocket::build().mount("/", routes![channel, stream, fallback]);
+    Client::debug(rocket).await.unwrap()
+}
+
+fn handshake<'c>(req: LocalRequest<'c>) -> LocalRequest<'c> {
+    req.header(Header::new("Connection", "keep-alive, Upgrade"))
+        .header(Header::new("Upgrade", "websocket"))
+        .header(Header::new("Sec-WebSocket-Version", "13"))
+        .header(Header::new("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+}
+
+#[async_test]
+async fn test_handshake() {
+    let client = client().await;
+    for uri in ["/channel", "/stream"] {
+        let response = handshake(client.get(uri)).dispatch().await;
+        assert_eq!(response.status(), Status::SwitchingProtocols);
+
+        let headers = response.headers();
+        assert_eq!(headers.get_one("Upgrade"), Some("websocket"));
+        assert_eq!(headers.get_one("Connection"), Some("Upgrade"));
+        // The example from RFC 6455, section 1.3.
+        let accept = headers.get_one("Sec-WebSocket-Accept");
+        assert_eq!(accept, Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+    }
+}
+
+#[async_test]
+async fn test_non_websocket_forwards() {
+    let client = client().await;
+    let response = client.get("/channel").dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().await.unwrap(), "not a websocket");
+
+    let response = client.get("/stream")
+        .header(Header::new("Connection", "Upgrade"))
+        .header(Header::new("Upgrade", "websocket"))
+        .header(Header::new("Sec-WebSocket-Version", "8"))
+        .header(Header::new("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.upgrade(|_| async {}).await.is_none());
+}
+
+#[async_test]
+async fn test_echo() {
+    let client = client().await;
+    for uri in ["/channel", "/stream"] {
+        let response = handshake(client.get(uri)).dispatch().await;
+        let replies = response.upgrade(|io| async move {
+            let mut ws = WebSocketStream::from_raw_socket(io, Role::Client, None).await;
+            ws.send(Message::text("hello")).await.unwrap();
+            ws.send(Message::binary(vec![1, 2, 3])).await.unwrap();
+            let first = ws.next().await.unwrap().unwrap();
+            let second = ws.next().await.unwrap().unwrap();
+            ws.close(None).await.unwrap();
+            (first, second)
+        }).await;
+
+        let (first, second) = replies.expect("upgraded");
+        assert_eq!(first, Message::text("hello"));
+        assert_eq!(second, Message::binary(vec![1, 2, 3]));
+    }
+}
+
+#[async_test]
+async fn test_shutdown_closes_connection() {
+    for uri in ["/channel", "/stream"] {
+        let client = client().await;
+        let shutdown = client.rocket().shutdown();
+        let response = handshake(client.get(uri)).dispatch().await;
+        let reply = response.upgrade(|io| async move {
+            let mut ws = WebSocketStream::from_raw_socket(io, Role::Client, None).await;
+            ws.send(Message::text("ping")).await.unwrap();
+            assert_eq!(ws.next().await.unwrap().unwrap(), Message::text("ping"));
+
+            shutdown.notify();
+            ws.next().await.unwrap().unwrap()
+        }).await;
+
+        match reply.expect("upgraded") {
+            Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Away),
+            message => panic!("expected close frame, found {:?}", message),
+        }
+    }
+}
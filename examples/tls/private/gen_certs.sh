@@ -8,9 +8,12 @@
 #   rsa_sha256
 #   ecdsa_nistp256_sha256
 #   ecdsa_nistp384_sha384
+#   crl
 #
 # Generate a certificate of the [cert-kind] key type, or if no cert-kind is
-# specified, all of the certificates.
+# specified, all of the certificates. `crl` generates certificate revocation
+# lists revoking the `ecdsa_nistp256_sha256` certificate: `crl.pem`, current
+# for ten years, and `stale_crl.pem`, which expired in 2020.
 #
 # Examples:
 #   ./gen_certs.sh ed25519
@@ -113,15 +116,35 @@ function gen_ecdsa_nistp384_sha384() {
   rm ca_cert.srl server.csr ecdsa_nistp384_sha384_key.pem
 }
 
+function gen_crl() {
+  gen_ca_if_non_existent
+
+  touch index.txt
+  printf "[ca]\ndefault_ca = rocket\n[rocket]\ndatabase = index.txt\ndefault_md = sha256\n" > ca.cnf
+
+  openssl ca -config ca.cnf -keyfile ca_key.pem -cert ca_cert.pem \
+    -revoke ecdsa_nistp256_sha256_cert.pem
+
+  openssl ca -config ca.cnf -keyfile ca_key.pem -cert ca_cert.pem \
+    -gencrl -crldays 3650 -out crl.pem
+
+  openssl ca -config ca.cnf -keyfile ca_key.pem -cert ca_cert.pem -gencrl \
+    -crl_lastupdate 20200101000000Z -crl_nextupdate 20200102000000Z -out stale_crl.pem
+
+  rm ca.cnf index.txt*
+}
+
 case $1 in
   ed25519) gen_ed25519 ;;
   rsa_sha256) gen_rsa_sha256 ;;
   ecdsa_nistp256_sha256) gen_ecdsa_nistp256_sha256 ;;
   ecdsa_nistp384_sha384) gen_ecdsa_nistp384_sha384 ;;
+  crl) gen_crl ;;
   *)
     gen_ed25519
     gen_rsa_sha256
     gen_ecdsa_nistp256_sha256
     gen_ecdsa_nistp384_sha384
+    gen_crl
     ;;
 esac
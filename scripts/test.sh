@@ -123,6 +123,12 @@ function test_core() {
     json
     msgpack
     uuid
+    ws
+    gzip
+    deflate
+    brotli
+    zstd
+    tracing
   )
 
   echo ":: Building and checking core [no features]..."